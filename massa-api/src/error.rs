@@ -4,6 +4,7 @@ use displaydoc::Display;
 use massa_consensus_exports::error::ConsensusError;
 use massa_execution_exports::ExecutionError;
 use massa_hash::MassaHashError;
+use massa_models::api::RejectedOperation;
 use massa_models::ModelsError;
 use massa_network_exports::NetworkError;
 use massa_pool::PoolError;
@@ -17,6 +18,10 @@ pub enum ApiError {
     PoolError(#[from] PoolError),
     /// too many arguments error: {0}
     TooManyArguments(String),
+    /// bad request: {0}
+    BadRequest(String),
+    /// operations rejected by the pool
+    OperationsRejected(Vec<RejectedOperation>),
     /// send channel error: {0}
     SendChannelError(String),
     /// receive channel error: {0}
@@ -29,6 +34,8 @@ pub enum ApiError {
     ExecutionError(#[from] ExecutionError),
     /// network error: {0}
     NetworkError(#[from] NetworkError),
+    /// bootstrap error: {0}
+    BootstrapError(String),
     /// models error: {0}
     ModelsError(#[from] ModelsError),
     /// time error: {0}
@@ -47,10 +54,25 @@ pub enum ApiError {
 
 impl From<ApiError> for jsonrpc_core::Error {
     fn from(err: ApiError) -> Self {
-        jsonrpc_core::Error {
-            code: jsonrpc_core::ErrorCode::ServerError(500),
-            message: err.to_string(),
-            data: None,
+        match err {
+            // the rejected operations are listed in the error data so that clients can tell them apart
+            ApiError::OperationsRejected(rejected) => jsonrpc_core::Error {
+                code: jsonrpc_core::ErrorCode::ServerError(500),
+                message: format!(
+                    "operations rejected by the pool: {}",
+                    rejected
+                        .iter()
+                        .map(|rejected| rejected.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                data: jsonrpc_core::serde_json::to_value(rejected).ok(),
+            },
+            err => jsonrpc_core::Error {
+                code: jsonrpc_core::ErrorCode::ServerError(500),
+                message: err.to_string(),
+                data: None,
+            },
         }
     }
 }
@@ -8,12 +8,15 @@ use error::ApiError;
 use jsonrpc_core::{BoxFuture, IoHandler, Value};
 use jsonrpc_derive::rpc;
 use jsonrpc_http_server::{CloseHandle, ServerBuilder};
+use massa_bootstrap::BootstrapPolicyHandle;
 use massa_consensus_exports::{ConsensusCommandSender, ConsensusConfig};
 use massa_execution_exports::ExecutionController;
 use massa_models::api::{
-    AddressInfo, BlockInfo, BlockSummary, DatastoreEntryInput, DatastoreEntryOutput,
-    EndorsementInfo, EventFilter, NodeStatus, OperationInfo, OperationInput,
-    ReadOnlyBytecodeExecution, ReadOnlyCall, TimeInterval,
+    AddressInfo, BlockInfo, BlockInput, BlockSummary, BlockTemplate, BlockTemplateInput,
+    DatastoreEntryInput, DatastoreEntryOutput, EndorsementInfo, EventFilter, FeeEstimate,
+    FeeEstimateInput, NodeStatus, OperationInfo, OperationInput, PoolOperationFilter,
+    PoolOperationsPage, ReadOnlyBytecodeExecution, ReadOnlyCall, SelectionDrawsInput,
+    SelectionDrawsOutput, SignatureVerificationInput, TimeInterval,
 };
 use massa_models::clique::Clique;
 use massa_models::composite::PubkeySig;
@@ -33,9 +36,11 @@ use tokio::sync::mpsc;
 use tracing::{info, warn};
 
 mod error;
+mod pool_events;
 mod private;
 mod public;
 mod settings;
+pub use pool_events::PoolEventsApi;
 pub use settings::APISettings;
 
 /// Public API component
@@ -76,6 +81,8 @@ pub struct Private {
     pub api_settings: &'static APISettings,
     /// stop channel
     pub stop_node_channel: mpsc::Sender<()>,
+    /// policy of the bootstrap server
+    pub bootstrap_policy: BootstrapPolicyHandle,
 }
 
 /// The API wrapper
@@ -101,21 +108,32 @@ fn serve(api: impl Endpoints, url: &SocketAddr) -> StopHandle {
     let join_handle = thread::spawn(|| server.wait());
 
     StopHandle {
-        close_handle,
+        close_handle: ServerCloseHandle::Http(close_handle),
         join_handle,
     }
 }
 
+/// Used to close an API server
+enum ServerCloseHandle {
+    /// HTTP server
+    Http(CloseHandle),
+    /// `WebSocket` server
+    WebSocket(jsonrpc_ws_server::CloseHandle),
+}
+
 /// Used to be able to stop the API
 pub struct StopHandle {
-    close_handle: CloseHandle,
+    close_handle: ServerCloseHandle,
     join_handle: JoinHandle<()>,
 }
 
 impl StopHandle {
     /// stop the API gracefully
     pub fn stop(self) {
-        self.close_handle.close();
+        match self.close_handle {
+            ServerCloseHandle::Http(close_handle) => close_handle.close(),
+            ServerCloseHandle::WebSocket(close_handle) => close_handle.close(),
+        }
         if let Err(err) = self.join_handle.join() {
             warn!("API thread panicked: {:?}", err);
         } else {
@@ -131,7 +149,7 @@ pub trait Endpoints {
     #[rpc(name = "stop_node")]
     fn stop_node(&self) -> BoxFuture<Result<(), ApiError>>;
 
-    /// Sign message with node's key.
+    /// Sign message with node's key, in the signed message envelope.
     /// Returns the public key that signed the message and the signature.
     #[rpc(name = "node_sign_message")]
     fn node_sign_message(&self, _: Vec<u8>) -> BoxFuture<Result<PubkeySig, ApiError>>;
@@ -164,6 +182,20 @@ pub trait Endpoints {
     #[rpc(name = "get_staking_addresses")]
     fn get_staking_addresses(&self) -> BoxFuture<Result<Set<Address>, ApiError>>;
 
+    /// Return the template of a block for the given slot and block producer:
+    /// parents, endorsements, denunciations and operations selected from the pool.
+    /// The block can then be built, signed and submitted outside of the node.
+    #[rpc(name = "get_block_template")]
+    fn get_block_template(
+        &self,
+        _: BlockTemplateInput,
+    ) -> BoxFuture<Result<BlockTemplate, ApiError>>;
+
+    /// Check a signed block built from a template, then add it to the graph for propagation.
+    /// Returns the id of the block.
+    #[rpc(name = "submit_block")]
+    fn submit_block(&self, _: BlockInput) -> BoxFuture<Result<BlockId, ApiError>>;
+
     /// Bans given IP address(es).
     /// No confirmation to expect.
     #[rpc(name = "node_ban_by_ip")]
@@ -196,6 +228,11 @@ pub trait Endpoints {
     #[rpc(name = "node_unban_by_id")]
     fn node_unban_by_id(&self, _: Vec<NodeId>) -> BoxFuture<Result<(), ApiError>>;
 
+    /// Read the bootstrap server access control and rate policy again from the configuration.
+    /// The new policy applies to the bootstrap sessions started afterwards.
+    #[rpc(name = "node_reload_bootstrap_policy")]
+    fn node_reload_bootstrap_policy(&self) -> BoxFuture<Result<(), ApiError>>;
+
     /// Summary of the current state: time, last final blocks (hash, thread, slot, timestamp), clique count, connected nodes count.
     #[rpc(name = "get_status")]
     fn get_status(&self) -> BoxFuture<Result<NodeStatus, ApiError>>;
@@ -243,13 +280,48 @@ pub trait Endpoints {
     #[rpc(name = "get_addresses")]
     fn get_addresses(&self, _: Vec<Address>) -> BoxFuture<Result<Vec<AddressInfo>, ApiError>>;
 
+    /// Get the block and endorsement draws of a list of addresses within a slot range.
+    /// By default the range starts at the current slot and spans `draw_lookahead_period_count` periods.
+    /// It cannot span more than `draw_lookahead_period_count` periods nor `pos_draw_cached_cycles` cycles.
+    #[rpc(name = "get_selection_draws")]
+    fn get_selection_draws(
+        &self,
+        _: SelectionDrawsInput,
+    ) -> BoxFuture<Result<SelectionDrawsOutput, ApiError>>;
+
     /// Adds operations to pool. Returns operations that were ok and sent to pool.
+    /// The operations refused by the pool are listed, with the reason why,
+    /// in the data of the returned error.
     #[rpc(name = "send_operations")]
     fn send_operations(
         &self,
         _: Vec<OperationInput>,
     ) -> BoxFuture<Result<Vec<OperationId>, ApiError>>;
 
+    /// Get the operations pending in the pool, optionally filtered by:
+    /// * thread
+    /// * operation kind
+    /// * sender address
+    /// * minimal fee
+    /// * periods at which they are valid
+    ///
+    /// The operations are sorted by thread then by decreasing fee density, and paginated with an offset and a limit.
+    #[rpc(name = "get_pool_operations")]
+    fn get_pool_operations(
+        &self,
+        _: PoolOperationFilter,
+    ) -> BoxFuture<Result<PoolOperationsPage, ApiError>>;
+
+    /// Get the fee densities of the pending operations and of the operations included in recent blocks
+    /// for each thread, and the fee recommended to include an operation of the given size within the given number of periods.
+    #[rpc(name = "get_fee_estimate")]
+    fn get_fee_estimate(&self, _: FeeEstimateInput) -> BoxFuture<Result<FeeEstimate, ApiError>>;
+
+    /// Check the signature of a message signed by an address, with the signed message envelope.
+    /// Returns true if the public key is the one of the address and signed the message.
+    #[rpc(name = "verify_signature")]
+    fn verify_signature(&self, _: SignatureVerificationInput) -> BoxFuture<Result<bool, ApiError>>;
+
     /// Get events optionally filtered by:
     /// * start slot
     /// * end slot
@@ -0,0 +1,188 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+//! WebSocket stream of the operations entering and leaving the pool
+
+use crate::{ServerCloseHandle, StopHandle};
+use jsonrpc_core::MetaIoHandler;
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{typed::Subscriber, PubSubHandler, Session, SubscriptionId};
+use jsonrpc_ws_server::{RequestContext, ServerBuilder};
+use massa_models::api::PoolOperationEvent;
+use massa_pool::PoolCommandSender;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// Exposed pool event subscriptions
+#[rpc(server)]
+pub trait PoolEvents {
+    /// `WebSocket` session
+    type Metadata;
+
+    /// Subscribe to the operations entering and leaving the pool.
+    /// Each notification is a `PoolOperationEvent`.
+    #[pubsub(
+        subscription = "pool_operation_events",
+        subscribe,
+        name = "subscribe_pool_operation_events"
+    )]
+    fn subscribe_pool_operation_events(&self, _: Self::Metadata, _: Subscriber<PoolOperationEvent>);
+
+    /// Stop a pool operation events subscription.
+    #[pubsub(
+        subscription = "pool_operation_events",
+        unsubscribe,
+        name = "unsubscribe_pool_operation_events"
+    )]
+    fn unsubscribe_pool_operation_events(
+        &self,
+        _: Option<Self::Metadata>,
+        _: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool>;
+}
+
+/// Pool event subscriptions component
+pub struct PoolEventsApi {
+    /// link to the pool component
+    pool_command_sender: PoolCommandSender,
+    /// runtime running the subscriptions
+    runtime: tokio::runtime::Handle,
+    /// id of the next subscription
+    next_id: AtomicU64,
+    /// tasks forwarding the pool events, by subscription
+    subscriptions: Arc<Mutex<HashMap<SubscriptionId, JoinHandle<()>>>>,
+    /// max number of simultaneous subscriptions
+    max_subscriptions: usize,
+}
+
+impl PoolEventsApi {
+    /// generate a new pool event subscriptions component, running on the current runtime
+    /// and accepting at most `max_subscriptions` simultaneous subscriptions
+    pub fn new(pool_command_sender: PoolCommandSender, max_subscriptions: usize) -> Self {
+        PoolEventsApi {
+            pool_command_sender,
+            runtime: tokio::runtime::Handle::current(),
+            next_id: AtomicU64::new(0),
+            subscriptions: Default::default(),
+            max_subscriptions,
+        }
+    }
+
+    /// Start the `WebSocket` server
+    pub fn serve(self, url: &SocketAddr) -> StopHandle {
+        let mut io = PubSubHandler::new(MetaIoHandler::default());
+        io.extend_with(self.to_delegate());
+
+        let server = ServerBuilder::with_meta_extractor(io, |context: &RequestContext| {
+            Arc::new(Session::new(context.sender()))
+        })
+        .event_loop_executor(tokio::runtime::Handle::current())
+        .start(url)
+        .expect("Unable to start WebSocket RPC server");
+
+        let close_handle = server.close_handle();
+        let join_handle = thread::spawn(|| {
+            if let Err(err) = server.wait() {
+                warn!("WebSocket RPC server error: {}", err);
+            }
+        });
+
+        StopHandle {
+            close_handle: ServerCloseHandle::WebSocket(close_handle),
+            join_handle,
+        }
+    }
+}
+
+#[doc(hidden)]
+impl PoolEvents for PoolEventsApi {
+    type Metadata = Arc<Session>;
+
+    fn subscribe_pool_operation_events(
+        &self,
+        _: Self::Metadata,
+        subscriber: Subscriber<PoolOperationEvent>,
+    ) {
+        // hold the lock until the task is registered, so that it cannot unregister itself before
+        // and so that concurrent subscriptions cannot exceed the limit
+        let mut subscriptions = self
+            .subscriptions
+            .lock()
+            .expect("pool events subscriptions lock poisoned");
+        if subscriptions.len() >= self.max_subscriptions {
+            debug!("pool events subscription refused: too many subscriptions");
+            let _ = subscriber.reject(jsonrpc_core::Error {
+                code: jsonrpc_core::ErrorCode::ServerError(-32000),
+                message: "too many subscriptions".into(),
+                data: None,
+            });
+            return;
+        }
+        let id = SubscriptionId::Number(self.next_id.fetch_add(1, Ordering::Relaxed));
+        let sink = match subscriber.assign_id(id.clone()) {
+            Ok(sink) => sink,
+            Err(()) => {
+                debug!("pool events subscriber left before its subscription started");
+                return;
+            }
+        };
+        let mut pool_command_sender = self.pool_command_sender.clone();
+        let task_subscriptions = self.subscriptions.clone();
+        let task_id = id.clone();
+        let task = self.runtime.spawn(async move {
+            match pool_command_sender.subscribe_operation_events().await {
+                Ok(mut receiver) => loop {
+                    match receiver.recv().await {
+                        Ok(event) => {
+                            if let Err(err) = sink.notify(Ok(event)) {
+                                if err.is_disconnected() {
+                                    break;
+                                }
+                                debug!(
+                                    "pool events subscription {:?} dropped an event: the subscriber is too slow",
+                                    task_id
+                                );
+                            }
+                        }
+                        Err(RecvError::Lagged(count)) => debug!(
+                            "pool events subscription {:?} missed {} events",
+                            task_id, count
+                        ),
+                        Err(RecvError::Closed) => break,
+                    }
+                },
+                Err(err) => warn!("could not subscribe to the pool events: {}", err),
+            }
+            task_subscriptions
+                .lock()
+                .expect("pool events subscriptions lock poisoned")
+                .remove(&task_id);
+        });
+        subscriptions.insert(id, task);
+    }
+
+    fn unsubscribe_pool_operation_events(
+        &self,
+        _: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool> {
+        match self
+            .subscriptions
+            .lock()
+            .expect("pool events subscriptions lock poisoned")
+            .remove(&id)
+        {
+            Some(task) => {
+                task.abort();
+                Ok(true)
+            }
+            None => Err(jsonrpc_core::Error::invalid_params(
+                "unknown subscription id",
+            )),
+        }
+    }
+}
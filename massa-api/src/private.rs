@@ -5,12 +5,15 @@ use crate::settings::APISettings;
 use crate::{Endpoints, Private, RpcServer, StopHandle, API};
 use jsonrpc_core::BoxFuture;
 use jsonrpc_http_server::tokio::sync::mpsc;
+use massa_bootstrap::BootstrapPolicyHandle;
 use massa_consensus_exports::{ConsensusCommandSender, ConsensusConfig};
 use massa_execution_exports::ExecutionController;
 use massa_models::api::{
-    AddressInfo, BlockInfo, BlockSummary, DatastoreEntryInput, DatastoreEntryOutput,
-    EndorsementInfo, EventFilter, NodeStatus, OperationInfo, OperationInput,
-    ReadOnlyBytecodeExecution, ReadOnlyCall, TimeInterval,
+    AddressInfo, BlockInfo, BlockInput, BlockSummary, BlockTemplate, BlockTemplateInput,
+    DatastoreEntryInput, DatastoreEntryOutput, EndorsementInfo, EventFilter, FeeEstimate,
+    FeeEstimateInput, NodeStatus, OperationInfo, OperationInput, PoolOperationFilter,
+    PoolOperationsPage, ReadOnlyBytecodeExecution, ReadOnlyCall, SelectionDrawsInput,
+    SelectionDrawsOutput, SignatureVerificationInput, TimeInterval,
 };
 use massa_models::clique::Clique;
 use massa_models::composite::PubkeySig;
@@ -18,8 +21,12 @@ use massa_models::execution::ExecuteReadOnlyResponse;
 use massa_models::node::NodeId;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::Set;
-use massa_models::{Address, BlockId, EndorsementId, OperationId};
+use massa_models::wrapped::WrappedDeserializer;
+use massa_models::{
+    Address, BlockDeserializer, BlockId, EndorsementId, ModelsError, OperationId, WrappedBlock,
+};
 use massa_network_exports::NetworkCommandSender;
+use massa_serialization::{DeserializeError, Deserializer};
 use massa_signature::KeyPair;
 use std::net::{IpAddr, SocketAddr};
 
@@ -31,6 +38,7 @@ impl API<Private> {
         execution_controller: Box<dyn ExecutionController>,
         api_settings: &'static APISettings,
         consensus_settings: ConsensusConfig,
+        bootstrap_policy: BootstrapPolicyHandle,
     ) -> (Self, mpsc::Receiver<()>) {
         let (stop_node_channel, rx) = mpsc::channel(1);
         (
@@ -41,6 +49,7 @@ impl API<Private> {
                 consensus_config: consensus_settings,
                 api_settings,
                 stop_node_channel,
+                bootstrap_policy,
             }),
             rx,
         )
@@ -108,6 +117,42 @@ impl Endpoints for API<Private> {
         Box::pin(closure())
     }
 
+    fn get_block_template(
+        &self,
+        input: BlockTemplateInput,
+    ) -> BoxFuture<Result<BlockTemplate, ApiError>> {
+        let cmd_sender = self.0.consensus_command_sender.clone();
+        let closure = async move || {
+            Ok(cmd_sender
+                .get_block_template(input.slot, input.creator_public_key)
+                .await?)
+        };
+        Box::pin(closure())
+    }
+
+    fn submit_block(&self, input: BlockInput) -> BoxFuture<Result<BlockId, ApiError>> {
+        let cmd_sender = self.0.consensus_command_sender.clone();
+        let closure = async move || {
+            let mut block_serialized = Vec::new();
+            block_serialized.extend(input.signature.to_bytes());
+            block_serialized.extend(input.creator_public_key.to_bytes());
+            block_serialized.extend(input.serialized_content);
+            let (rest, block): (&[u8], WrappedBlock) =
+                WrappedDeserializer::new(BlockDeserializer::new())
+                    .deserialize::<DeserializeError>(&block_serialized)
+                    .map_err(|err| {
+                        ApiError::ModelsError(ModelsError::DeserializeError(err.to_string()))
+                    })?;
+            if !rest.is_empty() {
+                return Err(ApiError::ModelsError(ModelsError::DeserializeError(
+                    "There is data left after block deserialization".to_owned(),
+                )));
+            }
+            Ok(cmd_sender.submit_block(block).await?)
+        };
+        Box::pin(closure())
+    }
+
     fn node_ban_by_ip(&self, ips: Vec<IpAddr>) -> BoxFuture<Result<(), ApiError>> {
         let network_command_sender = self.0.network_command_sender.clone();
         let closure = async move || Ok(network_command_sender.node_ban_by_ips(ips).await?);
@@ -180,6 +225,13 @@ impl Endpoints for API<Private> {
         crate::wrong_api::<Vec<AddressInfo>>()
     }
 
+    fn get_selection_draws(
+        &self,
+        _: SelectionDrawsInput,
+    ) -> BoxFuture<Result<SelectionDrawsOutput, ApiError>> {
+        crate::wrong_api::<SelectionDrawsOutput>()
+    }
+
     fn send_operations(
         &self,
         _: Vec<OperationInput>,
@@ -194,6 +246,21 @@ impl Endpoints for API<Private> {
         crate::wrong_api::<Vec<SCOutputEvent>>()
     }
 
+    fn get_pool_operations(
+        &self,
+        _: PoolOperationFilter,
+    ) -> BoxFuture<Result<PoolOperationsPage, ApiError>> {
+        crate::wrong_api::<PoolOperationsPage>()
+    }
+
+    fn get_fee_estimate(&self, _: FeeEstimateInput) -> BoxFuture<Result<FeeEstimate, ApiError>> {
+        crate::wrong_api::<FeeEstimate>()
+    }
+
+    fn verify_signature(&self, _: SignatureVerificationInput) -> BoxFuture<Result<bool, ApiError>> {
+        crate::wrong_api::<bool>()
+    }
+
     fn node_whitelist(&self, ips: Vec<IpAddr>) -> BoxFuture<Result<(), ApiError>> {
         let network_command_sender = self.0.network_command_sender.clone();
         let closure = async move || Ok(network_command_sender.whitelist(ips).await?);
@@ -205,4 +272,15 @@ impl Endpoints for API<Private> {
         let closure = async move || Ok(network_command_sender.remove_from_whitelist(ips).await?);
         Box::pin(closure())
     }
+
+    fn node_reload_bootstrap_policy(&self) -> BoxFuture<Result<(), ApiError>> {
+        let bootstrap_policy = self.0.bootstrap_policy.clone();
+        let closure = async move || {
+            bootstrap_policy
+                .reload()
+                .map(|_| ())
+                .map_err(ApiError::BootstrapError)
+        };
+        Box::pin(closure())
+    }
 }
@@ -11,13 +11,15 @@ use massa_execution_exports::{
 };
 use massa_graph::{DiscardReason, ExportBlockStatus};
 use massa_models::api::{
-    DatastoreEntryInput, DatastoreEntryOutput, OperationInput, ReadOnlyBytecodeExecution,
-    ReadOnlyCall,
+    AddressDraws, BlockInput, BlockTemplate, BlockTemplateInput, DatastoreEntryInput,
+    DatastoreEntryOutput, FeeEstimate, FeeEstimateInput, OperationInput, PoolOperationFilter,
+    PoolOperationsPage, ReadOnlyBytecodeExecution, ReadOnlyCall, RejectedOperation,
+    SelectionDrawsInput, SelectionDrawsOutput, SignatureVerificationInput, ThreadFeeEstimate,
 };
 use massa_models::execution::ReadOnlyResult;
 use massa_models::operation::OperationDeserializer;
-use massa_models::wrapped::WrappedDeserializer;
-use massa_models::{Amount, ModelsError, WrappedOperation};
+use massa_models::wrapped::WrappedContent;
+use massa_models::{Amount, ModelsError, Operation, WrappedOperation};
 use massa_serialization::{DeserializeError, Deserializer};
 
 use massa_models::{
@@ -233,6 +235,17 @@ impl Endpoints for API<Public> {
         crate::wrong_api::<Set<Address>>()
     }
 
+    fn get_block_template(
+        &self,
+        _: BlockTemplateInput,
+    ) -> BoxFuture<Result<BlockTemplate, ApiError>> {
+        crate::wrong_api::<BlockTemplate>()
+    }
+
+    fn submit_block(&self, _: BlockInput) -> BoxFuture<Result<BlockId, ApiError>> {
+        crate::wrong_api::<BlockId>()
+    }
+
     fn node_ban_by_ip(&self, _: Vec<IpAddr>) -> BoxFuture<Result<(), ApiError>> {
         crate::wrong_api::<()>()
     }
@@ -723,6 +736,97 @@ impl Endpoints for API<Public> {
         Box::pin(closure())
     }
 
+    fn get_selection_draws(
+        &self,
+        input: SelectionDrawsInput,
+    ) -> BoxFuture<Result<SelectionDrawsOutput, ApiError>> {
+        let cmd_sender = self.0.consensus_command_sender.clone();
+        let cfg = self.0.consensus_config.clone();
+        let api_cfg = self.0.api_settings;
+        let compensation_millis = self.0.compensation_millis;
+
+        let closure = async move || {
+            // check for address length
+            if input.addresses.len() as u64 > api_cfg.max_arguments {
+                return Err(ApiError::TooManyArguments("too many arguments".into()));
+            }
+
+            // slot range, the end is excluded
+            let start = match input.start {
+                Some(start) => start,
+                None => {
+                    let now = MassaTime::compensated_now(compensation_millis)?;
+                    get_latest_block_slot_at_timestamp(
+                        cfg.thread_count,
+                        cfg.t0,
+                        cfg.genesis_timestamp,
+                        now,
+                    )?
+                    .unwrap_or_else(|| Slot::new(0, 0))
+                }
+            };
+            let end = input.end.unwrap_or_else(|| {
+                Slot::new(
+                    start
+                        .period
+                        .saturating_add(api_cfg.draw_lookahead_period_count),
+                    start.thread,
+                )
+            });
+            if end <= start {
+                return Err(ApiError::BadRequest(
+                    "the end slot must be after the start slot".into(),
+                ));
+            }
+            if end.period.saturating_sub(start.period) > api_cfg.draw_lookahead_period_count {
+                return Err(ApiError::BadRequest(format!(
+                    "the slot range cannot span more than {} periods",
+                    api_cfg.draw_lookahead_period_count
+                )));
+            }
+
+            // all the drawn cycles must fit in the PoS draw cache
+            let cycle_count = get_cycle_count(start, end, cfg.periods_per_cycle);
+            if cycle_count > cfg.pos_draw_cached_cycles as u64 {
+                return Err(ApiError::BadRequest(format!(
+                    "the slot range spans {} cycles but at most {} cycles can be drawn at once",
+                    cycle_count, cfg.pos_draw_cached_cycles
+                )));
+            }
+
+            let draws = cmd_sender.get_selection_draws(start, end).await?;
+            Ok(SelectionDrawsOutput {
+                start,
+                end,
+                draws: input
+                    .addresses
+                    .into_iter()
+                    .map(|address| AddressDraws {
+                        address,
+                        block_draws: draws
+                            .iter()
+                            .filter(|(_, (ad, _))| *ad == address)
+                            .map(|(slot, _)| *slot)
+                            .collect(),
+                        endorsement_draws: draws
+                            .iter()
+                            .flat_map(|(slot, (_, addrs))| {
+                                addrs.iter().enumerate().filter_map(|(index, ad)| {
+                                    if *ad == address {
+                                        Some(IndexedSlot { slot: *slot, index })
+                                    } else {
+                                        None
+                                    }
+                                })
+                            })
+                            .collect(),
+                    })
+                    .collect(),
+            })
+        };
+        Box::pin(closure())
+    }
+
     fn send_operations(
         &self,
         ops: Vec<OperationInput>,
@@ -733,34 +837,53 @@ impl Endpoints for API<Public> {
             if ops.len() as u64 > api_cfg.max_arguments {
                 return Err(ApiError::TooManyArguments("too many arguments".into()));
             }
-            let operation_deserializer = WrappedDeserializer::new(OperationDeserializer::new());
+            let operation_deserializer = OperationDeserializer::new();
             let to_send = ops
                 .into_iter()
                 .map(|op_input| {
-                    let mut op_serialized = Vec::new();
-                    op_serialized.extend(op_input.signature.to_bytes());
-                    op_serialized.extend(op_input.creator_public_key.to_bytes());
-                    op_serialized.extend(op_input.serialized_content);
-                    let (rest, op): (&[u8], WrappedOperation) = operation_deserializer
-                        .deserialize::<DeserializeError>(&op_serialized)
+                    let (rest, content) = operation_deserializer
+                        .deserialize::<DeserializeError>(&op_input.serialized_content)
                         .map_err(|err| {
                             ApiError::ModelsError(ModelsError::DeserializeError(err.to_string()))
                         })?;
-                    if rest.is_empty() {
-                        Ok(op)
-                    } else {
-                        Err(ApiError::ModelsError(ModelsError::DeserializeError(
+                    if !rest.is_empty() {
+                        return Err(ApiError::ModelsError(ModelsError::DeserializeError(
                             "There is data left after operation deserialization".to_owned(),
-                        )))
+                        )));
                     }
+                    let op: WrappedOperation = match op_input.multisig {
+                        Some(proof) => Operation::new_multisig_wrapped(
+                            content,
+                            op_input.serialized_content,
+                            proof,
+                        )?,
+                        None => Operation::new_wrapped_with_signature(
+                            content,
+                            op_input.serialized_content,
+                            op_input.creator_public_key,
+                            op_input.signature,
+                        ),
+                    };
+                    Ok(op)
                 })
                 .map(|op| match op {
                     Ok(operation) => Ok((operation.verify_integrity()?, operation)),
                     Err(e) => Err(e),
                 })
                 .collect::<Result<Map<OperationId, _>, ApiError>>()?;
-            let ids = to_send.keys().copied().collect();
-            cmd_sender.add_operations(to_send).await?;
+            let ids: Vec<OperationId> = to_send.keys().copied().collect();
+            let mut rejected = cmd_sender.add_operations(to_send).await?;
+            if !rejected.is_empty() {
+                return Err(ApiError::OperationsRejected(
+                    ids.iter()
+                        .filter_map(|id| {
+                            rejected
+                                .remove(id)
+                                .map(|reason| RejectedOperation { id: *id, reason })
+                        })
+                        .collect(),
+                ));
+            }
             Ok(ids)
         };
         Box::pin(closure())
@@ -786,6 +909,113 @@ impl Endpoints for API<Public> {
         Box::pin(closure())
     }
 
+    fn get_pool_operations(
+        &self,
+        filter: PoolOperationFilter,
+    ) -> BoxFuture<Result<PoolOperationsPage, ApiError>> {
+        let mut pool_command_sender = self.0.pool_command_sender.clone();
+        let closure = async move || {
+            let (has_more, operations) =
+                pool_command_sender.get_filtered_operations(filter).await?;
+            Ok(PoolOperationsPage {
+                has_more,
+                operations: operations
+                    .into_iter()
+                    .map(|operation| OperationInfo {
+                        id: operation.id,
+                        in_pool: true,
+                        in_blocks: Vec::new(),
+                        is_final: false,
+                        operation,
+                    })
+                    .collect(),
+            })
+        };
+        Box::pin(closure())
+    }
+
+    /// Estimate the fee needed to be included within `target_periods` periods.
+    /// In each thread, the recommended fee density beats the pending operations that would
+    /// otherwise fill the blocks of these periods, and is at least the 10th percentile
+    /// of the fee densities of the operations included in recent active blocks.
+    fn get_fee_estimate(
+        &self,
+        input: FeeEstimateInput,
+    ) -> BoxFuture<Result<FeeEstimate, ApiError>> {
+        let api_cfg = self.0.api_settings;
+        let consensus_command_sender = self.0.consensus_command_sender.clone();
+        let consensus_settings = self.0.consensus_config.clone();
+        let compensation_millis = self.0.compensation_millis;
+        let mut pool_command_sender = self.0.pool_command_sender.clone();
+        let closure = async move || {
+            let target_periods = input.target_periods.max(1);
+            let inclusion_capacity = (consensus_settings.max_operations_per_block as u64)
+                .saturating_mul(target_periods)
+                .try_into()
+                .unwrap_or(usize::MAX);
+            let pool_densities = pool_command_sender
+                .get_fee_densities(inclusion_capacity)
+                .await?;
+
+            // fee densities of the operations included in recent active blocks
+            let now = MassaTime::compensated_now(compensation_millis)?;
+            let current_slot = get_latest_block_slot_at_timestamp(
+                consensus_settings.thread_count,
+                consensus_settings.t0,
+                consensus_settings.genesis_timestamp,
+                now,
+            )?
+            .unwrap_or_else(|| Slot::new(0, 0));
+            let start_slot = Slot::new(
+                current_slot
+                    .period
+                    .saturating_sub(api_cfg.fee_estimate_lookback_periods),
+                0,
+            );
+            let recent_densities = consensus_command_sender
+                .get_recent_fee_densities(start_slot)
+                .await?;
+
+            let threads = pool_densities
+                .into_iter()
+                .zip(recent_densities)
+                .enumerate()
+                .map(|(thread, (pool, recent_blocks))| {
+                    let pool_requirement = pool
+                        .inclusion_threshold
+                        .map(|threshold| threshold.saturating_add(Amount::from_raw(1)))
+                        .unwrap_or_default();
+                    let recent_floor = recent_blocks
+                        .map(|percentiles| percentiles.p10)
+                        .unwrap_or_default();
+                    let recommended_fee_density = std::cmp::max(pool_requirement, recent_floor);
+                    ThreadFeeEstimate {
+                        thread: thread as u8,
+                        pool: pool.percentiles,
+                        recent_blocks,
+                        recommended_fee_density,
+                        recommended_fee: recommended_fee_density
+                            .saturating_mul_u64(input.operation_size),
+                    }
+                })
+                .collect();
+            Ok(FeeEstimate {
+                operation_size: input.operation_size,
+                target_periods,
+                threads,
+            })
+        };
+        Box::pin(closure())
+    }
+
+    fn verify_signature(
+        &self,
+        input: SignatureVerificationInput,
+    ) -> BoxFuture<Result<bool, ApiError>> {
+        let closure = async move || Ok(input.signed_message.verify(&input.message).is_ok());
+        Box::pin(closure())
+    }
+
     fn node_whitelist(&self, _: Vec<IpAddr>) -> BoxFuture<Result<(), ApiError>> {
         crate::wrong_api::<()>()
     }
@@ -793,4 +1023,43 @@ impl Endpoints for API<Public> {
     fn node_remove_from_whitelist(&self, _: Vec<IpAddr>) -> BoxFuture<Result<(), ApiError>> {
         crate::wrong_api::<()>()
     }
+
+    fn node_reload_bootstrap_policy(&self) -> BoxFuture<Result<(), ApiError>> {
+        crate::wrong_api::<()>()
+    }
+}
+
+/// Number of cycles spanned by the slots from `start` (included) to `end` (excluded)
+fn get_cycle_count(start: Slot, end: Slot, periods_per_cycle: u64) -> u64 {
+    // period of the last slot of the range
+    let last_period = if end.thread == 0 {
+        end.period.saturating_sub(1)
+    } else {
+        end.period
+    };
+    (last_period / periods_per_cycle).saturating_sub(start.get_cycle(periods_per_cycle)) + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_cycle_count() {
+        // the end slot is excluded
+        assert_eq!(get_cycle_count(Slot::new(0, 0), Slot::new(128, 0), 128), 1);
+        assert_eq!(get_cycle_count(Slot::new(0, 0), Slot::new(128, 1), 128), 2);
+        assert_eq!(
+            get_cycle_count(Slot::new(127, 1), Slot::new(128, 0), 128),
+            1
+        );
+        assert_eq!(
+            get_cycle_count(Slot::new(128, 0), Slot::new(256, 0), 128),
+            1
+        );
+        assert_eq!(
+            get_cycle_count(Slot::new(130, 3), Slot::new(400, 5), 128),
+            3
+        );
+    }
 }
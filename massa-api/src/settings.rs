@@ -13,6 +13,12 @@ pub struct APISettings {
     pub bind_private: SocketAddr,
     /// bind for the public API
     pub bind_public: SocketAddr,
+    /// bind for the public `WebSocket` API streaming the pool events
+    pub bind_public_ws: SocketAddr,
+    /// max number of simultaneous subscriptions to the public `WebSocket` API
+    pub max_ws_subscriptions: usize,
     /// max argument count
     pub max_arguments: u64,
+    /// number of past periods whose active blocks are used to estimate fees
+    pub fee_estimate_lookback_periods: u64,
 }
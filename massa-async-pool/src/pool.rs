@@ -35,6 +35,26 @@ impl AsyncPool {
         }
     }
 
+    /// Number of messages in the pool
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Returns true if the pool contains no message
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Messages of the pool, sorted by decreasing priority
+    pub fn get_messages(&self) -> &BTreeMap<AsyncMessageId, AsyncMessage> {
+        &self.messages
+    }
+
+    /// Removes every message from the pool
+    pub fn clear(&mut self) {
+        self.messages.clear();
+    }
+
     /// Applies pre-compiled `AsyncPoolChanges` to the pool without checking for overflows.
     /// This function is used when applying pre-compiled `AsyncPoolChanges` to an `AsyncPool`.
     ///
@@ -1,6 +1,6 @@
 use std::{net::SocketAddr, sync::Arc, time::Duration};
 
-use massa_final_state::FinalState;
+use massa_final_state::{FinalState, FinalStateError};
 use massa_ledger_exports::get_address_from_key;
 use massa_logging::massa_trace;
 use massa_models::Version;
@@ -17,6 +17,7 @@ use tracing::{debug, info, warn};
 
 use crate::{
     client_binder::BootstrapClientBinder,
+    client_parallel::parallel_bootstrap,
     error::BootstrapError,
     messages::{BootstrapClientMessage, BootstrapServerMessage},
     BootstrapSettings, Establisher, GlobalBootstrapState,
@@ -25,7 +26,7 @@ use crate::{
 /// This function will send the starting point to receive a stream of the ledger and will receive and process each part until receive a `BootstrapServerMessage::FinalStateFinished` message from the server.
 /// `next_bootstrap_message` passed as parameter must be `BootstrapClientMessage::AskFinalStatePart` enum's variant.
 /// `next_bootstrap_message` will be updated after receiving each part so that in case of connection lost we can restart from the last message we processed.
-async fn stream_final_state(
+pub(crate) async fn stream_final_state(
     cfg: &BootstrapSettings,
     client: &mut BootstrapClientBinder,
     next_bootstrap_message: &mut BootstrapClientMessage,
@@ -77,14 +78,22 @@ async fn stream_final_state(
                         .async_pool
                         .apply_changes_unchecked(&final_state_changes.async_pool_changes);
                     write_final_state.slot = slot;
-                    if let BootstrapClientMessage::AskFinalStatePart {
-                        last_key: old_key,
-                        last_async_message_id: old_message_id,
-                        ..
-                    } = &next_bootstrap_message
-                    {
-                        debug!("Received ledger batch from {:#?} to {:#?}, an async pool batch from {:#?} to {:#?} a batch of ledger changes of size {:#?} and a batch of async pool changes of size {:#?}. for slot: {:#?}", old_key.clone().map(|key| get_address_from_key(&key)), last_key.clone().map(|key| get_address_from_key(&key)), old_message_id, last_last_async_id, final_state_changes.ledger_changes.0.len(), final_state_changes.async_pool_changes.0.len(), slot);
-                    }
+                    // Keep the previous positions if this part did not contain any ledger or async pool data
+                    let (last_key, last_last_async_id) =
+                        if let BootstrapClientMessage::AskFinalStatePart {
+                            last_key: old_key,
+                            last_async_message_id: old_message_id,
+                            ..
+                        } = &next_bootstrap_message
+                        {
+                            debug!("Received ledger batch from {:#?} to {:#?}, an async pool batch from {:#?} to {:#?} a batch of ledger changes of size {:#?} and a batch of async pool changes of size {:#?}. for slot: {:#?}", old_key.clone().map(|key| get_address_from_key(&key)), last_key.clone().map(|key| get_address_from_key(&key)), old_message_id, last_last_async_id, final_state_changes.ledger_changes.0.len(), final_state_changes.async_pool_changes.0.len(), slot);
+                            (
+                                last_key.or_else(|| old_key.clone()),
+                                last_last_async_id.or(*old_message_id),
+                            )
+                        } else {
+                            (last_key, last_last_async_id)
+                        };
                     // Set new message in case of disconnection
                     *next_bootstrap_message = BootstrapClientMessage::AskFinalStatePart {
                         last_key,
@@ -94,11 +103,18 @@ async fn stream_final_state(
                 }
                 BootstrapServerMessage::FinalStateFinished => {
                     info!("State bootstrap complete");
+                    global_bootstrap_state
+                        .final_state
+                        .read()
+                        .ledger
+                        .reset_bootstrap_progress();
                     *next_bootstrap_message = BootstrapClientMessage::AskBootstrapPeers;
                     return Ok(());
                 }
                 BootstrapServerMessage::SlotTooOld => {
                     info!("Slot is too old retry bootstrap from scratch");
+                    // the parts received so far are attached to a slot the server no longer has
+                    reset_final_state(&global_bootstrap_state.final_state)?;
                     *next_bootstrap_message = BootstrapClientMessage::AskFinalStatePart {
                         last_key: None,
                         slot: None,
@@ -121,17 +137,16 @@ async fn stream_final_state(
     }
 }
 
-/// Gets the state from a bootstrap server (internal private function)
+/// Reads the connection error possibly sent by the server, then performs the handshake and the clock synchronization.
 /// needs to be CANCELLABLE
-async fn bootstrap_from_server(
+///
+/// # Returns
+/// The clock compensation in milliseconds
+pub(crate) async fn handshake_with_server(
     cfg: &BootstrapSettings,
     client: &mut BootstrapClientBinder,
-    next_bootstrap_message: &mut BootstrapClientMessage,
-    global_bootstrap_state: &mut GlobalBootstrapState,
     our_version: Version,
-) -> Result<(), BootstrapError> {
-    massa_trace!("bootstrap.lib.bootstrap_from_server", {});
-
+) -> Result<i64, BootstrapError> {
     // read error (if sent by the server)
     // client.next() is not cancel-safe but we drop the whole client object if cancelled => it's OK
     match tokio::time::timeout(cfg.read_error_timeout.into(), client.next()).await {
@@ -231,7 +246,22 @@ async fn bootstrap_from_server(
         0
     };
 
-    global_bootstrap_state.compensation_millis = compensation_millis;
+    Ok(compensation_millis)
+}
+
+/// Gets the state from a bootstrap server (internal private function)
+/// needs to be CANCELLABLE
+async fn bootstrap_from_server(
+    cfg: &BootstrapSettings,
+    client: &mut BootstrapClientBinder,
+    next_bootstrap_message: &mut BootstrapClientMessage,
+    global_bootstrap_state: &mut GlobalBootstrapState,
+    our_version: Version,
+) -> Result<(), BootstrapError> {
+    massa_trace!("bootstrap.lib.bootstrap_from_server", {});
+
+    global_bootstrap_state.compensation_millis =
+        handshake_with_server(cfg, client, our_version).await?;
 
     let write_timeout: std::time::Duration = cfg.write_timeout.into();
     // Loop to ask data to the server depending on the last message we sent
@@ -310,7 +340,7 @@ async fn bootstrap_from_server(
     Ok(())
 }
 
-async fn send_client_message(
+pub(crate) async fn send_client_message(
     message_to_send: &BootstrapClientMessage,
     client: &mut BootstrapClientBinder,
     write_timeout: Duration,
@@ -329,7 +359,7 @@ async fn send_client_message(
     }
 }
 
-async fn connect_to_server(
+pub(crate) async fn connect_to_server(
     establisher: &mut Establisher,
     bootstrap_settings: &BootstrapSettings,
     addr: &SocketAddr,
@@ -347,6 +377,18 @@ async fn connect_to_server(
     ))
 }
 
+/// Drops the final state received by an unfinished bootstrap, and its progress,
+/// so that the next bootstrap starts from scratch
+fn reset_final_state(final_state: &RwLock<FinalState>) -> Result<(), BootstrapError> {
+    let mut write_final_state = final_state.write();
+    write_final_state
+        .ledger
+        .reset()
+        .map_err(|err| FinalStateError::LedgerError(err.to_string()))?;
+    write_final_state.async_pool.clear();
+    Ok(())
+}
+
 /// Gets the state from a bootstrap server
 /// needs to be CANCELLABLE
 pub async fn get_state(
@@ -362,6 +404,10 @@ pub async fn get_state(
     // if we are before genesis, do not bootstrap
     if now < genesis_timestamp {
         massa_trace!("bootstrap.lib.get_state.init_from_scratch", {});
+        // the entries of a bootstrap interrupted before are not part of the genesis state
+        if final_state.read().ledger.get_bootstrap_progress().is_some() {
+            reset_final_state(&final_state)?;
+        }
         return Ok(GlobalBootstrapState::new(final_state.clone()));
     }
     // we are after genesis => bootstrap
@@ -373,14 +419,52 @@ pub async fn get_state(
     }
     let mut shuffled_list = bootstrap_settings.bootstrap_list.clone();
     shuffled_list.shuffle(&mut StdRng::from_entropy());
-    // Will be none when bootstrap is over
-    let mut next_bootstrap_message: BootstrapClientMessage =
-        BootstrapClientMessage::AskFinalStatePart {
+    let mut global_bootstrap_state = GlobalBootstrapState::new(final_state.clone());
+
+    // An interrupted bootstrap is rather resumed from a single server
+    if bootstrap_settings.parallel_bootstrap_servers > 1
+        && final_state.read().ledger.get_bootstrap_progress().is_none()
+    {
+        match parallel_bootstrap(
+            bootstrap_settings,
+            &shuffled_list,
+            &mut establisher,
+            &mut global_bootstrap_state,
+            version,
+        )
+        .await
+        {
+            Ok(()) => return Ok(global_bootstrap_state),
+            Err(e) => {
+                warn!(
+                    "Parallel bootstrap failed, bootstrapping from one server at a time: {}",
+                    e
+                );
+                // restart from scratch, without the ledger ranges already received
+                reset_final_state(&final_state)?;
+                global_bootstrap_state = GlobalBootstrapState::new(final_state.clone());
+            }
+        }
+    }
+
+    // Resume the ledger stream where an interrupted bootstrap stopped, if any.
+    // The async pool is not persisted so it is received again entirely.
+    let bootstrap_progress = final_state.read().ledger.get_bootstrap_progress();
+    let mut next_bootstrap_message: BootstrapClientMessage = match bootstrap_progress {
+        Some((last_key, slot)) => {
+            info!("Resuming interrupted bootstrap at slot {}", slot);
+            BootstrapClientMessage::AskFinalStatePart {
+                last_key: Some(last_key),
+                slot: Some(slot),
+                last_async_message_id: None,
+            }
+        }
+        None => BootstrapClientMessage::AskFinalStatePart {
             last_key: None,
             slot: None,
             last_async_message_id: None,
-        };
-    let mut global_bootstrap_state = GlobalBootstrapState::new(final_state.clone());
+        },
+    };
     loop {
         for (addr, pub_key) in shuffled_list.iter() {
             if let Some(end) = end_timestamp {
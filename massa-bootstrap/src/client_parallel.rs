@@ -0,0 +1,594 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Parallel bootstrap.
+//!
+//! The ledger key space is split into ranges that are downloaded concurrently
+//! from several bootstrap servers. Every range is consistent at the slot of its
+//! last received part. The server of the last range then streams the state
+//! changes since the oldest of those slots along with the async pool, which
+//! brings the whole final state to a single slot. Finally the async pool and the
+//! consensus state are cross-checked with another server before being accepted.
+
+use std::{net::SocketAddr, sync::Arc};
+
+use futures::future::try_join_all;
+use massa_async_pool::AsyncPool;
+use massa_final_state::FinalState;
+use massa_graph::BootstrapableGraph;
+use massa_ledger_exports::BALANCE_IDENT;
+use massa_logging::massa_trace;
+use massa_models::{constants::ADDRESS_SIZE_BYTES, Address, Slot, Version};
+use massa_proof_of_stake_exports::ExportProofOfStake;
+use massa_signature::PublicKey;
+use nom::AsBytes;
+use parking_lot::RwLock;
+use tracing::{info, warn};
+
+use crate::{
+    client::{connect_to_server, handshake_with_server, send_client_message, stream_final_state},
+    client_binder::BootstrapClientBinder,
+    error::BootstrapError,
+    messages::{BootstrapClientMessage, BootstrapServerMessage},
+    BootstrapSettings, Establisher, GlobalBootstrapState,
+};
+
+/// Part of the ledger key space, delimited by the first byte of the keys (that is the first byte of the addresses)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LedgerRange {
+    /// first byte of the keys at the start of the range
+    start: u8,
+    /// first byte of the keys right after the range, `None` for the last range
+    end: Option<u8>,
+}
+
+impl LedgerRange {
+    /// Splits the key space into `count` ranges of equal width
+    fn split(count: usize) -> Vec<LedgerRange> {
+        let count = count.clamp(1, 256);
+        let starts: Vec<u8> = (0..count)
+            .map(|index| (index * 256 / count) as u8)
+            .collect();
+        starts
+            .iter()
+            .enumerate()
+            .map(|(index, start)| LedgerRange {
+                start: *start,
+                end: starts.get(index + 1).copied(),
+            })
+            .collect()
+    }
+
+    /// Key to send as the last received key so that the server streams the ledger from the start of the range.
+    /// It is the balance key of an address that cannot exist in practice and that is lower than every key of the range.
+    fn start_cursor(&self) -> Option<Vec<u8>> {
+        if self.start == 0 {
+            return None;
+        }
+        let mut address_bytes = [0u8; ADDRESS_SIZE_BYTES];
+        address_bytes[0] = self.start;
+        Some([&address_bytes[..], &[BALANCE_IDENT]].concat())
+    }
+
+    /// Excluded upper bound of the keys of the range
+    fn end_key(&self) -> Option<[u8; 1]> {
+        self.end.map(|end| [end])
+    }
+
+    /// Whether the entries of an address belong to the range
+    fn contains(&self, address: &Address) -> bool {
+        let first_byte = address.to_bytes()[0];
+        first_byte >= self.start && self.end.map_or(true, |end| first_byte < end)
+    }
+
+    /// Whether a key is after the end of the range
+    fn is_past_end(&self, key: &[u8]) -> bool {
+        match (self.end, key.first()) {
+            (Some(end), Some(first_byte)) => *first_byte >= end,
+            _ => false,
+        }
+    }
+}
+
+/// Result of the download of a ledger range
+#[derive(Debug)]
+struct RangeOutcome {
+    /// slot at which the range is consistent, `None` if the server did not send any part
+    slot: Option<Slot>,
+    /// last key received from the server, or the cursor of the start of the range
+    last_key: Option<Vec<u8>>,
+}
+
+/// Downloads the entries of a ledger range and keeps them up to date until the end of the range is reached.
+/// The async pool data is ignored: it is received afterwards from a single server.
+async fn stream_ledger_range(
+    cfg: &BootstrapSettings,
+    client: &mut BootstrapClientBinder,
+    range: LedgerRange,
+    final_state: Arc<RwLock<FinalState>>,
+) -> Result<RangeOutcome, BootstrapError> {
+    let mut outcome = RangeOutcome {
+        slot: None,
+        last_key: range.start_cursor(),
+    };
+    let request = BootstrapClientMessage::AskFinalStatePart {
+        last_key: range.start_cursor(),
+        slot: None,
+        last_async_message_id: None,
+    };
+    match tokio::time::timeout(cfg.write_timeout.into(), client.send(&request)).await {
+        Err(_) => Err(std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            "bootstrap ask ledger range send timed out",
+        )
+        .into()),
+        Ok(Err(e)) => Err(e),
+        Ok(Ok(_)) => Ok(()),
+    }?;
+    let end_key = range.end_key();
+    loop {
+        let msg = match tokio::time::timeout(cfg.read_timeout.into(), client.next()).await {
+            Err(_) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "ledger range bootstrap read timed out",
+                )
+                .into());
+            }
+            Ok(Err(e)) => return Err(e),
+            Ok(Ok(msg)) => msg,
+        };
+        match msg {
+            BootstrapServerMessage::FinalStatePart {
+                ledger_data,
+                slot,
+                mut final_state_changes,
+                ..
+            } => {
+                let last_key = {
+                    let mut write_final_state = final_state.write();
+                    let last_key = write_final_state
+                        .ledger
+                        .set_ledger_range_part(ledger_data, end_key.as_ref().map(|key| &key[..]))?;
+                    // the other ranges are kept up to date by their own server
+                    final_state_changes
+                        .ledger_changes
+                        .0
+                        .retain(|address, _| range.contains(address));
+                    write_final_state
+                        .ledger
+                        .apply_changes(final_state_changes.ledger_changes, slot);
+                    last_key
+                };
+                outcome.slot = Some(slot);
+                if let Some(last_key) = last_key {
+                    let past_end = range.is_past_end(&last_key);
+                    outcome.last_key = Some(last_key);
+                    if past_end {
+                        return Ok(outcome);
+                    }
+                }
+            }
+            BootstrapServerMessage::FinalStateFinished => return Ok(outcome),
+            BootstrapServerMessage::BootstrapError { error } => {
+                return Err(BootstrapError::ReceivedError(error))
+            }
+            other => return Err(BootstrapError::UnexpectedServerMessage(other)),
+        }
+    }
+}
+
+/// Asks the consensus state to a server
+async fn ask_consensus_state(
+    cfg: &BootstrapSettings,
+    client: &mut BootstrapClientBinder,
+) -> Result<(ExportProofOfStake, BootstrapableGraph), BootstrapError> {
+    match send_client_message(
+        &BootstrapClientMessage::AskConsensusState,
+        client,
+        cfg.write_timeout.into(),
+        cfg.read_timeout.into(),
+        "ask consensus state timed out",
+    )
+    .await?
+    {
+        BootstrapServerMessage::ConsensusState { pos, graph } => Ok((pos, graph)),
+        BootstrapServerMessage::BootstrapError { error } => {
+            Err(BootstrapError::ReceivedError(error))
+        }
+        other => Err(BootstrapError::UnexpectedServerMessage(other)),
+    }
+}
+
+/// Sends `BootstrapSuccess` to a server to close the session
+async fn send_bootstrap_success(
+    cfg: &BootstrapSettings,
+    client: &mut BootstrapClientBinder,
+) -> Result<(), BootstrapError> {
+    match tokio::time::timeout(
+        cfg.write_timeout.into(),
+        client.send(&BootstrapClientMessage::BootstrapSuccess),
+    )
+    .await
+    {
+        Err(_) => Err(std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            "send bootstrap success timed out",
+        )
+        .into()),
+        Ok(Err(e)) => Err(e),
+        Ok(Ok(_)) => Ok(()),
+    }
+}
+
+/// State received from the cross-check server
+struct CheckState {
+    /// async pool at `slot`
+    async_pool: AsyncPool,
+    /// slot of the last final state part, `None` if the server did not send any part
+    slot: Option<Slot>,
+    pos: ExportProofOfStake,
+    graph: BootstrapableGraph,
+}
+
+/// Receives the consensus state and the async pool from the cross-check server.
+/// The ledger is streamed from `last_key` to keep the download small, and ignored.
+async fn fetch_check_state(
+    cfg: &BootstrapSettings,
+    client: &mut BootstrapClientBinder,
+    last_key: Option<Vec<u8>>,
+    mut async_pool: AsyncPool,
+) -> Result<CheckState, BootstrapError> {
+    let (pos, graph) = ask_consensus_state(cfg, client).await?;
+    let request = BootstrapClientMessage::AskFinalStatePart {
+        last_key,
+        slot: None,
+        last_async_message_id: None,
+    };
+    match tokio::time::timeout(cfg.write_timeout.into(), client.send(&request)).await {
+        Err(_) => Err(std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            "bootstrap ask async pool send timed out",
+        )
+        .into()),
+        Ok(Err(e)) => Err(e),
+        Ok(Ok(_)) => Ok(()),
+    }?;
+    let mut slot = None;
+    loop {
+        match tokio::time::timeout(cfg.read_timeout.into(), client.next()).await {
+            Err(_) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "async pool bootstrap read timed out",
+                )
+                .into());
+            }
+            Ok(Err(e)) => return Err(e),
+            Ok(Ok(BootstrapServerMessage::FinalStatePart {
+                async_pool_part,
+                slot: part_slot,
+                final_state_changes,
+                ..
+            })) => {
+                async_pool.set_pool_part(async_pool_part.as_bytes())?;
+                async_pool.apply_changes_unchecked(&final_state_changes.async_pool_changes);
+                slot = Some(part_slot);
+            }
+            Ok(Ok(BootstrapServerMessage::FinalStateFinished)) => break,
+            Ok(Ok(BootstrapServerMessage::BootstrapError { error })) => {
+                return Err(BootstrapError::ReceivedError(error))
+            }
+            Ok(Ok(other)) => return Err(BootstrapError::UnexpectedServerMessage(other)),
+        }
+    }
+    Ok(CheckState {
+        async_pool,
+        slot,
+        pos,
+        graph,
+    })
+}
+
+/// Checks that two async pools are compatible.
+/// Pools attached to the same slot must be identical, otherwise the messages they share must be identical.
+fn check_async_pools(
+    pool: &AsyncPool,
+    slot: Option<Slot>,
+    other_pool: &AsyncPool,
+    other_slot: Option<Slot>,
+) -> Result<(), String> {
+    if slot == other_slot {
+        if pool.get_messages() != other_pool.get_messages() {
+            return Err(format!("async pools differ at slot {:?}", slot));
+        }
+        return Ok(());
+    }
+    for (id, message) in pool.get_messages() {
+        if let Some(other_message) = other_pool.get_messages().get(id) {
+            if other_message != message {
+                return Err(format!("async message {:?} differs", id));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that two consensus states are compatible:
+/// * the final blocks must be the same in the periods known by both graphs
+/// * the seeds of the cycles completed in both proof of stake states must be the same
+fn check_consensus_states(
+    (pos, graph): (&ExportProofOfStake, &BootstrapableGraph),
+    (other_pos, other_graph): (&ExportProofOfStake, &BootstrapableGraph),
+) -> Result<(), String> {
+    for (thread, ((block_id, period), (other_block_id, other_period))) in graph
+        .latest_final_blocks_periods
+        .iter()
+        .zip(other_graph.latest_final_blocks_periods.iter())
+        .enumerate()
+    {
+        // the most advanced graph must contain the final block of the other one if it still knows its period
+        let (lowest_block_id, lowest_period, highest_graph) = if period <= other_period {
+            (block_id, period, other_graph)
+        } else {
+            (other_block_id, other_period, graph)
+        };
+        let conflict = highest_graph.active_blocks.values().any(|active_block| {
+            let slot = active_block.block.content.header.content.slot;
+            active_block.is_final
+                && slot.thread as usize == thread
+                && slot.period == *lowest_period
+                && active_block.block_id != *lowest_block_id
+        });
+        if conflict || (period == other_period && block_id != other_block_id) {
+            return Err(format!(
+                "final blocks differ in thread {} at period {}",
+                thread, lowest_period
+            ));
+        }
+    }
+
+    for (thread, (cycles, other_cycles)) in pos
+        .cycle_states
+        .iter()
+        .zip(other_pos.cycle_states.iter())
+        .enumerate()
+    {
+        // the last cycle of each state may not be complete yet
+        let last_common_cycle = match (cycles.back(), other_cycles.back()) {
+            (Some(last), Some(other_last)) => last.cycle.min(other_last.cycle),
+            _ => continue,
+        };
+        for cycle_state in cycles
+            .iter()
+            .filter(|state| state.cycle < last_common_cycle)
+        {
+            let seeds_differ = other_cycles
+                .iter()
+                .find(|state| state.cycle == cycle_state.cycle)
+                .map_or(false, |other_cycle_state| {
+                    other_cycle_state.rng_seed != cycle_state.rng_seed
+                });
+            if seeds_differ {
+                return Err(format!(
+                    "roll seeds differ in thread {} for cycle {}",
+                    thread, cycle_state.cycle
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Gets the state by downloading the ledger from several bootstrap servers concurrently.
+/// needs to be CANCELLABLE
+///
+/// # Arguments
+/// * `servers`: candidate bootstrap servers. The first `parallel_bootstrap_servers` ones download the ledger ranges
+///   and the next ones are candidates for the cross-check.
+pub(crate) async fn parallel_bootstrap(
+    cfg: &BootstrapSettings,
+    servers: &[(SocketAddr, PublicKey)],
+    establisher: &mut Establisher,
+    global_bootstrap_state: &mut GlobalBootstrapState,
+    our_version: Version,
+) -> Result<(), BootstrapError> {
+    massa_trace!("bootstrap.lib.parallel_bootstrap", {});
+    let range_count = cfg
+        .parallel_bootstrap_servers
+        .min(servers.len().saturating_sub(1));
+    if range_count < 2 {
+        return Err(BootstrapError::GeneralError(format!(
+            "parallel bootstrap needs at least 3 bootstrap servers, {} available",
+            servers.len()
+        )));
+    }
+    let (range_servers, check_servers) = servers.split_at(range_count);
+    let ranges = LedgerRange::split(range_count);
+
+    let mut clients = Vec::with_capacity(range_count);
+    for (addr, pub_key) in range_servers {
+        let mut client = connect_to_server(establisher, cfg, addr, pub_key).await?;
+        let compensation_millis = handshake_with_server(cfg, &mut client, our_version).await?;
+        clients.push((*addr, client, compensation_millis));
+    }
+
+    info!(
+        "Downloading the ledger from {} bootstrap servers in parallel",
+        range_count
+    );
+    let final_state = global_bootstrap_state.final_state.clone();
+    let outcomes = try_join_all(clients.iter_mut().zip(ranges.iter()).map(
+        |((_, client, _), range)| stream_ledger_range(cfg, client, *range, final_state.clone()),
+    ))
+    .await?;
+
+    // The stream of the last range is the only one that was not interrupted:
+    // its server brings the whole ledger to its final slot and sends the async pool.
+    // The other connections are closed.
+    let (primary_addr, mut primary, compensation_millis) =
+        clients.pop().expect("at least two ranges were downloaded");
+    drop(clients);
+    let oldest_slot = outcomes.iter().filter_map(|outcome| outcome.slot).min();
+    let newest_slot = outcomes.iter().filter_map(|outcome| outcome.slot).max();
+    let mut next_bootstrap_message = BootstrapClientMessage::AskFinalStatePart {
+        last_key: outcomes.last().and_then(|outcome| outcome.last_key.clone()),
+        slot: oldest_slot,
+        last_async_message_id: None,
+    };
+    stream_final_state(
+        cfg,
+        &mut primary,
+        &mut next_bootstrap_message,
+        global_bootstrap_state,
+    )
+    .await?;
+    if !matches!(
+        next_bootstrap_message,
+        BootstrapClientMessage::AskBootstrapPeers
+    ) {
+        return Err(BootstrapError::GeneralError(format!(
+            "the history of bootstrap server {} does not reach the slot of the ledger ranges",
+            primary_addr
+        )));
+    }
+    let final_slot = final_state.read().slot;
+    if let Some(newest_slot) = newest_slot {
+        if final_slot < newest_slot {
+            return Err(BootstrapError::GeneralError(format!(
+                "bootstrap server {} is late: its final slot {} is before the slot {} of a ledger range",
+                primary_addr, final_slot, newest_slot
+            )));
+        }
+    }
+
+    let peers = match send_client_message(
+        &BootstrapClientMessage::AskBootstrapPeers,
+        &mut primary,
+        cfg.write_timeout.into(),
+        cfg.read_timeout.into(),
+        "ask bootstrap peers timed out",
+    )
+    .await?
+    {
+        BootstrapServerMessage::BootstrapPeers { peers } => peers,
+        BootstrapServerMessage::BootstrapError { error } => {
+            return Err(BootstrapError::ReceivedError(error))
+        }
+        other => return Err(BootstrapError::UnexpectedServerMessage(other)),
+    };
+    let (pos, graph) = ask_consensus_state(cfg, &mut primary).await?;
+
+    // cross-check the async pool and the consensus state with another server
+    let mut empty_pool = final_state.read().async_pool.clone();
+    empty_pool.clear();
+    let last_key = outcomes.last().and_then(|outcome| outcome.last_key.clone());
+    let mut checked = false;
+    for (addr, pub_key) in check_servers {
+        let mut client = match connect_to_server(establisher, cfg, addr, pub_key).await {
+            Ok(client) => client,
+            Err(e) => {
+                warn!(
+                    "Error while connecting to cross-check bootstrap server {}: {}",
+                    addr, e
+                );
+                continue;
+            }
+        };
+        let check_state = match handshake_with_server(cfg, &mut client, our_version).await {
+            Ok(_) => {
+                fetch_check_state(cfg, &mut client, last_key.clone(), empty_pool.clone()).await
+            }
+            Err(e) => Err(e),
+        };
+        let check_state = match check_state {
+            Ok(check_state) => check_state,
+            Err(e) => {
+                warn!(
+                    "Error while cross-checking with bootstrap server {}: {}",
+                    addr, e
+                );
+                continue;
+            }
+        };
+        let _ = send_bootstrap_success(cfg, &mut client).await;
+        let pools_check = {
+            let read_final_state = final_state.read();
+            check_async_pools(
+                &read_final_state.async_pool,
+                Some(read_final_state.slot),
+                &check_state.async_pool,
+                check_state.slot,
+            )
+        };
+        pools_check
+            .and_then(|_| {
+                check_consensus_states((&pos, &graph), (&check_state.pos, &check_state.graph))
+            })
+            .map_err(|disagreement| {
+                BootstrapError::ServersDisagreement(format!(
+                    "{} and {}: {}",
+                    primary_addr, addr, disagreement
+                ))
+            })?;
+        info!("State cross-checked with bootstrap server {}", addr);
+        checked = true;
+        break;
+    }
+    if !checked {
+        return Err(BootstrapError::GeneralError(
+            "no bootstrap server available to cross-check the state".into(),
+        ));
+    }
+
+    send_bootstrap_success(cfg, &mut primary).await?;
+    global_bootstrap_state.compensation_millis = compensation_millis;
+    global_bootstrap_state.peers = Some(peers);
+    global_bootstrap_state.pos = Some(pos);
+    global_bootstrap_state.graph = Some(graph);
+    info!("Successful parallel bootstrap");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LedgerRange;
+    use massa_models::{constants::ADDRESS_SIZE_BYTES, Address};
+
+    #[test]
+    fn test_ledger_ranges() {
+        let ranges = LedgerRange::split(3);
+        assert_eq!(
+            ranges,
+            vec![
+                LedgerRange {
+                    start: 0,
+                    end: Some(85)
+                },
+                LedgerRange {
+                    start: 85,
+                    end: Some(170)
+                },
+                LedgerRange {
+                    start: 170,
+                    end: None
+                },
+            ]
+        );
+        assert_eq!(ranges[0].start_cursor(), None);
+
+        let mut address_bytes = [0u8; ADDRESS_SIZE_BYTES];
+        address_bytes[0] = 85;
+        let address = Address::from_bytes(&address_bytes);
+        assert!(!ranges[0].contains(&address));
+        assert!(ranges[1].contains(&address));
+        assert!(!ranges[2].contains(&address));
+
+        // the cursor of a range is before every key of the range and after every key of the previous one
+        let cursor = ranges[1].start_cursor().unwrap();
+        assert!(cursor.as_slice() > &[84u8, 255, 255][..]);
+        assert!(cursor.as_slice() < &[85u8, 0, 1][..]);
+        assert!(ranges[0].is_past_end(&cursor));
+        assert!(!ranges[1].is_past_end(&cursor));
+        assert!(!ranges[2].is_past_end(&[255]));
+    }
+}
@@ -47,4 +47,8 @@ pub enum BootstrapError {
     IncompatibleVersionError(String),
     /// Received error: {0}
     ReceivedError(String),
+    /// bootstrap servers disagree: {0}
+    ServersDisagreement(String),
+    /// session traffic quota of {0} bytes exceeded
+    SessionQuotaExceeded(u64),
 }
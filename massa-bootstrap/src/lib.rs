@@ -3,9 +3,13 @@
 //!
 //! At start up, if now is after genesis timestamp,
 //! the node will bootstrap from one of the provided bootstrap servers.
+//! In parallel mode, the ledger is downloaded from several servers at once
+//! and the rest of the state is cross-checked between two of them.
 //!
 //! On server side, the server will query consensus for the graph and the ledger,
 //! execution for execution related data and network for the peer list.
+//! Incoming sessions are filtered and rate limited according to a policy
+//! that can be reloaded at runtime.
 //!
 #![feature(async_closure)]
 #![warn(missing_docs)]
@@ -23,15 +27,18 @@ use std::sync::Arc;
 
 mod client;
 mod client_binder;
+mod client_parallel;
 mod error;
 mod establisher;
 mod messages;
+mod policy;
 mod server;
 mod server_binder;
 mod settings;
 pub use client::get_state;
 pub use establisher::types;
-pub use server::{start_bootstrap_server, BootstrapManager};
+pub use policy::{BootstrapPolicyHandle, BootstrapPolicyLoader, BootstrapServerPolicy};
+pub use server::{start_bootstrap_server, BootstrapManager, BootstrapSessionCounter};
 pub use settings::BootstrapSettings;
 
 #[cfg(test)]
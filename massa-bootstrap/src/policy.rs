@@ -0,0 +1,108 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Access control and rate policy of the bootstrap server.
+//!
+//! The policy is read from the `[bootstrap.server_policy]` section of the
+//! configuration and can be reloaded at runtime through a `BootstrapPolicyHandle`.
+use ipnet::IpNet;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::{net::IpAddr, sync::Arc};
+
+/// Access control and rate policy applied by the bootstrap server to incoming sessions
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BootstrapServerPolicy {
+    /// if not empty, only the IPs belonging to one of these ranges are served
+    pub allow_list: Vec<IpNet>,
+    /// IPs belonging to one of these ranges are never served, even if they are in the allow list
+    pub deny_list: Vec<IpNet>,
+    /// bandwidth shared by all the sessions of a given IP, in bytes per second (up and down)
+    pub per_ip_max_bytes_per_second: Option<f64>,
+    /// bandwidth shared by all the sessions, in bytes per second (up and down)
+    pub global_max_bytes_per_second: Option<f64>,
+    /// max number of bytes exchanged (up and down) during a single session
+    pub max_bytes_per_session: Option<u64>,
+    /// refuse to serve when our final state is more than this number of periods behind the current slot
+    pub max_final_lag_periods: Option<u64>,
+}
+
+impl BootstrapServerPolicy {
+    /// Check the allow and deny lists. IPv4-mapped IPv6 addresses are matched as IPv4.
+    pub fn is_allowed(&self, ip: &IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        if self.deny_list.iter().any(|range| range.contains(&ip)) {
+            return false;
+        }
+        self.allow_list.is_empty() || self.allow_list.iter().any(|range| range.contains(&ip))
+    }
+}
+
+/// Function reading the bootstrap server policy from the node configuration
+pub type BootstrapPolicyLoader = fn() -> Result<BootstrapServerPolicy, String>;
+
+/// Shared handle on the policy currently applied by the bootstrap server
+#[derive(Clone)]
+pub struct BootstrapPolicyHandle {
+    policy: Arc<RwLock<BootstrapServerPolicy>>,
+    loader: BootstrapPolicyLoader,
+}
+
+impl BootstrapPolicyHandle {
+    /// Creates a new handle.
+    ///
+    /// # Arguments
+    /// * `policy`: initial policy
+    /// * `loader`: function used to read the policy again on reload
+    pub fn new(policy: BootstrapServerPolicy, loader: BootstrapPolicyLoader) -> Self {
+        BootstrapPolicyHandle {
+            policy: Arc::new(RwLock::new(policy)),
+            loader,
+        }
+    }
+
+    /// copy of the current policy
+    pub fn get(&self) -> BootstrapServerPolicy {
+        self.policy.read().clone()
+    }
+
+    /// Read the policy again from the configuration and apply it to the new sessions.
+    /// The current policy is kept if the configuration cannot be read.
+    pub fn reload(&self) -> Result<BootstrapServerPolicy, String> {
+        let policy = (self.loader)()?;
+        *self.policy.write() = policy.clone();
+        Ok(policy)
+    }
+}
+
+impl std::fmt::Debug for BootstrapPolicyHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("BootstrapPolicyHandle")
+            .field(&*self.policy.read())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allow_and_deny_lists() {
+        let mut policy = BootstrapServerPolicy::default();
+        let ip: IpAddr = "192.168.1.12".parse().unwrap();
+        let mapped_ip: IpAddr = "::ffff:192.168.1.12".parse().unwrap();
+        let other_ip: IpAddr = "10.0.0.1".parse().unwrap();
+        assert!(policy.is_allowed(&ip));
+
+        policy.allow_list = vec!["192.168.0.0/16".parse().unwrap()];
+        assert!(policy.is_allowed(&ip));
+        assert!(policy.is_allowed(&mapped_ip));
+        assert!(!policy.is_allowed(&other_ip));
+
+        policy.deny_list = vec!["192.168.1.0/24".parse().unwrap()];
+        assert!(!policy.is_allowed(&ip));
+        assert!(!policy.is_allowed(&mapped_ip));
+        assert!(policy.is_allowed(&"192.168.2.1".parse().unwrap()));
+    }
+}
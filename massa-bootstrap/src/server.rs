@@ -1,10 +1,14 @@
 use std::{
     collections::{hash_map, HashMap},
     net::{IpAddr, SocketAddr},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
     time::{Duration, Instant},
 };
 
+use async_speed_limit::Limiter;
 use futures::stream::FuturesUnordered;
 use futures::StreamExt;
 use massa_async_pool::AsyncMessageId;
@@ -13,7 +17,11 @@ use massa_final_state::{FinalState, StateChanges};
 use massa_graph::BootstrapableGraph;
 use massa_ledger_exports::get_address_from_key;
 use massa_logging::massa_trace;
-use massa_models::{Slot, Version};
+use massa_models::{
+    constants::{GENESIS_TIMESTAMP, T0, THREAD_COUNT},
+    timeslots::get_latest_block_slot_at_timestamp,
+    Slot, Version,
+};
 use massa_network_exports::{BootstrapPeers, NetworkCommandSender};
 use massa_proof_of_stake_exports::ExportProofOfStake;
 use massa_signature::KeyPair;
@@ -24,18 +32,41 @@ use tracing::{debug, info, warn};
 
 use crate::{
     error::BootstrapError,
+    establisher::types::Duplex,
     messages::{BootstrapClientMessage, BootstrapServerMessage},
+    policy::{BootstrapPolicyHandle, BootstrapServerPolicy},
     server_binder::BootstrapServerBinder,
     BootstrapSettings, Establisher,
 };
 
+/// Shared counter of the bootstrap sessions currently being served
+#[derive(Debug, Clone, Default)]
+pub struct BootstrapSessionCounter(Arc<AtomicUsize>);
+
+impl BootstrapSessionCounter {
+    /// number of bootstrap sessions currently being served
+    pub fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn set(&self, count: usize) {
+        self.0.store(count, Ordering::Relaxed);
+    }
+}
+
 /// handle on the bootstrap server
 pub struct BootstrapManager {
     join_handle: JoinHandle<Result<(), BootstrapError>>,
     manager_tx: mpsc::Sender<()>,
+    session_counter: BootstrapSessionCounter,
 }
 
 impl BootstrapManager {
+    /// get a handle on the number of bootstrap sessions currently being served
+    pub fn get_session_counter(&self) -> BootstrapSessionCounter {
+        self.session_counter.clone()
+    }
+
     /// stop the bootstrap server
     pub async fn stop(self) -> Result<(), BootstrapError> {
         massa_trace!("bootstrap.lib.stop", {});
@@ -57,6 +88,7 @@ pub async fn start_bootstrap_server(
     network_command_sender: NetworkCommandSender,
     final_state: Arc<RwLock<FinalState>>,
     bootstrap_settings: &'static BootstrapSettings,
+    policy: BootstrapPolicyHandle,
     establisher: Establisher,
     keypair: KeyPair,
     compensation_millis: i64,
@@ -65,6 +97,8 @@ pub async fn start_bootstrap_server(
     massa_trace!("bootstrap.lib.start_bootstrap_server", {});
     if let Some(bind) = bootstrap_settings.bind {
         let (manager_tx, manager_rx) = mpsc::channel::<()>(1);
+        let session_counter = BootstrapSessionCounter::default();
+        let server_session_counter = session_counter.clone();
         let join_handle = tokio::spawn(async move {
             BootstrapServer {
                 consensus_command_sender,
@@ -72,12 +106,16 @@ pub async fn start_bootstrap_server(
                 final_state,
                 establisher,
                 manager_rx,
+                session_counter: server_session_counter,
                 bind,
                 keypair,
                 compensation_millis,
                 version,
                 ip_hist_map: HashMap::with_capacity(bootstrap_settings.ip_list_max_size),
                 bootstrap_settings,
+                policy,
+                global_limiter: <Limiter>::new(f64::INFINITY),
+                ip_limiters: HashMap::new(),
             }
             .run()
             .await
@@ -85,6 +123,7 @@ pub async fn start_bootstrap_server(
         Ok(Some(BootstrapManager {
             join_handle,
             manager_tx,
+            session_counter,
         }))
     } else {
         Ok(None)
@@ -97,12 +136,18 @@ struct BootstrapServer {
     final_state: Arc<RwLock<FinalState>>,
     establisher: Establisher,
     manager_rx: mpsc::Receiver<()>,
+    session_counter: BootstrapSessionCounter,
     bind: SocketAddr,
     keypair: KeyPair,
     bootstrap_settings: &'static BootstrapSettings,
     compensation_millis: i64,
     version: Version,
     ip_hist_map: HashMap<IpAddr, Instant>,
+    policy: BootstrapPolicyHandle,
+    /// bandwidth limiter shared by all the sessions
+    global_limiter: Limiter,
+    /// bandwidth limiter shared by the sessions of each IP, and number of sessions using it
+    ip_limiters: HashMap<IpAddr, (Limiter, usize)>,
 }
 
 impl BootstrapServer {
@@ -146,13 +191,33 @@ impl BootstrapServer {
                 }
 
                 // bootstrap session finished
-                Some(_) = bootstrap_sessions.next() => {
+                Some(remote_ip) = bootstrap_sessions.next() => {
+                    self.release_ip_limiter(&remote_ip);
+                    self.session_counter.set(bootstrap_sessions.len());
                     massa_trace!("bootstrap.session.finished", {"active_count": bootstrap_sessions.len()});
                 }
 
                 // listener
                 Ok((dplx, remote_addr)) = listener.accept() => if bootstrap_sessions.len() < self.bootstrap_settings.max_simultaneous_bootstraps.try_into().map_err(|_| BootstrapError::GeneralError("Fail to convert u32 to usize".to_string()))? {
                     massa_trace!("bootstrap.lib.run.select.accept", {"remote_addr": remote_addr});
+                    let policy = self.policy.get();
+
+                    // check the access control lists
+                    if !policy.is_allowed(&remote_addr.ip()) {
+                        refuse_bootstrap(dplx, self.keypair.clone(), self.bootstrap_settings, "This bootstrap server does not serve your IP address.".to_string()).await;
+                        massa_trace!("bootstrap.lib.run.select.accept.refuse_policy", {"remote_addr": remote_addr});
+                        continue;
+                    }
+
+                    // do not spread an outdated state
+                    if let Some(max_lag) = policy.max_final_lag_periods {
+                        if !self.is_synced(max_lag).unwrap_or(false) {
+                            refuse_bootstrap(dplx, self.keypair.clone(), self.bootstrap_settings, "This bootstrap server is not synchronized with the network.".to_string()).await;
+                            debug!("did not bootstrap {}: our final state is lagging behind", remote_addr);
+                            continue;
+                        }
+                    }
+
                     let now = Instant::now();
 
                     // clear IP history if necessary
@@ -169,15 +234,9 @@ impl BootstrapServer {
                     match self.ip_hist_map.entry(remote_addr.ip()) {
                         hash_map::Entry::Occupied(mut occ) => {
                             if now.duration_since(*occ.get()) <= per_ip_min_interval {
-                                let mut server = BootstrapServerBinder::new(dplx, self.keypair.clone(), self.bootstrap_settings.max_bytes_read_write);
-                                let _ = match tokio::time::timeout(self.bootstrap_settings.write_error_timeout.into(), server.send(BootstrapServerMessage::BootstrapError {
-                                    error:
+                                refuse_bootstrap(dplx, self.keypair.clone(), self.bootstrap_settings,
                                     format!("Your last bootstrap on this server was {:#?} ago and you have to wait {:#?} before retrying.", occ.get().elapsed(), per_ip_min_interval.saturating_sub(occ.get().elapsed()))
-                                })).await {
-                                    Err(_) => Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "bootstrap error no available slots send timed out").into()),
-                                    Ok(Err(e)) => Err(e),
-                                    Ok(Ok(_)) => Ok(()),
-                                };
+                                ).await;
                                 // in list, non-expired => refuse
                                 massa_trace!("bootstrap.lib.run.select.accept.refuse_limit", {"remote_addr": remote_addr});
                                 continue;
@@ -211,10 +270,12 @@ impl BootstrapServer {
                     let version = self.version;
                     let (data_pos, data_graph, data_peers, data_execution) = bootstrap_data.clone().unwrap(); // will not panic (checked above)
                     let keypair = self.keypair.clone();
+                    let shared_limiters = self.acquire_limiters(&policy, remote_addr.ip());
                     bootstrap_sessions.push(async move {
                         //Socket lifetime
                         {
-                            let mut server = BootstrapServerBinder::new(dplx, keypair, self.bootstrap_settings.max_bytes_read_write);
+                            let mut server = BootstrapServerBinder::new(dplx, keypair, self.bootstrap_settings.max_bytes_read_write)
+                                .with_shared_limits(shared_limiters, policy.max_bytes_per_session);
                             match manage_bootstrap(self.bootstrap_settings, &mut server, data_pos, data_graph, data_peers, data_execution, compensation_millis, version).await {
                                 Ok(_) => info!("bootstrapped peer {}", remote_addr),
                                 Err(BootstrapError::ReceivedError(error)) => debug!("bootstrap serving error received from peer {}: {}", remote_addr, error),
@@ -225,27 +286,84 @@ impl BootstrapServer {
                                 },
                             }
                         }
+                        remote_addr.ip()
                     });
+                    self.session_counter.set(bootstrap_sessions.len());
                     massa_trace!("bootstrap.session.started", {"active_count": bootstrap_sessions.len()});
                 } else {
-                    let mut server = BootstrapServerBinder::new(dplx, self.keypair.clone(), self.bootstrap_settings.max_bytes_read_write);
-                    let _ = match tokio::time::timeout(self.bootstrap_settings.write_error_timeout.into(), server.send(BootstrapServerMessage::BootstrapError {
-                        error: "Bootstrap failed because the bootstrap server currently has no slots available.".to_string()
-                    })).await {
-                        Err(_) => Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "bootstrap error no available slots send timed out").into()),
-                        Ok(Err(e)) => Err(e),
-                        Ok(Ok(_)) => Ok(()),
-                    };
+                    refuse_bootstrap(dplx, self.keypair.clone(), self.bootstrap_settings, "Bootstrap failed because the bootstrap server currently has no slots available.".to_string()).await;
                     debug!("did not bootstrap {}: no available slots", remote_addr);
                 }
             }
         }
 
         // wait for bootstrap sessions to finish
-        while bootstrap_sessions.next().await.is_some() {}
+        while let Some(remote_ip) = bootstrap_sessions.next().await {
+            self.release_ip_limiter(&remote_ip);
+            self.session_counter.set(bootstrap_sessions.len());
+        }
 
         Ok(())
     }
+
+    /// Check that our final state is at most `max_lag` periods behind the current slot
+    fn is_synced(&self, max_lag: u64) -> Result<bool, BootstrapError> {
+        let now = MassaTime::compensated_now(self.compensation_millis)?;
+        let current_slot =
+            match get_latest_block_slot_at_timestamp(THREAD_COUNT, T0, *GENESIS_TIMESTAMP, now)? {
+                Some(slot) => slot,
+                // before genesis, there is nothing to be late on
+                None => return Ok(true),
+            };
+        let final_slot = self.final_state.read().slot;
+        Ok(current_slot.period <= final_slot.period.saturating_add(max_lag))
+    }
+
+    /// Get the bandwidth limiters shared by a new session of `remote_ip`,
+    /// updated with the speeds of the current policy
+    fn acquire_limiters(
+        &mut self,
+        policy: &BootstrapServerPolicy,
+        remote_ip: IpAddr,
+    ) -> Vec<Limiter> {
+        self.global_limiter
+            .set_speed_limit(policy.global_max_bytes_per_second.unwrap_or(f64::INFINITY));
+        let ip_speed = policy.per_ip_max_bytes_per_second.unwrap_or(f64::INFINITY);
+        let (ip_limiter, session_count) = self
+            .ip_limiters
+            .entry(remote_ip)
+            .or_insert_with(|| (<Limiter>::new(ip_speed), 0));
+        ip_limiter.set_speed_limit(ip_speed);
+        *session_count = session_count.saturating_add(1);
+        vec![ip_limiter.clone(), self.global_limiter.clone()]
+    }
+
+    /// Forget the per-IP limiter of `remote_ip` once its last session is finished
+    fn release_ip_limiter(&mut self, remote_ip: &IpAddr) {
+        if let hash_map::Entry::Occupied(mut occ) = self.ip_limiters.entry(*remote_ip) {
+            occ.get_mut().1 = occ.get().1.saturating_sub(1);
+            if occ.get().1 == 0 {
+                occ.remove();
+            }
+        }
+    }
+}
+
+/// Send an error message explaining why a bootstrap attempt is refused.
+/// Failures are ignored as the connection is dropped right after.
+async fn refuse_bootstrap(
+    dplx: Duplex,
+    keypair: KeyPair,
+    bootstrap_settings: &BootstrapSettings,
+    error: String,
+) {
+    let mut server =
+        BootstrapServerBinder::new(dplx, keypair, bootstrap_settings.max_bytes_read_write);
+    let _ = tokio::time::timeout(
+        bootstrap_settings.write_error_timeout.into(),
+        server.send(BootstrapServerMessage::BootstrapError { error }),
+    )
+    .await;
 }
 
 pub async fn send_final_state_stream(
@@ -285,11 +403,12 @@ pub async fn send_final_state_stream(
                 .get_pool_part(old_last_async_id)?;
             async_pool_data = pool_data;
 
-            if let Some(slot) = old_slot && let Some(key) = &old_key && let Some(async_pool_id) = old_last_async_id && slot != final_state_read.slot {
+            // the async pool may not have been received yet when resuming an interrupted bootstrap
+            if let Some(slot) = old_slot && let Some(key) = &old_key && slot != final_state_read.slot {
                 final_state_changes = final_state_read.get_state_changes_part(
                     slot,
                     get_address_from_key(key).ok_or_else(|| BootstrapError::GeneralError("Malformed key in slot changes".to_string()))?,
-                    async_pool_id,
+                    old_last_async_id,
                 );
             } else {
                 final_state_changes = Ok(StateChanges::default());
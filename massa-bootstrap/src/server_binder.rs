@@ -31,6 +31,9 @@ pub struct BootstrapServerBinder {
     prev_message: Option<Hash>,
     version_serializer: VersionSerializer,
     version_deserializer: VersionDeserializer,
+    shared_limiters: Vec<Limiter>,
+    max_session_bytes: Option<u64>,
+    session_bytes: u64,
 }
 
 impl BootstrapServerBinder {
@@ -52,8 +55,41 @@ impl BootstrapServerBinder {
             prev_message: None,
             version_serializer: VersionSerializer::new(),
             version_deserializer: VersionDeserializer::new(),
+            shared_limiters: Vec::new(),
+            max_session_bytes: None,
+            session_bytes: 0,
         }
     }
+
+    /// Apply limits shared with other sessions and a quota on the session traffic.
+    ///
+    /// # Arguments
+    /// * shared_limiters: bandwidth limiters shared with other sessions (per IP, global...)
+    /// * max_session_bytes: max number of bytes sent and received during the session
+    pub fn with_shared_limits(
+        mut self,
+        shared_limiters: Vec<Limiter>,
+        max_session_bytes: Option<u64>,
+    ) -> Self {
+        self.shared_limiters = shared_limiters;
+        self.max_session_bytes = max_session_bytes;
+        self
+    }
+
+    /// Account for `byte_count` bytes of traffic: check the session quota
+    /// then wait on the shared limiters
+    async fn consume(&mut self, byte_count: usize) -> Result<(), BootstrapError> {
+        self.session_bytes = self.session_bytes.saturating_add(byte_count as u64);
+        if let Some(max_session_bytes) = self.max_session_bytes {
+            if self.session_bytes > max_session_bytes {
+                return Err(BootstrapError::SessionQuotaExceeded(max_session_bytes));
+            }
+        }
+        for limiter in self.shared_limiters.iter() {
+            limiter.consume(byte_count).await;
+        }
+        Ok(())
+    }
 }
 
 impl BootstrapServerBinder {
@@ -108,20 +144,29 @@ impl BootstrapServerBinder {
             }
         };
 
+        let sig_bytes = sig.to_bytes();
+        let msg_len_bytes = msg_len.to_be_bytes_min(self.max_bootstrap_message_size)?;
+
+        // check the session quota and wait on the shared limiters
+        self.consume(
+            sig_bytes
+                .len()
+                .saturating_add(msg_len_bytes.len())
+                .saturating_add(msg_bytes.len()),
+        )
+        .await?;
+
         // send signature
-        self.duplex.write_all(&sig.to_bytes()).await?;
+        self.duplex.write_all(&sig_bytes).await?;
 
         // send message length
-        {
-            let msg_len_bytes = msg_len.to_be_bytes_min(self.max_bootstrap_message_size)?;
-            self.duplex.write_all(&msg_len_bytes).await?;
-        }
+        self.duplex.write_all(&msg_len_bytes).await?;
 
         // send message
         self.duplex.write_all(&msg_bytes).await?;
 
         // save prev sig
-        self.prev_message = Some(Hash::compute_from(&sig.to_bytes()));
+        self.prev_message = Some(Hash::compute_from(&sig_bytes));
 
         Ok(())
     }
@@ -150,6 +195,13 @@ impl BootstrapServerBinder {
         // read message
         let mut msg_bytes = vec![0u8; msg_len as usize];
         self.duplex.read_exact(&mut msg_bytes).await?;
+        self.consume(
+            received_prev_hash
+                .map_or(0, |_| HASH_SIZE_BYTES)
+                .saturating_add(self.size_field_len)
+                .saturating_add(msg_bytes.len()),
+        )
+        .await?;
 
         // check previous hash
         if received_prev_hash != self.prev_message {
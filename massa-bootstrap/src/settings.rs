@@ -1,5 +1,6 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::policy::BootstrapServerPolicy;
 use massa_signature::PublicKey;
 use massa_time::MassaTime;
 use serde::Deserialize;
@@ -39,4 +40,10 @@ pub struct BootstrapSettings {
     pub ip_list_max_size: usize,
     /// Read-Write limitation for a connection in bytes per seconds
     pub max_bytes_read_write: f64,
+    /// Number of bootstrap servers to download the ledger from concurrently, below 2 to disable.
+    /// One more server is needed to cross-check the async pool and the consensus state.
+    pub parallel_bootstrap_servers: usize,
+    /// [server] initial access control and rate policy, reloadable at runtime
+    #[serde(default)]
+    pub server_policy: BootstrapServerPolicy,
 }
@@ -1,5 +1,6 @@
 use std::str::FromStr;
 
+use crate::error::BootstrapError;
 use crate::messages::{BootstrapClientMessage, BootstrapServerMessage};
 use crate::BootstrapSettings;
 use crate::{
@@ -106,6 +107,46 @@ async fn test_binders() {
     client_thread.await.unwrap();
 }
 
+/// The server refuses to send more than its session quota
+#[tokio::test]
+#[serial]
+async fn test_binders_session_quota() {
+    let (bootstrap_settings, server_keypair): &(BootstrapSettings, KeyPair) =
+        &BOOTSTRAP_SETTINGS_KEYPAIR;
+    let (client, server) = duplex(1000000);
+    let mut server = BootstrapServerBinder::new(server, server_keypair.clone(), f64::INFINITY)
+        .with_shared_limits(Vec::new(), Some(2000));
+    let mut client = BootstrapClientBinder::new(
+        client,
+        bootstrap_settings.bootstrap_list[0].1,
+        f64::INFINITY,
+    );
+    let version: Version = Version::from_str("TEST.1.2").unwrap();
+    let peers_message = BootstrapServerMessage::BootstrapPeers {
+        peers: BootstrapPeers(vec![bootstrap_settings.bootstrap_list[0].0.ip(); 10]),
+    };
+
+    let server_thread = tokio::spawn(async move {
+        server.handshake(version).await.unwrap();
+        let mut sent_count = 0;
+        loop {
+            match server.send(peers_message.clone()).await {
+                Ok(()) => sent_count += 1,
+                Err(BootstrapError::SessionQuotaExceeded(2000)) => break,
+                Err(err) => panic!("unexpected error: {}", err),
+            }
+        }
+        assert!(sent_count > 0);
+        sent_count
+    });
+
+    client.handshake(version).await.unwrap();
+    let sent_count = server_thread.await.unwrap();
+    for _ in 0..sent_count {
+        client.next().await.unwrap();
+    }
+}
+
 /// The server and the client will handshake and then send message only from server to client
 #[tokio::test]
 #[serial]
@@ -3,17 +3,19 @@
 use super::{
     mock_establisher,
     tools::{
-        bridge_mock_streams, get_boot_state, get_peers, get_random_final_state_bootstrap,
-        wait_consensus_command, wait_network_command,
+        bridge_mock_streams, get_boot_state, get_dummy_block_id, get_peers,
+        get_random_final_state_bootstrap, wait_consensus_command, wait_network_command,
+        BASE_BOOTSTRAP_IP,
     },
 };
-use crate::BootstrapSettings;
+use crate::{client_parallel::parallel_bootstrap, error::BootstrapError, GlobalBootstrapState};
 use crate::{
     get_state, start_bootstrap_server,
     tests::tools::{
         assert_eq_bootstrap_graph, assert_eq_thread_cycle_states, get_bootstrap_config,
     },
 };
+use crate::{BootstrapPolicyHandle, BootstrapSettings};
 use massa_consensus_exports::{commands::ConsensusCommand, ConsensusCommandSender};
 use massa_final_state::{test_exports::assert_eq_final_state, FinalState};
 use massa_models::Version;
@@ -22,7 +24,7 @@ use massa_signature::KeyPair;
 use massa_time::MassaTime;
 use parking_lot::RwLock;
 use serial_test::serial;
-use std::{str::FromStr, sync::Arc};
+use std::{net::SocketAddr, str::FromStr, sync::Arc};
 use tokio::sync::mpsc;
 
 lazy_static::lazy_static! {
@@ -48,6 +50,7 @@ async fn test_bootstrap_server() {
         NetworkCommandSender(network_cmd_tx),
         final_state.clone(),
         bootstrap_settings,
+        BootstrapPolicyHandle::new(Default::default(), || Ok(Default::default())),
         bootstrap_establisher,
         keypair.clone(),
         0,
@@ -173,3 +176,176 @@ async fn test_bootstrap_server() {
         .await
         .expect("could not stop bootstrap server");
 }
+
+/// Runs a parallel bootstrap from three servers sharing the same final state:
+/// two of them download the ledger ranges and the third one cross-checks the state.
+/// If `disagree` is set, the cross-check server has another final block in thread 0.
+async fn run_parallel_bootstrap(
+    disagree: bool,
+) -> (
+    Result<(), BootstrapError>,
+    GlobalBootstrapState,
+    Arc<RwLock<FinalState>>,
+) {
+    let keypair = KeyPair::generate();
+    let mut settings = get_bootstrap_config(keypair.get_public_key());
+    settings.bootstrap_list = (16..19)
+        .map(|port| {
+            (
+                SocketAddr::new(BASE_BOOTSTRAP_IP, port),
+                keypair.get_public_key(),
+            )
+        })
+        .collect();
+    settings.parallel_bootstrap_servers = 2;
+    let settings: &'static BootstrapSettings = Box::leak(Box::new(settings));
+    let version = Version::from_str("TEST.1.2").unwrap();
+
+    // answer the consensus and network commands of all the servers
+    let (consensus_cmd_tx, mut consensus_cmd_rx) = mpsc::channel::<ConsensusCommand>(5);
+    let (network_cmd_tx, mut network_cmd_rx) = mpsc::channel::<NetworkCommand>(5);
+    let (sent_pos, sent_graph) = get_boot_state();
+    let responder_pos = sent_pos.clone();
+    let responder_graph = sent_graph.clone();
+    let responder = tokio::spawn(async move {
+        // the servers are asked one after the other, the cross-check server last
+        let mut state_requests = 0;
+        loop {
+            tokio::select! {
+                Some(cmd) = consensus_cmd_rx.recv() => {
+                    if let ConsensusCommand::GetBootstrapState(response) = cmd {
+                        state_requests += 1;
+                        let mut graph = responder_graph.clone();
+                        if disagree && state_requests == 3 {
+                            graph.latest_final_blocks_periods[0].0 = get_dummy_block_id("other");
+                        }
+                        response.send((responder_pos.clone(), graph)).unwrap();
+                    }
+                }
+                Some(cmd) = network_cmd_rx.recv() => {
+                    if let NetworkCommand::GetBootstrapPeers(response) = cmd {
+                        response.send(get_peers()).unwrap();
+                    }
+                }
+                else => break,
+            }
+        }
+    });
+
+    let final_state = Arc::new(RwLock::new(get_random_final_state_bootstrap(2)));
+    let mut managers = Vec::new();
+    let mut interfaces = Vec::new();
+    for _ in &settings.bootstrap_list {
+        let (bootstrap_establisher, bootstrap_interface) = mock_establisher::new();
+        managers.push(
+            start_bootstrap_server(
+                ConsensusCommandSender(consensus_cmd_tx.clone()),
+                NetworkCommandSender(network_cmd_tx.clone()),
+                final_state.clone(),
+                settings,
+                BootstrapPolicyHandle::new(Default::default(), || Ok(Default::default())),
+                bootstrap_establisher,
+                keypair.clone(),
+                0,
+                version,
+            )
+            .await
+            .unwrap()
+            .unwrap(),
+        );
+        interfaces.push(bootstrap_interface);
+    }
+    drop(consensus_cmd_tx);
+    drop(network_cmd_tx);
+
+    // route the connections of the client to the server at the requested address
+    let (remote_establisher, mut remote_interface) = mock_establisher::new();
+    let router = tokio::spawn(async move {
+        let mut bridges = Vec::new();
+        for client_port in 10000..10003 {
+            let (remote_rw, conn_addr, resp) = match remote_interface
+                .wait_connection_attempt_from_controller()
+                .await
+            {
+                Ok(attempt) => attempt,
+                Err(_) => break,
+            };
+            let index = settings
+                .bootstrap_list
+                .iter()
+                .position(|(addr, _)| *addr == conn_addr)
+                .expect("client connected to an unknown bootstrap server");
+            resp.send(true)
+                .expect("could not send connection accept to remote");
+            let remote_addr =
+                SocketAddr::from_str(&format!("82.245.72.98:{}", client_port)).unwrap();
+            let bootstrap_rw = interfaces[index]
+                .connect_to_controller(&remote_addr)
+                .await
+                .expect("could not connect to bootstrap");
+            bridges.push(tokio::spawn(bridge_mock_streams(remote_rw, bootstrap_rw)));
+        }
+        bridges
+    });
+
+    let mut establisher = remote_establisher;
+    let mut global_bootstrap_state =
+        GlobalBootstrapState::new(Arc::new(RwLock::new(FinalState::default())));
+    let result = parallel_bootstrap(
+        settings,
+        &settings.bootstrap_list,
+        &mut establisher,
+        &mut global_bootstrap_state,
+        version,
+    )
+    .await;
+    drop(establisher);
+
+    for manager in managers {
+        manager
+            .stop()
+            .await
+            .expect("could not stop bootstrap server");
+    }
+    for bridge in router.await.expect("router join failed") {
+        bridge.await.expect("bridge join failed");
+    }
+    responder.await.expect("responder join failed");
+
+    if result.is_ok() {
+        assert_eq_thread_cycle_states(&sent_pos, global_bootstrap_state.pos.as_ref().unwrap());
+        assert_eq_bootstrap_graph(&sent_graph, global_bootstrap_state.graph.as_ref().unwrap());
+    }
+    (result, global_bootstrap_state, final_state)
+}
+
+#[tokio::test]
+#[serial]
+async fn test_parallel_bootstrap() {
+    let (result, global_bootstrap_state, final_state) = run_parallel_bootstrap(false).await;
+    result.expect("parallel bootstrap failed");
+    assert_eq!(
+        get_peers().0,
+        global_bootstrap_state.peers.unwrap().0,
+        "mismatch between sent and received peers"
+    );
+    // the ledger ranges and the async pool are put together at the slot of the servers
+    assert_eq_final_state(
+        &final_state.read(),
+        &global_bootstrap_state.final_state.read(),
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_parallel_bootstrap_servers_disagreement() {
+    let (result, global_bootstrap_state, _) = run_parallel_bootstrap(true).await;
+    assert!(
+        matches!(result, Err(BootstrapError::ServersDisagreement(_))),
+        "unexpected result: {:?}",
+        result
+    );
+    // nothing is taken from servers that disagree
+    assert!(global_bootstrap_state.graph.is_none());
+    assert!(global_bootstrap_state.peers.is_none());
+}
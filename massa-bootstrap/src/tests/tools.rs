@@ -153,6 +153,8 @@ pub fn get_bootstrap_config(bootstrap_public_key: PublicKey) -> BootstrapSetting
         ip_list_max_size: 10,
         per_ip_min_interval: 10000.into(),
         max_bytes_read_write: std::f64::INFINITY,
+        parallel_bootstrap_servers: 0,
+        server_policy: Default::default(),
     }
 }
 
@@ -311,6 +313,10 @@ pub fn assert_eq_bootstrap_graph(v1: &BootstrapableGraph, v2: &BootstrapableGrap
                 "roll purchases mistmatch"
             );
             assert_eq!(itm1.roll_sales, itm2.roll_sales, "roll sales mismatch");
+            assert_eq!(
+                itm1.roll_slashes, itm2.roll_slashes,
+                "roll slashes mismatch"
+            );
         }
     }
     assert_eq!(v1.best_parents, v2.best_parents, "best parents mismatch");
@@ -366,6 +372,7 @@ pub fn get_boot_state() -> (ExportProofOfStake, BootstrapableGraph) {
                     RollUpdate {
                         roll_purchases: 147,
                         roll_sales: 44788,
+                        roll_slashes: 0,
                     },
                 ),
                 (
@@ -373,6 +380,7 @@ pub fn get_boot_state() -> (ExportProofOfStake, BootstrapableGraph) {
                     RollUpdate {
                         roll_purchases: 8887,
                         roll_sales: 114,
+                        roll_slashes: 0,
                     },
                 ),
             ]
@@ -425,6 +433,7 @@ pub fn get_boot_state() -> (ExportProofOfStake, BootstrapableGraph) {
                         )
                         .unwrap(),
                     ],
+                    denunciations: Vec::new(),
                 },
                 BlockHeaderSerializer::new(),
                 &keypair,
@@ -532,6 +541,7 @@ pub fn get_boot_state() -> (ExportProofOfStake, BootstrapableGraph) {
                     RollUpdate {
                         roll_purchases: 778,
                         roll_sales: 54851,
+                        roll_slashes: 0,
                     },
                 ),
                 (
@@ -539,6 +549,7 @@ pub fn get_boot_state() -> (ExportProofOfStake, BootstrapableGraph) {
                     RollUpdate {
                         roll_purchases: 788778,
                         roll_sales: 11451,
+                        roll_slashes: 0,
                     },
                 ),
             ]
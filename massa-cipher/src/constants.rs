@@ -6,8 +6,11 @@
 
 use pbkdf2::Params;
 
-/// Cipher version
-pub const VERSION: u32 = 0;
+/// Cipher version, written by `encrypt`: Argon2id key derivation
+pub const VERSION: u32 = 1;
+
+/// Cipher version of the files encrypted with a PBKDF2 key derivation, only read by `decrypt`
+pub const PBKDF2_VERSION: u32 = 0;
 
 /// AES-GCM-SIV nonce size.
 ///
@@ -15,11 +18,37 @@ pub const VERSION: u32 = 0;
 /// Nonces need not be random: a counter can be used so long as the values are never repeated under the same key.
 pub const NONCE_SIZE: usize = 12;
 
-/// PBKDF2 salt size.
-pub const SALT_SIZE: usize = 12;
+/// AES-256 key size, which is the size of the derived password hash.
+pub const KEY_SIZE: usize = 32;
+
+/// Argon2id salt size, the salt being random bytes.
+pub const ARGON2_SALT_SIZE: usize = 16;
+
+/// Default Argon2id memory cost, in KiB.
+pub const DEFAULT_MEMORY_COST_KIB: u32 = 19_456;
+
+/// Default Argon2id time cost, in passes over the memory.
+pub const DEFAULT_TIME_COST: u32 = 2;
+
+/// Default Argon2id parallelism, in lanes.
+pub const DEFAULT_PARALLELISM: u32 = 1;
+
+/// Maximum Argon2id memory cost accepted when decrypting, in KiB,
+/// so that a crafted file cannot make the decryption allocate without bound.
+pub const MAX_MEMORY_COST_KIB: u32 = 4 * 1024 * 1024;
+
+/// Maximum Argon2id time cost accepted when decrypting, in passes over the memory,
+/// so that a crafted file cannot make the decryption run for hours.
+pub const MAX_TIME_COST: u32 = 16;
+
+/// Maximum Argon2id parallelism accepted when decrypting, in lanes.
+pub const MAX_PARALLELISM: u32 = 16;
+
+/// PBKDF2 salt size, the salt being alphanumeric characters.
+pub const PBKDF2_SALT_SIZE: usize = 12;
 
 /// PBKDF2 hash parameters.
-pub const HASH_PARAMS: Params = Params {
+pub const PBKDF2_HASH_PARAMS: Params = Params {
     rounds: 10_000,
-    output_length: 32,
+    output_length: KEY_SIZE,
 };
@@ -7,64 +7,144 @@
 use aes_gcm::aead::{Aead, NewAead};
 use aes_gcm::{Aes256Gcm, Key, Nonce};
 use massa_models::DeserializeVarInt;
-use pbkdf2::{
-    password_hash::{PasswordHasher, SaltString},
-    Pbkdf2,
-};
 
-use crate::constants::{HASH_PARAMS, NONCE_SIZE, SALT_SIZE};
+use crate::constants::{ARGON2_SALT_SIZE, NONCE_SIZE, PBKDF2_SALT_SIZE, PBKDF2_VERSION, VERSION};
 use crate::error::CipherError;
+use crate::kdf::{argon2_key, pbkdf2_key, CipherParams};
 
 /// Decryption function using AES-GCM cipher.
+/// The key derivation depends on the cipher version read at the start of the data.
 ///
 /// Read `lib.rs` module documentation for more information.
 pub fn decrypt(password: &str, data: &[u8]) -> Result<(u32, Vec<u8>), CipherError> {
     // parse cipher version
-    let (version, index): (u32, usize) =
-        DeserializeVarInt::from_varint_bytes(data).map_err(|_| {
-            CipherError::DecryptionError(
-                "wallet file truncated: version missing or incomplete".to_string(),
-            )
-        })?;
-
-    // parse PBKDF2 salt
-    let salt_end_index = index + SALT_SIZE;
-    let salt_data = data.get(index..salt_end_index).ok_or_else(|| {
-        CipherError::DecryptionError(
-            "wallet file truncated: salt missing or incomplete".to_string(),
-        )
-    })?;
-    let salt = SaltString::new(std::str::from_utf8(salt_data)?)
-        .map_err(|e| CipherError::DecryptionError(e.to_string()))?;
+    let (version, mut index): (u32, usize) = DeserializeVarInt::from_varint_bytes(data)
+        .map_err(|_| truncated("version missing or incomplete"))?;
 
-    // compute PBKDF2 password hash
-    let password_hash = Pbkdf2
-        .hash_password_customized(password.as_bytes(), None, None, HASH_PARAMS, &salt)
-        .map_err(|e| CipherError::DecryptionError(e.to_string()))?
-        .hash
-        .expect("content is missing after a successful hash");
+    // derive the key from the password
+    let (password_hash, salt_end_index) = match version {
+        VERSION => {
+            let mut costs = [0u32; 3];
+            for cost in costs.iter_mut() {
+                let (value, size): (u32, usize) =
+                    DeserializeVarInt::from_varint_bytes(data.get(index..).unwrap_or_default())
+                        .map_err(|_| {
+                            truncated("key derivation parameters missing or incomplete")
+                        })?;
+                *cost = value;
+                index += size;
+            }
+            let params = CipherParams {
+                memory_cost_kib: costs[0],
+                time_cost: costs[1],
+                parallelism: costs[2],
+            };
+            let salt_end_index = index + ARGON2_SALT_SIZE;
+            let salt = data
+                .get(index..salt_end_index)
+                .ok_or_else(|| truncated("salt missing or incomplete"))?;
+            let key = argon2_key(password, salt, &params).map_err(CipherError::DecryptionError)?;
+            (key.to_vec(), salt_end_index)
+        }
+        PBKDF2_VERSION => {
+            let salt_end_index = index + PBKDF2_SALT_SIZE;
+            let salt = data
+                .get(index..salt_end_index)
+                .ok_or_else(|| truncated("salt missing or incomplete"))?;
+            (pbkdf2_key(password, salt)?, salt_end_index)
+        }
+        _ => {
+            return Err(CipherError::DecryptionError(format!(
+                "unsupported cipher version: {}",
+                version
+            )))
+        }
+    };
 
     // parse AES-GCM nonce
     let nonce_end_index = salt_end_index + NONCE_SIZE;
-    let nonce = Nonce::from_slice(data.get(salt_end_index..nonce_end_index).ok_or_else(|| {
-        CipherError::DecryptionError(
-            "wallet file truncated: nonce missing or incomplete".to_string(),
-        )
-    })?);
+    let nonce = Nonce::from_slice(
+        data.get(salt_end_index..nonce_end_index)
+            .ok_or_else(|| truncated("nonce missing or incomplete"))?,
+    );
 
     // decrypt the data
-    let cipher = Aes256Gcm::new(Key::from_slice(password_hash.as_bytes()));
+    let cipher = Aes256Gcm::new(Key::from_slice(&password_hash));
     let decrypted_bytes = cipher
         .decrypt(
             nonce,
-            data.get(nonce_end_index..).ok_or_else(|| {
-                CipherError::DecryptionError(
-                    "wallet file truncated: encrypted data missing or incomplete".to_string(),
-                )
-            })?,
+            data.get(nonce_end_index..)
+                .ok_or_else(|| truncated("encrypted data missing or incomplete"))?,
         )
         .map_err(|_| {
             CipherError::DecryptionError("wrong password or corrupted data".to_string())
         })?;
     Ok((version, decrypted_bytes))
 }
+
+/// Error of a file too short to be decrypted
+fn truncated(reason: &str) -> CipherError {
+    CipherError::DecryptionError(format!("wallet file truncated: {}", reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::PBKDF2_HASH_PARAMS;
+    use crate::encrypt::encrypt_with_params;
+    use massa_models::SerializeVarInt;
+    use pbkdf2::password_hash::{PasswordHasher, Salt};
+    use pbkdf2::Pbkdf2;
+
+    #[test]
+    fn test_decrypt_versions() {
+        let params = CipherParams {
+            memory_cost_kib: 1024,
+            time_cost: 1,
+            parallelism: 1,
+        };
+        let encrypted = encrypt_with_params("password", b"content", &params).unwrap();
+        let (version, decrypted) = decrypt("password", &encrypted).unwrap();
+        assert_eq!(version, VERSION);
+        assert_eq!(decrypted, b"content");
+        assert!(decrypt("wrong password", &encrypted).is_err());
+        assert!(decrypt("password", &encrypted[..encrypted.len() / 2]).is_err());
+
+        // files written before Argon2id are still read
+        let salt = Salt::new("0123456789ab").unwrap();
+        let key = Pbkdf2
+            .hash_password_customized(b"password", None, None, PBKDF2_HASH_PARAMS, salt)
+            .unwrap()
+            .hash
+            .unwrap();
+        let nonce_bytes = [7u8; NONCE_SIZE];
+        let mut legacy = PBKDF2_VERSION.to_varint_bytes();
+        legacy.extend(salt.as_bytes());
+        legacy.extend(nonce_bytes);
+        legacy.extend(
+            Aes256Gcm::new(Key::from_slice(key.as_bytes()))
+                .encrypt(Nonce::from_slice(&nonce_bytes), b"content".as_ref())
+                .unwrap(),
+        );
+        let (version, decrypted) = decrypt("password", &legacy).unwrap();
+        assert_eq!(version, PBKDF2_VERSION);
+        assert_eq!(decrypted, b"content");
+    }
+
+    #[test]
+    fn test_decrypt_bounds_costs() {
+        for (time_cost, parallelism) in [
+            (crate::constants::MAX_TIME_COST + 1, 1u32),
+            (1, crate::constants::MAX_PARALLELISM + 1),
+        ] {
+            let mut crafted = VERSION.to_varint_bytes();
+            crafted.extend(1024u32.to_varint_bytes());
+            crafted.extend(time_cost.to_varint_bytes());
+            crafted.extend(parallelism.to_varint_bytes());
+            crafted.extend([0u8; ARGON2_SALT_SIZE + NONCE_SIZE + 32]);
+            // refused before deriving the key, not as a wrong password
+            let err = decrypt("password", &crafted).unwrap_err();
+            assert!(err.to_string().contains("Argon2id"), "{}", err);
+        }
+    }
+}
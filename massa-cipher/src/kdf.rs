@@ -0,0 +1,87 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! massa-cipher key derivation module.
+//!
+//! Read `lib.rs` module documentation for more information.
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use pbkdf2::{
+    password_hash::{PasswordHasher, SaltString},
+    Pbkdf2,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{
+    DEFAULT_MEMORY_COST_KIB, DEFAULT_PARALLELISM, DEFAULT_TIME_COST, KEY_SIZE, MAX_MEMORY_COST_KIB,
+    MAX_PARALLELISM, MAX_TIME_COST, PBKDF2_HASH_PARAMS,
+};
+use crate::error::CipherError;
+
+/// Argon2id cost parameters, stored next to the encrypted data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CipherParams {
+    /// memory cost, in KiB
+    pub memory_cost_kib: u32,
+    /// time cost, in passes over the memory
+    pub time_cost: u32,
+    /// parallelism, in lanes
+    pub parallelism: u32,
+}
+
+impl Default for CipherParams {
+    fn default() -> Self {
+        CipherParams {
+            memory_cost_kib: DEFAULT_MEMORY_COST_KIB,
+            time_cost: DEFAULT_TIME_COST,
+            parallelism: DEFAULT_PARALLELISM,
+        }
+    }
+}
+
+/// Derives the AES-GCM key from the password with Argon2id
+pub(crate) fn argon2_key(
+    password: &str,
+    salt: &[u8],
+    params: &CipherParams,
+) -> Result<[u8; KEY_SIZE], String> {
+    if params.memory_cost_kib > MAX_MEMORY_COST_KIB {
+        return Err(format!(
+            "Argon2id memory cost above {} KiB",
+            MAX_MEMORY_COST_KIB
+        ));
+    }
+    if params.time_cost > MAX_TIME_COST {
+        return Err(format!("Argon2id time cost above {} passes", MAX_TIME_COST));
+    }
+    if params.parallelism > MAX_PARALLELISM {
+        return Err(format!(
+            "Argon2id parallelism above {} lanes",
+            MAX_PARALLELISM
+        ));
+    }
+    let argon2_params = Params::new(
+        params.memory_cost_kib,
+        params.time_cost,
+        params.parallelism,
+        Some(KEY_SIZE),
+    )
+    .map_err(|e| e.to_string())?;
+    let mut key = [0u8; KEY_SIZE];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, argon2_params)
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|e| e.to_string())?;
+    Ok(key)
+}
+
+/// Derives the AES-GCM key from the password with PBKDF2, for files of the `PBKDF2_VERSION`
+pub(crate) fn pbkdf2_key(password: &str, salt_data: &[u8]) -> Result<Vec<u8>, CipherError> {
+    let salt = SaltString::new(std::str::from_utf8(salt_data)?)
+        .map_err(|e| CipherError::DecryptionError(e.to_string()))?;
+    Ok(Pbkdf2
+        .hash_password_customized(password.as_bytes(), None, None, PBKDF2_HASH_PARAMS, &salt)
+        .map_err(|e| CipherError::DecryptionError(e.to_string()))?
+        .hash
+        .expect("content is missing after a successful hash")
+        .as_bytes()
+        .to_vec())
+}
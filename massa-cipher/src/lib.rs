@@ -7,8 +7,14 @@
 //! AES-GCM is a state-of-the-art high-performance Authenticated Encryption with Associated Data (AEAD)
 //! that provides confidentiality and authenticity.
 //!
-//! To hash the password before using it as a cipher key, we use the PBKDF2 key derivation function
-//! as specified in [RFC 2898](https://datatracker.ietf.org/doc/html/rfc2898).
+//! To hash the password before using it as a cipher key, we use the Argon2id key derivation function
+//! as specified in [RFC 9106](https://datatracker.ietf.org/doc/html/rfc9106), with a random salt.
+//! Its memory and time costs are stored next to the encrypted data, so that they can be raised later
+//! without breaking the existing files.
+//!
+//! The encrypted data starts with the cipher version. Files of version 0 were encrypted with a key
+//! derived with PBKDF2 as specified in [RFC 2898](https://datatracker.ietf.org/doc/html/rfc2898):
+//! they are still decrypted, and are upgraded by encrypting their content again.
 //!
 //! The AES-GCM crate we use has received one security audit by NCC Group, with no significant findings.
 
@@ -16,7 +22,10 @@ mod constants;
 mod decrypt;
 mod encrypt;
 mod error;
+mod kdf;
 
+pub use constants::VERSION;
 pub use decrypt::decrypt;
-pub use encrypt::encrypt;
+pub use encrypt::{encrypt, encrypt_with_params};
 pub use error::CipherError;
+pub use kdf::CipherParams;
@@ -0,0 +1,304 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Batch mode: runs the commands of a script, one per line, and stops at the first error.
+//!
+//! Besides the client commands, a script can use:
+//! * `set NAME value`: sets a variable
+//! * `let NAME = command parameters...`: runs a command and stores its JSON output in a variable,
+//!   failing if the command has no output, as most commands outside of json mode
+//! * `wait_final OperationId [TimeoutMillis]`: waits until an operation is final
+//! * `wait_period N`: waits until N more periods have started
+//!
+//! `${NAME}` is replaced in the parameters by the value of a variable or environment variable.
+//! Strings are inserted as is, a list of a single item is replaced by that item,
+//! and the other values by their JSON text.
+//! Empty lines and lines starting with `#` are ignored.
+
+use crate::cmds::{Command, DEFAULT_WAIT_FINAL_TIMEOUT, WAIT_FINAL_POLL_INTERVAL};
+use crate::repl::Output;
+use anyhow::{anyhow, bail, Result};
+use console::style;
+use massa_models::timeslots::{get_block_slot_timestamp, get_current_latest_block_slot};
+use massa_models::{OperationId, Slot};
+use massa_sdk::Client;
+use massa_time::MassaTime;
+use massa_wallet::Wallet;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+
+/// JSON output of a step of the script
+#[derive(Serialize)]
+struct StepOutput<'a> {
+    /// line of the step in the script
+    line: usize,
+    /// the step, before variables are replaced
+    command: &'a str,
+    /// output of the step, if it succeeded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output: Option<Value>,
+    /// error of the step, if it failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Runs the script at `path`, or the standard input if `path` is `-`.
+/// In json mode, one JSON object is written per step.
+pub(crate) async fn run(
+    client: &Client,
+    wallet: &mut Wallet,
+    path: &Path,
+    json: bool,
+) -> Result<()> {
+    let script = if path == Path::new("-") {
+        let mut script = String::new();
+        std::io::stdin().read_to_string(&mut script)?;
+        script
+    } else {
+        tokio::fs::read_to_string(path).await?
+    };
+    let mut variables = HashMap::new();
+    for (index, line) in script.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if !json {
+            println!("{} {}", style(">").dim(), line);
+        }
+        let result = run_step(client, wallet, &mut variables, line, json).await;
+        if json {
+            let (output, error) = match &result {
+                Ok(output) => (Some(output.clone()), None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            println!(
+                "{}",
+                serde_json::to_string(&StepOutput {
+                    line: index + 1,
+                    command: line,
+                    output,
+                    error,
+                })?
+            );
+        } else if let Err(e) = &result {
+            println!("{}", style(format!("Error: {}", e)).red());
+        }
+        if result.is_err() {
+            bail!("the script stopped at line {}", index + 1);
+        }
+    }
+    Ok(())
+}
+
+/// Runs a step of the script and returns its JSON output.
+/// Outside of json mode, the output is also printed.
+async fn run_step(
+    client: &Client,
+    wallet: &mut Wallet,
+    variables: &mut HashMap<String, Value>,
+    line: &str,
+    json: bool,
+) -> Result<Value> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let (target, words) = match words[..] {
+        ["set", name, ref value @ ..] => {
+            let value = value
+                .iter()
+                .map(|word| substitute(word, variables))
+                .collect::<Result<Vec<_>>>()?
+                .join(" ");
+            variables.insert(name.to_string(), Value::String(value));
+            return Ok(Value::Null);
+        }
+        ["let", name, "=", ref command @ ..] if !command.is_empty() => {
+            (Some(name.to_string()), command)
+        }
+        ["let", ..] => bail!("expected `let NAME = command parameters...`"),
+        _ => (None, &words[..]),
+    };
+    let parameters = words[1..]
+        .iter()
+        .map(|word| substitute(word, variables))
+        .collect::<Result<Vec<String>>>()?;
+    let output: Box<dyn Output> = match words[0] {
+        "wait_final" => wait_final(client, &parameters, json).await?,
+        "wait_period" => wait_period(client, &parameters, json).await?,
+        command => {
+            let command: Command = command
+                .parse()
+                .map_err(|_| anyhow!("command {} not found", command))?;
+            command.run(client, wallet, &parameters, json).await?
+        }
+    };
+    if !json {
+        output.pretty_print();
+    }
+    let value = output.to_json()?;
+    if let Some(name) = target {
+        if value.is_null() {
+            bail!(
+                "{} has no output to store in {}, outside of json mode most commands only print their result: run the script with --json",
+                words[0],
+                name
+            );
+        }
+        variables.insert(name, value.clone());
+    }
+    Ok(value)
+}
+
+/// Replaces the `${NAME}` patterns of a word by the value of the variables
+fn substitute(word: &str, variables: &HashMap<String, Value>) -> Result<String> {
+    let mut result = String::new();
+    let mut rest = word;
+    while let Some(start) = rest.find("${") {
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| anyhow!("unclosed variable in {}", word))?
+            + start;
+        let name = &rest[start + 2..end];
+        result.push_str(&rest[..start]);
+        match variables.get(name) {
+            Some(value) => result.push_str(&render(value)),
+            None => result.push_str(
+                &std::env::var(name).map_err(|_| anyhow!("undefined variable {}", name))?,
+            ),
+        }
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Text inserted in place of a variable
+fn render(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(items) if items.len() == 1 => render(&items[0]),
+        other => other.to_string(),
+    }
+}
+
+/// Waits until an operation is final
+async fn wait_final(client: &Client, parameters: &[String], json: bool) -> Result<Box<dyn Output>> {
+    if parameters.is_empty() || parameters.len() > 2 {
+        bail!("wrong number of parameters");
+    }
+    let id = parameters[0].parse::<OperationId>()?;
+    let timeout = match parameters.get(1) {
+        Some(timeout) => MassaTime::from(timeout.parse::<u64>()?),
+        None => MassaTime::from(DEFAULT_WAIT_FINAL_TIMEOUT),
+    };
+    if !json {
+        println!("Waiting for operation {} to be final...", id);
+    }
+    let info = client
+        .public
+        .wait_operation_final(id, timeout, MassaTime::from(WAIT_FINAL_POLL_INTERVAL))
+        .await
+        .map_err(|e| anyhow!("{}", e))?;
+    Ok(Box::new(vec![info]))
+}
+
+/// Waits until N more periods have started
+async fn wait_period(
+    client: &Client,
+    parameters: &[String],
+    json: bool,
+) -> Result<Box<dyn Output>> {
+    if parameters.len() != 1 {
+        bail!("wrong number of parameters");
+    }
+    let count = parameters[0].parse::<u64>()?;
+    let cfg = client
+        .public
+        .get_status()
+        .await
+        .map_err(|e| anyhow!("check if your node is running: {}", e))?
+        .config;
+    let slot = get_current_latest_block_slot(cfg.thread_count, cfg.t0, cfg.genesis_timestamp, 0)? // clock compensation is zero
+        .unwrap_or_else(|| Slot::new(0, 0));
+    let target = Slot::new(slot.period + count, 0);
+    if !json {
+        println!("Waiting for period {}...", target.period);
+    }
+    let target_timestamp =
+        get_block_slot_timestamp(cfg.thread_count, cfg.t0, cfg.genesis_timestamp, target)?;
+    let remaining = target_timestamp.saturating_sub(MassaTime::now()?);
+    tokio::time::sleep(remaining.to_duration()).await;
+    Ok(Box::new(()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::net::IpAddr;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_substitute() {
+        let mut variables = HashMap::new();
+        variables.insert("name".to_string(), Value::String("alice".into()));
+        variables.insert("ids".to_string(), serde_json::json!(["op1"]));
+        variables.insert("count".to_string(), serde_json::json!(3));
+        variables.insert("pair".to_string(), serde_json::json!([1, 2]));
+        assert_eq!(substitute("${name}", &variables).unwrap(), "alice");
+        assert_eq!(
+            substitute("a${ids}b${count}", &variables).unwrap(),
+            "aop1b3"
+        );
+        assert_eq!(substitute("${pair}", &variables).unwrap(), "[1,2]");
+        assert_eq!(substitute("plain", &variables).unwrap(), "plain");
+        assert!(substitute("${missing_batch_variable}", &variables).is_err());
+        assert!(substitute("${name", &variables).is_err());
+    }
+
+    async fn test_client_and_wallet(dir: &TempDir) -> (Client, Wallet) {
+        // no node is needed by the commands of the tests
+        let client = Client::new(IpAddr::from([127, 0, 0, 1]), 1, 2)
+            .await
+            .unwrap();
+        let wallet = Wallet::new(dir.path().join("wallet.dat"), "password".into()).unwrap();
+        (client, wallet)
+    }
+
+    #[tokio::test]
+    async fn test_let_without_output() {
+        let dir = TempDir::new().unwrap();
+        let (client, mut wallet) = test_client_and_wallet(&dir).await;
+        let mut variables = HashMap::new();
+
+        // outside of json mode, the command only prints its result
+        let line = "let ADDRESS = wallet_generate_secret_key";
+        assert!(run_step(&client, &mut wallet, &mut variables, line, false)
+            .await
+            .is_err());
+        assert!(!variables.contains_key("ADDRESS"));
+
+        let address = run_step(&client, &mut wallet, &mut variables, line, true)
+            .await
+            .unwrap();
+        assert!(address.is_string());
+        assert_eq!(variables.get("ADDRESS"), Some(&address));
+    }
+
+    #[tokio::test]
+    async fn test_run_stops_at_first_error() {
+        let dir = TempDir::new().unwrap();
+        let (client, mut wallet) = test_client_and_wallet(&dir).await;
+        let script = dir.path().join("script.txt");
+        std::fs::write(
+            &script,
+            "# comment\nset A 1\nnot_a_command ${A}\nwallet_generate_secret_key\n",
+        )
+        .unwrap();
+
+        let err = run(&client, &mut wallet, &script, true).await.unwrap_err();
+        assert_eq!(err.to_string(), "the script stopped at line 3");
+        // the steps after the error are not run
+        assert!(wallet.get_wallet_address_list().is_empty());
+    }
+}
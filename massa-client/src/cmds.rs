@@ -3,22 +3,31 @@
 use crate::repl::Output;
 use anyhow::{anyhow, bail, Result};
 use console::style;
+use dialoguer::{Confirm, Password};
 use massa_models::api::{
-    AddressInfo, CompactAddressInfo, DatastoreEntryInput, EventFilter, OperationInput,
+    AddressInfo, CompactAddressInfo, DatastoreEntryInput, EventFilter, FeeEstimateInput,
+    OperationInput, SelectionDrawsInput, SelectionDrawsOutput,
 };
 use massa_models::api::{ReadOnlyBytecodeExecution, ReadOnlyCall};
+use massa_models::execution::{ExecuteReadOnlyResponse, ReadOnlyResult};
 use massa_models::node::NodeId;
-use massa_models::prehash::Map;
-use massa_models::timeslots::get_current_latest_block_slot;
+use massa_models::output_event::SCOutputEvent;
+use massa_models::prehash::{Map, Set};
+use massa_models::signed_message::SignedMessage;
+use massa_models::timeslots::{get_block_slot_timestamp, get_current_latest_block_slot};
+use massa_models::wrapped::WrappedContent;
 use massa_models::{
-    Address, Amount, BlockId, EndorsementId, Operation, OperationId, OperationType, Slot,
+    Address, Amount, BlockId, CompactConfig, EndorsementId, ModelsError, MultisigPolicy,
+    MultisigProof, Operation, OperationId, OperationSerializer, OperationType, Slot,
+    WrappedOperation,
 };
-use massa_sdk::Client;
-use massa_signature::KeyPair;
+use massa_sdk::{rejected_operations, Client};
+use massa_serialization::Serializer;
+use massa_signature::{KeyPair, PublicKey, Signature};
 use massa_time::MassaTime;
-use massa_wallet::{Wallet, WalletError};
-use serde::Serialize;
-use std::collections::HashMap;
+use massa_wallet::{ExternalSigner, ExternalSignerEndpoint, Wallet, WalletError};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Write as _;
 use std::fmt::{Debug, Display};
 use std::net::IpAddr;
@@ -108,6 +117,12 @@ pub enum Command {
     )]
     node_remove_from_whitelist,
 
+    #[strum(
+        ascii_case_insensitive,
+        message = "reload the bootstrap server access control and rate policy from the node configuration"
+    )]
+    node_reload_bootstrap_policy,
+
     #[strum(
         ascii_case_insensitive,
         message = "show the status of the node (reachable? number of peers connected, consensus, version, config parameter summary...)"
@@ -121,6 +136,13 @@ pub enum Command {
     )]
     get_addresses,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address1 Address2 ... start=Slot end=Slot"),
+        message = "show the block and endorsement draws of a list of addresses between two slots (from the current slot by default), flagging the addresses the node is not staking"
+    )]
+    get_selection_draws,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "Address Key"),
@@ -158,6 +180,13 @@ pub enum Command {
     )]
     get_filtered_sc_output_event,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "OperationSize TargetPeriods"),
+        message = "show the fee densities of pending and recently included operations, and the fee recommended to include an operation of the given size in bytes within the given number of periods"
+    )]
+    get_fee_estimate,
+
     #[strum(
         ascii_case_insensitive,
         message = "show wallet info (keys, addresses, balances ...)"
@@ -170,6 +199,24 @@ pub enum Command {
     )]
     wallet_generate_secret_key,
 
+    #[strum(
+        ascii_case_insensitive,
+        message = "generate a seed phrase from which the wallet derives its accounts, and derive the first one"
+    )]
+    wallet_generate_mnemonic,
+
+    #[strum(
+        ascii_case_insensitive,
+        message = "restore the seed phrase from which the wallet derives its accounts, and derive the first one. The seed phrase is prompted for, so that it is not saved in the command history"
+    )]
+    wallet_restore_mnemonic,
+
+    #[strum(
+        ascii_case_insensitive,
+        message = "derive the next account from the seed phrase of the wallet"
+    )]
+    wallet_derive_account,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "SecretKey1 SecretKey2 ..."),
@@ -184,13 +231,85 @@ pub enum Command {
     )]
     wallet_remove_addresses,
 
+    #[strum(
+        ascii_case_insensitive,
+        message = "write the wallet file again with the current encryption, to upgrade an outdated file"
+    )]
+    wallet_reencrypt,
+
+    #[strum(
+        ascii_case_insensitive,
+        message = "change the password of the wallet file, which is also written with the current encryption"
+    )]
+    wallet_change_password,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Threshold PublicKey1 PublicKey2 ..."),
+        message = "add to the wallet a multisig address requiring Threshold signatures among the given public keys"
+    )]
+    wallet_add_multisig,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(
+            args = "PublicKey command Program [Arg1 Arg2 ...] | PublicKey tcp IpAddress:Port | PublicKey unix SocketPath"
+        ),
+        message = "add to the wallet the address of a public key whose operations are signed by an external signer, such as a hardware wallet"
+    )]
+    wallet_add_external_signer,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Label Address"),
+        message = "label an address, in the wallet or not, so that the label can be typed in place of the address"
+    )]
+    wallet_set_label,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Label"),
+        message = "remove a label from the address book of the wallet"
+    )]
+    wallet_remove_label,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "[FilePath]"),
+        message = "write the labels of the wallet to a JSON file, or print them if no file is given"
+    )]
+    wallet_export_labels,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "FilePath [--force]"),
+        message = "add to the wallet the labels of a JSON file written by wallet_export_labels, refusing the labels already used for other addresses unless --force is given"
+    )]
+    wallet_import_labels,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "Address string"),
-        message = "sign provided string with given address (address must be in the wallet)"
+        message = "sign provided string with given address (address must be in the wallet), returning only the public key and the signature of wallet_sign_message"
     )]
     wallet_sign,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address text Message | Address file FilePath"),
+        message = "sign a text or the content of a file with a wallet address, in an envelope that can not be mistaken for an operation"
+    )]
+    wallet_sign_message,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(
+            args = "Address PublicKey Signature text Message | Address PublicKey Signature file FilePath"
+        ),
+        message = "check that a text or the content of a file was signed by an address with wallet_sign_message"
+    )]
+    verify_signature,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "Address RollCount Fee"),
@@ -207,11 +326,41 @@ pub enum Command {
 
     #[strum(
         ascii_case_insensitive,
-        props(args = "SenderAddress ReceiverAddress Amount Fee"),
-        message = "send coins from a wallet address"
+        props(args = "SenderAddress ReceiverAddress Amount [Fee]"),
+        message = "send coins from a wallet address, the fee is estimated if omitted"
     )]
     send_transaction,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(
+            args = "OutputFile SenderAddress transaction ReceiverAddress Amount [Fee] | OutputFile SenderAddress buy_rolls/sell_rolls RollCount [Fee] | OutputFile SenderAddress execute_smart_contract PathToBytecode MaxGas GasPrice Coins [Fee] | OutputFile SenderAddress call_smart_contract TargetAddress FunctionName Parameter MaxGas GasPrice Coins [Fee]"
+        ),
+        message = "write an unsigned operation to a file, to be signed offline with sign_operation. The fee is estimated if omitted"
+    )]
+    build_operation,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "UnsignedFile OutputFile"),
+        message = "sign an operation written by build_operation with a wallet address, without contacting the node"
+    )]
+    sign_operation,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "InputFile OutputFile"),
+        message = "add the signatures of the wallet keys to an operation of a multisig address, written by build_operation or by another sign_multisig_operation"
+    )]
+    sign_multisig_operation,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "SignedFile"),
+        message = "send an operation signed by sign_operation or sign_multisig_operation"
+    )]
+    send_signed_operation,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "SenderAddress PathToBytecode MaxGas GasPrice Coins Fee",),
@@ -222,9 +371,9 @@ pub enum Command {
     #[strum(
         ascii_case_insensitive,
         props(
-            args = "SenderAddress TargetAddress FunctionName Parameter MaxGas GasPrice Coins Fee",
+            args = "SenderAddress TargetAddress FunctionName Parameter MaxGas GasPrice Coins [Fee]",
         ),
-        message = "create and send an operation to call a function of a smart contract"
+        message = "create and send an operation to call a function of a smart contract, the fee is estimated if omitted"
     )]
     call_smart_contract,
 
@@ -242,6 +391,22 @@ pub enum Command {
     )]
     read_only_call,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "SenderAddress PathToBytecode MaxGas GasPrice Coins [Fee] [--dry-run]",),
+        message = "deploy a smart contract, then show the contracts it created, the addresses in its events and its events and wait until it is final. With --dry-run, it is only simulated. The fee is estimated if omitted"
+    )]
+    deploy_smart_contract,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(
+            args = "SenderAddress TargetAddress FunctionName MaxGas GasPrice Coins Fee JsonParameter [--dry-run]",
+        ),
+        message = "call a function of a smart contract with a JSON parameter, then show its events and wait until it is final. With --dry-run, it is only simulated"
+    )]
+    call_function,
+
     #[strum(
         ascii_case_insensitive,
         message = "show time remaining to end of current episode"
@@ -276,16 +441,32 @@ macro_rules! client_warning {
 /// TODO re-factor me
 #[derive(Debug, Serialize)]
 struct ExtendedWalletEntry {
-    /// the keypair
-    pub keypair: KeyPair,
+    /// the keypair, if the address is not signed by an external signer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keypair: Option<KeyPair>,
+    /// the external signer of the address, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_signer: Option<ExternalSigner>,
+    /// labels of the address in the address book
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
     /// address and balance information
     pub address_info: CompactAddressInfo,
 }
 
 impl Display for ExtendedWalletEntry {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "Secret key: {}", self.keypair)?;
-        writeln!(f, "Public key: {}", self.keypair.get_public_key())?;
+        if let Some(keypair) = &self.keypair {
+            writeln!(f, "Secret key: {}", keypair)?;
+            writeln!(f, "Public key: {}", keypair.get_public_key())?;
+        }
+        if let Some(signer) = &self.external_signer {
+            writeln!(f, "External signer: {}", signer.endpoint)?;
+            writeln!(f, "Public key: {}", signer.public_key)?;
+        }
+        if !self.labels.is_empty() {
+            writeln!(f, "Labels: {}", self.labels.join(", "))?;
+        }
         writeln!(f, "{}", self.address_info)?;
         writeln!(f, "\n=====\n")?;
         Ok(())
@@ -304,14 +485,21 @@ impl ExtendedWallet {
             addresses_info
                 .iter()
                 .map(|x| {
-                    let keypair = wallet
-                        .keys
-                        .get(&x.address)
-                        .ok_or_else(|| anyhow!("missing key"))?;
+                    let keypair = wallet.keys.get(&x.address).cloned();
+                    let external_signer = wallet.find_external_signer(&x.address).cloned();
+                    if keypair.is_none() && external_signer.is_none() {
+                        bail!("missing key");
+                    }
                     Ok((
                         x.address,
                         ExtendedWalletEntry {
-                            keypair: keypair.clone(),
+                            keypair,
+                            external_signer,
+                            labels: wallet
+                                .get_labels(&x.address)
+                                .into_iter()
+                                .map(String::from)
+                                .collect(),
                             address_info: x.compact(),
                         },
                     ))
@@ -334,6 +522,264 @@ impl Display for ExtendedWallet {
     }
 }
 
+/// An account derived from the seed phrase of the wallet, to be printed by the client
+#[derive(Debug, Serialize)]
+pub struct MnemonicAccount {
+    /// seed phrase, only set when it was just generated
+    pub mnemonic: Option<String>,
+    /// derived address
+    pub address: Address,
+    /// derivation path of the address
+    pub derivation_path: Option<String>,
+}
+
+impl Display for MnemonicAccount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(mnemonic) = &self.mnemonic {
+            writeln!(f, "Seed phrase: {}", mnemonic)?;
+        }
+        writeln!(f, "Address: {}", self.address)?;
+        if let Some(path) = &self.derivation_path {
+            writeln!(f, "Derivation path: {}", path)?;
+        }
+        Ok(())
+    }
+}
+
+/// version of the files exchanged by `build_operation`, `sign_operation` and `send_signed_operation`
+const OFFLINE_OPERATION_VERSION: u32 = 1;
+
+/// An operation built by a client connected to a node, to be signed offline
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UnsignedOperation {
+    /// file format version
+    pub version: u32,
+    /// address expected to sign the operation
+    pub creator_address: Address,
+    /// readable content of the operation
+    pub operation: Operation,
+    /// content of the operation in the `OperationSerializer` binary format
+    pub serialized_content: Vec<u8>,
+}
+
+impl UnsignedOperation {
+    fn new(creator_address: Address, operation: Operation) -> Result<Self> {
+        Ok(UnsignedOperation {
+            version: OFFLINE_OPERATION_VERSION,
+            creator_address,
+            serialized_content: serialize_operation(&operation)?,
+            operation,
+        })
+    }
+
+    /// Signs the operation with the signer of its creator address in the wallet
+    async fn sign(self, wallet: &Wallet) -> Result<SignedOperation> {
+        if self.version != OFFLINE_OPERATION_VERSION {
+            bail!("unsupported unsigned operation version: {}", self.version);
+        }
+        let (operation, creator_address) = (self.operation, self.creator_address);
+        let op = spawn_signature(wallet, move |wallet| {
+            wallet.create_operation(operation, creator_address)
+        })
+        .await??;
+        // what is signed must be exactly what was built, not only what is displayed
+        if op.serialized_data != self.serialized_content {
+            bail!("the operation content does not match its serialized content");
+        }
+        Ok(SignedOperation::new(op))
+    }
+}
+
+impl Display for UnsignedOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Creator address: {}", self.creator_address)?;
+        write!(f, "{}", self.operation)
+    }
+}
+
+/// An operation signed offline, to be sent by a client connected to a node
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SignedOperation {
+    /// file format version
+    pub version: u32,
+    /// id of the signed operation
+    pub operation_id: OperationId,
+    /// address that signed the operation
+    pub creator_address: Address,
+    /// readable content of the operation
+    pub operation: Operation,
+    /// public key that signed the operation
+    pub creator_public_key: PublicKey,
+    /// signature of the operation
+    pub signature: Signature,
+    /// content of the operation in the `OperationSerializer` binary format
+    pub serialized_content: Vec<u8>,
+    /// signatures collected so far if the creator address is a multisig address,
+    /// `creator_public_key` and `signature` being the first of them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multisig: Option<MultisigProof>,
+}
+
+impl SignedOperation {
+    fn new(op: WrappedOperation) -> Self {
+        SignedOperation {
+            version: OFFLINE_OPERATION_VERSION,
+            operation_id: op.id,
+            creator_address: op.creator_address,
+            operation: op.content,
+            creator_public_key: op.creator_public_key,
+            signature: op.signature,
+            serialized_content: op.serialized_data,
+            multisig: op.multisig,
+        }
+    }
+}
+
+impl Display for SignedOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Operation ID: {}", self.operation_id)?;
+        writeln!(f, "Creator address: {}", self.creator_address)?;
+        if let Some(proof) = &self.multisig {
+            writeln!(
+                f,
+                "Multisig signatures: {} of {} required",
+                proof.signatures.len(),
+                proof.policy.threshold
+            )?;
+        }
+        write!(f, "{}", self.operation)
+    }
+}
+
+/// Execution of an operation deploying or calling a smart contract
+#[derive(Debug, Serialize)]
+pub struct SmartContractExecution {
+    /// id of the operation
+    pub operation_id: OperationId,
+    /// addresses of the contracts created by the execution, from the events of the deployer
+    /// starting with `DEPLOYED_CONTRACT_EVENT_PREFIX`
+    pub deployed_addresses: Vec<Address>,
+    /// other addresses mentioned in the events, except the sender
+    pub mentioned_addresses: Vec<Address>,
+    /// events emitted by the execution
+    pub events: Vec<SCOutputEvent>,
+    /// true if the operation is final
+    pub is_final: bool,
+}
+
+impl Display for SmartContractExecution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Operation ID: {}", self.operation_id)?;
+        for address in &self.deployed_addresses {
+            writeln!(f, "Deployed contract: {}", address)?;
+        }
+        for address in &self.mentioned_addresses {
+            writeln!(f, "Address in the events: {}", address)?;
+        }
+        for event in &self.events {
+            writeln!(f, "{}", event)?;
+        }
+        if self.is_final {
+            writeln!(f, "The operation is final")
+        } else {
+            writeln!(f, "The operation is not final yet")
+        }
+    }
+}
+
+/// A block or endorsement draw of an address, to be printed by the client
+#[derive(Debug, Serialize)]
+struct ScheduledDraw {
+    /// drawn slot
+    pub slot: Slot,
+    /// time of the slot
+    pub timestamp: MassaTime,
+    /// drawn address
+    pub address: Address,
+    /// endorsement index, none for a block draw
+    pub endorsement_index: Option<usize>,
+    /// true if the node stakes the address, none if the staking addresses are unknown
+    pub staked: Option<bool>,
+}
+
+/// Draws of a list of addresses sorted by slot, to be printed by the client.
+#[derive(Debug, Serialize)]
+pub struct DrawSchedule(Vec<ScheduledDraw>);
+
+impl DrawSchedule {
+    /// Sort the draws by slot and check them against the staking addresses of the node
+    fn new(
+        draws: SelectionDrawsOutput,
+        config: &CompactConfig,
+        staking_addresses: Option<&Set<Address>>,
+    ) -> Result<Self> {
+        let mut schedule = Vec::new();
+        for address_draws in draws.draws {
+            let staked = staking_addresses.map(|addrs| addrs.contains(&address_draws.address));
+            let slots = address_draws
+                .block_draws
+                .into_iter()
+                .map(|slot| (slot, None))
+                .chain(
+                    address_draws
+                        .endorsement_draws
+                        .into_iter()
+                        .map(|indexed_slot| (indexed_slot.slot, Some(indexed_slot.index))),
+                );
+            for (slot, endorsement_index) in slots {
+                schedule.push(ScheduledDraw {
+                    slot,
+                    timestamp: get_block_slot_timestamp(
+                        config.thread_count,
+                        config.t0,
+                        config.genesis_timestamp,
+                        slot,
+                    )?,
+                    address: address_draws.address,
+                    endorsement_index,
+                    staked,
+                });
+            }
+        }
+        schedule.sort_by_key(|draw| (draw.slot, draw.endorsement_index));
+        Ok(DrawSchedule(schedule))
+    }
+
+    /// Number of draws of addresses that the node does not stake
+    fn unstaked_count(&self) -> usize {
+        self.0
+            .iter()
+            .filter(|draw| draw.staked == Some(false))
+            .count()
+    }
+}
+
+impl Display for DrawSchedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_empty() {
+            return writeln!(f, "No draws in this slot range");
+        }
+        for draw in self.0.iter() {
+            write!(
+                f,
+                "{} (slot {}): {} ",
+                draw.timestamp.to_utc_string(),
+                draw.slot,
+                draw.address
+            )?;
+            match draw.endorsement_index {
+                Some(index) => write!(f, "endorsement {}", index)?,
+                None => write!(f, "block")?,
+            }
+            if draw.staked == Some(false) {
+                write!(f, " {}", style("NOT STAKED BY THE NODE").red())?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
 impl Command {
     /// Display the help of the command
     /// with fancy colors and so on
@@ -463,7 +909,7 @@ impl Command {
             }
 
             Command::node_remove_staking_addresses => {
-                let addresses = parse_vec::<Address>(parameters)?;
+                let addresses = wallet.resolve_addresses(parameters)?;
                 match client.private.remove_staking_addresses(addresses).await {
                     Ok(()) => {
                         if !json {
@@ -493,10 +939,20 @@ impl Command {
                     bail!("wrong number of parameters");
                 }
                 // parse
-                let addr = parameters[0].parse::<Address>()?;
+                let addr = wallet.resolve_address(&parameters[0])?;
                 let msg = parameters[1].as_bytes().to_vec();
+                confirm_external_signing(
+                    wallet,
+                    &[addr],
+                    &format!("Message: {}", parameters[1]),
+                    json,
+                )?;
                 // get address signature
-                if let Some(addr_sig) = wallet.sign_message(addr, msg.clone()) {
+                let addr_msg = msg.clone();
+                if let Some(addr_sig) =
+                    spawn_signature(wallet, move |wallet| wallet.sign_message(addr, addr_msg))
+                        .await?
+                {
                     // get node signature
                     match client.private.node_sign_message(msg).await {
                         // print concatenation
@@ -525,18 +981,73 @@ impl Command {
             },
 
             Command::get_addresses => {
-                let addresses = parse_vec::<Address>(parameters)?;
+                let addresses = wallet.resolve_addresses(parameters)?;
                 match client.public.get_addresses(addresses).await {
                     Ok(addresses_info) => Ok(Box::new(addresses_info)),
                     Err(e) => rpc_error!(e),
                 }
             }
 
+            Command::get_selection_draws => {
+                let mut addresses = Vec::new();
+                let (mut start, mut end) = (None, None);
+                for parameter in parameters {
+                    match parameter.split_once('=') {
+                        Some(("start", slot)) => start = Some(slot.parse::<Slot>()?),
+                        Some(("end", slot)) => end = Some(slot.parse::<Slot>()?),
+                        Some(_) => bail!("invalid parameter"),
+                        None => addresses.push(wallet.resolve_address(parameter)?),
+                    }
+                }
+                if addresses.is_empty() {
+                    bail!("wrong number of parameters");
+                }
+                let config = match client.public.get_status().await {
+                    Ok(node_status) => node_status.config,
+                    Err(e) => rpc_error!(e),
+                };
+                let staking_addresses = match client.private.get_staking_addresses().await {
+                    Ok(staking_addresses) => Some(staking_addresses),
+                    Err(e) => {
+                        if !json {
+                            client_warning!(format!(
+                                "the draws are not checked against the staking addresses of the node: {}",
+                                e
+                            ));
+                        }
+                        None
+                    }
+                };
+                match client
+                    .public
+                    .get_selection_draws(SelectionDrawsInput {
+                        addresses,
+                        start,
+                        end,
+                    })
+                    .await
+                {
+                    Ok(draws) => {
+                        let schedule =
+                            DrawSchedule::new(draws, &config, staking_addresses.as_ref())?;
+                        let unstaked_count = schedule.unstaked_count();
+                        if !json && unstaked_count > 0 {
+                            client_warning!(format!(
+                                "{} draws fall on addresses the node is not staking, they will be missed",
+                                unstaked_count
+                            ));
+                        }
+                        Ok(Box::new(schedule))
+                    }
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
             Command::get_datastore_entry => {
                 if parameters.len() != 2 {
                     bail!("invalid number of parameters");
                 }
-                let address = parameters[0].parse::<Address>()?;
+                let address = wallet.resolve_address(&parameters[0])?;
                 let key = parameters[1].as_bytes().to_vec();
                 match client
                     .public
@@ -548,6 +1059,25 @@ impl Command {
                 }
             }
 
+            Command::get_fee_estimate => {
+                if parameters.len() != 2 {
+                    bail!("wrong number of parameters");
+                }
+                let operation_size = parameters[0].parse::<u64>()?;
+                let target_periods = parameters[1].parse::<u64>()?;
+                match client
+                    .public
+                    .get_fee_estimate(FeeEstimateInput {
+                        operation_size,
+                        target_periods,
+                    })
+                    .await
+                {
+                    Ok(estimate) => Ok(Box::new(estimate)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
             Command::get_block => {
                 if parameters.len() != 1 {
                     bail!("wrong param numbers")
@@ -595,8 +1125,14 @@ impl Command {
                 let filter = EventFilter {
                     start: parse_value(&p, p_list[0]),
                     end: parse_value(&p, p_list[1]),
-                    emitter_address: parse_value(&p, p_list[2]),
-                    original_caller_address: parse_value(&p, p_list[3]),
+                    emitter_address: p
+                        .get(p_list[2])
+                        .map(|address| wallet.resolve_address(address))
+                        .transpose()?,
+                    original_caller_address: p
+                        .get(p_list[3])
+                        .map(|address| wallet.resolve_address(address))
+                        .transpose()?,
                     original_operation_id: parse_value(&p, p_list[4]),
                 };
                 match client.public.get_filtered_sc_output_event(filter).await {
@@ -611,7 +1147,7 @@ impl Command {
                 }
                 match client
                     .public
-                    .get_addresses(wallet.get_full_wallet().keys().copied().collect())
+                    .get_addresses(wallet.get_wallet_address_list().into_iter().collect())
                     .await
                 {
                     Ok(addresses_info) => {
@@ -636,6 +1172,65 @@ impl Command {
                 }
             }
 
+            Command::wallet_generate_mnemonic => {
+                let (mnemonic, ad) = wallet.generate_mnemonic()?;
+                if json {
+                    Ok(Box::new(MnemonicAccount {
+                        mnemonic: Some(mnemonic),
+                        address: ad,
+                        derivation_path: wallet.get_derivation_path(&ad),
+                    }))
+                } else {
+                    client_warning!("write down your seed phrase and keep it secret: it is the only way to restore the accounts derived from it");
+                    println!("Seed phrase: {}", mnemonic);
+                    println!("Derived {} address and added it to the wallet", ad);
+                    println!("Type `wallet_derive_account` to derive more accounts.\n");
+                    Ok(Box::new(()))
+                }
+            }
+
+            Command::wallet_restore_mnemonic => {
+                if !parameters.is_empty() {
+                    bail!("the seed phrase is prompted for and must not be passed as parameters");
+                }
+                let phrase = Password::new()
+                    .with_prompt("Enter the seed phrase, with its words separated by spaces")
+                    .interact()?;
+                let ad = wallet.restore_from_mnemonic(phrase.trim())?;
+                if json {
+                    Ok(Box::new(MnemonicAccount {
+                        mnemonic: None,
+                        address: ad,
+                        derivation_path: wallet.get_derivation_path(&ad),
+                    }))
+                } else {
+                    println!("Derived {} address and added it to the wallet", ad);
+                    println!(
+                        "Type `wallet_derive_account` to derive the next accounts of the seed phrase.\n"
+                    );
+                    Ok(Box::new(()))
+                }
+            }
+
+            Command::wallet_derive_account => {
+                let ad = wallet.derive_next_account()?;
+                let derivation_path = wallet.get_derivation_path(&ad);
+                if json {
+                    Ok(Box::new(MnemonicAccount {
+                        mnemonic: None,
+                        address: ad,
+                        derivation_path,
+                    }))
+                } else {
+                    println!(
+                        "Derived {} address at path {} and added it to the wallet",
+                        ad,
+                        derivation_path.unwrap_or_default()
+                    );
+                    Ok(Box::new(()))
+                }
+            }
+
             Command::wallet_add_secret_keys => {
                 let addresses = parse_vec::<KeyPair>(parameters)?
                     .into_iter()
@@ -657,7 +1252,7 @@ impl Command {
 
             Command::wallet_remove_addresses => {
                 let mut res = "".to_string();
-                for key in parse_vec::<Address>(parameters)?.into_iter() {
+                for key in wallet.resolve_addresses(parameters)?.into_iter() {
                     match wallet.remove_address(key) {
                         Ok(_) => {
                             let _ = writeln!(res, "Removed address {} from the wallet", key);
@@ -677,33 +1272,161 @@ impl Command {
                 Ok(Box::new(()))
             }
 
-            Command::buy_rolls => {
-                if parameters.len() != 3 {
-                    bail!("wrong number of parameters");
+            Command::wallet_reencrypt => {
+                let was_outdated = wallet.is_outdated();
+                wallet.reencrypt()?;
+                if !json {
+                    if was_outdated {
+                        println!("Wallet file upgraded to the current encryption");
+                    } else {
+                        println!("Wallet file encrypted again");
+                    }
                 }
-                let addr = parameters[0].parse::<Address>()?;
-                let roll_count = parameters[1].parse::<u64>()?;
-                let fee = parameters[2].parse::<Amount>()?;
+                Ok(Box::new(()))
+            }
 
+            Command::wallet_change_password => {
+                let password = Password::new()
+                    .with_prompt("Enter new password for wallet")
+                    .with_confirmation("Confirm password", "Passwords mismatching")
+                    .interact()?;
+                wallet.change_password(password)?;
                 if !json {
-                    let roll_price = match client.public.get_status().await {
-                        Err(e) => bail!("RpcError: {}", e),
-                        Ok(status) => status.config.roll_price,
-                    };
-                    match roll_price
-                        .checked_mul_u64(roll_count)
-                        .and_then(|x| x.checked_add(fee))
-                    {
-                        Some(total) => {
-                            if let Ok(addresses_info) =
-                                client.public.get_addresses(vec![addr]).await
-                            {
-                                match addresses_info.get(0) {
-                                    Some(info) => {
-                                        if info.ledger_info.candidate_ledger_info.balance < total {
-                                            client_warning!("this operation may be rejected due to insufficient balance");
-                                        }
-                                    }
+                    println!("Wallet password changed");
+                }
+                Ok(Box::new(()))
+            }
+
+            Command::wallet_add_multisig => {
+                if parameters.len() < 2 {
+                    bail!("wrong number of parameters");
+                }
+                let threshold = parameters[0].parse::<u8>()?;
+                let public_keys = parse_vec::<PublicKey>(&parameters[1..])?;
+                let policy = MultisigPolicy::new(threshold, public_keys)?;
+                let ad = wallet.add_multisig_policy(policy.clone())?;
+                if json {
+                    Ok(Box::new(ad.to_string()))
+                } else {
+                    print!("{}", policy);
+                    println!(
+                        "Type `build_operation <OutputFile> {} ...` to spend from this address, then `sign_multisig_operation` on the wallet of each signer.\n",
+                        ad
+                    );
+                    Ok(Box::new(()))
+                }
+            }
+
+            Command::wallet_add_external_signer => {
+                if parameters.len() < 3 {
+                    bail!("wrong number of parameters");
+                }
+                let public_key = parameters[0].parse::<PublicKey>()?;
+                let endpoint = match (parameters[1].to_lowercase().as_str(), &parameters[2..]) {
+                    ("command", command) => ExternalSignerEndpoint::Command(command.to_vec()),
+                    ("tcp", [addr]) => ExternalSignerEndpoint::Tcp(addr.parse()?),
+                    ("unix", [path]) => ExternalSignerEndpoint::Unix(path.parse()?),
+                    _ => bail!("the signer must be reached with command, tcp or unix"),
+                };
+                let ad = wallet.add_external_signer(ExternalSigner {
+                    public_key,
+                    endpoint,
+                })?;
+                if json {
+                    Ok(Box::new(ad.to_string()))
+                } else {
+                    println!("Address {} is signed by the external signer", ad);
+                    Ok(Box::new(()))
+                }
+            }
+
+            Command::wallet_set_label => {
+                if parameters.len() != 2 {
+                    bail!("wrong number of parameters");
+                }
+                let address = wallet.resolve_address(&parameters[1])?;
+                wallet.set_label(parameters[0].clone(), address)?;
+                if !json {
+                    println!("{} is now a label of {}", parameters[0], address);
+                }
+                Ok(Box::new(()))
+            }
+
+            Command::wallet_remove_label => {
+                if parameters.len() != 1 {
+                    bail!("wrong number of parameters");
+                }
+                let address = wallet.remove_label(&parameters[0])?;
+                if !json {
+                    println!("Removed label {} of {}", parameters[0], address);
+                }
+                Ok(Box::new(()))
+            }
+
+            Command::wallet_export_labels => {
+                if parameters.len() > 1 {
+                    bail!("wrong number of parameters");
+                }
+                match parameters.get(0) {
+                    Some(path) => {
+                        tokio::fs::write(path, serde_json::to_string_pretty(&wallet.labels)?)
+                            .await?;
+                        if !json {
+                            println!("{} labels written to {}", wallet.labels.len(), path);
+                        }
+                        Ok(Box::new(()))
+                    }
+                    None => Ok(Box::new(wallet.labels.clone())),
+                }
+            }
+
+            Command::wallet_import_labels => {
+                let (parameters, force) = take_flag(parameters, "--force");
+                if parameters.len() != 1 {
+                    bail!("wrong number of parameters");
+                }
+                let labels: BTreeMap<String, Address> =
+                    serde_json::from_str(&tokio::fs::read_to_string(&parameters[0]).await?)?;
+                let count = labels.len();
+                wallet.import_labels(labels, force).map_err(|e| match e {
+                    WalletError::LabelConflictError(_) => {
+                        anyhow!("{}, add --force to replace them", e)
+                    }
+                    e => e.into(),
+                })?;
+                if !json {
+                    println!("{} labels imported", count);
+                }
+                Ok(Box::new(()))
+            }
+
+            Command::buy_rolls => {
+                if parameters.len() != 3 {
+                    bail!("wrong number of parameters");
+                }
+                let addr = wallet.resolve_address(&parameters[0])?;
+                let roll_count = parameters[1].parse::<u64>()?;
+                let fee = parameters[2].parse::<Amount>()?;
+
+                if !json {
+                    let roll_price = match client.public.get_status().await {
+                        Err(e) => bail!("RpcError: {}", e),
+                        Ok(status) => status.config.roll_price,
+                    };
+                    match roll_price
+                        .checked_mul_u64(roll_count)
+                        .and_then(|x| x.checked_add(fee))
+                    {
+                        Some(total) => {
+                            if let Ok(addresses_info) =
+                                client.public.get_addresses(vec![addr]).await
+                            {
+                                match addresses_info.get(0) {
+                                    Some(info) => {
+                                        if info.ledger_info.candidate_ledger_info.balance < total {
+                                            client_warning!("this operation may be rejected due to insufficient balance");
+                                        }
+                                    }
                                     None => {
                                         client_warning!(format!("address {} not found", addr))
                                     }
@@ -724,7 +1447,7 @@ impl Command {
                     client,
                     wallet,
                     OperationType::RollBuy { roll_count },
-                    fee,
+                    Some(fee),
                     addr,
                     json,
                 )
@@ -735,7 +1458,7 @@ impl Command {
                 if parameters.len() != 3 {
                     bail!("wrong number of parameters");
                 }
-                let addr = parameters[0].parse::<Address>()?;
+                let addr = wallet.resolve_address(&parameters[0])?;
                 let roll_count = parameters[1].parse::<u64>()?;
                 let fee = parameters[2].parse::<Amount>()?;
 
@@ -758,7 +1481,7 @@ impl Command {
                     client,
                     wallet,
                     OperationType::RollSell { roll_count },
-                    fee,
+                    Some(fee),
                     addr,
                     json,
                 )
@@ -766,16 +1489,19 @@ impl Command {
             }
 
             Command::send_transaction => {
-                if parameters.len() != 4 {
+                if parameters.len() != 3 && parameters.len() != 4 {
                     bail!("wrong number of parameters");
                 }
-                let addr = parameters[0].parse::<Address>()?;
-                let recipient_address = parameters[1].parse::<Address>()?;
+                let addr = wallet.resolve_address(&parameters[0])?;
+                let recipient_address = wallet.resolve_address(&parameters[1])?;
                 let amount = parameters[2].parse::<Amount>()?;
-                let fee = parameters[3].parse::<Amount>()?;
+                let fee = parameters
+                    .get(3)
+                    .map(|fee| fee.parse::<Amount>())
+                    .transpose()?;
 
                 if !json {
-                    match amount.checked_add(fee) {
+                    match amount.checked_add(fee.unwrap_or_default()) {
                         Some(total) => {
                             if let Ok(addresses_info) =
                                 client.public.get_addresses(vec![addr]).await
@@ -811,6 +1537,252 @@ impl Command {
                 )
                 .await
             }
+            Command::build_operation => {
+                if parameters.len() < 4 {
+                    bail!("wrong number of parameters");
+                }
+                let path = parameters[0].parse::<PathBuf>()?;
+                let addr = wallet.resolve_address(&parameters[1])?;
+                let (op, fee) = match (parameters[2].as_str(), parameters.len()) {
+                    ("transaction", 5 | 6) => (
+                        OperationType::Transaction {
+                            recipient_address: wallet.resolve_address(&parameters[3])?,
+                            amount: parameters[4].parse::<Amount>()?,
+                        },
+                        parameters.get(5),
+                    ),
+                    ("buy_rolls", 4 | 5) => (
+                        OperationType::RollBuy {
+                            roll_count: parameters[3].parse::<u64>()?,
+                        },
+                        parameters.get(4),
+                    ),
+                    ("sell_rolls", 4 | 5) => (
+                        OperationType::RollSell {
+                            roll_count: parameters[3].parse::<u64>()?,
+                        },
+                        parameters.get(4),
+                    ),
+                    ("execute_smart_contract", 7 | 8) => (
+                        OperationType::ExecuteSC {
+                            data: get_file_as_byte_vec(&parameters[3].parse::<PathBuf>()?).await?,
+                            max_gas: parameters[4].parse::<u64>()?,
+                            gas_price: parameters[5].parse::<Amount>()?,
+                            coins: parameters[6].parse::<Amount>()?,
+                        },
+                        parameters.get(7),
+                    ),
+                    ("call_smart_contract", 9 | 10) => (
+                        OperationType::CallSC {
+                            target_addr: wallet.resolve_address(&parameters[3])?,
+                            target_func: parameters[4].clone(),
+                            param: parameters[5].clone(),
+                            max_gas: parameters[6].parse::<u64>()?,
+                            gas_price: parameters[7].parse::<Amount>()?,
+                            sequential_coins: Amount::zero(),
+                            parallel_coins: parameters[8].parse::<Amount>()?,
+                        },
+                        parameters.get(9),
+                    ),
+                    (
+                        "transaction"
+                        | "buy_rolls"
+                        | "sell_rolls"
+                        | "execute_smart_contract"
+                        | "call_smart_contract",
+                        _,
+                    ) => {
+                        bail!("wrong number of parameters")
+                    }
+                    (kind, _) => bail!("unknown operation kind: {}", kind),
+                };
+                let fee = fee.map(|fee| fee.parse::<Amount>()).transpose()?;
+                let operation = build_operation(client, op, fee, addr, json).await?;
+                let unsigned = UnsignedOperation::new(addr, operation)?;
+                tokio::fs::write(&path, serde_json::to_string_pretty(&unsigned)?).await?;
+                if json {
+                    Ok(Box::new(unsigned))
+                } else {
+                    println!("Unsigned operation written to {}", path.display());
+                    println!(
+                        "Type `sign_operation {} <OutputFile>` on the machine holding the wallet of {} to sign it.\n",
+                        path.display(),
+                        addr
+                    );
+                    Ok(Box::new(()))
+                }
+            }
+
+            Command::sign_operation => {
+                if parameters.len() != 2 {
+                    bail!("wrong number of parameters");
+                }
+                let input_path = parameters[0].parse::<PathBuf>()?;
+                let output_path = parameters[1].parse::<PathBuf>()?;
+                let unsigned: UnsignedOperation =
+                    serde_json::from_slice(&get_file_as_byte_vec(&input_path).await?)?;
+                if wallet
+                    .multisig_policies
+                    .contains_key(&unsigned.creator_address)
+                {
+                    bail!("the creator address is a multisig address, use sign_multisig_operation");
+                }
+                confirm_external_signing(wallet, &[unsigned.creator_address], &unsigned, json)?;
+                let signed = unsigned.sign(wallet).await?;
+                tokio::fs::write(&output_path, serde_json::to_string_pretty(&signed)?).await?;
+                if json {
+                    Ok(Box::new(signed))
+                } else {
+                    println!("{}", signed);
+                    println!("Signed operation written to {}", output_path.display());
+                    println!(
+                        "Type `send_signed_operation {}` on a machine connected to a node to send it.\n",
+                        output_path.display()
+                    );
+                    Ok(Box::new(()))
+                }
+            }
+
+            Command::sign_multisig_operation => {
+                if parameters.len() != 2 {
+                    bail!("wrong number of parameters");
+                }
+                let input_path = parameters[0].parse::<PathBuf>()?;
+                let output_path = parameters[1].parse::<PathBuf>()?;
+                let input = get_file_as_byte_vec(&input_path).await?;
+                // the first signer starts from an unsigned operation, the next ones from its output
+                let (version, creator_address, operation, serialized_content, mut proof) =
+                    match serde_json::from_slice::<SignedOperation>(&input) {
+                        Ok(signed) => (
+                            signed.version,
+                            signed.creator_address,
+                            signed.operation,
+                            signed.serialized_content,
+                            signed.multisig.ok_or_else(|| {
+                                anyhow!("the operation is not a multisig operation")
+                            })?,
+                        ),
+                        Err(_) => {
+                            let unsigned: UnsignedOperation = serde_json::from_slice(&input)?;
+                            let policy = wallet
+                                .multisig_policies
+                                .get(&unsigned.creator_address)
+                                .ok_or_else(|| {
+                                    anyhow!(
+                                        "{} is not a multisig address of the wallet, add it with wallet_add_multisig",
+                                        unsigned.creator_address
+                                    )
+                                })?;
+                            (
+                                unsigned.version,
+                                unsigned.creator_address,
+                                unsigned.operation,
+                                unsigned.serialized_content,
+                                MultisigProof::new(policy.clone()),
+                            )
+                        }
+                    };
+                if version != OFFLINE_OPERATION_VERSION {
+                    bail!("unsupported operation version: {}", version);
+                }
+                if Address::from_multisig_policy(&proof.policy) != creator_address {
+                    bail!("the multisig policy does not match the creator address");
+                }
+                // what is signed must be exactly what was built, not only what is displayed
+                if serialize_operation(&operation)? != serialized_content {
+                    bail!("the operation content does not match its serialized content");
+                }
+                let signer_addresses: Vec<Address> = proof
+                    .policy
+                    .public_keys
+                    .iter()
+                    .map(Address::from_public_key)
+                    .collect();
+                confirm_external_signing(
+                    wallet,
+                    &signer_addresses,
+                    &format!("Creator address: {}\n{}", creator_address, operation),
+                    json,
+                )?;
+                let (operation, proof, signer_count) = spawn_signature(wallet, move |wallet| {
+                    wallet
+                        .sign_multisig_operation(&operation, &mut proof)
+                        .map(|signer_count| (operation, proof, signer_count))
+                })
+                .await??;
+                if signer_count == 0 {
+                    bail!("no key of the multisig policy is in the wallet");
+                }
+                let op: WrappedOperation =
+                    Operation::new_multisig_wrapped(operation, serialized_content, proof)?;
+                let signed = SignedOperation::new(op);
+                tokio::fs::write(&output_path, serde_json::to_string_pretty(&signed)?).await?;
+                if json {
+                    Ok(Box::new(signed))
+                } else {
+                    println!("{}", signed);
+                    println!(
+                        "Added {} signatures, written to {}",
+                        signer_count,
+                        output_path.display()
+                    );
+                    if signed.multisig.as_ref().map_or(false, |p| p.is_complete()) {
+                        println!(
+                            "Type `send_signed_operation {}` on a machine connected to a node to send it.\n",
+                            output_path.display()
+                        );
+                    } else {
+                        println!(
+                            "Type `sign_multisig_operation {} <OutputFile>` on the machine of another signer.\n",
+                            output_path.display()
+                        );
+                    }
+                    Ok(Box::new(()))
+                }
+            }
+
+            Command::send_signed_operation => {
+                if parameters.len() != 1 {
+                    bail!("wrong number of parameters");
+                }
+                let path = parameters[0].parse::<PathBuf>()?;
+                let signed: SignedOperation =
+                    serde_json::from_slice(&get_file_as_byte_vec(&path).await?)?;
+                if signed.version != OFFLINE_OPERATION_VERSION {
+                    bail!("unsupported signed operation version: {}", signed.version);
+                }
+                if let Some(proof) = &signed.multisig {
+                    if !proof.is_complete() {
+                        bail!(
+                            "the operation has {} multisig signatures, {} are required",
+                            proof.signatures.len(),
+                            proof.policy.threshold
+                        );
+                    }
+                }
+                match client
+                    .public
+                    .send_operations(vec![OperationInput {
+                        creator_public_key: signed.creator_public_key,
+                        serialized_content: signed.serialized_content,
+                        signature: signed.signature,
+                        multisig: signed.multisig,
+                    }])
+                    .await
+                {
+                    Ok(operation_ids) => {
+                        if !json {
+                            println!("Sent operation IDs:");
+                        }
+                        Ok(Box::new(operation_ids))
+                    }
+                    Err(e) => match rejected_operations(&e).first() {
+                        Some(rejected) => bail!("the node did not accept operation {}", rejected),
+                        None => rpc_error!(e),
+                    },
+                }
+            }
+
             Command::when_episode_ends => {
                 let end = match client.public.get_status().await {
                     Ok(node_status) => node_status.config.end_timestamp,
@@ -841,7 +1813,7 @@ impl Command {
                 if parameters.len() != 6 {
                     bail!("wrong number of parameters");
                 }
-                let addr = parameters[0].parse::<Address>()?;
+                let addr = wallet.resolve_address(&parameters[0])?;
                 let path = parameters[1].parse::<PathBuf>()?;
                 let max_gas = parameters[2].parse::<u64>()?;
                 let gas_price = parameters[3].parse::<Amount>()?;
@@ -895,28 +1867,31 @@ impl Command {
                         coins,
                         gas_price,
                     },
-                    fee,
+                    Some(fee),
                     addr,
                     json,
                 )
                 .await
             }
             Command::call_smart_contract => {
-                if parameters.len() != 8 {
+                if parameters.len() != 7 && parameters.len() != 8 {
                     bail!("wrong number of parameters");
                 }
-                let addr = parameters[0].parse::<Address>()?;
-                let target_addr = parameters[1].parse::<Address>()?;
+                let addr = wallet.resolve_address(&parameters[0])?;
+                let target_addr = wallet.resolve_address(&parameters[1])?;
                 let target_func = parameters[2].clone();
                 let param = parameters[3].clone();
                 let max_gas = parameters[4].parse::<u64>()?;
                 let gas_price = parameters[5].parse::<Amount>()?;
                 let coins = parameters[6].parse::<Amount>()?;
-                let fee = parameters[7].parse::<Amount>()?;
+                let fee = parameters
+                    .get(7)
+                    .map(|fee| fee.parse::<Amount>())
+                    .transpose()?;
                 if !json {
                     match gas_price
                         .checked_mul_u64(max_gas)
-                        .and_then(|x| x.checked_add(fee))
+                        .and_then(|x| x.checked_add(fee.unwrap_or_default()))
                     {
                         Some(total) => {
                             if let Ok(addresses_info) =
@@ -967,14 +1942,54 @@ impl Command {
                 if parameters.len() != 2 {
                     bail!("wrong number of parameters");
                 }
-                let addr = parameters[0].parse::<Address>()?;
+                let addr = wallet.resolve_address(&parameters[0])?;
                 let msg = parameters[1].clone();
-                if let Some(signed) = wallet.sign_message(addr, msg.into_bytes()) {
+                confirm_external_signing(wallet, &[addr], &format!("Message: {}", msg), json)?;
+                if let Some(signed) = spawn_signature(wallet, move |wallet| {
+                    wallet.sign_message(addr, msg.into_bytes())
+                })
+                .await?
+                {
                     Ok(Box::new(signed))
                 } else {
                     bail!("Missing public key")
                 }
             }
+            Command::wallet_sign_message => {
+                if parameters.len() < 3 {
+                    bail!("wrong number of parameters");
+                }
+                let addr = wallet.resolve_address(&parameters[0])?;
+                let msg = read_message(&parameters[1..]).await?;
+                confirm_external_signing(
+                    wallet,
+                    &[addr],
+                    &format!("Message: {}", String::from_utf8_lossy(&msg)),
+                    json,
+                )?;
+                Ok(Box::new(
+                    spawn_signature(wallet, move |wallet| {
+                        wallet.sign_message_with_envelope(addr, &msg)
+                    })
+                    .await??,
+                ))
+            }
+            Command::verify_signature => {
+                if parameters.len() < 5 {
+                    bail!("wrong number of parameters");
+                }
+                let signed = SignedMessage {
+                    address: wallet.resolve_address(&parameters[0])?,
+                    public_key: parameters[1].parse::<PublicKey>()?,
+                    signature: parameters[2].parse::<Signature>()?,
+                };
+                let msg = read_message(&parameters[3..]).await?;
+                signed.verify(&msg)?;
+                Ok(Box::new(format!(
+                    "Valid signature of the message by {}",
+                    signed.address
+                )))
+            }
             Command::read_only_smart_contract => {
                 if parameters.len() != 3 && parameters.len() != 4 {
                     bail!("wrong number of parameters");
@@ -984,7 +1999,7 @@ impl Command {
                 let max_gas = parameters[1].parse::<u64>()?;
                 let simulated_gas_price = parameters[2].parse::<Amount>()?;
                 let address = if let Some(adr) = parameters.get(3) {
-                    Some(adr.parse::<Address>()?)
+                    Some(wallet.resolve_address(adr)?)
                 } else {
                     None
                 };
@@ -1008,13 +2023,13 @@ impl Command {
                     bail!("wrong number of parameters");
                 }
 
-                let target_address = parameters[0].parse::<Address>()?;
+                let target_address = wallet.resolve_address(&parameters[0])?;
                 let target_function = parameters[1].parse::<String>()?;
                 let parameter = parameters[2].parse::<String>()?;
                 let max_gas = parameters[3].parse::<u64>()?;
                 let simulated_gas_price = parameters[4].parse::<Amount>()?;
                 let caller_address = if let Some(addr) = parameters.get(5) {
-                    Some(addr.parse::<Address>()?)
+                    Some(wallet.resolve_address(addr)?)
                 } else {
                     None
                 };
@@ -1034,6 +2049,110 @@ impl Command {
                     Err(e) => rpc_error!(e),
                 }
             }
+            Command::deploy_smart_contract => {
+                let (parameters, dry_run) = take_flag(parameters, "--dry-run");
+                if parameters.len() != 5 && parameters.len() != 6 {
+                    bail!("wrong number of parameters");
+                }
+                let addr = wallet.resolve_address(&parameters[0])?;
+                let path = parameters[1].parse::<PathBuf>()?;
+                let max_gas = parameters[2].parse::<u64>()?;
+                let gas_price = parameters[3].parse::<Amount>()?;
+                let coins = parameters[4].parse::<Amount>()?;
+                let fee = parameters
+                    .get(5)
+                    .map(|fee| fee.parse::<Amount>())
+                    .transpose()?;
+                let data = get_file_as_byte_vec(&path).await?;
+                if dry_run {
+                    let res = match client
+                        .public
+                        .execute_read_only_bytecode(ReadOnlyBytecodeExecution {
+                            max_gas,
+                            simulated_gas_price: gas_price,
+                            bytecode: data,
+                            address: Some(addr),
+                        })
+                        .await
+                    {
+                        Ok(res) => res,
+                        Err(e) => rpc_error!(e),
+                    };
+                    return dry_run_output(res, json);
+                }
+                let operation_id = sign_and_send_operation(
+                    client,
+                    wallet,
+                    OperationType::ExecuteSC {
+                        data,
+                        max_gas,
+                        coins,
+                        gas_price,
+                    },
+                    fee,
+                    addr,
+                    json,
+                )
+                .await?;
+                Ok(Box::new(
+                    wait_smart_contract_execution(client, operation_id, addr, json).await?,
+                ))
+            }
+
+            Command::call_function => {
+                let (parameters, dry_run) = take_flag(parameters, "--dry-run");
+                if parameters.len() < 8 {
+                    bail!("wrong number of parameters");
+                }
+                let addr = wallet.resolve_address(&parameters[0])?;
+                let target_addr = wallet.resolve_address(&parameters[1])?;
+                let target_func = parameters[2].clone();
+                let max_gas = parameters[3].parse::<u64>()?;
+                let gas_price = parameters[4].parse::<Amount>()?;
+                let coins = parameters[5].parse::<Amount>()?;
+                let fee = parameters[6].parse::<Amount>()?;
+                // the parameter is the rest of the line, so that it can contain spaces
+                let param = encode_json_parameter(&parameters[7..].join(" "))?;
+                if dry_run {
+                    let res = match client
+                        .public
+                        .execute_read_only_call(ReadOnlyCall {
+                            caller_address: Some(addr),
+                            target_address: target_addr,
+                            target_function: target_func,
+                            parameter: param,
+                            max_gas,
+                            simulated_gas_price: gas_price,
+                        })
+                        .await
+                    {
+                        Ok(res) => res,
+                        Err(e) => rpc_error!(e),
+                    };
+                    return dry_run_output(res, json);
+                }
+                let operation_id = sign_and_send_operation(
+                    client,
+                    wallet,
+                    OperationType::CallSC {
+                        target_addr,
+                        target_func,
+                        param,
+                        max_gas,
+                        sequential_coins: Amount::zero(),
+                        parallel_coins: coins,
+                        gas_price,
+                    },
+                    Some(fee),
+                    addr,
+                    json,
+                )
+                .await?;
+                Ok(Box::new(
+                    wait_smart_contract_execution(client, operation_id, addr, json).await?,
+                ))
+            }
+
             Command::node_whitelist => {
                 let ips = parse_vec::<IpAddr>(parameters)?;
                 match client.private.node_whitelist(ips).await {
@@ -1058,40 +2177,71 @@ impl Command {
                 }
                 Ok(Box::new(()))
             }
+            Command::node_reload_bootstrap_policy => {
+                match client.private.node_reload_bootstrap_policy().await {
+                    Ok(()) => {
+                        if !json {
+                            println!("Bootstrap server policy successfully reloaded!")
+                        }
+                    }
+                    Err(e) => rpc_error!(e),
+                }
+                Ok(Box::new(()))
+            }
         }
     }
 }
 
-/// helper to wrap and send an operation with proper validity period
+/// number of periods within which operations sent without an explicit fee should be included
+const FEE_ESTIMATE_TARGET_PERIODS: u64 = 5;
+
+/// maximum duration of the waits for an operation to be final, in milliseconds
+pub(crate) const DEFAULT_WAIT_FINAL_TIMEOUT: u64 = 300_000;
+
+/// interval at which the node is polled while waiting for an operation, in milliseconds
+pub(crate) const WAIT_FINAL_POLL_INTERVAL: u64 = 1_000;
+
+/// start of the event a deployer emits with the address of each contract it creates,
+/// e.g. `Contract deployed at: A12...`
+const DEPLOYED_CONTRACT_EVENT_PREFIX: &str = "Contract deployed at:";
+
+/// helper to wrap and send an operation with proper validity period.
+/// The fee is estimated by the node if `fee` is `None`.
 async fn send_operation(
     client: &Client,
     wallet: &Wallet,
     op: OperationType,
-    fee: Amount,
+    fee: Option<Amount>,
     addr: Address,
     json: bool,
 ) -> Result<Box<dyn Output>> {
-    let cfg = match client.public.get_status().await {
-        Ok(node_status) => node_status,
-        Err(e) => rpc_error!(e),
+    let operation_id = sign_and_send_operation(client, wallet, op, fee, addr, json).await?;
+    if !json {
+        println!("Sent operation IDs:");
     }
-    .config;
-
-    let slot = get_current_latest_block_slot(cfg.thread_count, cfg.t0, cfg.genesis_timestamp, 0)? // clock compensation is zero
-        .unwrap_or_else(|| Slot::new(0, 0));
-    let mut expire_period = slot.period + cfg.operation_validity_periods;
-    if slot.thread >= addr.get_thread(cfg.thread_count) {
-        expire_period += 1;
-    };
+    Ok(Box::new(vec![operation_id]))
+}
 
-    let op = wallet.create_operation(
-        Operation {
-            fee,
-            expire_period,
-            op,
-        },
-        addr,
+/// helper to wrap and send an operation with proper validity period, returning its id.
+/// The fee is estimated by the node if `fee` is `None`.
+async fn sign_and_send_operation(
+    client: &Client,
+    wallet: &Wallet,
+    op: OperationType,
+    fee: Option<Amount>,
+    addr: Address,
+    json: bool,
+) -> Result<OperationId> {
+    let content = build_operation(client, op, fee, addr, json).await?;
+    confirm_external_signing(
+        wallet,
+        &[addr],
+        &format!("Creator address: {}\n{}", addr, content),
+        json,
     )?;
+    let op =
+        spawn_signature(wallet, move |wallet| wallet.create_operation(content, addr)).await??;
+    let operation_id = op.id;
 
     match client
         .public
@@ -1099,17 +2249,253 @@ async fn send_operation(
             creator_public_key: op.creator_public_key,
             serialized_content: op.serialized_data,
             signature: op.signature,
+            multisig: None,
         }])
         .await
     {
-        Ok(operation_ids) => {
-            if !json {
-                println!("Sent operation IDs:");
+        Ok(_) => Ok(operation_id),
+        Err(e) => match rejected_operations(&e).first() {
+            Some(rejected) => bail!("the node did not accept operation {}", rejected),
+            None => rpc_error!(e),
+        },
+    }
+}
+
+/// Polls the events of a smart contract operation until some are emitted or the operation is final,
+/// then waits until the operation is final
+async fn wait_smart_contract_execution(
+    client: &Client,
+    operation_id: OperationId,
+    sender: Address,
+    json: bool,
+) -> Result<SmartContractExecution> {
+    if !json {
+        println!("Sent operation ID: {}", operation_id);
+        println!("Waiting for its execution...");
+    }
+    let poll_interval = MassaTime::from(WAIT_FINAL_POLL_INTERVAL);
+    let deadline = MassaTime::now()?.saturating_add(MassaTime::from(DEFAULT_WAIT_FINAL_TIMEOUT));
+    let filter = EventFilter {
+        original_operation_id: Some(operation_id),
+        ..Default::default()
+    };
+    let mut is_final;
+    let events = loop {
+        let events = match client
+            .public
+            .get_filtered_sc_output_event(filter.clone())
+            .await
+        {
+            Ok(events) => events,
+            Err(e) => rpc_error!(e),
+        };
+        is_final = match client.public.get_operations(vec![operation_id]).await {
+            Ok(infos) => infos.iter().any(|info| info.is_final),
+            Err(e) => rpc_error!(e),
+        };
+        if !events.is_empty() || is_final {
+            break events;
+        }
+        if MassaTime::now()? >= deadline {
+            bail!("operation {} was not executed in time", operation_id);
+        }
+        tokio::time::sleep(poll_interval.to_duration()).await;
+    };
+    let deployed_addresses: Vec<Address> = events
+        .iter()
+        .filter_map(|event| {
+            event
+                .data
+                .trim()
+                .strip_prefix(DEPLOYED_CONTRACT_EVENT_PREFIX)
+                .and_then(|address| address.trim().parse::<Address>().ok())
+        })
+        .collect();
+    let mut mentioned_addresses = Vec::new();
+    for event in &events {
+        for word in event.data.split(|c: char| !c.is_ascii_alphanumeric()) {
+            if let Ok(address) = word.parse::<Address>() {
+                if address != sender
+                    && !deployed_addresses.contains(&address)
+                    && !mentioned_addresses.contains(&address)
+                {
+                    mentioned_addresses.push(address);
+                }
             }
-            Ok(Box::new(operation_ids))
         }
+    }
+    if !is_final {
+        if !json {
+            println!(
+                "Executed with {} events, waiting for the operation to be final...",
+                events.len()
+            );
+        }
+        let remaining = deadline.saturating_sub(MassaTime::now()?);
+        is_final = client
+            .public
+            .wait_operation_final(operation_id, remaining, poll_interval)
+            .await
+            .is_ok();
+    }
+    Ok(SmartContractExecution {
+        operation_id,
+        deployed_addresses,
+        mentioned_addresses,
+        events,
+        is_final,
+    })
+}
+
+/// Checks the result of a simulated execution, which fails if the execution failed
+fn dry_run_output(res: ExecuteReadOnlyResponse, json: bool) -> Result<Box<dyn Output>> {
+    if let ReadOnlyResult::Error(e) = &res.result {
+        bail!("the simulated execution failed: {}", e);
+    }
+    if !json {
+        println!("Simulated execution, nothing was sent:");
+    }
+    Ok(Box::new(res))
+}
+
+/// Removes a flag from the parameters, returning true if it was present
+fn take_flag(parameters: &[String], flag: &str) -> (Vec<String>, bool) {
+    let rest: Vec<String> = parameters
+        .iter()
+        .filter(|parameter| *parameter != flag)
+        .cloned()
+        .collect();
+    let present = rest.len() != parameters.len();
+    (rest, present)
+}
+
+/// Encodes the JSON parameter of a smart contract function:
+/// a JSON string is passed without its quotes, any other value as its compact JSON text
+fn encode_json_parameter(parameter: &str) -> Result<String> {
+    match serde_json::from_str(parameter)
+        .map_err(|e| anyhow!("the parameter is not valid JSON: {}", e))?
+    {
+        serde_json::Value::String(s) => Ok(s),
+        value => Ok(value.to_string()),
+    }
+}
+
+/// Signs with the wallet on a blocking thread,
+/// as an external signer can take up to `EXTERNAL_SIGNER_TIMEOUT` to answer
+async fn spawn_signature<T, F>(wallet: &Wallet, sign: F) -> Result<T>
+where
+    F: FnOnce(&Wallet) -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let wallet = wallet.clone();
+    Ok(tokio::task::spawn_blocking(move || sign(&wallet)).await?)
+}
+
+/// Shows an operation or a message before it is signed by the external signer of one of the given addresses
+/// and asks the user to confirm it. Nothing is asked in json mode.
+fn confirm_external_signing(
+    wallet: &Wallet,
+    addresses: &[Address],
+    content: &dyn Display,
+    json: bool,
+) -> Result<()> {
+    let signers: Vec<&ExternalSigner> = addresses
+        .iter()
+        .filter_map(|addr| wallet.find_external_signer(addr))
+        .collect();
+    if json || signers.is_empty() {
+        return Ok(());
+    }
+    println!("{}", content);
+    for signer in signers {
+        println!("To be signed by the external signer {}", signer.endpoint);
+    }
+    if !Confirm::new()
+        .with_prompt("Sign it?")
+        .default(false)
+        .interact()?
+    {
+        bail!("signature refused");
+    }
+    Ok(())
+}
+
+/// helper to build the content of an operation with proper validity period.
+/// The fee is estimated by the node if `fee` is `None`.
+async fn build_operation(
+    client: &Client,
+    op: OperationType,
+    fee: Option<Amount>,
+    addr: Address,
+    json: bool,
+) -> Result<Operation> {
+    let cfg = match client.public.get_status().await {
+        Ok(node_status) => node_status,
         Err(e) => rpc_error!(e),
     }
+    .config;
+
+    let slot = get_current_latest_block_slot(cfg.thread_count, cfg.t0, cfg.genesis_timestamp, 0)? // clock compensation is zero
+        .unwrap_or_else(|| Slot::new(0, 0));
+    let mut expire_period = slot.period + cfg.operation_validity_periods;
+    if slot.thread >= addr.get_thread(cfg.thread_count) {
+        expire_period += 1;
+    };
+
+    let fee = match fee {
+        Some(fee) => fee,
+        None => {
+            // measure the operation with a zero fee, which takes 9 bytes less than the largest fee
+            // the draft is never sent, and its size does not depend on the key wrapping it
+            let draft: WrappedOperation = Operation::new_wrapped(
+                Operation {
+                    fee: Amount::default(),
+                    expire_period,
+                    op: op.clone(),
+                },
+                OperationSerializer::new(),
+                &KeyPair::generate(),
+            )?;
+            let estimate = match client
+                .public
+                .get_fee_estimate(FeeEstimateInput {
+                    operation_size: draft.serialized_size() as u64 + 9,
+                    target_periods: FEE_ESTIMATE_TARGET_PERIODS,
+                })
+                .await
+            {
+                Ok(estimate) => estimate,
+                Err(e) => rpc_error!(e),
+            };
+            let thread_estimate = estimate
+                .threads
+                .get(addr.get_thread(cfg.thread_count) as usize)
+                .ok_or_else(|| anyhow!("missing fee estimate for the thread of {}", addr))?;
+            // the gas coins are paid to the block producer as well
+            let fee = thread_estimate
+                .recommended_fee
+                .saturating_sub(draft.get_gas_coins());
+            if !json {
+                println!("Using estimated fee: {}", fee);
+            }
+            fee
+        }
+    };
+
+    Ok(Operation {
+        fee,
+        expire_period,
+        op,
+    })
+}
+
+/// Serializes the content of an operation in the `OperationSerializer` binary format, which is what gets signed
+fn serialize_operation(operation: &Operation) -> Result<Vec<u8>> {
+    let mut serialized_content = Vec::new();
+    OperationSerializer::new()
+        .serialize(operation, &mut serialized_content)
+        .map_err(ModelsError::from)?;
+    Ok(serialized_content)
 }
 
 /// TODO: ugly utilities functions
@@ -1119,6 +2505,16 @@ pub fn parse_vec<T: std::str::FromStr>(args: &[String]) -> anyhow::Result<Vec<T>
 }
 
 /// reads a file
+/// Reads the message of `text Message` or `file FilePath` parameters.
+/// The words of a text message are joined with single spaces
+async fn read_message(parameters: &[String]) -> Result<Vec<u8>> {
+    match (parameters[0].to_lowercase().as_str(), &parameters[1..]) {
+        ("text", words) if !words.is_empty() => Ok(words.join(" ").into_bytes()),
+        ("file", [path]) => get_file_as_byte_vec(&PathBuf::from(path)).await,
+        _ => bail!("the message must be given as text Message or file FilePath"),
+    }
+}
+
 async fn get_file_as_byte_vec(filename: &std::path::Path) -> Result<Vec<u8>> {
     Ok(tokio::fs::read(filename).await?)
 }
@@ -1136,3 +2532,122 @@ pub fn parse_value<T: std::str::FromStr>(p: &HashMap<&str, &str>, key: &str) ->
             .ok()
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_models::wrapped::compute_wrapped_hash;
+    use std::str::FromStr;
+    use tempfile::TempDir;
+
+    fn create_operations(recipient: Address) -> Vec<Operation> {
+        [
+            OperationType::Transaction {
+                recipient_address: recipient,
+                amount: Amount::from_str("12.5").unwrap(),
+            },
+            OperationType::RollBuy { roll_count: 3 },
+            OperationType::ExecuteSC {
+                data: vec![0, 97, 115, 109],
+                max_gas: 100_000,
+                coins: Amount::from_str("1").unwrap(),
+                gas_price: Amount::from_str("0.001").unwrap(),
+            },
+            OperationType::CallSC {
+                target_addr: recipient,
+                target_func: "transfer".into(),
+                param: "{\"amount\":3}".into(),
+                max_gas: 100_000,
+                sequential_coins: Amount::zero(),
+                parallel_coins: Amount::from_str("2").unwrap(),
+                gas_price: Amount::from_str("0.001").unwrap(),
+            },
+        ]
+        .into_iter()
+        .map(|op| Operation {
+            fee: Amount::from_str("0.01").unwrap(),
+            expire_period: 42,
+            op,
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_offline_operation_files() {
+        let dir = TempDir::new().unwrap();
+        let mut wallet = Wallet::new(dir.path().join("wallet.dat"), "password".into()).unwrap();
+        let creator = wallet.add_keypair(KeyPair::generate()).unwrap();
+        let recipient = Address::from_public_key(&KeyPair::generate().get_public_key());
+
+        for operation in create_operations(recipient) {
+            // build_operation output, read by sign_operation
+            let unsigned = UnsignedOperation::new(creator, operation).unwrap();
+            let unsigned: UnsignedOperation =
+                serde_json::from_str(&serde_json::to_string_pretty(&unsigned).unwrap()).unwrap();
+
+            // sign_operation output, read by send_signed_operation
+            let signed = unsigned.sign(&wallet).unwrap();
+            let signed: SignedOperation =
+                serde_json::from_str(&serde_json::to_string_pretty(&signed).unwrap()).unwrap();
+
+            assert_eq!(signed.creator_address, creator);
+            assert_eq!(
+                signed.serialized_content,
+                serialize_operation(&signed.operation).unwrap()
+            );
+            let hash = compute_wrapped_hash(&signed.creator_public_key, &signed.serialized_content);
+            signed
+                .creator_public_key
+                .verify_signature(&hash, &signed.signature)
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_sign_altered_operation_file() {
+        let dir = TempDir::new().unwrap();
+        let mut wallet = Wallet::new(dir.path().join("wallet.dat"), "password".into()).unwrap();
+        let creator = wallet.add_keypair(KeyPair::generate()).unwrap();
+        let recipient = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let operation = create_operations(recipient).remove(0);
+
+        // the displayed content of the file was changed, not the serialized one
+        let mut unsigned = UnsignedOperation::new(creator, operation).unwrap();
+        unsigned.operation.fee = Amount::from_str("0.0001").unwrap();
+        assert!(unsigned.sign(&wallet).is_err());
+
+        // an address of another wallet cannot sign
+        let other = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let unsigned =
+            UnsignedOperation::new(other, create_operations(recipient).remove(0)).unwrap();
+        assert!(unsigned.sign(&wallet).is_err());
+    }
+
+    #[test]
+    fn test_take_flag() {
+        let parameters: Vec<String> = ["A1", "--dry-run", "10"]
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        let (rest, present) = take_flag(&parameters, "--dry-run");
+        assert!(present);
+        assert_eq!(rest, vec!["A1".to_string(), "10".to_string()]);
+        let (rest, present) = take_flag(&rest, "--dry-run");
+        assert!(!present);
+        assert_eq!(rest.len(), 2);
+    }
+
+    #[test]
+    fn test_encode_json_parameter() {
+        // a string is passed without its quotes
+        assert_eq!(encode_json_parameter("\"hello\"").unwrap(), "hello");
+        // the other values as their compact text
+        assert_eq!(
+            encode_json_parameter("{ \"amount\": 3, \"to\": [1, 2] }").unwrap(),
+            "{\"amount\":3,\"to\":[1,2]}"
+        );
+        assert_eq!(encode_json_parameter("42").unwrap(), "42");
+        assert!(encode_json_parameter("hello").is_err());
+        assert!(encode_json_parameter("{\"amount\":").is_err());
+    }
+}
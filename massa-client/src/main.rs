@@ -16,6 +16,7 @@ use std::net::IpAddr;
 use std::path::{Path, PathBuf};
 use structopt::StructOpt;
 
+mod batch;
 mod cmds;
 mod repl;
 mod settings;
@@ -49,6 +50,9 @@ struct Args {
         default_value = "wallet.dat"
     )]
     wallet: PathBuf,
+    /// Run the commands of a script file, one per line, or of the standard input if the path is `-`
+    #[structopt(short = "b", long = "batch", parse(from_os_str))]
+    batch: Option<PathBuf>,
     /// Enable a mode where input/output are serialized as JSON
     #[structopt(short = "j", long = "json")]
     json: bool,
@@ -109,9 +113,22 @@ async fn main(args: Args) -> Result<()> {
 
     // ...
     let password = args.password.unwrap_or_else(|| ask_password(&args.wallet));
-    let mut wallet = Wallet::new(args.wallet, password)?;
-    let client = Client::new(address, public_port, private_port).await;
-    if atty::is(Stream::Stdout) && args.command == Command::help && !args.json {
+    let mut wallet = Wallet::new_with_cipher_params(args.wallet, password, settings.wallet_cipher)?;
+    if wallet.is_outdated() && !args.json {
+        println!(
+            "{}: the wallet file uses an outdated encryption, type `wallet_reencrypt` to upgrade it",
+            style("WARNING").yellow()
+        );
+    }
+    let client =
+        match Client::new_with_config(address, public_port, private_port, settings.rpc).await {
+            Ok(client) => client,
+            Err(e) => anyhow::bail!("unable to create the node client: {}", e),
+        };
+    if let Some(script) = &args.batch {
+        // Batch mode
+        batch::run(&client, &mut wallet, script, args.json).await?;
+    } else if atty::is(Stream::Stdout) && args.command == Command::help && !args.json {
         // Interactive mode
         repl::run(&client, &mut wallet).await;
     } else {
@@ -1,6 +1,9 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use crate::cmds::{Command, ExtendedWallet};
+use crate::cmds::{
+    Command, DrawSchedule, ExtendedWallet, MnemonicAccount, SignedOperation,
+    SmartContractExecution, UnsignedOperation,
+};
 use crate::settings::SETTINGS;
 use crate::utils::longest_common_prefix;
 use console::style;
@@ -8,17 +11,19 @@ use dialoguer::{theme::ColorfulTheme, Completion, History, Input};
 use erased_serde::{Serialize, Serializer};
 use glob::glob;
 use massa_models::api::{
-    AddressInfo, BlockInfo, DatastoreEntryOutput, EndorsementInfo, NodeStatus, OperationInfo,
+    AddressInfo, BlockInfo, DatastoreEntryOutput, EndorsementInfo, FeeEstimate, NodeStatus,
+    OperationInfo,
 };
 use massa_models::composite::PubkeySig;
 use massa_models::execution::ExecuteReadOnlyResponse;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::Set;
+use massa_models::signed_message::SignedMessage;
 use massa_models::{Address, OperationId};
 use massa_sdk::Client;
 use massa_wallet::Wallet;
 use rev_lines::RevLines;
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
 use std::io::Error;
 use std::str;
 use std::{
@@ -206,6 +211,15 @@ impl dyn Output {
         self.erased_serialize(&mut format)?;
         Ok(())
     }
+
+    pub(crate) fn to_json(&self) -> anyhow::Result<serde_json::Value> {
+        let mut buffer = Vec::new();
+        let json = &mut serde_json::Serializer::new(&mut buffer);
+        let mut format: Box<dyn Serializer> = Box::new(<dyn Serializer>::erase(json));
+        self.erased_serialize(&mut format)?;
+        drop(format);
+        Ok(serde_json::from_slice(&buffer)?)
+    }
 }
 
 impl Output for Wallet {
@@ -220,6 +234,36 @@ impl Output for ExtendedWallet {
     }
 }
 
+impl Output for DrawSchedule {
+    fn pretty_print(&self) {
+        println!("{}", self);
+    }
+}
+
+impl Output for MnemonicAccount {
+    fn pretty_print(&self) {
+        println!("{}", self);
+    }
+}
+
+impl Output for UnsignedOperation {
+    fn pretty_print(&self) {
+        println!("{}", self);
+    }
+}
+
+impl Output for SignedOperation {
+    fn pretty_print(&self) {
+        println!("{}", self);
+    }
+}
+
+impl Output for SmartContractExecution {
+    fn pretty_print(&self) {
+        println!("{}", self);
+    }
+}
+
 impl Output for () {
     fn pretty_print(&self) {}
 }
@@ -248,6 +292,15 @@ impl Output for BlockInfo {
     }
 }
 
+impl Output for BTreeMap<String, Address> {
+    fn pretty_print(&self) {
+        match serde_json::to_string_pretty(self) {
+            Ok(labels) => println!("{}", labels),
+            Err(e) => println!("{}", style(format!("Error: {}", e)).red()),
+        }
+    }
+}
+
 impl Output for Set<Address> {
     fn pretty_print(&self) {
         println!(
@@ -320,6 +373,18 @@ impl Output for PubkeySig {
     }
 }
 
+impl Output for FeeEstimate {
+    fn pretty_print(&self) {
+        println!("{}", self);
+    }
+}
+
+impl Output for SignedMessage {
+    fn pretty_print(&self) {
+        println!("{}", self);
+    }
+}
+
 impl Output for ExecuteReadOnlyResponse {
     fn pretty_print(&self) {
         println!("{}", self);
@@ -2,7 +2,8 @@
 
 //! Build here the default client settings from the configuration file toml
 use massa_models::constants::build_massa_settings;
-use massa_time::MassaTime;
+use massa_sdk::ClientConfig;
+use massa_wallet::CipherParams;
 use serde::Deserialize;
 use std::{net::IpAddr, path::PathBuf};
 
@@ -15,7 +16,8 @@ pub struct Settings {
     pub default_node: DefaultNode,
     pub history: usize,
     pub history_file_path: PathBuf,
-    pub timeout: MassaTime,
+    pub rpc: ClientConfig,
+    pub wallet_cipher: CipherParams,
 }
 
 #[derive(Debug, Deserialize, Clone)]
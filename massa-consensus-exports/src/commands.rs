@@ -3,14 +3,22 @@
 //! Contains definitions of commands used by the controller
 use massa_graph::ledger::ConsensusLedgerSubset;
 use massa_graph::{BlockGraphExport, BootstrapableGraph, ExportBlockStatus, Status};
-use massa_models::{address::AddressState, api::EndorsementInfo, EndorsementId, OperationId};
-use massa_models::{clique::Clique, stats::ConsensusStats};
 use massa_models::{
-    Address, BlockId, OperationSearchResult, Slot, StakersCycleProductionStats, WrappedEndorsement,
+    address::AddressState,
+    api::{BlockTemplate, EndorsementInfo},
+    EndorsementId, OperationId,
+};
+use massa_models::{
+    clique::Clique,
+    stats::{ConsensusStats, FeeDensityPercentiles},
+};
+use massa_models::{
+    Address, BlockId, OperationSearchResult, Slot, StakersCycleProductionStats,
+    StakingAddressHealth, WrappedBlock, WrappedEndorsement,
 };
 
 use massa_proof_of_stake_exports::ExportProofOfStake;
-use massa_signature::KeyPair;
+use massa_signature::{KeyPair, PublicKey};
 
 use massa_models::prehash::{Map, Set};
 use tokio::sync::oneshot;
@@ -45,6 +53,22 @@ pub enum ConsensusCommand {
         /// response channel
         response_tx: oneshot::Sender<Result<SelectionDraws, ConsensusError>>,
     },
+    /// Returns through a channel the template of a block to be built externally
+    GetBlockTemplate {
+        /// slot of the block
+        slot: Slot,
+        /// public key of the block producer drawn at that slot
+        creator_public_key: PublicKey,
+        /// response channel
+        response_tx: oneshot::Sender<Result<BlockTemplate, ConsensusError>>,
+    },
+    /// Checks a block built and signed externally, then adds it to the graph for propagation
+    SubmitBlock {
+        /// the signed block
+        block: WrappedBlock,
+        /// response channel
+        response_tx: oneshot::Sender<Result<BlockId, ConsensusError>>,
+    },
     /// Returns the bootstrap state
     GetBootstrapState(oneshot::Sender<(ExportProofOfStake, BootstrapableGraph)>),
     /// Returns a part of the ledger
@@ -94,6 +118,15 @@ pub enum ConsensusCommand {
         /// response channel
         response_tx: oneshot::Sender<Vec<StakersCycleProductionStats>>,
     },
+    /// Get the production health of the staking addresses during the latest cycle
+    GetStakingHealth(oneshot::Sender<Vec<StakingAddressHealth>>),
+    /// Get the fee density percentiles of the operations included in recent active blocks, by thread
+    GetRecentFeeDensities {
+        /// blocks before this slot are ignored
+        start_slot: Slot,
+        /// response channel: percentiles of each thread, `None` for the threads without operations
+        response_tx: oneshot::Sender<Vec<Option<FeeDensityPercentiles>>>,
+    },
     /// Get block id and status by block creator address
     GetBlockIdsByCreator {
         /// wanted address
@@ -1,14 +1,22 @@
 use massa_graph::ledger::ConsensusLedgerSubset;
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 use massa_graph::{BlockGraphExport, BootstrapableGraph, ExportBlockStatus, Status};
-use massa_models::{address::AddressState, api::EndorsementInfo, EndorsementId, OperationId};
-use massa_models::{clique::Clique, stats::ConsensusStats};
 use massa_models::{
-    Address, BlockId, OperationSearchResult, Slot, StakersCycleProductionStats, WrappedEndorsement,
+    address::AddressState,
+    api::{BlockTemplate, EndorsementInfo},
+    EndorsementId, OperationId,
+};
+use massa_models::{
+    clique::Clique,
+    stats::{ConsensusStats, FeeDensityPercentiles},
+};
+use massa_models::{
+    Address, BlockId, OperationSearchResult, Slot, StakersCycleProductionStats,
+    StakingAddressHealth, WrappedBlock, WrappedEndorsement,
 };
 use massa_proof_of_stake_exports::ExportProofOfStake;
 use massa_protocol_exports::ProtocolEventReceiver;
-use massa_signature::KeyPair;
+use massa_signature::{KeyPair, PublicKey};
 
 use std::collections::VecDeque;
 
@@ -141,6 +149,55 @@ impl ConsensusCommandSender {
         })?
     }
 
+    /// get the template of a block to be built and signed outside of the node
+    ///
+    /// # Arguments
+    /// * slot: slot of the block
+    /// * `creator_public_key`: public key of the block producer drawn at that slot
+    pub async fn get_block_template(
+        &self,
+        slot: Slot,
+        creator_public_key: PublicKey,
+    ) -> Result<BlockTemplate, ConsensusError> {
+        massa_trace!("consensus.consensus_controller.get_block_template", {});
+        let (response_tx, response_rx) = oneshot::channel();
+        self.0
+            .send(ConsensusCommand::GetBlockTemplate {
+                slot,
+                creator_public_key,
+                response_tx,
+            })
+            .await
+            .map_err(|_| {
+                ConsensusError::SendChannelError(
+                    "send error consensus command get_block_template".into(),
+                )
+            })?;
+        response_rx.await.map_err(|_| {
+            ConsensusError::ReceiveChannelError(
+                "consensus command get_block_template response read error".to_string(),
+            )
+        })?
+    }
+
+    /// submit a block built and signed outside of the node
+    /// the block is checked, added to the graph and propagated if valid
+    pub async fn submit_block(&self, block: WrappedBlock) -> Result<BlockId, ConsensusError> {
+        massa_trace!("consensus.consensus_controller.submit_block", { "block_id": block.id });
+        let (response_tx, response_rx) = oneshot::channel();
+        self.0
+            .send(ConsensusCommand::SubmitBlock { block, response_tx })
+            .await
+            .map_err(|_| {
+                ConsensusError::SendChannelError("send error consensus command submit_block".into())
+            })?;
+        response_rx.await.map_err(|_| {
+            ConsensusError::ReceiveChannelError(
+                "consensus command submit_block response read error".to_string(),
+            )
+        })?
+    }
+
     /// get bootstrap snapshot
     pub async fn get_bootstrap_state(
         &self,
@@ -409,6 +466,53 @@ impl ConsensusCommandSender {
         })
     }
 
+    /// get the production health of the staking addresses during the latest cycle
+    pub async fn get_staking_health(&self) -> Result<Vec<StakingAddressHealth>, ConsensusError> {
+        let (response_tx, response_rx) = oneshot::channel();
+        massa_trace!("consensus.consensus_controller.get_staking_health", {});
+        self.0
+            .send(ConsensusCommand::GetStakingHealth(response_tx))
+            .await
+            .map_err(|_| {
+                ConsensusError::SendChannelError(
+                    "send error consensus command get_staking_health".to_string(),
+                )
+            })?;
+        response_rx.await.map_err(|_| {
+            ConsensusError::ReceiveChannelError(
+                "consensus command get_staking_health response read error".to_string(),
+            )
+        })
+    }
+
+    /// get the fee density percentiles of the operations included in the active blocks
+    /// at or after `start_slot`, by thread
+    pub async fn get_recent_fee_densities(
+        &self,
+        start_slot: Slot,
+    ) -> Result<Vec<Option<FeeDensityPercentiles>>, ConsensusError> {
+        let (response_tx, response_rx) = oneshot::channel();
+        massa_trace!("consensus.consensus_controller.get_recent_fee_densities", {
+            "start_slot": start_slot
+        });
+        self.0
+            .send(ConsensusCommand::GetRecentFeeDensities {
+                start_slot,
+                response_tx,
+            })
+            .await
+            .map_err(|_| {
+                ConsensusError::SendChannelError(
+                    "send error consensus command get_recent_fee_densities".to_string(),
+                )
+            })?;
+        response_rx.await.map_err(|_| {
+            ConsensusError::ReceiveChannelError(
+                "consensus command get_recent_fee_densities response read error".to_string(),
+            )
+        })
+    }
+
     /// get endorsements info by involved address
     pub async fn get_endorsements_by_address(
         &self,
@@ -52,6 +52,8 @@ pub enum ConsensusError {
     SerdeError(#[from] serde_json::Error),
     /// block creation error {0}
     BlockCreationError(String),
+    /// invalid submitted block: {0}
+    InvalidSubmittedBlock(String),
     /// error sending consensus event: {0}
     TokioSendError(#[from] tokio::sync::mpsc::error::SendError<ConsensusEvent>),
     /// channel error: {0}
@@ -64,6 +66,8 @@ pub enum ConsensusError {
     SlotOverflowError,
     /// `MassaCipher` error: {0}
     MassaCipherError(#[from] massa_cipher::CipherError),
+    /// remote signer error: {0}
+    RemoteSignerError(#[from] massa_signer::SignerError),
 }
 
 impl std::convert::From<massa_protocol_exports::ProtocolError> for ConsensusError {
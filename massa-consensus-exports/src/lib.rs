@@ -11,11 +11,13 @@ extern crate massa_logging;
 
 pub use consensus_controller::{ConsensusCommandSender, ConsensusEventReceiver, ConsensusManager};
 pub use error::ConsensusError;
+pub use pool_balances::ConsensusBalanceSource;
 pub use settings::{ConsensusConfig, ConsensusSettings};
 
 use massa_models::{Address, Slot};
 
 mod consensus_controller;
+mod pool_balances;
 
 /// consensus errors
 pub mod error;
@@ -0,0 +1,91 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Balances of the operation senders, used by the pool to check that they can pay for their operations
+
+use crate::ConsensusCommandSender;
+use massa_execution_exports::ExecutionController;
+use massa_models::{prehash::Set, Address, OperationId};
+use massa_pool::{BalanceSource, BalancesFuture, CandidateBalances, SenderBalances};
+use tokio::task::spawn_blocking;
+
+/// Balance source combining the candidate sequential balances from consensus
+/// and the active parallel balances from execution.
+/// The operations included in active blocks are looked up in consensus as well.
+pub struct ConsensusBalanceSource {
+    consensus_command_sender: ConsensusCommandSender,
+    execution_controller: Box<dyn ExecutionController>,
+}
+
+impl ConsensusBalanceSource {
+    /// Creates a new balance source
+    pub fn new(
+        consensus_command_sender: ConsensusCommandSender,
+        execution_controller: Box<dyn ExecutionController>,
+    ) -> Self {
+        ConsensusBalanceSource {
+            consensus_command_sender,
+            execution_controller,
+        }
+    }
+}
+
+impl BalanceSource for ConsensusBalanceSource {
+    fn get_balances(
+        &self,
+        addresses: Set<Address>,
+        pending_operations: Set<OperationId>,
+    ) -> BalancesFuture {
+        let address_list: Vec<Address> = addresses.iter().copied().collect();
+        let execution_controller = self.execution_controller.clone();
+        let consensus_command_sender = self.consensus_command_sender.clone();
+        Box::pin(async move {
+            // the execution state is locked for the whole execution of a slot:
+            // do not block the pool worker while waiting for it
+            let parallel_balances = {
+                let address_list = address_list.clone();
+                spawn_blocking(move || {
+                    execution_controller.get_final_and_active_parallel_balance(address_list)
+                })
+                .await
+                .map_err(|err| err.to_string())?
+            };
+            let states = consensus_command_sender
+                .get_addresses_info(addresses)
+                .await
+                .map_err(|err| err.to_string())?;
+            let included_operations = if pending_operations.is_empty() {
+                Set::default()
+            } else {
+                consensus_command_sender
+                    .get_operations(pending_operations)
+                    .await
+                    .map_err(|err| err.to_string())?
+                    .into_iter()
+                    .filter(|(_, search)| !search.in_blocks.is_empty())
+                    .map(|(op_id, _)| op_id)
+                    .collect()
+            };
+            let balances = address_list
+                .into_iter()
+                .zip(parallel_balances)
+                .map(|(address, (_final_parallel, active_parallel))| {
+                    let sequential = states
+                        .get(&address)
+                        .map(|state| state.ledger_info.candidate_ledger_info.balance)
+                        .unwrap_or_default();
+                    (
+                        address,
+                        SenderBalances {
+                            sequential,
+                            parallel: active_parallel.unwrap_or_default(),
+                        },
+                    )
+                })
+                .collect();
+            Ok(CandidateBalances {
+                balances,
+                included_operations,
+            })
+        })
+    }
+}
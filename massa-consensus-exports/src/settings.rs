@@ -51,6 +51,7 @@ use massa_pool::PoolCommandSender;
 use massa_proof_of_stake_exports::ProofOfStakeConfig;
 use massa_protocol_exports::{ProtocolCommandSender, ProtocolEventReceiver};
 use massa_signature::KeyPair;
+use massa_signer::RemoteSignerSettings;
 use massa_time::MassaTime;
 use num::rational::Ratio;
 use serde::{Deserialize, Serialize};
@@ -108,6 +109,15 @@ pub struct ConsensusSettings {
     /// Parameter that shouldn't be defined in prod.
     #[serde(skip, default = "Default::default")]
     pub disable_block_creation: bool,
+    /// remote signer holding staking keys, used for the staking addresses whose keys are not in `staking_keys_path`
+    #[serde(default)]
+    pub remote_signer: Option<RemoteSignerSettings>,
+    /// program run with a JSON staking alert as argument when a staking address misses a block (at most once per cycle)
+    /// or when the miss rate of a staking address gets close to the roll deactivation threshold
+    #[serde(default)]
+    pub staking_alert_command: Option<PathBuf>,
+    /// percentage of the roll deactivation threshold above which the miss rate of a staking address is alerted
+    pub staking_warning_percent: u64,
 }
 
 /// Consensus full configuration (static + user defined)
@@ -156,6 +166,10 @@ pub struct ConsensusConfig {
     pub pos_draw_cached_cycles: usize,
     /// number of cycle misses (strictly) above which stakers are deactivated
     pub pos_miss_rate_deactivation_threshold: Ratio<u64>,
+    /// a denunciation can only be included in a block at most `denunciation_expire_periods` periods after the denounced slot
+    pub denunciation_expire_periods: u64,
+    /// share of the slashed roll value credited to the creator of the block including a denunciation
+    pub denunciation_reward_ratio: Ratio<u64>,
     /// path to ledger db after initialization (merge with `initial_ledger_path` on node start)
     pub ledger_path: PathBuf,
     /// Cache capacity allowed to the ledger
@@ -191,6 +205,13 @@ pub struct ConsensusConfig {
     /// If we want to generate blocks.
     /// Parameter that shouldn't be defined in prod.
     pub disable_block_creation: bool,
+    /// remote signer holding staking keys, used for the staking addresses whose keys are not in `staking_keys_path`
+    pub remote_signer: Option<RemoteSignerSettings>,
+    /// program run with a JSON staking alert as argument when a staking address misses a block (at most once per cycle)
+    /// or when the miss rate of a staking address gets close to the roll deactivation threshold
+    pub staking_alert_command: Option<PathBuf>,
+    /// share of `pos_miss_rate_deactivation_threshold` above which the miss rate of a staking address is alerted
+    pub pos_miss_rate_warning_ratio: Ratio<u64>,
     /// Max gas per block for the execution configuration
     pub max_gas_per_block: u64,
 }
@@ -234,6 +255,8 @@ impl Clone for ConsensusConfig {
             pos_lock_cycles: self.pos_lock_cycles,
             pos_draw_cached_cycles: self.pos_draw_cached_cycles,
             pos_miss_rate_deactivation_threshold: self.pos_miss_rate_deactivation_threshold,
+            denunciation_expire_periods: self.denunciation_expire_periods,
+            denunciation_reward_ratio: self.denunciation_reward_ratio,
             ledger_path: self.ledger_path.clone(),
             ledger_cache_capacity: self.ledger_cache_capacity,
             ledger_flush_interval: self.ledger_flush_interval,
@@ -251,6 +274,9 @@ impl Clone for ConsensusConfig {
             block_db_prune_interval: self.block_db_prune_interval,
             max_item_return_count: self.max_item_return_count,
             disable_block_creation: self.disable_block_creation,
+            remote_signer: self.remote_signer.clone(),
+            staking_alert_command: self.staking_alert_command.clone(),
+            pos_miss_rate_warning_ratio: self.pos_miss_rate_warning_ratio,
             max_gas_per_block: self.max_gas_per_block,
         }
     }
@@ -268,6 +294,8 @@ impl From<&ConsensusConfig> for GraphConfig {
             delta_f0: cfg.delta_f0,
             operation_validity_periods: cfg.operation_validity_periods,
             periods_per_cycle: cfg.periods_per_cycle,
+            denunciation_expire_periods: cfg.denunciation_expire_periods,
+            denunciation_reward_ratio: cfg.denunciation_reward_ratio,
             initial_ledger_path: cfg.initial_ledger_path.clone(),
             block_reward: cfg.block_reward,
             roll_price: cfg.roll_price,
@@ -291,6 +319,7 @@ impl From<&ConsensusConfig> for ProofOfStakeConfig {
             pos_lock_cycles: cfg.pos_lock_cycles,
             pos_draw_cached_cycles: cfg.pos_draw_cached_cycles,
             pos_miss_rate_deactivation_threshold: cfg.pos_miss_rate_deactivation_threshold,
+            pos_miss_rate_warning_ratio: cfg.pos_miss_rate_warning_ratio,
             initial_rolls_path: cfg.initial_rolls_path.clone(),
             initial_draw_seed: cfg.initial_draw_seed.clone(),
             roll_price: cfg.roll_price,
@@ -376,6 +405,8 @@ impl From<&ConsensusSettings> for ConsensusConfig {
             pos_lock_cycles: POS_LOCK_CYCLES,
             pos_draw_cached_cycles: settings.pos_draw_cached_cycles,
             pos_miss_rate_deactivation_threshold: *POS_MISS_RATE_DEACTIVATION_THRESHOLD,
+            denunciation_expire_periods: DENUNCIATION_EXPIRE_PERIODS,
+            denunciation_reward_ratio: *DENUNCIATION_REWARD_RATIO,
             ledger_path: settings.ledger_path.clone(),
             ledger_cache_capacity: settings.ledger_cache_capacity,
             ledger_flush_interval: settings.ledger_flush_interval,
@@ -393,6 +424,9 @@ impl From<&ConsensusSettings> for ConsensusConfig {
             block_db_prune_interval: settings.block_db_prune_interval,
             max_item_return_count: settings.max_item_return_count,
             disable_block_creation: settings.disable_block_creation,
+            remote_signer: settings.remote_signer.clone(),
+            staking_alert_command: settings.staking_alert_command.clone(),
+            pos_miss_rate_warning_ratio: Ratio::new(settings.staking_warning_percent, 100),
             max_gas_per_block: MAX_GAS_PER_BLOCK,
         }
     }
@@ -429,6 +463,8 @@ impl From<ConsensusSettings> for ConsensusConfig {
             pos_lock_cycles: POS_LOCK_CYCLES,
             pos_draw_cached_cycles: settings.pos_draw_cached_cycles,
             pos_miss_rate_deactivation_threshold: *POS_MISS_RATE_DEACTIVATION_THRESHOLD,
+            denunciation_expire_periods: DENUNCIATION_EXPIRE_PERIODS,
+            denunciation_reward_ratio: *DENUNCIATION_REWARD_RATIO,
             ledger_path: settings.ledger_path,
             ledger_cache_capacity: settings.ledger_cache_capacity,
             ledger_flush_interval: settings.ledger_flush_interval,
@@ -446,6 +482,9 @@ impl From<ConsensusSettings> for ConsensusConfig {
             block_db_prune_interval: settings.block_db_prune_interval,
             max_item_return_count: settings.max_item_return_count,
             disable_block_creation: settings.disable_block_creation,
+            remote_signer: settings.remote_signer,
+            staking_alert_command: settings.staking_alert_command,
+            pos_miss_rate_warning_ratio: Ratio::new(settings.staking_warning_percent, 100),
             max_gas_per_block: MAX_GAS_PER_BLOCK,
         }
     }
@@ -484,6 +523,9 @@ impl Default for ConsensusSettings {
             block_db_prune_interval: *BLOCK_DB_PRUNE_INTERVAL,
             max_item_return_count: MAX_ITEM_RETURN_COUNT,
             disable_block_creation: DISABLE_BLOCK_CREATION,
+            remote_signer: None,
+            staking_alert_command: None,
+            staking_warning_percent: STAKING_WARNING_PERCENT,
         }
     }
 }
@@ -575,6 +617,8 @@ impl Default for ConsensusConfig {
             pos_lock_cycles: POS_LOCK_CYCLES,
             pos_draw_cached_cycles: POS_DRAW_CACHED_CYCLE,
             pos_miss_rate_deactivation_threshold: *POS_MISS_RATE_DEACTIVATION_THRESHOLD,
+            denunciation_expire_periods: DENUNCIATION_EXPIRE_PERIODS,
+            denunciation_reward_ratio: *DENUNCIATION_REWARD_RATIO,
             ledger_path: path_buf,
             ledger_cache_capacity: LEDGER_CACHE_CAPACITY,
             ledger_flush_interval: *LEDGER_FLUSH_INTERVAL,
@@ -592,6 +636,9 @@ impl Default for ConsensusConfig {
             block_db_prune_interval: *BLOCK_DB_PRUNE_INTERVAL,
             max_item_return_count: MAX_ITEM_RETURN_COUNT,
             disable_block_creation: DISABLE_BLOCK_CREATION,
+            remote_signer: None,
+            staking_alert_command: None,
+            pos_miss_rate_warning_ratio: Ratio::new(STAKING_WARNING_PERCENT, 100),
             max_gas_per_block: MAX_GAS_PER_BLOCK,
         }
     }
@@ -61,6 +61,7 @@ pub fn generate_default_roll_counts_file(stakers: Vec<KeyPair>) -> NamedTempFile
         let update = RollUpdate {
             roll_purchases: 1,
             roll_sales: 0,
+            roll_slashes: 0,
         };
         let mut updates = RollUpdates::default();
         updates.apply(&address, &update).unwrap();
@@ -1,4 +1,5 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
+use futures::{stream::FuturesUnordered, StreamExt};
 use massa_cipher::encrypt;
 use massa_consensus_exports::{
     commands::ConsensusCommand,
@@ -15,29 +16,32 @@ use massa_models::{
     OperationId, WrappedBlock,
 };
 use massa_models::{
-    api::{LedgerInfo, RollsInfo},
-    WrappedEndorsement,
+    api::{BlockTemplate, LedgerInfo, RollsInfo},
+    EndorsementId, OperationSerializer, WrappedEndorsement,
 };
-use massa_models::{ledger_models::LedgerData, WrappedOperation};
+use massa_models::{ledger_models::LedgerData, ModelsError, WrappedOperation};
 use massa_models::{
     prehash::{BuildMap, Map, Set},
     EndorsementSerializer,
 };
 use massa_models::{
     timeslots::{get_block_slot_timestamp, get_latest_block_slot_at_timestamp},
-    wrapped::{Id, WrappedContent},
+    wrapped::{Id, Wrapped, WrappedContent},
 };
 use massa_models::{
-    Address, Block, BlockHeader, BlockHeaderSerializer, BlockId, BlockSerializer, Endorsement, Slot,
+    Address, Block, BlockHeader, BlockHeaderSerializer, BlockId, BlockSerializer, Endorsement,
+    Slot, StakingAlert,
 };
 use massa_proof_of_stake_exports::{error::ProofOfStakeError, ExportProofOfStake, ProofOfStake};
 use massa_protocol_exports::{ProtocolEvent, ProtocolEventReceiver};
+use massa_serialization::Serializer;
 use massa_signature::{KeyPair, PublicKey};
+use massa_signer::{RemoteSigner, SignedContent};
 use massa_time::MassaTime;
-use std::{cmp::max, collections::HashSet, collections::VecDeque};
+use std::{cmp::max, collections::HashSet, collections::VecDeque, path::Path};
 use tokio::{
     sync::mpsc::error::SendTimeoutError,
-    time::{sleep, sleep_until, Sleep},
+    time::{sleep, sleep_until, timeout_at, Instant, Sleep},
 };
 use tracing::{debug, info, warn};
 
@@ -67,6 +71,10 @@ pub struct ConsensusWorker {
     staking_keys: Map<Address, KeyPair>,
     /// staking keys password
     password: String,
+    /// remote signer holding the keys of `remote_staking_keys`
+    remote_signer: Option<RemoteSigner>,
+    /// public keys of the staking addresses whose keys are held by the remote signer
+    remote_staking_keys: Map<Address, PublicKey>,
     /// stats `(block -> tx_count, creator)`
     final_block_stats: VecDeque<(MassaTime, u64, Address)>,
     /// No idea what this is used for. My guess is one timestamp per stale block
@@ -93,6 +101,8 @@ impl ConsensusWorker {
     /// * `controller_command_rx`: Channel receiving consensus commands.
     /// * `controller_event_tx`: Channel sending out consensus events.
     /// * `controller_manager_rx`: Channel receiving consensus management commands.
+    /// * `remote_signer`: optional remote signer for the staking keys not held locally.
+    /// * `remote_staking_keys`: staking addresses whose keys are held by the remote signer.
     pub(crate) async fn new(
         cfg: ConsensusConfig,
         channels: ConsensusWorkerChannels,
@@ -101,6 +111,8 @@ impl ConsensusWorker {
         clock_compensation: i64,
         staking_keys: Map<Address, KeyPair>,
         password: String,
+        remote_signer: Option<RemoteSigner>,
+        remote_staking_keys: Map<Address, PublicKey>,
     ) -> Result<ConsensusWorker> {
         let now = MassaTime::compensated_now(clock_compensation)?;
         let previous_slot = get_latest_block_slot_at_timestamp(
@@ -137,6 +149,9 @@ impl ConsensusWorker {
         for addr in staking_keys.keys() {
             info!("Staking enabled for address: {}", addr);
         }
+        for addr in remote_staking_keys.keys() {
+            info!("Staking enabled for address: {} (remote signer)", addr);
+        }
         massa_trace!("consensus.consensus_worker.new", {});
 
         // add genesis blocks to stats
@@ -188,6 +203,8 @@ impl ConsensusWorker {
             channels,
             staking_keys,
             password,
+            remote_signer,
+            remote_staking_keys,
             final_block_stats,
             stale_block_stats: VecDeque::new(),
             stats_desync_detection_timespan,
@@ -348,7 +365,7 @@ impl ConsensusWorker {
                 .saturating_add(self.stats_desync_detection_timespan)
             && !self.final_block_stats.iter().any(|(time, _, addr)| {
                 time > &now.saturating_sub(self.stats_desync_detection_timespan)
-                    && !self.staking_keys.contains_key(addr)
+                    && !self.is_staking_address(addr)
             })
         {
             warn!("desynchronization detected because the recent final block history is empty or contains only blocks produced by this node");
@@ -361,6 +378,11 @@ impl ConsensusWorker {
             .update_current_slot(observed_slot)
             .await?;
 
+        // retry to get the staking addresses of a remote signer that was unreachable, once per period
+        if observed_slot.thread == 0 {
+            self.load_remote_staking_keys().await;
+        }
+
         // create blocks
         if !self.cfg.disable_block_creation && observed_slot.period > 0 {
             let mut cur_slot = self.next_slot;
@@ -379,9 +401,16 @@ impl ConsensusWorker {
                     Err(err) => return Err(err.into()),
                 };
                 if let Some(addr) = block_draw {
-                    if let Some(key) = self.staking_keys.get(&addr).cloned() {
-                        massa_trace!("consensus.consensus_worker.slot_tick.block_creator_addr", { "addr": addr, "pubkey": key.get_public_key(), "unlocked": true });
-                        self.create_block(cur_slot, &addr, &key).await?;
+                    if let Some(public_key) = self.get_staking_public_key(&addr) {
+                        massa_trace!("consensus.consensus_worker.slot_tick.block_creator_addr", { "addr": addr, "pubkey": public_key, "unlocked": true });
+                        match self.create_block(cur_slot, &addr, public_key).await {
+                            // an unreachable or refusing remote signer only costs us this block
+                            Err(ConsensusError::RemoteSignerError(err)) => warn!(
+                                "could not sign the block of address {} at slot {}: {}",
+                                addr, cur_slot, err
+                            ),
+                            res => res?,
+                        }
                         if let Some(next_addr_slot) =
                             self.pos.get_next_selected_slot(self.next_slot, addr)
                         {
@@ -438,59 +467,60 @@ impl ConsensusWorker {
         Ok(())
     }
 
-    /// creates a block with given address
-    /// first an empty block is created then it's filled with operations
+    /// builds the template of a block for the given slot and creator
+    /// first an empty block is created to measure the remaining space, then it's filled with operations
     /// the operations are retrieved from the pool
-    /// the block is added to the graph as it it was received from the outside
-    /// so it will on go the same checks
-    async fn create_block(
+    async fn get_block_template(
         &mut self,
         cur_slot: Slot,
-        creator_addr: &Address,
-        creator_keypair: &KeyPair,
-    ) -> Result<()> {
+        creator_public_key: PublicKey,
+    ) -> Result<BlockTemplate> {
         // get parents
         let parents = self.block_db.get_best_parents();
         let (thread_parent, thread_parent_period) = parents[cur_slot.thread as usize];
 
         // get endorsements
         // it is assumed that only valid endorsements in that context are selected by pool
-        let (endorsement_ids, endorsements) = if thread_parent_period > 0 {
+        let endorsements = if thread_parent_period > 0 {
             let thread_parent_slot = Slot::new(thread_parent_period, cur_slot.thread);
             let endorsement_draws = self.pos.draw_endorsement_producers(thread_parent_slot)?;
             self.channels
                 .pool_command_sender
                 .get_endorsements(thread_parent_slot, thread_parent, endorsement_draws)
                 .await?
-                .into_iter()
-                .map(|e| ((e.id, e.content.index), e))
-                .unzip()
         } else {
-            (Map::default(), Vec::new())
+            Vec::new()
         };
 
         massa_trace!("consensus.create_block.get_endorsements.result", {
             "endorsements": endorsements
         });
 
+        // get denunciations of stakers of this thread who signed twice for the same slot
+        let denunciations = self
+            .block_db
+            .get_block_denunciations(cur_slot, thread_parent)?;
+
         // create empty block
-        let header = BlockHeader::new_wrapped(
+        // it is only used to measure the remaining space, so it is not signed with the staking key
+        let header = wrap_unsigned(
             BlockHeader {
                 slot: cur_slot,
                 parents: parents.iter().map(|(b, _p)| *b).collect(),
                 operation_merkle_root: Hash::compute_from(&Vec::new()[..]),
                 endorsements: endorsements.clone(),
+                denunciations: denunciations.clone(),
             },
             BlockHeaderSerializer::new(),
-            creator_keypair,
+            creator_public_key,
         )?;
-        let block: WrappedBlock = Block::new_wrapped(
+        let block: WrappedBlock = wrap_unsigned(
             Block {
                 header,
                 operations: Vec::new(),
             },
             BlockSerializer::new(),
-            creator_keypair,
+            creator_public_key,
         )?;
 
         // initialize remaining block space and remaining operation count
@@ -539,7 +569,6 @@ impl ConsensusWorker {
         // gather operations
         let mut total_hash: Vec<u8> = Vec::new();
         let mut operations: Vec<WrappedOperation> = Vec::new();
-        let mut operation_set: Map<OperationId, (usize, u64)> = Map::default(); // (index, validity end period)
         let mut finished = remaining_block_space == 0
             || remaining_operation_count == 0
             || self.cfg.max_operations_fill_attempts == 0;
@@ -598,7 +627,6 @@ impl ConsensusWorker {
 
                 // add operation
                 let op_hash = op.id.hash().into_bytes();
-                operation_set.insert(op.id, (operation_set.len(), op.content.expire_period));
                 operations.push(op);
                 remaining_block_space -= op_size;
                 remaining_operation_count -= 1;
@@ -613,22 +641,71 @@ impl ConsensusWorker {
             }
         }
 
+        Ok(BlockTemplate {
+            slot: cur_slot,
+            creator_address: Address::from_public_key(&creator_public_key),
+            parents: parents.iter().map(|(b, _p)| *b).collect(),
+            endorsements,
+            denunciations,
+            operations,
+            operation_merkle_root: Hash::compute_from(&total_hash),
+            max_block_size: self.cfg.max_block_size,
+            max_operations_per_block: self.cfg.max_operations_per_block,
+            max_gas_per_block: self.cfg.max_gas_per_block,
+        })
+    }
+
+    /// creates a block with given address
+    /// the block is built from a template, then signed with the staking key
+    /// the block is added to the graph as it it was received from the outside
+    /// so it will on go the same checks
+    async fn create_block(
+        &mut self,
+        cur_slot: Slot,
+        creator_addr: &Address,
+        creator_public_key: PublicKey,
+    ) -> Result<()> {
+        let template = self
+            .get_block_template(cur_slot, creator_public_key)
+            .await?;
+        let endorsement_ids = template
+            .endorsements
+            .iter()
+            .map(|e| (e.id, e.content.index))
+            .collect();
+        let operation_set = template
+            .operations
+            .iter()
+            .enumerate()
+            .map(|(index, op)| (op.id, (index, op.content.expire_period)))
+            .collect();
+
         // compile resulting block
-        let header = BlockHeader::new_wrapped(
-            BlockHeader {
-                slot: cur_slot,
-                parents: parents.iter().map(|(b, _p)| *b).collect(),
-                operation_merkle_root: Hash::compute_from(&total_hash),
-                endorsements,
-            },
-            BlockHeaderSerializer::new(),
-            creator_keypair,
-        )?;
-        let block = Block::new_wrapped(
-            Block { header, operations },
-            BlockSerializer::new(),
-            creator_keypair,
-        )?;
+        let header = self
+            .sign_wrapped(
+                creator_addr,
+                BlockHeader {
+                    slot: cur_slot,
+                    parents: template.parents,
+                    operation_merkle_root: template.operation_merkle_root,
+                    endorsements: template.endorsements,
+                    denunciations: template.denunciations,
+                },
+                BlockHeaderSerializer::new(),
+                SignedContent::BlockHeader,
+            )
+            .await?;
+        let block: WrappedBlock = self
+            .sign_wrapped(
+                creator_addr,
+                Block {
+                    header,
+                    operations: template.operations,
+                },
+                BlockSerializer::new(),
+                SignedContent::Block,
+            )
+            .await?;
         let slot = block.content.header.content.slot;
         massa_trace!("create block", { "block": block });
         let block_id = block.id;
@@ -658,6 +735,139 @@ impl ConsensusWorker {
         Ok(())
     }
 
+    /// checks a block built and signed outside of the node,
+    /// with the checks protocol performs on blocks received from the network
+    ///
+    /// Returns the operation set and the endorsement ids of the block
+    fn check_submitted_block(
+        &mut self,
+        block: &WrappedBlock,
+    ) -> Result<(Map<OperationId, (usize, u64)>, Map<EndorsementId, u32>)> {
+        let invalid = ConsensusError::InvalidSubmittedBlock;
+        let header = &block.content.header;
+        let slot = header.content.slot;
+
+        if slot.period == 0 || header.content.parents.is_empty() {
+            return Err(invalid("genesis blocks cannot be submitted".into()));
+        }
+        let creator_address = Address::from_public_key(&header.creator_public_key);
+        if self.pos.draw_block_producer(slot)? != creator_address {
+            return Err(invalid(format!(
+                "address {} is not the block producer drawn at slot {}",
+                creator_address, slot
+            )));
+        }
+        if block.creator_public_key != header.creator_public_key {
+            return Err(invalid(
+                "the block and its header are signed by different keys".into(),
+            ));
+        }
+        if block.serialized_data.len() > self.cfg.max_block_size as usize {
+            return Err(invalid(format!(
+                "block size {} is above the limit of {}",
+                block.serialized_data.len(),
+                self.cfg.max_block_size
+            )));
+        }
+        if block.content.operations.len() > self.cfg.max_operations_per_block as usize {
+            return Err(invalid(format!(
+                "block contains {} operations, above the limit of {}",
+                block.content.operations.len(),
+                self.cfg.max_operations_per_block
+            )));
+        }
+        block
+            .verify_signature(BlockSerializer::new(), &block.creator_public_key)
+            .map_err(|err| invalid(format!("wrong block signature: {}", err)))?;
+        header
+            .verify_signature(BlockHeaderSerializer::new(), &header.creator_public_key)
+            .map_err(|err| invalid(format!("wrong header signature: {}", err)))?;
+
+        // check endorsements
+        let mut endorsement_ids = Map::default();
+        let mut used_endorsement_indices =
+            HashSet::with_capacity(header.content.endorsements.len());
+        for endorsement in header.content.endorsements.iter() {
+            endorsement
+                .verify_signature(
+                    EndorsementSerializer::new(),
+                    &endorsement.creator_public_key,
+                )
+                .map_err(|err| {
+                    invalid(format!(
+                        "wrong signature of endorsement {}: {}",
+                        endorsement.id, err
+                    ))
+                })?;
+            if !used_endorsement_indices.insert(endorsement.content.index)
+                || endorsement_ids
+                    .insert(endorsement.id, endorsement.content.index)
+                    .is_some()
+            {
+                return Err(invalid(format!("endorsement {} is reused", endorsement.id)));
+            }
+            if endorsement.content.slot.thread != slot.thread || endorsement.content.slot >= slot {
+                return Err(invalid(format!(
+                    "endorsement {} has an incompatible slot",
+                    endorsement.id
+                )));
+            }
+            if endorsement.content.endorsed_block != header.content.parents[slot.thread as usize] {
+                return Err(invalid(format!(
+                    "endorsement {} does not endorse the thread parent",
+                    endorsement.id
+                )));
+            }
+        }
+
+        // check operations
+        let mut operation_set = Map::default();
+        let mut total_hash: Vec<u8> = Vec::new();
+        let mut total_gas = 0u64;
+        for (index, op) in block.content.operations.iter().enumerate() {
+            op.verify_signature(OperationSerializer::new(), &op.creator_public_key)
+                .map_err(|err| {
+                    invalid(format!("wrong signature of operation {}: {}", op.id, err))
+                })?;
+            op.check_multisig_rules()
+                .map_err(|err| invalid(format!("operation {} is forbidden: {}", op.id, err)))?;
+            if operation_set
+                .insert(op.id, (index, op.content.expire_period))
+                .is_some()
+            {
+                return Err(invalid(format!("operation {} is duplicated", op.id)));
+            }
+            if !op
+                .get_validity_range(self.cfg.operation_validity_periods)
+                .contains(&slot.period)
+            {
+                return Err(invalid(format!(
+                    "operation {} is not valid at period {}",
+                    op.id, slot.period
+                )));
+            }
+            if op.thread != slot.thread {
+                return Err(invalid(format!(
+                    "operation {} does not belong to thread {}",
+                    op.id, slot.thread
+                )));
+            }
+            total_gas = total_gas.saturating_add(op.get_gas_usage());
+            total_hash.extend(op.id.hash().into_bytes());
+        }
+        if total_gas > self.cfg.max_gas_per_block {
+            return Err(invalid(format!(
+                "operations use {} gas, above the block limit of {}",
+                total_gas, self.cfg.max_gas_per_block
+            )));
+        }
+        if header.content.operation_merkle_root != Hash::compute_from(&total_hash) {
+            return Err(invalid("wrong operation merkle root".into()));
+        }
+
+        Ok((operation_set, endorsement_ids))
+    }
+
     /// Channel management stuff
     /// todo delete
     /// or at least introduce some generic
@@ -786,6 +996,80 @@ impl ConsensusWorker {
                 }
                 Ok(())
             }
+            ConsensusCommand::GetBlockTemplate {
+                slot,
+                creator_public_key,
+                response_tx,
+            } => {
+                massa_trace!(
+                    "consensus.consensus_worker.process_consensus_command.get_block_template",
+                    { "slot": slot }
+                );
+                let creator_address = Address::from_public_key(&creator_public_key);
+                let result = if slot.period == 0 {
+                    Err(ConsensusError::BlockCreationError(
+                        "no block can be created in the genesis period".into(),
+                    ))
+                } else {
+                    match self.pos.draw_block_producer(slot) {
+                        Ok(addr) if addr == creator_address => {
+                            self.get_block_template(slot, creator_public_key).await
+                        }
+                        Ok(addr) => Err(ConsensusError::BlockCreationError(format!(
+                            "address {} is not the block producer drawn at slot {} ({} is)",
+                            creator_address, slot, addr
+                        ))),
+                        Err(err) => Err(err.into()),
+                    }
+                };
+                if response_tx.send(result).is_err() {
+                    warn!("consensus: could not send GetBlockTemplate response");
+                }
+                Ok(())
+            }
+            ConsensusCommand::SubmitBlock { block, response_tx } => {
+                massa_trace!(
+                    "consensus.consensus_worker.process_consensus_command.submit_block",
+                    { "block_id": block.id }
+                );
+                let (operation_set, endorsement_ids) = match self.check_submitted_block(&block) {
+                    Ok(sets) => sets,
+                    Err(err) => {
+                        if response_tx.send(Err(err)).is_err() {
+                            warn!("consensus: could not send SubmitBlock response");
+                        }
+                        return Ok(());
+                    }
+                };
+                let block_id = block.id;
+                let slot = block.content.header.content.slot;
+                info!(
+                    "Received submitted block {} with address {}, at cycle {}, period {}, thread {}",
+                    block_id,
+                    Address::from_public_key(&block.creator_public_key),
+                    slot.get_cycle(self.cfg.periods_per_cycle),
+                    slot.period,
+                    slot.thread
+                );
+
+                // Store block in shared storage.
+                self.block_db.storage.store_block(block);
+
+                // the block goes through the graph checks and gets propagated like a staked block
+                self.block_db.incoming_block(
+                    block_id,
+                    slot,
+                    operation_set,
+                    endorsement_ids,
+                    &mut self.pos,
+                    self.previous_slot,
+                )?;
+                self.block_db_changed().await?;
+                if response_tx.send(Ok(block_id)).is_err() {
+                    warn!("consensus: could not send SubmitBlock response");
+                }
+                Ok(())
+            }
             ConsensusCommand::GetBootstrapState(response_tx) => {
                 massa_trace!(
                     "consensus.consensus_worker.process_consensus_command.get_bootstrap_state",
@@ -892,8 +1176,7 @@ impl ConsensusWorker {
                     info!("Staking with address {}", address);
                     self.staking_keys.insert(address, key);
                 }
-                self.pos
-                    .set_watched_addresses(self.staking_keys.keys().copied().collect());
+                self.pos.set_watched_addresses(self.get_staking_addresses());
                 self.dump_staking_keys().await?;
                 Ok(())
             }
@@ -901,8 +1184,7 @@ impl ConsensusWorker {
                 for address in addresses.into_iter() {
                     self.staking_keys.remove(&address);
                 }
-                self.pos
-                    .set_watched_addresses(self.staking_keys.keys().copied().collect());
+                self.pos.set_watched_addresses(self.get_staking_addresses());
                 self.dump_staking_keys().await?;
                 Ok(())
             }
@@ -911,10 +1193,7 @@ impl ConsensusWorker {
                     "consensus.consensus_worker.process_consensus_command.get_staking_addresses",
                     {}
                 );
-                if response_tx
-                    .send(self.staking_keys.keys().cloned().collect())
-                    .is_err()
-                {
+                if response_tx.send(self.get_staking_addresses()).is_err() {
                     warn!("consensus: could not send get_staking addresses response");
                 }
                 Ok(())
@@ -932,6 +1211,32 @@ impl ConsensusWorker {
                 }
                 Ok(())
             }
+            ConsensusCommand::GetStakingHealth(response_tx) => {
+                massa_trace!(
+                    "consensus.consensus_worker.process_consensus_command.get_staking_health",
+                    {}
+                );
+                if response_tx.send(self.pos.get_staking_health()).is_err() {
+                    warn!("consensus: could not send get_staking_health response");
+                }
+                Ok(())
+            }
+            ConsensusCommand::GetRecentFeeDensities {
+                start_slot,
+                response_tx,
+            } => {
+                massa_trace!(
+                    "consensus.consensus_worker.process_consensus_command.get_recent_fee_densities",
+                    { "start_slot": start_slot }
+                );
+                if response_tx
+                    .send(self.block_db.get_recent_fee_densities(start_slot)?)
+                    .is_err()
+                {
+                    warn!("consensus: could not send get_recent_fee_densities response");
+                }
+                Ok(())
+            }
             ConsensusCommand::GetBlockIdsByCreator {
                 address,
                 response_tx,
@@ -969,11 +1274,100 @@ impl ConsensusWorker {
         }
     }
 
+    /// Returns true if we stake with that address, with a local key or through the remote signer
+    fn is_staking_address(&self, addr: &Address) -> bool {
+        self.staking_keys.contains_key(addr) || self.remote_staking_keys.contains_key(addr)
+    }
+
+    /// Public key of one of our staking addresses
+    fn get_staking_public_key(&self, addr: &Address) -> Option<PublicKey> {
+        self.staking_keys
+            .get(addr)
+            .map(|keypair| keypair.get_public_key())
+            .or_else(|| self.remote_staking_keys.get(addr).copied())
+    }
+
+    /// All our staking addresses, local and remote
+    fn get_staking_addresses(&self) -> Set<Address> {
+        self.staking_keys
+            .keys()
+            .chain(self.remote_staking_keys.keys())
+            .copied()
+            .collect()
+    }
+
+    /// Get the staking addresses of the remote signer if they are not known yet,
+    /// because it was unreachable until now
+    async fn load_remote_staking_keys(&mut self) {
+        let public_keys = match &self.remote_signer {
+            Some(remote_signer) if self.remote_staking_keys.is_empty() => {
+                remote_signer.get_public_keys().await
+            }
+            _ => return,
+        };
+        match public_keys {
+            Ok(public_keys) => {
+                for public_key in public_keys {
+                    let addr = Address::from_public_key(&public_key);
+                    info!("Staking enabled for address: {} (remote signer)", addr);
+                    self.remote_staking_keys.insert(addr, public_key);
+                }
+                self.pos.set_watched_addresses(self.get_staking_addresses());
+            }
+            Err(err) => debug!("the remote signer is still unreachable: {}", err),
+        }
+    }
+
+    /// Signs a content with the key of one of our staking addresses.
+    /// The local key is used if we have it, otherwise the content is sent to the remote signer.
+    async fn sign_wrapped<T, U, SC>(
+        &self,
+        addr: &Address,
+        content: T,
+        content_serializer: SC,
+        signed_content: fn(Vec<u8>) -> SignedContent,
+    ) -> Result<Wrapped<T, U>>
+    where
+        T: WrappedContent,
+        U: Id,
+        SC: Serializer<T>,
+    {
+        if let Some(keypair) = self.staking_keys.get(addr) {
+            return Ok(T::new_wrapped(content, content_serializer, keypair)?);
+        }
+        let (remote_signer, public_key) =
+            match (&self.remote_signer, self.remote_staking_keys.get(addr)) {
+                (Some(remote_signer), Some(public_key)) => (remote_signer, *public_key),
+                _ => {
+                    return Err(ConsensusError::ContainerInconsistency(format!(
+                        "no staking key for address {}",
+                        addr
+                    )))
+                }
+            };
+        let mut content_serialized = Vec::new();
+        content_serializer
+            .serialize(&content, &mut content_serialized)
+            .map_err(ModelsError::from)?;
+        let signature = remote_signer
+            .sign(public_key, signed_content(content_serialized.clone()))
+            .await?;
+        Ok(T::new_wrapped_with_signature(
+            content,
+            content_serialized,
+            public_key,
+            signature,
+        ))
+    }
+
     /// Save the staking keys to a file
     async fn dump_staking_keys(&self) -> Result<()> {
         let json = serde_json::to_string(&self.staking_keys)?;
         let encrypted_data = encrypt(&self.password, json.as_bytes())?;
-        tokio::fs::write(self.cfg.staking_keys_path.clone(), encrypted_data).await?;
+        // written aside first so that a crash while writing does not lose the keys
+        let tmp_path = self.cfg.staking_keys_path.with_extension("tmp");
+        tokio::fs::write(&tmp_path, encrypted_data).await?;
+        tokio::fs::rename(&tmp_path, &self.cfg.staking_keys_path).await?;
         Ok(())
     }
 
@@ -1184,6 +1578,15 @@ impl ConsensusWorker {
                     .send_get_blocks_results(results)
                     .await?;
             }
+            ProtocolEvent::ReceivedDenunciations(denunciations) => {
+                massa_trace!(
+                    "consensus.consensus_worker.process_protocol_event.received_denunciations",
+                    { "denunciations": denunciations }
+                );
+                self.block_db
+                    .add_denunciations(denunciations, &mut self.pos)?;
+                self.block_db_changed().await?;
+            }
         }
         Ok(())
     }
@@ -1202,7 +1605,7 @@ impl ConsensusWorker {
     /// Please refactor me
     ///
     /// 1. propagate blocks
-    /// 2. Notify of attack attempts
+    /// 2. Notify of attack attempts and propagate new denunciations
     /// 3. get new final blocks
     /// 4. get blockclique
     /// 5. notify Execution
@@ -1238,6 +1641,15 @@ impl ConsensusWorker {
             });
         }
 
+        // Propagate new denunciations.
+        let new_denunciations = self.block_db.get_new_denunciations();
+        if !new_denunciations.is_empty() {
+            self.channels
+                .protocol_command_sender
+                .propagate_denunciations(new_denunciations)
+                .await?;
+        }
+
         // get new final blocks
         let new_final_block_ids = self.block_db.get_new_final_blocks();
 
@@ -1304,6 +1716,12 @@ impl ConsensusWorker {
         }
         // Notify PoS of final blocks
         self.pos.note_final_blocks(new_final_blocks)?;
+        let staking_alerts = self.pos.take_staking_alerts();
+        if let Some(command) = &self.cfg.staking_alert_command {
+            for alert in staking_alerts.iter() {
+                run_staking_alert_command(command, alert);
+            }
+        }
 
         // notify protocol of block wishlist
         let new_wishlist = self.block_db.get_block_wishlist()?;
@@ -1343,6 +1761,7 @@ impl ConsensusWorker {
 
         // Produce endorsements
         if !self.cfg.disable_block_creation {
+            let mut to_endorse = Vec::new();
             // iterate on all blockclique blocks
             for block_id in blockclique_set.into_iter() {
                 let block_slot = match self.block_db.get_active_block(&block_id) {
@@ -1373,33 +1792,91 @@ impl ConsensusWorker {
                     }
                 };
 
-                // actually create endorsements
-                let mut endorsements = Map::default();
+                // endorsements to create
                 for (endorsement_index, addr) in endorsement_draws.into_iter().enumerate() {
-                    if let Some(key) = self.staking_keys.get(&addr) {
+                    if let Some(public_key) = self.get_staking_public_key(&addr) {
                         massa_trace!("consensus.consensus_worker.slot_tick.endorsement_creator_addr",
-                            { "index": endorsement_index, "addr": addr, "pubkey": key.get_public_key(), "unlocked": true });
-                        let endorsement = create_endorsement(
-                            block_slot,
-                            key,
-                            endorsement_index as u32,
-                            block_id,
-                        )?;
-                        endorsements.insert(endorsement.id, endorsement);
-                        self.endorsed_slots.insert(block_slot);
+                            { "index": endorsement_index, "addr": addr, "pubkey": public_key, "unlocked": true });
+                        to_endorse.push((
+                            addr,
+                            Endorsement {
+                                slot: block_slot,
+                                index: endorsement_index as u32,
+                                endorsed_block: block_id,
+                            },
+                        ));
                     } else {
                         massa_trace!("consensus.consensus_worker.slot_tick.endorsement_creator_addr",
                             { "index": endorsement_index, "addr": addr, "unlocked": false });
                     }
                 }
-                // send endorsement batch to pool
-                if !endorsements.is_empty() {
-                    self.channels
-                        .pool_command_sender
-                        .add_endorsements(endorsements)
-                        .await?;
+            }
+
+            // sign the endorsements concurrently, the remote signer having one timeout for all of them
+            let deadline = self
+                .cfg
+                .remote_signer
+                .as_ref()
+                .map(|settings| Instant::now() + settings.timeout.to_duration());
+            let worker = &*self;
+            let mut signings: FuturesUnordered<_> = to_endorse
+                .into_iter()
+                .map(|(addr, content)| async move {
+                    let slot = content.slot;
+                    let signed: Result<WrappedEndorsement> = worker
+                        .sign_wrapped(
+                            &addr,
+                            content,
+                            EndorsementSerializer::new(),
+                            SignedContent::Endorsement,
+                        )
+                        .await;
+                    (addr, slot, signed)
+                })
+                .collect();
+            let mut signed_endorsements = Vec::new();
+            loop {
+                let next = match deadline {
+                    Some(deadline) => match timeout_at(deadline, signings.next()).await {
+                        Ok(next) => next,
+                        Err(_) => {
+                            warn!(
+                                "the remote signer did not sign {} endorsements in time",
+                                signings.len()
+                            );
+                            break;
+                        }
+                    },
+                    None => signings.next().await,
+                };
+                match next {
+                    Some(signed) => signed_endorsements.push(signed),
+                    None => break,
+                }
+            }
+            drop(signings);
+
+            let mut endorsements = Map::default();
+            for (addr, slot, signed) in signed_endorsements {
+                match signed {
+                    Ok(endorsement) => {
+                        endorsements.insert(endorsement.id, endorsement);
+                        self.endorsed_slots.insert(slot);
+                    }
+                    Err(ConsensusError::RemoteSignerError(err)) => warn!(
+                        "could not sign the endorsement of address {} at slot {}: {}",
+                        addr, slot, err
+                    ),
+                    Err(err) => return Err(err),
                 }
             }
+            // send endorsement batch to pool
+            if !endorsements.is_empty() {
+                self.channels
+                    .pool_command_sender
+                    .add_endorsements(endorsements)
+                    .await?;
+            }
         }
 
         // add stale blocks to stats
@@ -1409,7 +1886,7 @@ impl ConsensusWorker {
             self.stale_block_stats.push_back(timestamp);
 
             let creator_addr = Address::from_public_key(&b_creator);
-            if self.staking_keys.contains_key(&creator_addr) {
+            if self.is_staking_address(&creator_addr) {
                 warn!("block {} that was produced by our address {} at slot {} became stale. This is probably due to a temporary desynchronization.", b_id, creator_addr, b_slot);
             }
         }
@@ -1418,19 +1895,63 @@ impl ConsensusWorker {
     }
 }
 
-/// A convenient function to create an endorsement
-/// should probably be moved to models (or fully deleted)
-pub fn create_endorsement(
-    slot: Slot,
-    keypair: &KeyPair,
-    index: u32,
-    endorsed_block: BlockId,
-) -> Result<WrappedEndorsement> {
-    let content = Endorsement {
-        slot,
-        index,
-        endorsed_block,
+/// Wraps a content with a throwaway signature, for when only its serialized size
+/// and creator matter and the staking key must not sign it.
+fn wrap_unsigned<T, U, SC>(
+    content: T,
+    content_serializer: SC,
+    public_key: PublicKey,
+) -> Result<Wrapped<T, U>>
+where
+    T: WrappedContent,
+    U: Id,
+    SC: Serializer<T>,
+{
+    let mut content_serialized = Vec::new();
+    content_serializer
+        .serialize(&content, &mut content_serialized)
+        .map_err(ModelsError::from)?;
+    let placeholder_signature = KeyPair::generate()
+        .sign(&Hash::compute_from(&content_serialized))
+        .map_err(ModelsError::from)?;
+    Ok(T::new_wrapped_with_signature(
+        content,
+        content_serialized,
+        public_key,
+        placeholder_signature,
+    ))
+}
+
+/// Runs the staking alert command with the JSON alert as argument, without waiting for it to exit.
+fn run_staking_alert_command(command: &Path, alert: &StakingAlert) {
+    let alert = match serde_json::to_string(alert) {
+        Ok(alert) => alert,
+        Err(err) => {
+            warn!("consensus: could not serialize staking alert: {}", err);
+            return;
+        }
+    };
+    let mut child = match tokio::process::Command::new(command).arg(alert).spawn() {
+        Ok(child) => child,
+        Err(err) => {
+            warn!(
+                "consensus: could not run staking alert command {}: {}",
+                command.display(),
+                err
+            );
+            return;
+        }
     };
-    let endorsement = Endorsement::new_wrapped(content, EndorsementSerializer::new(), keypair)?;
-    Ok(endorsement)
+    tokio::spawn(async move {
+        match child.wait().await {
+            Ok(status) if !status.success() => {
+                warn!("consensus: staking alert command exited with {}", status)
+            }
+            Ok(_) => {}
+            Err(err) => warn!(
+                "consensus: could not wait for staking alert command: {}",
+                err
+            ),
+        }
+    });
 }
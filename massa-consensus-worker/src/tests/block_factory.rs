@@ -53,6 +53,7 @@ impl BlockFactory {
                         .collect::<Vec<_>>()[..],
                 ),
                 endorsements: self.endorsements.clone(),
+                denunciations: Vec::new(),
             },
             BlockHeaderSerializer::new(),
             &self.creator_keypair,
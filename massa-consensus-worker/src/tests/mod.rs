@@ -10,6 +10,8 @@ mod scenarios106;
 mod scenarios91_1;
 mod scenarios91_2;
 mod scenarios_basic;
+mod scenarios_block_template;
+mod scenarios_denunciations;
 mod scenarios_endorsements;
 mod scenarios_get_operations;
 mod scenarios_get_selection_draws;
@@ -47,6 +47,7 @@ async fn test_genesis_block_creation() {
     let update = RollUpdate {
         roll_purchases: 1,
         roll_sales: 0,
+        roll_slashes: 0,
     };
     let mut updates = RollUpdates::default();
     updates.apply(&address_1, &update).unwrap();
@@ -164,6 +165,7 @@ async fn test_block_creation_with_draw() {
     let update = RollUpdate {
         roll_purchases: 1,
         roll_sales: 0,
+        roll_slashes: 0,
     };
     let mut updates = RollUpdates::default();
     updates.apply(&address_1, &update).unwrap();
@@ -326,6 +328,7 @@ async fn test_interleaving_block_creation_with_reception() {
     let update = RollUpdate {
         roll_purchases: 1,
         roll_sales: 0,
+        roll_slashes: 0,
     };
     let mut updates = RollUpdates::default();
     updates.apply(&address_1, &update).unwrap();
@@ -832,6 +835,7 @@ async fn test_block_filling() {
                     parents: block.content.header.content.parents.clone(),
                     operation_merkle_root: Hash::compute_from(&Vec::new()[..]),
                     endorsements: eds,
+                    denunciations: Vec::new(),
                 },
                 BlockHeaderSerializer::new(),
                 &keypair_a,
@@ -0,0 +1,146 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use super::tools::*;
+use super::{
+    mock_pool_controller::{MockPoolController, PoolCommandSink},
+    mock_protocol_controller::MockProtocolController,
+};
+use crate::start_consensus_controller;
+
+use massa_consensus_exports::settings::ConsensusChannels;
+use massa_consensus_exports::tools::TEST_PASSWORD;
+use massa_consensus_exports::{ConsensusConfig, ConsensusError};
+use massa_execution_exports::test_exports::MockExecutionController;
+use massa_models::prehash::Map;
+use massa_models::wrapped::WrappedContent;
+use massa_models::{
+    Address, Block, BlockHeader, BlockHeaderSerializer, BlockId, BlockSerializer, Slot,
+};
+use massa_signature::KeyPair;
+use massa_storage::Storage;
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn test_block_template_and_submit() {
+    let staking_keys: Vec<KeyPair> = (0..1).map(|_| KeyPair::generate()).collect();
+    let cfg = ConsensusConfig {
+        t0: 1000.into(),
+        future_block_processing_max_periods: 50,
+        disable_block_creation: true,
+        ..ConsensusConfig::default_with_staking_keys(&staking_keys)
+    };
+
+    let storage: Storage = Default::default();
+
+    // mock protocol & pool
+    let (mut protocol_controller, protocol_command_sender, protocol_event_receiver) =
+        MockProtocolController::new();
+    let (pool_controller, pool_command_sender) = MockPoolController::new();
+    let pool_sink = PoolCommandSink::new(pool_controller).await;
+    let (execution_controller, _execution_rx) = MockExecutionController::new_with_receiver();
+    // launch consensus controller
+    let (consensus_command_sender, consensus_event_receiver, consensus_manager) =
+        start_consensus_controller(
+            cfg.clone(),
+            ConsensusChannels {
+                execution_controller,
+                protocol_command_sender: protocol_command_sender.clone(),
+                protocol_event_receiver,
+                pool_command_sender,
+            },
+            None,
+            None,
+            storage,
+            0,
+            TEST_PASSWORD.to_string(),
+            Map::default(),
+        )
+        .await
+        .expect("could not start consensus controller");
+
+    let parents: Vec<BlockId> = consensus_command_sender
+        .get_block_graph_status(None, None)
+        .await
+        .expect("could not get block graph status")
+        .best_parents
+        .iter()
+        .map(|(b, _p)| *b)
+        .collect();
+    let slot = Slot::new(1, 0);
+
+    // only the drawn block producer gets a template
+    let intruder = KeyPair::generate();
+    match consensus_command_sender
+        .get_block_template(slot, intruder.get_public_key())
+        .await
+    {
+        Err(ConsensusError::BlockCreationError(_)) => {}
+        res => panic!("unexpected template result for an intruder: {:?}", res),
+    }
+
+    let template = consensus_command_sender
+        .get_block_template(slot, staking_keys[0].get_public_key())
+        .await
+        .expect("could not get block template");
+    assert_eq!(template.slot, slot);
+    assert_eq!(
+        template.creator_address,
+        Address::from_public_key(&staking_keys[0].get_public_key())
+    );
+    assert_eq!(template.parents, parents);
+    assert!(template.operations.is_empty());
+
+    // blocks signed by a staker that was not drawn are refused
+    let intruder_block = create_block_with_merkle_root(
+        &cfg,
+        template.operation_merkle_root,
+        slot,
+        template.parents.clone(),
+        &intruder,
+    );
+    match consensus_command_sender.submit_block(intruder_block).await {
+        Err(ConsensusError::InvalidSubmittedBlock(_)) => {}
+        res => panic!("unexpected submission result for an intruder: {:?}", res),
+    }
+
+    // build and sign the block outside of consensus, then submit it
+    let header = BlockHeader::new_wrapped(
+        BlockHeader {
+            slot: template.slot,
+            parents: template.parents,
+            operation_merkle_root: template.operation_merkle_root,
+            endorsements: template.endorsements,
+            denunciations: template.denunciations,
+        },
+        BlockHeaderSerializer::new(),
+        &staking_keys[0],
+    )
+    .unwrap();
+    let block = Block::new_wrapped(
+        Block {
+            header,
+            operations: template.operations,
+        },
+        BlockSerializer::new(),
+        &staking_keys[0],
+    )
+    .unwrap();
+    let block_id = consensus_command_sender
+        .submit_block(block.clone())
+        .await
+        .expect("could not submit block");
+    assert_eq!(block_id, block.id);
+
+    // the submitted block is propagated once its slot is reached
+    validate_propagate_block(&mut protocol_controller, block_id, 3000).await;
+
+    // stop controller while ignoring all commands
+    let stop_fut = consensus_manager.stop(consensus_event_receiver);
+    tokio::pin!(stop_fut);
+    protocol_controller
+        .ignore_commands_while(stop_fut)
+        .await
+        .unwrap();
+    pool_sink.stop().await;
+}
@@ -0,0 +1,373 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use super::tools::*;
+use super::{
+    mock_pool_controller::{MockPoolController, PoolCommandSink},
+    mock_protocol_controller::MockProtocolController,
+};
+use crate::start_consensus_controller;
+
+use massa_consensus_exports::settings::ConsensusChannels;
+use massa_consensus_exports::tools::TEST_PASSWORD;
+use massa_consensus_exports::ConsensusConfig;
+use massa_execution_exports::test_exports::MockExecutionController;
+use massa_hash::Hash;
+use massa_models::ledger_models::LedgerData;
+use massa_models::prehash::Map;
+use massa_models::wrapped::WrappedContent;
+use massa_models::{
+    Address, Amount, Block, BlockHeader, BlockHeaderSerializer, BlockId, BlockSerializer,
+    Denunciation, Slot, WrappedBlock,
+};
+use massa_protocol_exports::ProtocolCommand;
+use massa_signature::KeyPair;
+use massa_storage::Storage;
+use serial_test::serial;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Two stakers with one roll each in thread 0, and no block reward so that
+/// the only balance change of a block creator is its denunciation reward.
+fn denunciation_test_setup() -> (ConsensusConfig, Vec<KeyPair>) {
+    let thread_count = 2;
+    let (address_a, keypair_a) = random_address_on_thread(0, thread_count).into();
+    let (address_b, keypair_b) = random_address_on_thread(0, thread_count).into();
+    let mut ledger = HashMap::new();
+    ledger.insert(address_a, LedgerData::new(Amount::from_str("10").unwrap()));
+    ledger.insert(address_b, LedgerData::new(Amount::from_str("10").unwrap()));
+    let staking_keys = vec![keypair_a, keypair_b];
+    let cfg = ConsensusConfig {
+        t0: 500.into(),
+        thread_count,
+        block_reward: Amount::default(),
+        future_block_processing_max_periods: 50,
+        disable_block_creation: true,
+        ..ConsensusConfig::default_with_staking_keys_and_ledger(&staking_keys, &ledger)
+    };
+    (cfg, staking_keys)
+}
+
+/// Denounces `offender` for signing two different blocks at `slot`
+fn denounce_double_block(
+    cfg: &ConsensusConfig,
+    slot: Slot,
+    parents: Vec<BlockId>,
+    offender: &KeyPair,
+) -> Denunciation {
+    let block_1 = create_block_with_merkle_root(
+        cfg,
+        Hash::compute_from("first".as_bytes()),
+        slot,
+        parents.clone(),
+        offender,
+    );
+    let block_2 = create_block_with_merkle_root(
+        cfg,
+        Hash::compute_from("second".as_bytes()),
+        slot,
+        parents,
+        offender,
+    );
+    Denunciation::from_headers(&block_1.content.header, &block_2.content.header).unwrap()
+}
+
+fn create_block_with_denunciations(
+    slot: Slot,
+    parents: Vec<BlockId>,
+    creator: &KeyPair,
+    denunciations: Vec<Denunciation>,
+) -> WrappedBlock {
+    let header = BlockHeader::new_wrapped(
+        BlockHeader {
+            slot,
+            parents,
+            operation_merkle_root: Hash::compute_from("default_val".as_bytes()),
+            endorsements: Vec::new(),
+            denunciations,
+        },
+        BlockHeaderSerializer::new(),
+        creator,
+    )
+    .unwrap();
+    Block::new_wrapped(
+        Block {
+            header,
+            operations: Vec::new(),
+        },
+        BlockSerializer::new(),
+        creator,
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn test_double_staking_is_denounced() {
+    let staking_keys: Vec<KeyPair> = (0..1).map(|_| KeyPair::generate()).collect();
+    let cfg = ConsensusConfig {
+        t0: 1000.into(),
+        future_block_processing_max_periods: 50,
+        disable_block_creation: true,
+        ..ConsensusConfig::default_with_staking_keys(&staking_keys)
+    };
+
+    let storage: Storage = Default::default();
+
+    // mock protocol & pool
+    let (mut protocol_controller, protocol_command_sender, protocol_event_receiver) =
+        MockProtocolController::new();
+    let (pool_controller, pool_command_sender) = MockPoolController::new();
+    let pool_sink = PoolCommandSink::new(pool_controller).await;
+    let (execution_controller, _execution_rx) = MockExecutionController::new_with_receiver();
+    // launch consensus controller
+    let (consensus_command_sender, consensus_event_receiver, consensus_manager) =
+        start_consensus_controller(
+            cfg.clone(),
+            ConsensusChannels {
+                execution_controller,
+                protocol_command_sender: protocol_command_sender.clone(),
+                protocol_event_receiver,
+                pool_command_sender,
+            },
+            None,
+            None,
+            storage,
+            0,
+            TEST_PASSWORD.to_string(),
+            Map::default(),
+        )
+        .await
+        .expect("could not start consensus controller");
+
+    let parents: Vec<BlockId> = consensus_command_sender
+        .get_block_graph_status(None, None)
+        .await
+        .expect("could not get block graph status")
+        .best_parents
+        .iter()
+        .map(|(b, _p)| *b)
+        .collect();
+
+    // the only staker signs two different blocks for the same slot
+    let slot = Slot::new(1, 0);
+    let block_1 = create_block_with_merkle_root(
+        &cfg,
+        Hash::compute_from("first".as_bytes()),
+        slot,
+        parents.clone(),
+        &staking_keys[0],
+    );
+    let block_2 = create_block_with_merkle_root(
+        &cfg,
+        Hash::compute_from("second".as_bytes()),
+        slot,
+        parents.clone(),
+        &staking_keys[0],
+    );
+    protocol_controller.receive_block(block_1).await;
+    protocol_controller.receive_block(block_2).await;
+
+    // the double signing is denounced and propagated
+    let denunciations = protocol_controller
+        .wait_command(3000.into(), |cmd| match cmd {
+            ProtocolCommand::PropagateDenunciations(denunciations) => Some(denunciations),
+            _ => None,
+        })
+        .await
+        .expect("double staking not denounced before timeout");
+    assert_eq!(denunciations.len(), 1);
+    let key = denunciations[0].check().expect("invalid denunciation");
+    assert_eq!(
+        key.address,
+        Address::from_public_key(&staking_keys[0].get_public_key())
+    );
+    assert_eq!(key.slot, slot);
+    assert_eq!(key.index, None);
+
+    // stop controller while ignoring all commands
+    let stop_fut = consensus_manager.stop(consensus_event_receiver);
+    tokio::pin!(stop_fut);
+    protocol_controller
+        .ignore_commands_while(stop_fut)
+        .await
+        .unwrap();
+    pool_sink.stop().await;
+}
+
+#[tokio::test]
+#[serial]
+async fn test_denunciation_slashes_offender() {
+    let (cfg, staking_keys) = denunciation_test_setup();
+
+    consensus_without_pool_test(
+        cfg.clone(),
+        async move |mut protocol_controller, consensus_command_sender, consensus_event_receiver| {
+            let draws: HashMap<_, _> = consensus_command_sender
+                .get_selection_draws(Slot::new(1, 0), Slot::new(4, 0))
+                .await
+                .unwrap()
+                .into_iter()
+                .collect();
+            let genesis: Vec<BlockId> = consensus_command_sender
+                .get_block_graph_status(None, None)
+                .await
+                .unwrap()
+                .best_parents
+                .iter()
+                .map(|(b, _p)| *b)
+                .collect();
+
+            // the staker selected at slot (1, 0) signs a block and a conflicting one
+            let offender_addr = draws.get(&Slot::new(1, 0)).unwrap().0;
+            let offender = get_creator_for_draw(&offender_addr, &staking_keys);
+            let b1 = create_block(&cfg, Slot::new(1, 0), genesis.clone(), &offender);
+            propagate_block(&mut protocol_controller, b1.clone(), true, 3000).await;
+            let denunciation = denounce_double_block(&cfg, Slot::new(1, 0), genesis, &offender);
+
+            // the next block includes the denunciation
+            let includer_addr = draws.get(&Slot::new(2, 0)).unwrap().0;
+            let includer = get_creator_for_draw(&includer_addr, &staking_keys);
+            let parents: Vec<BlockId> = consensus_command_sender
+                .get_block_graph_status(None, None)
+                .await
+                .unwrap()
+                .best_parents
+                .iter()
+                .map(|(b, _p)| *b)
+                .collect();
+            assert!(parents.contains(&b1.id));
+            let b2 = create_block_with_denunciations(
+                Slot::new(2, 0),
+                parents.clone(),
+                &includer,
+                vec![denunciation.clone()],
+            );
+            propagate_block(&mut protocol_controller, b2.clone(), true, 3000).await;
+
+            // the rolls of the offender are slashed
+            let addresses_state = consensus_command_sender
+                .get_addresses_info(vec![offender_addr, includer_addr].into_iter().collect())
+                .await
+                .unwrap();
+            assert_eq!(
+                addresses_state
+                    .get(&offender_addr)
+                    .unwrap()
+                    .rolls
+                    .candidate_rolls,
+                0
+            );
+
+            // the includer gets its share of the value of the slashed roll
+            let reward = cfg
+                .roll_price
+                .checked_mul_u64(*cfg.denunciation_reward_ratio.numer())
+                .unwrap()
+                .checked_div_u64(*cfg.denunciation_reward_ratio.denom())
+                .unwrap();
+            assert_eq!(
+                addresses_state
+                    .get(&includer_addr)
+                    .unwrap()
+                    .ledger_info
+                    .candidate_ledger_info
+                    .balance,
+                Amount::from_str("10").unwrap().saturating_add(reward)
+            );
+
+            // the same denunciation cannot be included again in a descendant
+            let creator_addr = draws.get(&Slot::new(3, 0)).unwrap().0;
+            let creator = get_creator_for_draw(&creator_addr, &staking_keys);
+            let mut parents = parents;
+            parents[0] = b2.id;
+            let b3 = create_block_with_denunciations(
+                Slot::new(3, 0),
+                parents,
+                &creator,
+                vec![denunciation],
+            );
+            propagate_block(&mut protocol_controller, b3, false, 3000).await;
+
+            (
+                protocol_controller,
+                consensus_command_sender,
+                consensus_event_receiver,
+            )
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn test_invalid_denunciations_are_rejected() {
+    let (cfg, staking_keys) = denunciation_test_setup();
+
+    consensus_without_pool_test(
+        cfg.clone(),
+        async move |mut protocol_controller, consensus_command_sender, consensus_event_receiver| {
+            let draws: HashMap<_, _> = consensus_command_sender
+                .get_selection_draws(Slot::new(1, 0), Slot::new(3, 0))
+                .await
+                .unwrap()
+                .into_iter()
+                .collect();
+            let genesis: Vec<BlockId> = consensus_command_sender
+                .get_block_graph_status(None, None)
+                .await
+                .unwrap()
+                .best_parents
+                .iter()
+                .map(|(b, _p)| *b)
+                .collect();
+
+            let selected_addr = draws.get(&Slot::new(1, 0)).unwrap().0;
+            let selected = get_creator_for_draw(&selected_addr, &staking_keys);
+            let not_selected = staking_keys
+                .iter()
+                .find(|keypair| {
+                    Address::from_public_key(&keypair.get_public_key()) != selected_addr
+                })
+                .unwrap()
+                .clone();
+            let b1 = create_block(&cfg, Slot::new(1, 0), genesis.clone(), &selected);
+            propagate_block(&mut protocol_controller, b1.clone(), true, 3000).await;
+
+            let creator_addr = draws.get(&Slot::new(2, 0)).unwrap().0;
+            let creator = get_creator_for_draw(&creator_addr, &staking_keys);
+            let mut parents = genesis.clone();
+            parents[0] = b1.id;
+
+            // a staker that was not selected for the denounced slot cannot be denounced
+            let block = create_block_with_denunciations(
+                Slot::new(2, 0),
+                parents.clone(),
+                &creator,
+                vec![denounce_double_block(
+                    &cfg,
+                    Slot::new(1, 0),
+                    genesis.clone(),
+                    &not_selected,
+                )],
+            );
+            propagate_block(&mut protocol_controller, block, false, 3000).await;
+
+            // the same double signing cannot be denounced twice in a header
+            let denunciation = denounce_double_block(&cfg, Slot::new(1, 0), genesis, &selected);
+            let block = create_block_with_denunciations(
+                Slot::new(2, 0),
+                parents,
+                &creator,
+                vec![denunciation.clone(), denunciation],
+            );
+            propagate_block(&mut protocol_controller, block, false, 3000).await;
+
+            (
+                protocol_controller,
+                consensus_command_sender,
+                consensus_event_receiver,
+            )
+        },
+    )
+    .await;
+}
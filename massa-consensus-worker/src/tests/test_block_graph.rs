@@ -43,6 +43,7 @@ fn get_export_active_test_block() -> (WrappedBlock, ExportActiveBlock) {
                         &keypair,
                     )
                     .unwrap()],
+                    denunciations: Vec::new(),
                 },
                 BlockHeaderSerializer::new(),
                 &keypair,
@@ -471,6 +471,7 @@ pub fn create_block_with_merkle_root(
             parents: best_parents,
             operation_merkle_root,
             endorsements: Vec::new(),
+            denunciations: Vec::new(),
         },
         BlockHeaderSerializer::new(),
         &creator,
@@ -525,6 +526,7 @@ pub fn get_export_active_test_block(
                     parents: parents.iter().map(|(id, _)| *id).collect(),
                     slot,
                     endorsements: Vec::new(),
+                    denunciations: Vec::new(),
                 },
                 BlockHeaderSerializer::new(),
                 &keypair,
@@ -569,6 +571,7 @@ pub fn create_block_with_operations(
             parents: best_parents.clone(),
             operation_merkle_root,
             endorsements: Vec::new(),
+            denunciations: Vec::new(),
         },
         BlockHeaderSerializer::new(),
         &creator,
@@ -605,6 +608,7 @@ pub fn create_block_with_operations_and_endorsements(
             parents: best_parents.clone(),
             operation_merkle_root,
             endorsements,
+            denunciations: Vec::new(),
         },
         BlockHeaderSerializer::new(),
         creator,
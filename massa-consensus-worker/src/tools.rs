@@ -9,10 +9,11 @@ use massa_consensus_exports::{
 use massa_graph::{settings::GraphConfig, BlockGraph, BootstrapableGraph};
 use massa_models::{constants::CHANNEL_SIZE, prehash::Map, Address};
 use massa_proof_of_stake_exports::{ExportProofOfStake, ProofOfStake, ProofOfStakeConfig};
-use massa_signature::KeyPair;
+use massa_signature::{KeyPair, PublicKey};
+use massa_signer::RemoteSigner;
 use massa_storage::Storage;
 use tokio::sync::mpsc;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 use crate::consensus_worker::ConsensusWorker;
 
@@ -69,7 +70,32 @@ pub async fn start_consensus_controller(
         boot_pos,
     )
     .await?;
-    pos.set_watched_addresses(staking_keys.keys().copied().collect());
+
+    // get the staking addresses of the remote signer
+    let mut remote_staking_keys: Map<Address, PublicKey> = Map::default();
+    let remote_signer = cfg.remote_signer.clone().map(RemoteSigner::new);
+    if let Some(remote_signer) = &remote_signer {
+        // an unreachable signer does not prevent the node from starting, the worker retries later
+        match remote_signer.get_public_keys().await {
+            Ok(public_keys) => {
+                for public_key in public_keys {
+                    remote_staking_keys.insert(Address::from_public_key(&public_key), public_key);
+                }
+            }
+            Err(err) => warn!(
+                "could not get the staking addresses of the remote signer, retrying later: {}",
+                err
+            ),
+        }
+    }
+
+    pos.set_watched_addresses(
+        staking_keys
+            .keys()
+            .chain(remote_staking_keys.keys())
+            .copied()
+            .collect(),
+    );
     let (command_tx, command_rx) = mpsc::channel::<ConsensusCommand>(CHANNEL_SIZE);
     let (event_tx, event_rx) = mpsc::channel::<ConsensusEvent>(CHANNEL_SIZE);
     let (manager_tx, manager_rx) = mpsc::channel::<ConsensusManagementCommand>(1);
@@ -91,6 +117,8 @@ pub async fn start_consensus_controller(
             clock_compensation,
             staking_keys,
             password,
+            remote_signer,
+            remote_staking_keys,
         )
         .await?
         .run_loop()
@@ -3,6 +3,7 @@
 //! This module exports generic traits representing interfaces for interacting with the Execution worker
 
 use crate::types::ExecutionOutput;
+use crate::types::ExecutionStats;
 use crate::types::ReadOnlyExecutionRequest;
 use crate::ExecutionError;
 use massa_models::api::EventFilter;
@@ -76,6 +77,10 @@ pub trait ExecutionController: Send + Sync {
         req: ReadOnlyExecutionRequest,
     ) -> Result<ExecutionOutput, ExecutionError>;
 
+    /// Get statistics about the execution worker: cursors, slot execution time and queue lengths.
+    /// Blocks while a slot is being executed.
+    fn get_stats(&self) -> ExecutionStats;
+
     /// Returns a boxed clone of self.
     /// Useful to allow cloning `Box<dyn ExecutionController>`.
     fn clone_box(&self) -> Box<dyn ExecutionController>;
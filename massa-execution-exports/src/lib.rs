@@ -55,8 +55,8 @@ pub use error::ExecutionError;
 pub use event_store::EventStore;
 pub use settings::ExecutionConfig;
 pub use types::{
-    ExecutionOutput, ExecutionStackElement, ReadOnlyCallRequest, ReadOnlyExecutionRequest,
-    ReadOnlyExecutionTarget,
+    ExecutionOutput, ExecutionStackElement, ExecutionStats, ReadOnlyCallRequest,
+    ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
 };
 
 #[cfg(feature = "testing")]
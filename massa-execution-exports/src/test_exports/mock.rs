@@ -2,7 +2,10 @@
 
 //! This file defines utilities to mock the crate for testing purposes
 
-use crate::{ExecutionController, ExecutionError, ExecutionOutput, ReadOnlyExecutionRequest};
+use crate::{
+    ExecutionController, ExecutionError, ExecutionOutput, ExecutionStats,
+    ReadOnlyExecutionRequest,
+};
 use massa_ledger_exports::LedgerEntry;
 use massa_models::{api::EventFilter, output_event::SCOutputEvent, Address, Amount, BlockId, Slot};
 use std::{
@@ -41,6 +44,11 @@ pub enum MockExecutionControllerMessage {
         /// response channel
         response_tx: mpsc::Sender<(Option<LedgerEntry>, Option<LedgerEntry>)>,
     },
+    /// get execution statistics
+    GetStats {
+        /// response channel
+        response_tx: mpsc::Sender<ExecutionStats>,
+    },
     /// read only execution request
     ExecuteReadonlyRequest {
         /// read only execution request
@@ -140,6 +148,16 @@ impl ExecutionController for MockExecutionController {
         response_rx.recv().unwrap()
     }
 
+    fn get_stats(&self) -> ExecutionStats {
+        let (response_tx, response_rx) = mpsc::channel();
+        self.0
+            .lock()
+            .unwrap()
+            .send(MockExecutionControllerMessage::GetStats { response_tx })
+            .unwrap();
+        response_rx.recv().unwrap()
+    }
+
     fn clone_box(&self) -> Box<dyn ExecutionController> {
         Box::new(self.clone())
     }
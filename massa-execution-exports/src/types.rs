@@ -5,6 +5,7 @@
 use crate::event_store::EventStore;
 use massa_final_state::StateChanges;
 use massa_models::{Address, Amount, BlockId, Slot};
+use std::time::Duration;
 
 /// structure describing the output of a single execution
 #[derive(Debug, Clone)]
@@ -88,3 +89,18 @@ pub struct ExecutionStackElement {
     /// the performance hit of linear search remains minimal because `owned_addresses` will always contain very few elements.
     pub owned_addresses: Vec<Address>,
 }
+
+/// Statistics about the execution worker
+#[derive(Debug, Clone)]
+pub struct ExecutionStats {
+    /// highest executed slot
+    pub active_cursor: Slot,
+    /// highest executed final slot
+    pub final_cursor: Slot,
+    /// number of slots executed since the start of the worker, finally or speculatively
+    pub executed_slot_count: u64,
+    /// total time spent executing those slots
+    pub slot_execution_time: Duration,
+    /// number of read-only execution requests waiting to be executed
+    pub readonly_queue_length: usize,
+}
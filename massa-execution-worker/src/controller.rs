@@ -7,7 +7,7 @@ use crate::execution::ExecutionState;
 use crate::request_queue::{RequestQueue, RequestWithResponseSender};
 use massa_execution_exports::{
     ExecutionConfig, ExecutionController, ExecutionError, ExecutionManager, ExecutionOutput,
-    ReadOnlyExecutionRequest,
+    ExecutionStats, ReadOnlyExecutionRequest,
 };
 use massa_models::api::EventFilter;
 use massa_models::output_event::SCOutputEvent;
@@ -15,6 +15,7 @@ use massa_models::{Address, Amount};
 use massa_models::{BlockId, Slot};
 use parking_lot::{Condvar, Mutex, RwLock};
 use std::collections::{BTreeSet, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use tracing::info;
 
@@ -61,6 +62,9 @@ pub struct ExecutionControllerImpl {
     pub(crate) input_data: Arc<(Condvar, Mutex<ExecutionInputData>)>,
     /// current execution state (see execution.rs for details)
     pub(crate) execution_state: Arc<RwLock<ExecutionState>>,
+    /// number of read-only requests queued in the execution thread,
+    /// kept apart from the execution state so that updating it does not wait for slot executions
+    pub(crate) readonly_queue_length: Arc<AtomicUsize>,
 }
 
 impl ExecutionController for ExecutionControllerImpl {
@@ -179,6 +183,21 @@ impl ExecutionController for ExecutionControllerImpl {
         }
     }
 
+    /// Get statistics about the execution worker.
+    /// The read-only queue length includes the requests not yet taken by the worker.
+    fn get_stats(&self) -> ExecutionStats {
+        let pending_readonly_requests = self.input_data.1.lock().readonly_requests.len();
+        let exec_state = self.execution_state.read();
+        ExecutionStats {
+            active_cursor: exec_state.active_cursor,
+            final_cursor: exec_state.final_cursor,
+            executed_slot_count: exec_state.executed_slot_count,
+            slot_execution_time: exec_state.slot_execution_time,
+            readonly_queue_length: self.readonly_queue_length.load(Ordering::Relaxed)
+                + pending_readonly_requests,
+        }
+    }
+
     /// Returns a boxed clone of self.
     /// Allows cloning `Box<dyn ExecutionController>`,
     /// see `massa-execution-exports/controller_traits.rs`
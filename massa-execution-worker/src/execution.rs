@@ -26,7 +26,7 @@ use massa_sc_runtime::Interface;
 use massa_storage::Storage;
 use parking_lot::{Mutex, RwLock};
 use std::collections::BTreeSet;
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 use tracing::debug;
 
 /// Used to acquire a lock on the execution context
@@ -61,6 +61,10 @@ pub(crate) struct ExecutionState {
     execution_interface: Box<dyn Interface>,
     /// Shared storage across all modules
     storage: Storage,
+    /// number of slots executed since the start of the worker, finally or speculatively
+    pub executed_slot_count: u64,
+    /// total time spent executing slots
+    pub slot_execution_time: Duration,
 }
 
 impl ExecutionState {
@@ -111,6 +115,8 @@ impl ExecutionState {
             active_cursor: last_final_slot,
             final_cursor: last_final_slot,
             storage,
+            executed_slot_count: 0,
+            slot_execution_time: Duration::ZERO,
         }
     }
 
@@ -600,6 +606,12 @@ impl ExecutionState {
         }
     }
 
+    /// Accounts for a slot execution that lasted `duration` in the execution statistics
+    pub fn record_slot_execution(&mut self, duration: Duration) {
+        self.executed_slot_count = self.executed_slot_count.saturating_add(1);
+        self.slot_execution_time = self.slot_execution_time.saturating_add(duration);
+    }
+
     /// Executes a full slot (with or without a block inside) without causing any changes to the state,
     /// just yielding the execution output.
     ///
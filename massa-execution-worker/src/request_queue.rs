@@ -147,6 +147,11 @@ impl<T, R> RequestQueue<T, R> {
         self.queue.len() >= self.max_items
     }
 
+    /// Number of queued requests
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
     /// Checks whether the queue is empty
     ///
     /// # Returns
@@ -100,6 +100,41 @@ fn test_sending_read_only_execution_command() {
     manager.stop();
 }
 
+#[test]
+#[serial]
+fn test_execution_stats() {
+    let exec_cfg = ExecutionConfig {
+        t0: 10.into(),
+        ..ExecutionConfig::default()
+    };
+    let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    let (mut manager, controller) =
+        start_execution_worker(exec_cfg, sample_state, Default::default());
+    controller.update_blockclique_status(Default::default(), Default::default());
+
+    // empty slots are executed as time goes by
+    std::thread::sleep(Duration::from_millis(300));
+    let stats = controller.get_stats();
+    assert!(stats.executed_slot_count > 0);
+    assert!(stats.active_cursor > Slot::new(0, 0));
+    assert!(stats.final_cursor <= stats.active_cursor);
+
+    // a read-only request leaves the queue once executed
+    controller
+        .execute_readonly_request(ReadOnlyExecutionRequest {
+            max_gas: 1_000_000,
+            simulated_gas_price: Amount::from_mantissa_scale(1_000_000, 0),
+            call_stack: vec![],
+            target: ReadOnlyExecutionTarget::BytecodeExecution(
+                include_bytes!("./wasm/event_test.wasm").to_vec(),
+            ),
+        })
+        .unwrap();
+    assert_eq!(controller.get_stats().readonly_queue_length, 0);
+    assert!(controller.get_stats().executed_slot_count >= stats.executed_slot_count);
+    manager.stop();
+}
+
 /// Test the gas usage in nested calls using call SC operation
 ///
 /// Create a smart contract and send it in the blockclique.
@@ -372,6 +407,7 @@ fn create_block(
             parents: vec![],
             operation_merkle_root,
             endorsements: vec![],
+            denunciations: Vec::new(),
         },
         BlockHeaderSerializer::new(),
         &creator_keypair,
@@ -21,7 +21,8 @@ use massa_models::{
 use massa_storage::Storage;
 use massa_time::MassaTime;
 use parking_lot::{Condvar, Mutex, RwLock};
-use std::{collections::HashMap, sync::Arc};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{collections::HashMap, sync::Arc, time::Instant};
 use tracing::{info, warn};
 
 /// Structure gathering all elements needed by the execution thread
@@ -48,6 +49,8 @@ pub(crate) struct ExecutionThread {
     execution_state: Arc<RwLock<ExecutionState>>,
     /// queue for read-only requests and response MPSCs to send back their outputs
     readonly_requests: RequestQueue<ReadOnlyExecutionRequest, ExecutionOutput>,
+    /// length of `readonly_requests`, shared with the controller for statistics
+    readonly_queue_length: Arc<AtomicUsize>,
 }
 
 impl ExecutionThread {
@@ -62,6 +65,7 @@ impl ExecutionThread {
         config: ExecutionConfig,
         input_data: Arc<(Condvar, Mutex<ExecutionInputData>)>,
         execution_state: Arc<RwLock<ExecutionState>>,
+        readonly_queue_length: Arc<AtomicUsize>,
     ) -> Self {
         // get the latest executed final slot, at the output of which the final ledger is attached
         let final_cursor = execution_state.read().final_cursor;
@@ -78,6 +82,7 @@ impl ExecutionThread {
             readonly_requests: RequestQueue::new(config.readonly_queue_length),
             config,
             execution_state,
+            readonly_queue_length,
         }
     }
 
@@ -279,7 +284,9 @@ impl ExecutionThread {
         exec_state.clear_history();
 
         // execute slot
+        let execution_start = Instant::now();
         let exec_out = exec_state.execute_slot(slot, exec_target);
+        exec_state.record_slot_execution(execution_start.elapsed());
 
         // apply execution output to final state
         exec_state.apply_final_execution_output(exec_out);
@@ -321,7 +328,9 @@ impl ExecutionThread {
         };
 
         // execute the slot
+        let execution_start = Instant::now();
         let exec_out = exec_state.execute_slot(slot, exec_target);
+        exec_state.record_slot_execution(execution_start.elapsed());
 
         // apply execution output to active state
         exec_state.apply_active_execution_output(exec_out);
@@ -379,6 +388,8 @@ impl ExecutionThread {
         // Append incoming readonly requests to our readonly request queue
         // Excess requests are cancelled
         self.readonly_requests.extend(new_requests);
+        self.readonly_queue_length
+            .store(self.readonly_requests.len(), Ordering::Relaxed);
     }
 
     /// Executes a read-only request from the queue, if any.
@@ -388,6 +399,8 @@ impl ExecutionThread {
     /// true if a request was executed, false otherwise
     fn execute_one_readonly_request(&mut self) -> bool {
         if let Some(req_resp) = self.readonly_requests.pop() {
+            self.readonly_queue_length
+                .store(self.readonly_requests.len(), Ordering::Relaxed);
             let (req, resp_tx) = req_resp.into_request_sender_pair();
 
             // Acquire read access to the execution state and execute the read-only request
@@ -596,16 +609,26 @@ pub fn start_execution_worker(
         Mutex::new(ExecutionInputData::new(config.clone())),
     ));
 
+    // length of the read-only request queue of the execution thread
+    let readonly_queue_length = Arc::new(AtomicUsize::new(0));
+
     // create a controller
     let controller = ExecutionControllerImpl {
         input_data: input_data.clone(),
         execution_state: execution_state.clone(),
+        readonly_queue_length: readonly_queue_length.clone(),
     };
 
     // launch the execution thread
     let input_data_clone = input_data.clone();
     let thread_handle = std::thread::spawn(move || {
-        ExecutionThread::new(config, input_data_clone, execution_state).main_loop();
+        ExecutionThread::new(
+            config,
+            input_data_clone,
+            execution_state,
+            readonly_queue_length,
+        )
+        .main_loop();
     });
 
     // create a manager
@@ -88,13 +88,14 @@ impl FinalState {
     /// Take a part of the final state changes (ledger and async pool) using a `Slot`, a `Address` and a `AsyncMessageId`.
     /// Every ledgers changes that are after `last_slot` and before or equal of `last_address` must be returned.
     /// Every async pool changes that are after `last_slot` and before or equal of `last_id_async_pool` must be returned.
+    /// If `last_id_async_pool` is None, no async pool part was received yet and no async pool change is returned.
     ///
     /// Error case: When the last_slot is too old for `self.changes_history`
     pub fn get_state_changes_part(
         &self,
         last_slot: Slot,
         last_address: Address,
-        last_id_async_pool: AsyncMessageId,
+        last_id_async_pool: Option<AsyncMessageId>,
     ) -> Result<StateChanges, FinalStateError> {
         let pos_slot = if !self.changes_history.is_empty() {
             // Safe because we checked that there is changes just above.
@@ -138,12 +139,11 @@ impl FinalState {
                     .async_pool_changes
                     .0
                     .iter()
-                    .filter_map(|change| match change {
-                        Change::Add(id, _) if id <= &last_id_async_pool => Some(change.clone()),
-                        Change::Delete(id) if id <= &last_id_async_pool => Some(change.clone()),
-                        Change::Add(..) => None,
-                        Change::Delete(..) => None,
+                    .filter(|change| match (change, &last_id_async_pool) {
+                        (Change::Add(id, _) | Change::Delete(id), Some(last_id)) => id <= last_id,
+                        (_, None) => false,
                     })
+                    .cloned()
                     .collect(),
             );
             res_changes.async_pool_changes = async_pool_changes;
@@ -206,12 +206,12 @@ mod tests {
         final_state.changes_history = history_state_changes;
         // Test slot filter
         let part = final_state
-            .get_state_changes_part(Slot::new(2, 0), low_address, message.compute_id())
+            .get_state_changes_part(Slot::new(2, 0), low_address, Some(message.compute_id()))
             .unwrap();
         assert_eq!(part.ledger_changes.0.len(), 1);
         // Test address filter
         let part = final_state
-            .get_state_changes_part(Slot::new(2, 0), high_address, message.compute_id())
+            .get_state_changes_part(Slot::new(2, 0), high_address, Some(message.compute_id()))
             .unwrap();
         assert_eq!(part.ledger_changes.0.len(), 1);
     }
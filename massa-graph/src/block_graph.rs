@@ -12,12 +12,14 @@ use crate::{
 use massa_hash::Hash;
 use massa_logging::massa_trace;
 use massa_models::clique::Clique;
+use massa_models::constants::MAX_DENUNCIATIONS_PER_BLOCK;
+use massa_models::stats::FeeDensityPercentiles;
 use massa_models::{
     active_block::ActiveBlock,
     api::EndorsementInfo,
     ledger_models::LedgerChange,
     rolls::{RollCounts, RollUpdate, RollUpdates},
-    WrappedEndorsement, WrappedHeader, WrappedOperation,
+    Denunciation, DenunciationKey, WrappedEndorsement, WrappedHeader, WrappedOperation,
 };
 use massa_models::{
     ledger_models::LedgerChanges, Address, Block, BlockHeader, BlockHeaderSerializer, BlockId,
@@ -38,7 +40,7 @@ use serde::{Deserialize, Serialize};
 use std::mem;
 use std::{collections::HashSet, usize};
 use std::{
-    collections::{hash_map, BTreeSet, HashMap, VecDeque},
+    collections::{btree_map, hash_map, BTreeMap, BTreeSet, HashMap, VecDeque},
     convert::TryFrom,
 };
 use tracing::{debug, error, info, warn};
@@ -315,6 +317,14 @@ pub struct BlockGraph {
     new_final_blocks: Set<BlockId>,
     /// Newly stale block mapped to creator and slot
     new_stale_blocks: Map<BlockId, (PublicKey, Slot)>,
+    /// Headers seen in valid blocks, to detect stakers signing two headers for the same slot
+    seen_headers: HashMap<DenunciationKey, WrappedHeader>,
+    /// Endorsements seen in valid blocks, to detect stakers signing two endorsements for the same slot and index
+    seen_endorsements: HashMap<DenunciationKey, WrappedEndorsement>,
+    /// Denunciations that can still be included in a block
+    denunciations: BTreeMap<DenunciationKey, Denunciation>,
+    /// Denunciations that need to be propagated
+    new_denunciations: Vec<Denunciation>,
     /// ledger
     ledger: Ledger,
     /// Shared storage,
@@ -356,6 +366,20 @@ enum EndorsementsCheckOutcome {
     WaitForSlot,
 }
 
+/// Possible outcomes of denunciations check
+#[derive(Debug)]
+enum DenunciationsCheckOutcome {
+    /// Everything is ok
+    Proceed {
+        /// same thread ancestors searched for already included denunciations
+        dependencies: Set<BlockId>,
+    },
+    /// There is something wrong with a denunciation
+    Discard(DiscardReason),
+    /// The draws of a denounced slot are not available yet
+    WaitForSlot,
+}
+
 /// Possible outcome of block check
 #[derive(Debug)]
 enum BlockCheckOutcome {
@@ -419,6 +443,7 @@ pub fn create_genesis_block(
             parents: Vec::new(),
             operation_merkle_root: Hash::compute_from(&Vec::new()),
             endorsements: Vec::new(),
+            denunciations: Vec::new(),
         },
         BlockHeaderSerializer::new(),
         keypair,
@@ -525,6 +550,10 @@ impl BlockGraph {
                 ledger,
                 new_final_blocks: Default::default(),
                 new_stale_blocks: Default::default(),
+                seen_headers: Default::default(),
+                seen_endorsements: Default::default(),
+                denunciations: Default::default(),
+                new_denunciations: Default::default(),
                 storage,
             };
             // compute block descendants
@@ -582,6 +611,10 @@ impl BlockGraph {
                 ledger,
                 new_final_blocks: Default::default(),
                 new_stale_blocks: Default::default(),
+                seen_headers: Default::default(),
+                seen_endorsements: Default::default(),
+                denunciations: Default::default(),
+                new_denunciations: Default::default(),
                 storage,
             })
         }
@@ -1031,6 +1064,7 @@ impl BlockGraph {
                     &RollUpdate {
                         roll_purchases: 0,
                         roll_sales: *roll_count,
+                        roll_slashes: 0,
                     },
                 )?;
             }
@@ -1039,6 +1073,74 @@ impl BlockGraph {
             self.block_state_try_apply(&mut accu, header, None, Some(roll_updates), pos)?;
         }
 
+        // slash the rolls of denounced stakers and reward the block creator with a share of them
+        if !header.content.denunciations.is_empty() {
+            let mut roll_updates = RollUpdates::default();
+            let mut slashed_roll_count: u64 = 0;
+
+            // the offenders are in the block's thread: this was checked with the header
+            let offender_addrs: Set<Address> = header
+                .content
+                .denunciations
+                .iter()
+                .map(|denunciation| Address::from_public_key(&denunciation.public_key))
+                .collect();
+
+            // load missing address info (because we need to read roll counts)
+            self.block_state_sync_rolls(&mut accu, header, pos, &offender_addrs)?;
+
+            // accumulate roll updates
+            for addr in offender_addrs {
+                let roll_count = *accu.roll_counts.0.get(&addr).unwrap_or(&0);
+                slashed_roll_count =
+                    slashed_roll_count.checked_add(roll_count).ok_or_else(|| {
+                        GraphError::InvalidLedgerChange("overflow counting slashed rolls".into())
+                    })?;
+                roll_updates.apply(
+                    &addr,
+                    &RollUpdate {
+                        roll_purchases: 0,
+                        roll_sales: 0,
+                        roll_slashes: roll_count,
+                    },
+                )?;
+            }
+
+            // credit the block creator
+            let reward = self
+                .cfg
+                .roll_price
+                .checked_mul_u64(slashed_roll_count)
+                .and_then(|amount| {
+                    amount.checked_mul_u64(*self.cfg.denunciation_reward_ratio.numer())
+                })
+                .and_then(|amount| {
+                    amount.checked_div_u64(*self.cfg.denunciation_reward_ratio.denom())
+                })
+                .ok_or_else(|| {
+                    GraphError::InvalidLedgerChange(
+                        "overflow getting denunciation reward amount".into(),
+                    )
+                })?;
+            let mut reward_ledger_changes = LedgerChanges::default();
+            reward_ledger_changes.apply(
+                &header.creator_address,
+                &LedgerChange {
+                    balance_delta: reward,
+                    balance_increment: true,
+                },
+            )?;
+
+            // apply changes to block state
+            self.block_state_try_apply(
+                &mut accu,
+                header,
+                Some(reward_ledger_changes),
+                Some(roll_updates),
+                pos,
+            )?;
+        }
+
         Ok(accu)
     }
 
@@ -1082,6 +1184,43 @@ impl BlockGraph {
             .collect()
     }
 
+    /// Gets the fee density percentiles of the operations included in the active (final and non-final) blocks
+    /// at or after `start_slot`, by thread. `None` for the threads without such operations.
+    pub fn get_recent_fee_densities(
+        &self,
+        start_slot: Slot,
+    ) -> Result<Vec<Option<FeeDensityPercentiles>>> {
+        let mut densities = vec![Vec::new(); self.cfg.thread_count as usize];
+        for b_id in self.active_index.iter() {
+            if let Some(BlockStatus::Active(active_block)) = self.block_statuses.get(b_id) {
+                if active_block.slot < start_slot || active_block.operation_set.is_empty() {
+                    continue;
+                }
+                let stored_block = self.storage.retrieve_block(b_id).ok_or_else(|| {
+                    GraphError::MissingBlock(format!(
+                        "missing block in get_recent_fee_densities: {}",
+                        b_id
+                    ))
+                })?;
+                let stored_block = stored_block.read();
+                if let Some(thread_densities) = densities.get_mut(active_block.slot.thread as usize)
+                {
+                    thread_densities.extend(
+                        stored_block
+                            .content
+                            .operations
+                            .iter()
+                            .map(|op| op.get_fee_density()),
+                    );
+                }
+            }
+        }
+        Ok(densities
+            .into_iter()
+            .map(FeeDensityPercentiles::from_densities)
+            .collect())
+    }
+
     /// for algorithm see `pos.md`
     /// if `addrs_opt` is `Some(addrs)`, restrict to address. If None, return all addresses.
     /// returns (`roll_counts`, `cycle_roll_updates`)
@@ -1588,6 +1727,8 @@ impl BlockGraph {
                 };
                 match self.check_header(&block_id, &header, pos, current_slot)? {
                     HeaderCheckOutcome::Proceed { .. } => {
+                        self.note_signed_contents(&header);
+
                         // set as waiting dependencies
                         let mut dependencies = Set::<BlockId>::default();
                         dependencies.insert(block_id); // add self as unsatisfied
@@ -1712,6 +1853,7 @@ impl BlockGraph {
                         massa_trace!("consensus.block_graph.process.incoming_block.valid", {
                             "block_id": block_id
                         });
+                        self.note_signed_contents(&stored_block.content.header);
                         (
                             stored_block.involved_addresses(&operation_set)?,
                             stored_block.addresses_to_endorsements()?,
@@ -1928,6 +2070,218 @@ impl BlockGraph {
         }
     }
 
+    /// Notes a valid header and its endorsements.
+    /// If their creators already signed a different header or endorsement
+    /// for the same slot (and index), they are denounced.
+    fn note_signed_contents(&mut self, header: &WrappedHeader) {
+        let mut conflicts = Vec::new();
+        let header_key = DenunciationKey {
+            address: header.creator_address,
+            slot: header.content.slot,
+            index: None,
+        };
+        match self.seen_headers.entry(header_key) {
+            hash_map::Entry::Occupied(occ) => {
+                if occ.get().id != header.id {
+                    conflicts.push((header_key, Denunciation::from_headers(occ.get(), header)));
+                }
+            }
+            hash_map::Entry::Vacant(vac) => {
+                vac.insert(header.clone());
+            }
+        }
+        for endorsement in header.content.endorsements.iter() {
+            let endorsement_key = DenunciationKey {
+                address: endorsement.creator_address,
+                slot: endorsement.content.slot,
+                index: Some(endorsement.content.index),
+            };
+            match self.seen_endorsements.entry(endorsement_key) {
+                hash_map::Entry::Occupied(occ) => {
+                    if occ.get().id != endorsement.id {
+                        conflicts.push((
+                            endorsement_key,
+                            Denunciation::from_endorsements(occ.get(), endorsement),
+                        ));
+                    }
+                }
+                hash_map::Entry::Vacant(vac) => {
+                    vac.insert(endorsement.clone());
+                }
+            }
+        }
+        for (key, denunciation) in conflicts {
+            match denunciation {
+                Ok(denunciation) => {
+                    if self.insert_denunciation(key, denunciation) {
+                        info!("double signing detected: denouncing {}", key);
+                    }
+                }
+                Err(err) => warn!("could not denounce {}: {}", key, err),
+            }
+        }
+    }
+
+    /// true if a double signing can no longer be denounced in a block
+    fn is_denunciation_expired(&self, key: &DenunciationKey) -> bool {
+        let offender_thread = key.address.get_thread(self.cfg.thread_count);
+        key.slot
+            .period
+            .saturating_add(self.cfg.denunciation_expire_periods)
+            <= self.latest_final_blocks_periods[offender_thread as usize].1
+    }
+
+    /// Adds a denunciation to the ones that can be included in blocks and propagated.
+    /// Returns false if it was already known or has expired.
+    fn insert_denunciation(&mut self, key: DenunciationKey, denunciation: Denunciation) -> bool {
+        if self.is_denunciation_expired(&key) {
+            return false;
+        }
+        match self.denunciations.entry(key) {
+            btree_map::Entry::Occupied(_) => false,
+            btree_map::Entry::Vacant(vac) => {
+                vac.insert(denunciation.clone());
+                self.new_denunciations.push(denunciation);
+                true
+            }
+        }
+    }
+
+    /// Adds denunciations received from the network.
+    /// Denunciations of stakers that were not selected for the denounced slot are ignored.
+    pub fn add_denunciations(
+        &mut self,
+        denunciations: Vec<Denunciation>,
+        pos: &mut ProofOfStake,
+    ) -> Result<()> {
+        for denunciation in denunciations {
+            let key = match denunciation.check() {
+                Ok(key) => key,
+                Err(err) => {
+                    debug!("ignoring invalid denunciation: {}", err);
+                    continue;
+                }
+            };
+            if self.denunciations.contains_key(&key) || self.is_denunciation_expired(&key) {
+                continue;
+            }
+            match BlockGraph::is_offender_selected(&key, pos) {
+                Ok(true) => {
+                    if self.insert_denunciation(key, denunciation) {
+                        info!("received denunciation of {}", key);
+                    }
+                }
+                Ok(false) => debug!("ignoring denunciation of {}: it was not selected", key),
+                Err(ProofOfStakeError::PosCycleUnavailable(_)) => {
+                    debug!("ignoring denunciation of {}: draws unavailable", key)
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the denounced address was selected to produce the denounced block or endorsement
+    fn is_offender_selected(
+        key: &DenunciationKey,
+        pos: &mut ProofOfStake,
+    ) -> std::result::Result<bool, ProofOfStakeError> {
+        match key.index {
+            None => pos
+                .draw_block_producer(key.slot)
+                .map(|addr| addr == key.address),
+            Some(index) => pos
+                .draw_endorsement_producers(key.slot)
+                .map(|draws| draws.get(index as usize) == Some(&key.address)),
+        }
+    }
+
+    /// Gets the denunciations to include in a block created at `slot`
+    /// on top of `parent_in_own_thread`
+    pub fn get_block_denunciations(
+        &self,
+        slot: Slot,
+        parent_in_own_thread: BlockId,
+    ) -> Result<Vec<Denunciation>> {
+        let mut candidates: BTreeMap<DenunciationKey, &Denunciation> = self
+            .denunciations
+            .iter()
+            .filter(|(key, _)| {
+                key.address.get_thread(self.cfg.thread_count) == slot.thread
+                    && key.slot < slot
+                    && key
+                        .slot
+                        .period
+                        .saturating_add(self.cfg.denunciation_expire_periods)
+                        >= slot.period
+            })
+            .map(|(key, denunciation)| (*key, denunciation))
+            .collect();
+
+        // remove the denunciations already included in same thread ancestors
+        let min_period = match candidates.keys().map(|key| key.slot.period).min() {
+            Some(period) => period,
+            None => return Ok(Vec::new()),
+        };
+        let mut current_block_id = parent_in_own_thread;
+        while let Some(current_block) = self.get_active_block(&current_block_id) {
+            if current_block.slot.period < min_period || current_block.parents.is_empty() {
+                break;
+            }
+            let block = self
+                .storage
+                .retrieve_block(&current_block_id)
+                .ok_or_else(|| {
+                    GraphError::MissingBlock(format!(
+                        "missing block when getting block denunciations: {}",
+                        current_block_id
+                    ))
+                })?;
+            for denunciation in block.read().content.header.content.denunciations.iter() {
+                candidates.remove(&denunciation.get_key()?);
+            }
+            current_block_id = current_block.parents[slot.thread as usize].0;
+        }
+
+        Ok(candidates
+            .into_values()
+            .take(MAX_DENUNCIATIONS_PER_BLOCK as usize)
+            .cloned()
+            .collect())
+    }
+
+    /// Forgets the double signings that can no longer be denounced
+    fn prune_denunciations(&mut self) {
+        let expired = |key: &DenunciationKey| self.is_denunciation_expired(key);
+        let expired_headers: Vec<DenunciationKey> = self
+            .seen_headers
+            .keys()
+            .filter(|key| expired(key))
+            .copied()
+            .collect();
+        let expired_endorsements: Vec<DenunciationKey> = self
+            .seen_endorsements
+            .keys()
+            .filter(|key| expired(key))
+            .copied()
+            .collect();
+        let expired_denunciations: Vec<DenunciationKey> = self
+            .denunciations
+            .keys()
+            .filter(|key| expired(key))
+            .copied()
+            .collect();
+        for key in expired_headers {
+            self.seen_headers.remove(&key);
+        }
+        for key in expired_endorsements {
+            self.seen_endorsements.remove(&key);
+        }
+        for key in expired_denunciations {
+            self.denunciations.remove(&key);
+        }
+    }
+
     /// Gets whole `ActiveBlock` corresponding to given `block_id`
     ///
     /// # Argument
@@ -2051,8 +2405,8 @@ impl BlockGraph {
             return Ok(HeaderCheckOutcome::WaitForSlot);
         }
 
-        // Note: here we will check if we already have a block for that slot
-        // and if someone double staked, they will be denounced
+        // Note: stakers signing two headers for the same slot are denounced
+        // once the header passes all checks (see `note_signed_contents`)
 
         // list parents and ensure they are present
         let parent_set: Set<BlockId> = header.content.parents.iter().copied().collect();
@@ -2195,6 +2549,15 @@ impl BlockGraph {
             EndorsementsCheckOutcome::WaitForSlot => return Ok(HeaderCheckOutcome::WaitForSlot),
         }
 
+        // check denunciations
+        match self.check_denunciations(header, pos)? {
+            DenunciationsCheckOutcome::Proceed { dependencies } => deps.extend(dependencies),
+            DenunciationsCheckOutcome::Discard(reason) => {
+                return Ok(HeaderCheckOutcome::Discard(reason))
+            }
+            DenunciationsCheckOutcome::WaitForSlot => return Ok(HeaderCheckOutcome::WaitForSlot),
+        }
+
         // thread incompatibility test
         parent_in_own_thread.children[header.content.slot.thread as usize]
             .keys()
@@ -2362,6 +2725,124 @@ impl BlockGraph {
         Ok(EndorsementsCheckOutcome::Proceed)
     }
 
+    /// check denunciations:
+    /// * both denounced contents are signed by the offender and conflict
+    /// * the offender was selected for the denounced slot
+    /// * the offender's address is in the block's thread, so that its rolls can be slashed
+    /// * the denounced slot is before the block's slot and has not expired
+    /// * the double signing is not already denounced in the block or its same thread ancestors
+    fn check_denunciations(
+        &self,
+        header: &WrappedHeader,
+        pos: &mut ProofOfStake,
+    ) -> Result<DenunciationsCheckOutcome> {
+        let mut dependencies = Set::<BlockId>::default();
+        if header.content.denunciations.is_empty() {
+            return Ok(DenunciationsCheckOutcome::Proceed { dependencies });
+        }
+        let block_slot = header.content.slot;
+        let mut keys = HashSet::with_capacity(header.content.denunciations.len());
+        for denunciation in header.content.denunciations.iter() {
+            let key = match denunciation.check() {
+                Ok(key) => key,
+                Err(err) => {
+                    return Ok(DenunciationsCheckOutcome::Discard(DiscardReason::Invalid(
+                        format!("invalid denunciation in header: {}", err),
+                    )))
+                }
+            };
+            if key.address.get_thread(self.cfg.thread_count) != block_slot.thread {
+                return Ok(DenunciationsCheckOutcome::Discard(DiscardReason::Invalid(
+                    format!(
+                        "denunciation of {} included in a block of another thread",
+                        key
+                    ),
+                )));
+            }
+            if key.slot >= block_slot
+                || key
+                    .slot
+                    .period
+                    .saturating_add(self.cfg.denunciation_expire_periods)
+                    < block_slot.period
+            {
+                return Ok(DenunciationsCheckOutcome::Discard(DiscardReason::Invalid(
+                    format!(
+                        "denunciation of {} not includable at slot {}",
+                        key, block_slot
+                    ),
+                )));
+            }
+            match BlockGraph::is_offender_selected(&key, pos) {
+                Ok(true) => {}
+                Ok(false) => {
+                    return Ok(DenunciationsCheckOutcome::Discard(DiscardReason::Invalid(
+                        format!("denunciation of {} that was not selected", key),
+                    )))
+                }
+                Err(ProofOfStakeError::PosCycleUnavailable(_)) => {
+                    return Ok(DenunciationsCheckOutcome::WaitForSlot)
+                }
+                Err(err) => return Err(err.into()),
+            }
+            if !keys.insert(key) {
+                return Ok(DenunciationsCheckOutcome::Discard(DiscardReason::Invalid(
+                    format!("denunciation of {} included twice in header", key),
+                )));
+            }
+        }
+
+        // check that the denunciations were not included in previous blocks of the thread
+        let min_period = keys
+            .iter()
+            .map(|key| key.slot.period)
+            .min()
+            .unwrap_or(block_slot.period);
+        let mut current_block_id = header.content.parents[block_slot.thread as usize];
+        loop {
+            let current_block = match self.block_statuses.get(&current_block_id) {
+                Some(BlockStatus::Active(block)) => block,
+                _ => {
+                    return Err(GraphError::ContainerInconsistency(format!(
+                        "block {} is not active but is an ancestor of a potentially active block",
+                        current_block_id
+                    )))
+                }
+            };
+
+            // a denunciation can only be included after the denounced slot
+            if current_block.slot.period < min_period || current_block.parents.is_empty() {
+                break;
+            }
+
+            let block = self
+                .storage
+                .retrieve_block(&current_block_id)
+                .ok_or_else(|| {
+                    GraphError::MissingBlock(format!(
+                        "missing block when checking denunciations: {}",
+                        current_block_id
+                    ))
+                })?;
+            for denunciation in block.read().content.header.content.denunciations.iter() {
+                let key = denunciation.get_key()?;
+                if keys.contains(&key) {
+                    return Ok(DenunciationsCheckOutcome::Discard(DiscardReason::Invalid(
+                        format!(
+                            "denunciation of {} already included in block {}",
+                            key, current_block_id
+                        ),
+                    )));
+                }
+            }
+            dependencies.insert(current_block_id);
+
+            current_block_id = current_block.parents[block_slot.thread as usize].0;
+        }
+
+        Ok(DenunciationsCheckOutcome::Proceed { dependencies })
+    }
+
     /// Process and incoming block.
     ///
     /// Checks performed:
@@ -3288,12 +3769,15 @@ impl BlockGraph {
                 retain_active.insert(current_block_id);
 
                 // stop traversing when reaching a block with period number low enough
-                // so that any of its operations will have their validity period expired at the latest final block in thread
+                // so that any of its operations and denunciations will have expired at the latest final block in thread
                 // note: one more is kept because of the way we iterate
                 if current_block.slot.period
                     < self.latest_final_blocks_periods[thread]
                         .1
-                        .saturating_sub(self.cfg.operation_validity_periods)
+                        .saturating_sub(std::cmp::max(
+                            self.cfg.operation_validity_periods,
+                            self.cfg.denunciation_expire_periods,
+                        ))
                 {
                     break;
                 }
@@ -3709,6 +4193,9 @@ impl BlockGraph {
         // Step 4: prune discarded
         self.prune_discarded()?;
 
+        // Step 5: prune expired double signings
+        self.prune_denunciations();
+
         let after = self.max_cliques.len();
         if before != after {
             debug!(
@@ -3792,6 +4279,12 @@ impl BlockGraph {
         mem::take(&mut self.attack_attempts)
     }
 
+    /// Get the denunciations that need to be propagated.
+    /// Must be called by the consensus worker within `block_db_changed`.
+    pub fn get_new_denunciations(&mut self) -> Vec<Denunciation> {
+        mem::take(&mut self.new_denunciations)
+    }
+
     /// Get the ids of blocks that became final.
     /// Must be called by the consensus worker within `block_db_changed`.
     pub fn get_new_final_blocks(&mut self) -> Set<BlockId> {
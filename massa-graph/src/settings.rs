@@ -5,6 +5,7 @@
 use massa_models::Amount;
 use massa_signature::KeyPair;
 use massa_time::MassaTime;
+use num::rational::Ratio;
 use serde::{Deserialize, Serialize};
 use std::{path::PathBuf, usize};
 
@@ -57,6 +58,10 @@ pub struct GraphConfig {
     pub operation_validity_periods: u64,
     /// cycle duration in periods
     pub periods_per_cycle: u64,
+    /// a denunciation can only be included in a block at most `denunciation_expire_periods` periods after the denounced slot
+    pub denunciation_expire_periods: u64,
+    /// share of the slashed roll value credited to the creator of the block including a denunciation
+    pub denunciation_reward_ratio: Ratio<u64>,
     /// Initial file path that describe the ledger to merge in `ledger_path` after starting
     pub initial_ledger_path: PathBuf,
     /// Reward for the creation of a block
@@ -2,7 +2,7 @@ use massa_models::{Address, Amount, ModelsError, Slot};
 use std::collections::BTreeSet;
 use std::fmt::Debug;
 
-use crate::LedgerChanges;
+use crate::{LedgerChanges, LedgerError};
 
 pub trait LedgerController: Send + Sync + Debug {
     /// Allows applying `LedgerChanges` to the final ledger
@@ -65,6 +65,32 @@ pub trait LedgerController: Send + Sync + Debug {
     /// Return: Last key inserted
     fn set_ledger_part(&self, data: Vec<u8>) -> Result<Option<Vec<u8>>, ModelsError>;
 
+    /// Set a part of the ledger, ignoring the keys greater or equal to `end_key`
+    /// Used for parallel bootstrap
+    /// Return: Last key of the part, inserted or not
+    fn set_ledger_range_part(
+        &self,
+        data: Vec<u8>,
+        end_key: Option<&[u8]>,
+    ) -> Result<Option<Vec<u8>>, ModelsError>;
+
+    /// Get the progress of an interrupted bootstrap
+    /// Used for bootstrap
+    /// Return: last key inserted and slot the ledger is attached to, if a bootstrap did not finish
+    fn get_bootstrap_progress(&self) -> Option<(Vec<u8>, Slot)>;
+
+    /// Forget the progress of the bootstrap, once it is finished or restarted from scratch
+    /// Used for bootstrap
+    fn reset_bootstrap_progress(&self);
+
+    /// Drop the entries received by an unfinished bootstrap, and its progress,
+    /// and go back to the initial ledger
+    /// Used for bootstrap
+    fn reset(&mut self) -> Result<(), LedgerError>;
+
+    /// Get the estimated size of the ledger on disk, in bytes
+    fn get_disk_size(&self) -> u64;
+
     /// Get every address and their corresponding balance.
     ///
     /// IMPORTANT: This should only be used for debug and test purposes.
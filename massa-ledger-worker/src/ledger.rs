@@ -18,7 +18,7 @@ use std::collections::{BTreeSet, HashMap};
 #[derive(Debug)]
 pub struct FinalLedger {
     /// ledger configuration
-    pub(crate) _config: LedgerConfig,
+    pub(crate) config: LedgerConfig,
     /// ledger tree, sorted by address
     pub(crate) sorted_ledger: LedgerDB,
 }
@@ -39,36 +39,45 @@ macro_rules! init_file_error {
 }
 pub(crate) use init_file_error;
 
+/// Reads the initial ledger from its file
+fn load_initial_ledger(
+    config: &LedgerConfig,
+) -> Result<HashMap<Address, LedgerEntry>, LedgerError> {
+    Ok(serde_json::from_str::<HashMap<Address, Amount>>(
+        &std::fs::read_to_string(&config.initial_sce_ledger_path)
+            .map_err(init_file_error!("loading", config))?,
+    )
+    .map_err(init_file_error!("parsing", config))?
+    .into_iter()
+    .map(|(addr, amount)| {
+        (
+            addr,
+            LedgerEntry {
+                parallel_balance: amount,
+                ..Default::default()
+            },
+        )
+    })
+    .collect())
+}
+
 impl FinalLedger {
     /// Initializes a new `FinalLedger` by reading its initial state from file.
     pub fn new(config: LedgerConfig) -> Result<Self, LedgerError> {
         // load the ledger tree from file
-        let initial_ledger: HashMap<Address, LedgerEntry> =
-            serde_json::from_str::<HashMap<Address, Amount>>(
-                &std::fs::read_to_string(&config.initial_sce_ledger_path)
-                    .map_err(init_file_error!("loading", config))?,
-            )
-            .map_err(init_file_error!("parsing", config))?
-            .into_iter()
-            .map(|(addr, amount)| {
-                (
-                    addr,
-                    LedgerEntry {
-                        parallel_balance: amount,
-                        ..Default::default()
-                    },
-                )
-            })
-            .collect();
+        let initial_ledger = load_initial_ledger(&config)?;
 
         // create and initialize the disk ledger
+        // if a bootstrap was interrupted, keep the received entries instead so that it can be resumed
         let mut sorted_ledger = LedgerDB::new(config.disk_ledger_path.clone());
-        sorted_ledger.set_initial_ledger(initial_ledger);
+        if sorted_ledger.get_bootstrap_progress().is_none() {
+            sorted_ledger.set_initial_ledger(initial_ledger);
+        }
 
         // generate the final ledger
         Ok(FinalLedger {
             sorted_ledger,
-            _config: config,
+            config,
         })
     }
 }
@@ -170,6 +179,54 @@ impl LedgerController for FinalLedger {
         self.sorted_ledger.set_ledger_part(data.as_bytes())
     }
 
+    /// Set a part of the disk ledger, ignoring the keys greater or equal to `end_key`.
+    ///
+    /// Solely used by the parallel bootstrap.
+    ///
+    /// # Returns
+    /// The last key of the part, inserted or not
+    fn set_ledger_range_part(
+        &self,
+        data: Vec<u8>,
+        end_key: Option<&[u8]>,
+    ) -> Result<Option<Vec<u8>>, ModelsError> {
+        self.sorted_ledger
+            .set_ledger_range_part(data.as_bytes(), end_key)
+    }
+
+    /// Get the progress of an interrupted bootstrap of the disk ledger.
+    ///
+    /// Solely used by the bootstrap.
+    ///
+    /// # Returns
+    /// The last key inserted and the attached slot, if a bootstrap did not finish
+    fn get_bootstrap_progress(&self) -> Option<(Vec<u8>, Slot)> {
+        self.sorted_ledger.get_bootstrap_progress()
+    }
+
+    /// Forget the progress of the bootstrap of the disk ledger.
+    ///
+    /// Solely used by the bootstrap.
+    fn reset_bootstrap_progress(&self) {
+        self.sorted_ledger.reset_bootstrap_progress()
+    }
+
+    /// Drop every entry of the disk ledger, including the ones received by an unfinished bootstrap,
+    /// and write the initial ledger again.
+    ///
+    /// Solely used by the bootstrap.
+    fn reset(&mut self) -> Result<(), LedgerError> {
+        let initial_ledger = load_initial_ledger(&self.config)?;
+        self.sorted_ledger.clear();
+        self.sorted_ledger.set_initial_ledger(initial_ledger);
+        Ok(())
+    }
+
+    /// Get the estimated size of the disk ledger, in bytes
+    fn get_disk_size(&self) -> u64 {
+        self.sorted_ledger.get_disk_size()
+    }
+
     /// Get every address and their corresponding balance.
     ///
     /// IMPORTANT: This should only be used for debug and test purposes.
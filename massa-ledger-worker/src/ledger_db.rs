@@ -5,7 +5,8 @@
 use massa_ledger_exports::*;
 use massa_models::constants::{ADDRESS_SIZE_BYTES, LEDGER_PART_SIZE_MESSAGE_BYTES};
 use massa_models::{
-    Address, ModelsError, SerializeCompact, Slot, VecU8Deserializer, VecU8Serializer,
+    Address, DeserializeCompact, ModelsError, SerializeCompact, Slot, VecU8Deserializer,
+    VecU8Serializer,
 };
 use massa_serialization::{Deserializer, Serializer};
 use nom::multi::many0;
@@ -24,6 +25,7 @@ const OPEN_ERROR: &str = "critical: rocksdb open operation failed";
 const CRUD_ERROR: &str = "critical: rocksdb crud operation failed";
 const CF_ERROR: &str = "critical: rocksdb column family operation failed";
 const SLOT_KEY: &[u8; 1] = b"s";
+const BOOTSTRAP_KEY: &[u8; 1] = b"b";
 
 /// Ledger sub entry enum
 pub enum LedgerSubEntry {
@@ -64,7 +66,6 @@ fn test_end_prefix() {
     assert_eq!(end_prefix(&[5, 6, 255]), Some(vec![5, 7]));
 }
 
-// TODO: save attached slot in metadata for a lighter bootstrap after disconnection
 impl LedgerDB {
     /// Create and initialize a new LedgerDB.
     ///
@@ -309,19 +310,18 @@ impl LedgerDB {
         let opt = ReadOptions::default();
 
         // Creates an iterator from the next element after the last if defined, otherwise initialize it at the first key of the ledger.
+        // `last_key` is skipped only if it is present: it can be an arbitrary cursor when the ledger is downloaded by ranges.
         let db_iterator = if let Some(key) = last_key {
-            let mut iter =
-                self.0
-                    .iterator_cf_opt(handle, opt, IteratorMode::From(key, Direction::Forward));
-            iter.next();
-            iter
+            self.0
+                .iterator_cf_opt(handle, opt, IteratorMode::From(key, Direction::Forward))
         } else {
             self.0.iterator_cf_opt(handle, opt, IteratorMode::Start)
         };
+        let start_key = last_key.as_deref();
         let mut last_key = None;
 
         // Iterates over the whole database
-        for (key, entry) in db_iterator {
+        for (key, entry) in db_iterator.skip_while(|(key, _)| Some(&key[..]) == start_key) {
             if (part.len() as u64) < (LEDGER_PART_SIZE_MESSAGE_BYTES) {
                 key_serializer.serialize(&key.to_vec(), &mut part)?;
                 ser.serialize(&entry.to_vec(), &mut part)?;
@@ -335,6 +335,7 @@ impl LedgerDB {
 
     /// Set a part of the ledger in the database.
     /// We deserialize in this function because we insert in the ledger while deserializing.
+    /// The last inserted key is saved in the metadata along with the part so that an interrupted bootstrap can be resumed.
     /// Used for bootstrap.
     ///
     /// # Arguments
@@ -342,13 +343,54 @@ impl LedgerDB {
     ///
     /// # Returns
     /// The last key of the inserted entry (this is an optimization to easily keep a reference to the last key)
-    pub fn set_ledger_part<'a>(&self, data: &'a [u8]) -> Result<Option<Vec<u8>>, ModelsError> {
+    pub fn set_ledger_part(&self, data: &[u8]) -> Result<Option<Vec<u8>>, ModelsError> {
+        let mut batch = WriteBatch::default();
+        let last_key = self.put_ledger_part(data, None, &mut batch)?;
+        if let Some(key) = last_key.as_ref() {
+            let metadata_handle = self.0.cf_handle(METADATA_CF).expect(CF_ERROR);
+            batch.put_cf(metadata_handle, BOOTSTRAP_KEY, key);
+        }
+        self.0.write(batch).expect(CRUD_ERROR);
+        Ok(last_key)
+    }
+
+    /// Set a part of the ledger in the database, ignoring the keys greater or equal to `end_key`.
+    /// Unlike `set_ledger_part`, the bootstrap progress is not saved because the parts do not start at the beginning of the ledger.
+    /// Used for parallel bootstrap.
+    ///
+    /// # Arguments
+    /// * data: must be the serialized version provided by `get_ledger_part`
+    /// * end_key: excluded upper bound of the keys to insert, if any
+    ///
+    /// # Returns
+    /// The last key of the part, inserted or not
+    pub fn set_ledger_range_part(
+        &self,
+        data: &[u8],
+        end_key: Option<&[u8]>,
+    ) -> Result<Option<Vec<u8>>, ModelsError> {
+        let mut batch = WriteBatch::default();
+        let last_key = self.put_ledger_part(data, end_key, &mut batch)?;
+        self.0.write(batch).expect(CRUD_ERROR);
+        Ok(last_key)
+    }
+
+    /// Deserialize a ledger part and add its entries to the given batch,
+    /// except the ones with a key greater or equal to `end_key`.
+    ///
+    /// # Returns
+    /// The last key of the part
+    fn put_ledger_part<'a>(
+        &self,
+        data: &'a [u8],
+        end_key: Option<&[u8]>,
+        batch: &mut WriteBatch,
+    ) -> Result<Option<Vec<u8>>, ModelsError> {
         let handle = self.0.cf_handle(LEDGER_CF).expect(CF_ERROR);
         let vec_u8_deserializer =
             VecU8Deserializer::new(Bound::Included(0), Bound::Excluded(u64::MAX));
         let key_deserializer = KeyDeserializer::new();
         let mut last_key = Rc::new(None);
-        let mut batch = WriteBatch::default();
 
         // Since this data is coming from the network, deser to address and ser back to bytes for a security check.
         let (rest, _) = many0(|input: &'a [u8]| {
@@ -359,23 +401,72 @@ impl LedgerDB {
             *Rc::get_mut(&mut last_key).ok_or_else(|| {
                 nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::Fail))
             })? = Some(key.clone());
-            batch.put_cf(handle, key, value);
+            if end_key.map_or(true, |end_key| key.as_slice() < end_key) {
+                batch.put_cf(handle, key, value);
+            }
             Ok((rest, ()))
         })(data)
         .map_err(|_| ModelsError::SerializeError("Error in deserialization".to_string()))?;
 
         // Every byte should have been read
         if rest.is_empty() {
-            self.0.write(batch).expect(CRUD_ERROR);
             Ok((*last_key).clone())
         } else {
-            println!("REST LEN = {}", rest.len());
             Err(ModelsError::SerializeError(
                 "rest is not empty.".to_string(),
             ))
         }
     }
 
+    /// Get the progress of an interrupted ledger bootstrap.
+    ///
+    /// # Returns
+    /// The last key received and the slot the ledger is attached to,
+    /// or None if no bootstrap was started or if it finished
+    pub fn get_bootstrap_progress(&self) -> Option<(Vec<u8>, Slot)> {
+        let handle = self.0.cf_handle(METADATA_CF).expect(CF_ERROR);
+        let last_key = self.0.get_cf(handle, BOOTSTRAP_KEY).expect(CRUD_ERROR)?;
+        let slot_bytes = self.0.get_cf(handle, SLOT_KEY).expect(CRUD_ERROR)?;
+        let (slot, _) = Slot::from_bytes_compact(&slot_bytes).ok()?;
+        Some((last_key, slot))
+    }
+
+    /// Forget the progress of the ledger bootstrap, once it is finished or abandoned
+    pub fn reset_bootstrap_progress(&self) {
+        let handle = self.0.cf_handle(METADATA_CF).expect(CF_ERROR);
+        self.0.delete_cf(handle, BOOTSTRAP_KEY).expect(CRUD_ERROR);
+    }
+
+    /// Delete every entry of the ledger and the progress of an interrupted bootstrap
+    pub fn clear(&mut self) {
+        let handle = self.0.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        let metadata_handle = self.0.cf_handle(METADATA_CF).expect(CF_ERROR);
+        let mut batch = WriteBatch::default();
+        for (key, _) in self.0.iterator_cf(handle, IteratorMode::Start) {
+            batch.delete_cf(handle, key);
+        }
+        batch.delete_cf(metadata_handle, BOOTSTRAP_KEY);
+        self.write_batch(batch);
+    }
+
+    /// Get the estimated size of the ledger column family, in bytes.
+    /// Includes the SST files and the memtables that were not flushed yet.
+    pub fn get_disk_size(&self) -> u64 {
+        let handle = self.0.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        [
+            "rocksdb.total-sst-files-size",
+            "rocksdb.cur-size-all-mem-tables",
+        ]
+        .iter()
+        .filter_map(|property| {
+            self.0
+                .property_int_value_cf(handle, property)
+                .ok()
+                .flatten()
+        })
+        .sum()
+    }
+
     /// Get every address and their corresponding balance.
     ///
     /// IMPORTANT: This should only be used for debug purposes.
@@ -384,7 +475,7 @@ impl LedgerDB {
     /// A BTreeMap with the address as key and the balance as value
     #[cfg(feature = "testing")]
     pub fn get_every_address(&self) -> std::collections::BTreeMap<Address, massa_models::Amount> {
-        use massa_models::{address::AddressDeserializer, DeserializeCompact};
+        use massa_models::address::AddressDeserializer;
         use massa_serialization::DeserializeError;
 
         let handle = self.0.cf_handle(LEDGER_CF).expect(CF_ERROR);
@@ -446,8 +537,10 @@ impl LedgerDB {
 mod tests {
     use super::LedgerDB;
     use crate::ledger_db::LedgerSubEntry;
-    use massa_ledger_exports::{LedgerEntry, LedgerEntryUpdate, SetOrKeep};
-    use massa_models::{Address, Amount, DeserializeCompact};
+    use massa_ledger_exports::{
+        balance_key, LedgerChanges, LedgerEntry, LedgerEntryUpdate, SetOrKeep, BALANCE_IDENT,
+    };
+    use massa_models::{Address, Amount, DeserializeCompact, Slot};
     use massa_signature::KeyPair;
     use rocksdb::WriteBatch;
     use std::collections::BTreeMap;
@@ -522,4 +615,77 @@ mod tests {
         let res = db.get_ledger_part(&None).unwrap();
         db.set_ledger_part(&res.0[..]).unwrap();
     }
+
+    #[test]
+    fn test_ledger_range_parts() {
+        let pub_a = KeyPair::generate().get_public_key();
+        let a = Address::from_public_key(&pub_a);
+        let (db, _) = init_test_ledger(a);
+        let (part, last_key) = db.get_ledger_part(&None).unwrap();
+        let last_key = last_key.unwrap();
+
+        // keys after the end of the range are ignored but the last key of the part is returned
+        let temp_dir = TempDir::new().unwrap();
+        let range_db = LedgerDB::new(temp_dir.path().to_path_buf());
+        let end_key = balance_key!(a);
+        assert_eq!(
+            range_db
+                .set_ledger_range_part(&part[..], Some(&end_key[..]))
+                .unwrap(),
+            Some(last_key)
+        );
+        assert!(range_db.get_ledger_part(&None).unwrap().0.is_empty());
+        assert!(range_db.get_bootstrap_progress().is_none());
+
+        // a cursor that is not in the ledger is not skipped
+        let (part_from_cursor, _) = db.get_ledger_part(&Some(vec![0])).unwrap();
+        assert_eq!(part_from_cursor, part);
+    }
+
+    #[test]
+    fn test_bootstrap_progress() {
+        let pub_a = KeyPair::generate().get_public_key();
+        let a = Address::from_public_key(&pub_a);
+        let (db, _) = init_test_ledger(a);
+        let (part, last_key) = db.get_ledger_part(&None).unwrap();
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut bootstrapped_db = LedgerDB::new(temp_dir.path().to_path_buf());
+        assert!(bootstrapped_db.get_bootstrap_progress().is_none());
+
+        // receive a part attached to a slot
+        let inserted_key = bootstrapped_db.set_ledger_part(&part[..]).unwrap();
+        assert_eq!(inserted_key, last_key);
+        bootstrapped_db.apply_changes(LedgerChanges::default(), Slot::new(3, 1));
+        assert_eq!(
+            bootstrapped_db.get_bootstrap_progress(),
+            Some((last_key.unwrap(), Slot::new(3, 1)))
+        );
+
+        // the progress survives a restart
+        drop(bootstrapped_db);
+        let bootstrapped_db = LedgerDB::new(temp_dir.path().to_path_buf());
+        assert!(bootstrapped_db.get_bootstrap_progress().is_some());
+
+        bootstrapped_db.reset_bootstrap_progress();
+        assert!(bootstrapped_db.get_bootstrap_progress().is_none());
+    }
+
+    #[test]
+    fn test_clear() {
+        let pub_a = KeyPair::generate().get_public_key();
+        let a = Address::from_public_key(&pub_a);
+        let (db, _) = init_test_ledger(a);
+        let (part, _) = db.get_ledger_part(&None).unwrap();
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut bootstrapped_db = LedgerDB::new(temp_dir.path().to_path_buf());
+        bootstrapped_db.set_ledger_part(&part[..]).unwrap();
+        bootstrapped_db.apply_changes(LedgerChanges::default(), Slot::new(3, 1));
+        assert!(bootstrapped_db.get_bootstrap_progress().is_some());
+
+        bootstrapped_db.clear();
+        assert!(bootstrapped_db.get_ledger_part(&None).unwrap().0.is_empty());
+        assert!(bootstrapped_db.get_bootstrap_progress().is_none());
+    }
 }
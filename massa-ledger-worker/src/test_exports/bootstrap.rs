@@ -17,7 +17,7 @@ pub fn create_final_ledger(
     let mut db = LedgerDB::new(temp_dir.path().to_path_buf());
     db.set_initial_ledger(initial_ledger.unwrap_or_default());
     FinalLedger {
-        _config: config,
+        config,
         sorted_ledger: db,
     }
 }
@@ -11,7 +11,7 @@ impl Default for FinalLedger {
         let temp_dir = TempDir::new().unwrap();
         let db = LedgerDB::new(temp_dir.path().to_path_buf());
         FinalLedger {
-            _config: Default::default(),
+            config: Default::default(),
             sorted_ledger: db,
         }
     }
@@ -1,5 +1,6 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::multisig::{multisig_address_data, MultisigPolicy};
 use crate::prehash::PreHashed;
 use crate::{
     api::{LedgerInfo, RollsInfo},
@@ -157,6 +158,11 @@ impl Address {
         Address(Hash::compute_from(public_key.to_bytes()))
     }
 
+    /// Computes the multisig address associated with given policy
+    pub fn from_multisig_policy(policy: &MultisigPolicy) -> Self {
+        Address(Hash::compute_from(&multisig_address_data(policy)))
+    }
+
     /// ## Example
     /// ```rust
     /// # use massa_signature::{PublicKey, KeyPair, Signature};
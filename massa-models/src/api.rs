@@ -4,12 +4,15 @@ use crate::address::AddressCycleProductionStats;
 use crate::ledger_models::LedgerData;
 use crate::node::NodeId;
 use crate::prehash::Set;
-use crate::stats::{ConsensusStats, NetworkStats, PoolStats};
+use crate::signed_message::SignedMessage;
+use crate::stats::{ConsensusStats, FeeDensityPercentiles, NetworkStats, PoolStats};
 use crate::WrappedEndorsement;
 use crate::WrappedOperation;
 use crate::{
-    Address, Amount, Block, BlockId, CompactConfig, EndorsementId, OperationId, Slot, Version,
+    Address, Amount, Block, BlockId, CompactConfig, Denunciation, EndorsementId, MultisigProof,
+    OperationId, OperationType, Slot, Version,
 };
+use massa_hash::Hash;
 use massa_signature::{PublicKey, Signature};
 use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
@@ -26,6 +29,25 @@ pub struct OperationInput {
     pub signature: Signature,
     /// The serialized version of the content base58 encoded
     pub serialized_content: Vec<u8>,
+    /// Signatures of a multisig creator address,
+    /// in which case `creator_public_key` and `signature` are ignored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multisig: Option<MultisigProof>,
+}
+
+/// operation refused by the pool
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct RejectedOperation {
+    /// operation id
+    pub id: OperationId,
+    /// why the operation was refused
+    pub reason: String,
+}
+
+impl std::fmt::Display for RejectedOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.id, self.reason)
+    }
 }
 
 /// node status
@@ -317,7 +339,7 @@ impl AddressInfo {
 }
 
 /// When an address is drawn to create an endorsement it is selected for a specific index
-#[derive(Debug, Deserialize, Serialize, Hash, PartialEq, Eq)]
+#[derive(Debug, Deserialize, Serialize, Hash, PartialEq, Eq, Clone, Copy)]
 pub struct IndexedSlot {
     /// slot
     pub slot: Slot,
@@ -571,3 +593,317 @@ pub struct ReadOnlyCall {
     /// caller's address, optional
     pub caller_address: Option<Address>,
 }
+
+/// fee estimate request
+#[derive(Debug, Deserialize, Clone, Copy, Serialize)]
+pub struct FeeEstimateInput {
+    /// size of the serialized operation, in bytes
+    pub operation_size: u64,
+    /// number of periods within which the operation should be included
+    pub target_periods: u64,
+}
+
+/// fee estimate for a thread
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct ThreadFeeEstimate {
+    /// thread
+    pub thread: u8,
+    /// fee densities of the operations pending in the pool
+    pub pool: Option<FeeDensityPercentiles>,
+    /// fee densities of the operations included in recent active blocks
+    pub recent_blocks: Option<FeeDensityPercentiles>,
+    /// recommended fee per byte
+    pub recommended_fee_density: Amount,
+    /// recommended fee for an operation of the requested size
+    pub recommended_fee: Amount,
+}
+
+impl std::fmt::Display for ThreadFeeEstimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Thread {}:", self.thread)?;
+        match &self.pool {
+            Some(percentiles) => writeln!(f, "\tPool: {}", percentiles)?,
+            None => writeln!(f, "\tPool: no pending operations")?,
+        }
+        match &self.recent_blocks {
+            Some(percentiles) => writeln!(f, "\tRecent blocks: {}", percentiles)?,
+            None => writeln!(f, "\tRecent blocks: no operations")?,
+        }
+        writeln!(
+            f,
+            "\tRecommended fee: {} ({} per byte)",
+            self.recommended_fee, self.recommended_fee_density
+        )?;
+        Ok(())
+    }
+}
+
+/// fee estimate for all the threads
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct FeeEstimate {
+    /// size of the serialized operation, in bytes
+    pub operation_size: u64,
+    /// number of periods within which the operation should be included
+    pub target_periods: u64,
+    /// estimate of each thread. The thread of an operation is the thread of its sender
+    pub threads: Vec<ThreadFeeEstimate>,
+}
+
+impl std::fmt::Display for FeeEstimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Fee estimate for an operation of {} bytes included within {} periods:",
+            self.operation_size, self.target_periods
+        )?;
+        for thread_estimate in self.threads.iter() {
+            write!(f, "{}", thread_estimate)?;
+        }
+        Ok(())
+    }
+}
+
+/// kind of an operation
+#[derive(Debug, Deserialize, Clone, Copy, Serialize, PartialEq, Eq)]
+pub enum OperationKind {
+    /// coin transfer
+    Transaction,
+    /// roll purchase
+    RollBuy,
+    /// roll sale
+    RollSell,
+    /// smart contract execution
+    ExecuteSC,
+    /// smart contract function call
+    CallSC,
+}
+
+impl From<&OperationType> for OperationKind {
+    fn from(op: &OperationType) -> Self {
+        match op {
+            OperationType::Transaction { .. } => OperationKind::Transaction,
+            OperationType::RollBuy { .. } => OperationKind::RollBuy,
+            OperationType::RollSell { .. } => OperationKind::RollSell,
+            OperationType::ExecuteSC { .. } => OperationKind::ExecuteSC,
+            OperationType::CallSC { .. } => OperationKind::CallSC,
+        }
+    }
+}
+
+/// filter used when browsing the operations pending in the pool
+#[derive(Default, Debug, Deserialize, Clone, Serialize)]
+#[serde(default)]
+pub struct PoolOperationFilter {
+    /// optional thread
+    pub thread: Option<u8>,
+    /// optional operation kind
+    pub kind: Option<OperationKind>,
+    /// optional sender address
+    pub sender: Option<Address>,
+    /// optional minimal fee
+    pub min_fee: Option<Amount>,
+    /// optional first period (included) at which the operation must be valid
+    pub valid_from_period: Option<u64>,
+    /// optional last period (included) at which the operation must be valid
+    pub valid_until_period: Option<u64>,
+    /// number of matching operations to skip
+    pub offset: usize,
+    /// optional maximal number of operations to return
+    pub limit: Option<usize>,
+}
+
+impl PoolOperationFilter {
+    /// Whether an operation matches the filter, pagination aside.
+    /// An operation matches the validity bounds if it can be included in a block during at least one of the periods in them.
+    ///
+    /// # Arguments
+    /// * `operation`: the operation
+    /// * `operation_validity_periods`: number of periods during which an operation is valid
+    pub fn matches(&self, operation: &WrappedOperation, operation_validity_periods: u64) -> bool {
+        let validity_range = operation.get_validity_range(operation_validity_periods);
+        self.thread
+            .map_or(true, |thread| operation.thread == thread)
+            && self.kind.map_or(true, |kind| {
+                OperationKind::from(&operation.content.op) == kind
+            })
+            && self
+                .sender
+                .map_or(true, |sender| operation.creator_address == sender)
+            && self
+                .min_fee
+                .map_or(true, |min_fee| operation.content.fee >= min_fee)
+            && self
+                .valid_from_period
+                .map_or(true, |period| *validity_range.end() >= period)
+            && self
+                .valid_until_period
+                .map_or(true, |period| *validity_range.start() <= period)
+    }
+}
+
+/// page of operations pending in the pool
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct PoolOperationsPage {
+    /// whether more pending operations match the filter after this page
+    pub has_more: bool,
+    /// requested operations, by thread then by decreasing fee density
+    pub operations: Vec<OperationInfo>,
+}
+
+/// why an operation left the pool without being included in a block
+#[derive(Debug, Deserialize, Clone, Copy, Serialize, PartialEq, Eq)]
+pub enum PoolEvictionReason {
+    /// its expire period is final
+    Expired,
+    /// it is included in a final block
+    Final,
+    /// the pool of its thread is full of operations with a higher fee density
+    PoolFull,
+    /// the sender replaced it with an operation paying a higher fee
+    Replaced(OperationId),
+}
+
+impl std::fmt::Display for PoolEvictionReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PoolEvictionReason::Expired => write!(f, "expired"),
+            PoolEvictionReason::Final => write!(f, "final"),
+            PoolEvictionReason::PoolFull => write!(f, "pushed out of a full pool"),
+            PoolEvictionReason::Replaced(op_id) => write!(f, "replaced by {}", op_id),
+        }
+    }
+}
+
+/// change of the operations pending in the pool
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub enum PoolOperationEvent {
+    /// an operation entered the pool
+    Added {
+        /// operation id
+        id: OperationId,
+        /// thread of the operation
+        thread: u8,
+        /// sender address
+        sender: Address,
+        /// fee paid to the block producer
+        fee: Amount,
+    },
+    /// an operation left the pool
+    Removed {
+        /// operation id
+        id: OperationId,
+        /// why it was removed
+        reason: PoolEvictionReason,
+    },
+}
+
+impl std::fmt::Display for PoolOperationEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PoolOperationEvent::Added {
+                id,
+                thread,
+                sender,
+                fee,
+            } => write!(
+                f,
+                "added {} (thread {}, sender {}, fee {})",
+                id, thread, sender, fee
+            ),
+            PoolOperationEvent::Removed { id, reason } => {
+                write!(f, "removed {}: {}", id, reason)
+            }
+        }
+    }
+}
+
+/// selection draws request
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct SelectionDrawsInput {
+    /// addresses whose draws are wanted
+    pub addresses: Vec<Address>,
+    /// first slot (included), the current slot if missing
+    pub start: Option<Slot>,
+    /// last slot (excluded), `draw_lookahead_period_count` periods after the start if missing
+    pub end: Option<Slot>,
+}
+
+/// block and endorsement draws of an address
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct AddressDraws {
+    /// the address
+    pub address: Address,
+    /// slots at which the address is selected to produce a block, in increasing order
+    pub block_draws: Vec<Slot>,
+    /// slots at which the address is selected to produce an endorsement, in increasing order
+    pub endorsement_draws: Vec<IndexedSlot>,
+}
+
+/// selection draws of a set of addresses over a slot range
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct SelectionDrawsOutput {
+    /// first slot (included)
+    pub start: Slot,
+    /// last slot (excluded)
+    pub end: Slot,
+    /// draws of each requested address, in the order of the request
+    pub draws: Vec<AddressDraws>,
+}
+
+/// block template request
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct BlockTemplateInput {
+    /// slot of the block to build
+    pub slot: Slot,
+    /// public key of the staker drawn to produce the block at that slot
+    pub creator_public_key: PublicKey,
+}
+
+/// unsigned contents of a block proposed by the node for a given slot
+///
+/// The operations are selected from the pool and can be replaced by the builder,
+/// as long as the operation merkle root is recomputed and the block limits are respected.
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct BlockTemplate {
+    /// slot of the block
+    pub slot: Slot,
+    /// address of the staker drawn to produce the block
+    pub creator_address: Address,
+    /// best parents, one per thread
+    pub parents: Vec<BlockId>,
+    /// endorsements of the thread parent
+    pub endorsements: Vec<WrappedEndorsement>,
+    /// denunciations of stakers who signed twice for the same slot
+    pub denunciations: Vec<Denunciation>,
+    /// operations selected from the pool, in block order
+    pub operations: Vec<WrappedOperation>,
+    /// merkle root of the selected operations
+    pub operation_merkle_root: Hash,
+    /// maximum size of a serialized block, in bytes
+    pub max_block_size: u32,
+    /// maximum number of operations in a block
+    pub max_operations_per_block: u32,
+    /// maximum gas usable by the operations of a block
+    pub max_gas_per_block: u64,
+}
+
+/// signed block input
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BlockInput {
+    /// The public key of the creator of the block
+    pub creator_public_key: PublicKey,
+    /// The signature of the block
+    pub signature: Signature,
+    /// The serialized version of the block content
+    pub serialized_content: Vec<u8>,
+}
+
+/// message and signature to check with `verify_signature`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SignatureVerificationInput {
+    /// the signed message
+    pub message: Vec<u8>,
+    /// address and public key of the signer, and signature of the message
+    #[serde(flatten)]
+    pub signed_message: SignedMessage,
+}
@@ -1,14 +1,18 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use crate::constants::BLOCK_ID_SIZE_BYTES;
+use crate::multisig::MultisigProof;
 use crate::node_configuration::default::ENDORSEMENT_COUNT;
-use crate::node_configuration::{MAX_BLOCK_SIZE, MAX_OPERATIONS_PER_BLOCK, THREAD_COUNT};
+use crate::node_configuration::{
+    MAX_BLOCK_SIZE, MAX_DENUNCIATIONS_PER_BLOCK, MAX_OPERATIONS_PER_BLOCK, THREAD_COUNT,
+};
 use crate::operation::OperationDeserializer;
 use crate::prehash::{Map, PreHashed, Set};
 use crate::wrapped::{Id, Wrapped, WrappedContent, WrappedDeserializer, WrappedSerializer};
 use crate::{
-    Address, Endorsement, EndorsementDeserializer, EndorsementId, ModelsError, Operation,
-    OperationId, Slot, SlotDeserializer, SlotSerializer, WrappedEndorsement, WrappedOperation,
+    Address, Denunciation, DenunciationDeserializer, DenunciationSerializer, Endorsement,
+    EndorsementDeserializer, EndorsementId, ModelsError, Operation, OperationId, Slot,
+    SlotDeserializer, SlotSerializer, WrappedEndorsement, WrappedOperation,
 };
 use massa_hash::{Hash, HashDeserializer};
 use massa_serialization::{
@@ -151,12 +155,14 @@ impl WrappedContent for Block {
             id: U::new(content.header.id.hash()),
             content,
             serialized_data: content_serialized,
+            multisig: None,
         })
     }
 
     fn serialize(
         _signature: &Signature,
         _creator_public_key: &PublicKey,
+        _multisig: Option<&MultisigProof>,
         serialized_content: &[u8],
         buffer: &mut Vec<u8>,
     ) -> Result<(), SerializeError> {
@@ -186,6 +192,7 @@ impl WrappedContent for Block {
                 id: U::new(content.header.id.hash()),
                 content,
                 serialized_data: buffer[..buffer.len() - rest.len()].to_vec(),
+                multisig: None,
             },
         ))
     }
@@ -390,6 +397,8 @@ pub struct BlockHeader {
     pub operation_merkle_root: Hash,
     /// endorsements
     pub endorsements: Vec<WrappedEndorsement>,
+    /// denunciations of stakers who signed twice for the same slot
+    pub denunciations: Vec<Denunciation>,
 }
 // NOTE: TODO
 // impl Signable<BlockId> for BlockHeader {
@@ -412,6 +421,7 @@ impl WrappedContent for BlockHeader {}
 pub struct BlockHeaderSerializer {
     slot_serializer: SlotSerializer,
     endorsement_serializer: WrappedSerializer,
+    denunciation_serializer: DenunciationSerializer,
     u32_serializer: U32VarIntSerializer,
 }
 
@@ -421,6 +431,7 @@ impl BlockHeaderSerializer {
         Self {
             slot_serializer: SlotSerializer::new(),
             endorsement_serializer: WrappedSerializer::new(),
+            denunciation_serializer: DenunciationSerializer::new(),
             u32_serializer: U32VarIntSerializer::new(),
         }
     }
@@ -457,6 +468,17 @@ impl Serializer<BlockHeader> for BlockHeaderSerializer {
         for endorsement in value.endorsements.iter() {
             self.endorsement_serializer.serialize(endorsement, buffer)?;
         }
+
+        self.u32_serializer.serialize(
+            &value.denunciations.len().try_into().map_err(|err| {
+                SerializeError::GeneralError(format!("too many denunciations: {}", err))
+            })?,
+            buffer,
+        )?;
+        for denunciation in value.denunciations.iter() {
+            self.denunciation_serializer
+                .serialize(denunciation, buffer)?;
+        }
         Ok(())
     }
 }
@@ -465,7 +487,9 @@ impl Serializer<BlockHeader> for BlockHeaderSerializer {
 pub struct BlockHeaderDeserializer {
     slot_deserializer: SlotDeserializer,
     endorsement_deserializer: WrappedDeserializer<Endorsement, EndorsementDeserializer>,
+    denunciation_deserializer: DenunciationDeserializer,
     u32_deserializer: U32VarIntDeserializer,
+    denunciation_count_deserializer: U32VarIntDeserializer,
     hash_deserializer: HashDeserializer,
 }
 
@@ -480,7 +504,12 @@ impl BlockHeaderDeserializer {
             endorsement_deserializer: WrappedDeserializer::new(EndorsementDeserializer::new(
                 ENDORSEMENT_COUNT,
             )),
+            denunciation_deserializer: DenunciationDeserializer::new(),
             u32_deserializer: U32VarIntDeserializer::new(Included(0), Included(u32::MAX)),
+            denunciation_count_deserializer: U32VarIntDeserializer::new(
+                Included(0),
+                Included(MAX_DENUNCIATIONS_PER_BLOCK),
+            ),
             hash_deserializer: HashDeserializer::new(),
         }
     }
@@ -530,14 +559,22 @@ impl Deserializer<BlockHeader> for BlockHeaderDeserializer {
                         |input| self.endorsement_deserializer.deserialize(input),
                     ),
                 ),
+                context(
+                    "Failed denunciations deserialization",
+                    length_count(
+                        |input| self.denunciation_count_deserializer.deserialize(input),
+                        |input| self.denunciation_deserializer.deserialize(input),
+                    ),
+                ),
             )),
         )
         .map(
-            |(slot, parents, operation_merkle_root, endorsements)| BlockHeader {
+            |(slot, parents, operation_merkle_root, endorsements, denunciations)| BlockHeader {
                 slot,
                 parents,
                 operation_merkle_root,
                 endorsements,
+                denunciations,
             },
         )
         .parse(buffer)
@@ -573,6 +610,18 @@ impl std::fmt::Display for BlockHeader {
         if self.endorsements.is_empty() {
             writeln!(f, "\tNo endorsements found")?;
         }
+        if !self.denunciations.is_empty() {
+            writeln!(f, "\tDenunciations:")?;
+            for denunciation in self.denunciations.iter() {
+                writeln!(f, "\t\t-----")?;
+                writeln!(f, "\t\tKind: {}", denunciation.kind)?;
+                writeln!(
+                    f,
+                    "\t\tDenounced address: {}",
+                    Address::from_public_key(&denunciation.public_key)
+                )?;
+            }
+        }
         Ok(())
     }
 }
@@ -621,6 +670,7 @@ mod test {
                     )
                     .unwrap(),
                 ],
+                denunciations: Vec::new(),
             },
             BlockHeaderSerializer::new(),
             &keypair,
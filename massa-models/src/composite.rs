@@ -1,7 +1,7 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use crate::prehash::Map;
-use crate::{Address, BlockId, WrappedOperation};
+use crate::{Address, BlockId, Slot, WrappedOperation};
 use massa_signature::{PublicKey, Signature};
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
@@ -65,6 +65,48 @@ pub struct StakersCycleProductionStats {
     pub ok_nok_counts: Map<Address, (u64, u64)>,
 }
 
+/// block production of a staking address of the node during the latest cycle
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StakingAddressHealth {
+    /// staking address
+    pub address: Address,
+    /// latest cycle with final blocks
+    pub cycle: u64,
+    /// produced block count
+    pub ok_count: u64,
+    /// missed block count
+    pub nok_count: u64,
+    /// true if the miss rate is close to the deactivation threshold (above the configured share of it):
+    /// the rolls of the address will be deactivated if it crosses the threshold by the end of the cycle
+    pub deactivation_imminent: bool,
+}
+
+/// alert about the block production of a staking address of the node
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum StakingAlert {
+    /// the address missed a block that became final, only alerted for the first miss of the cycle
+    MissedBlock {
+        /// staking address
+        address: Address,
+        /// missed slot
+        slot: Slot,
+    },
+    /// the miss rate of the address got close to the deactivation threshold during the cycle
+    DeactivationImminent {
+        /// staking address
+        address: Address,
+        /// cycle of the misses
+        cycle: u64,
+        /// cycle at which the rolls will be deactivated if the miss rate stays above the threshold
+        deactivation_cycle: u64,
+        /// produced block count
+        ok_count: u64,
+        /// missed block count
+        nok_count: u64,
+    },
+}
+
 /// just a public key and a signature it has produced
 /// used for serialization/deserialization purpose
 #[derive(Debug, Serialize, Deserialize)]
@@ -0,0 +1,420 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::node_configuration::{MAX_BLOCK_SIZE, THREAD_COUNT};
+use crate::wrapped::compute_wrapped_hash;
+use crate::{Address, ModelsError, Slot, SlotDeserializer, WrappedEndorsement, WrappedHeader};
+use massa_serialization::{
+    DeserializeError, Deserializer, SerializeError, Serializer, U32VarIntDeserializer,
+    U32VarIntSerializer,
+};
+use massa_signature::{PublicKey, PublicKeyDeserializer, Signature, SignatureDeserializer};
+use nom::branch::alt;
+use nom::bytes::complete::tag;
+use nom::error::context;
+use nom::multi::length_data;
+use nom::sequence::tuple;
+use nom::Parser;
+use nom::{
+    error::{ContextError, ParseError},
+    IResult,
+};
+use serde::{Deserialize, Serialize};
+use std::convert::TryInto;
+use std::fmt::Formatter;
+use std::ops::Bound::{Excluded, Included};
+
+/// Kind of the contents a staker is denounced for signing twice
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DenunciationKind {
+    /// two different block headers for the same slot
+    BlockHeader,
+    /// two different endorsements for the same slot and index
+    Endorsement,
+}
+
+impl std::fmt::Display for DenunciationKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DenunciationKind::BlockHeader => write!(f, "block header"),
+            DenunciationKind::Endorsement => write!(f, "endorsement"),
+        }
+    }
+}
+
+/// A content signed by the denounced staker,
+/// kept serialized so that its signature can be checked without the original structure
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DenouncedContent {
+    /// signature of the content by the denounced staker
+    pub signature: Signature,
+    /// serialized content, without signature nor public key
+    pub serialized_content: Vec<u8>,
+}
+
+/// Proof that a staker signed two different block headers for the same slot,
+/// or two different endorsements for the same slot and index.
+/// Once included in a block, the rolls of the staker are slashed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Denunciation {
+    /// kind of the conflicting contents
+    pub kind: DenunciationKind,
+    /// public key of the denounced staker
+    pub public_key: PublicKey,
+    /// the two conflicting contents, ordered by serialized content
+    pub contents: (DenouncedContent, DenouncedContent),
+}
+
+/// Identifies a double signing: a staker is denounced at most once per key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct DenunciationKey {
+    /// address of the denounced staker
+    pub address: Address,
+    /// slot of the conflicting contents
+    pub slot: Slot,
+    /// index of the conflicting endorsements, `None` for block headers
+    pub index: Option<u32>,
+}
+
+impl std::fmt::Display for DenunciationKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.index {
+            Some(index) => write!(
+                f,
+                "address {} at slot {} (endorsement index {})",
+                self.address, self.slot, index
+            ),
+            None => write!(f, "address {} at slot {}", self.address, self.slot),
+        }
+    }
+}
+
+impl Denunciation {
+    /// Denounces the creator of two different headers for the same slot
+    pub fn from_headers(
+        header_1: &WrappedHeader,
+        header_2: &WrappedHeader,
+    ) -> Result<Self, ModelsError> {
+        Denunciation::new(
+            DenunciationKind::BlockHeader,
+            (&header_1.creator_public_key, &header_2.creator_public_key),
+            (
+                DenouncedContent {
+                    signature: header_1.signature,
+                    serialized_content: header_1.serialized_data.clone(),
+                },
+                DenouncedContent {
+                    signature: header_2.signature,
+                    serialized_content: header_2.serialized_data.clone(),
+                },
+            ),
+        )
+    }
+
+    /// Denounces the creator of two different endorsements for the same slot and index
+    pub fn from_endorsements(
+        endorsement_1: &WrappedEndorsement,
+        endorsement_2: &WrappedEndorsement,
+    ) -> Result<Self, ModelsError> {
+        Denunciation::new(
+            DenunciationKind::Endorsement,
+            (
+                &endorsement_1.creator_public_key,
+                &endorsement_2.creator_public_key,
+            ),
+            (
+                DenouncedContent {
+                    signature: endorsement_1.signature,
+                    serialized_content: endorsement_1.serialized_data.clone(),
+                },
+                DenouncedContent {
+                    signature: endorsement_2.signature,
+                    serialized_content: endorsement_2.serialized_data.clone(),
+                },
+            ),
+        )
+    }
+
+    fn new(
+        kind: DenunciationKind,
+        public_keys: (&PublicKey, &PublicKey),
+        contents: (DenouncedContent, DenouncedContent),
+    ) -> Result<Self, ModelsError> {
+        if public_keys.0 != public_keys.1 {
+            return Err(ModelsError::InvalidDenunciation(
+                "the contents were signed by different keys".into(),
+            ));
+        }
+        // the order is fixed so that both orders of discovery give the same denunciation
+        let contents = if contents.0.serialized_content <= contents.1.serialized_content {
+            contents
+        } else {
+            (contents.1, contents.0)
+        };
+        let denunciation = Denunciation {
+            kind,
+            public_key: *public_keys.0,
+            contents,
+        };
+        denunciation.check()?;
+        Ok(denunciation)
+    }
+
+    /// Checks that both contents are signed by the denounced staker and that they conflict.
+    /// Returns the key of the denounced double signing.
+    pub fn check(&self) -> Result<DenunciationKey, ModelsError> {
+        let (content_1, content_2) = &self.contents;
+        if content_1.serialized_content == content_2.serialized_content {
+            return Err(ModelsError::InvalidDenunciation(
+                "the two contents are identical".into(),
+            ));
+        }
+        for content in [content_1, content_2] {
+            let hash = compute_wrapped_hash(&self.public_key, &content.serialized_content);
+            self.public_key
+                .verify_signature(&hash, &content.signature)?;
+        }
+        let position_1 = self.read_position(&content_1.serialized_content)?;
+        let position_2 = self.read_position(&content_2.serialized_content)?;
+        if position_1 != position_2 {
+            return Err(ModelsError::InvalidDenunciation(format!(
+                "the two {}s do not conflict",
+                self.kind
+            )));
+        }
+        Ok(DenunciationKey {
+            address: Address::from_public_key(&self.public_key),
+            slot: position_1.0,
+            index: position_1.1,
+        })
+    }
+
+    /// Gets the key of an already checked denunciation, without checking the signatures again
+    pub fn get_key(&self) -> Result<DenunciationKey, ModelsError> {
+        let (slot, index) = self.read_position(&self.contents.0.serialized_content)?;
+        Ok(DenunciationKey {
+            address: Address::from_public_key(&self.public_key),
+            slot,
+            index,
+        })
+    }
+
+    /// Reads the slot (and the index for endorsements) at the start of a serialized content
+    fn read_position(&self, serialized_content: &[u8]) -> Result<(Slot, Option<u32>), ModelsError> {
+        let slot_deserializer = SlotDeserializer::new(
+            (Included(0), Included(u64::MAX)),
+            (Included(0), Excluded(THREAD_COUNT)),
+        );
+        let (rest, slot) = slot_deserializer
+            .deserialize::<DeserializeError>(serialized_content)
+            .map_err(|err| ModelsError::DeserializeError(err.to_string()))?;
+        match self.kind {
+            DenunciationKind::BlockHeader => Ok((slot, None)),
+            DenunciationKind::Endorsement => {
+                let (_, index) = U32VarIntDeserializer::new(Included(0), Included(u32::MAX))
+                    .deserialize::<DeserializeError>(rest)
+                    .map_err(|err| ModelsError::DeserializeError(err.to_string()))?;
+                Ok((slot, Some(index)))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Denunciation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Denunciation of two {}s", self.kind)?;
+        writeln!(
+            f,
+            "Denounced address: {}",
+            Address::from_public_key(&self.public_key)
+        )?;
+        writeln!(f, "Signatures: {}", self.contents.0.signature)?;
+        writeln!(f, "            {}", self.contents.1.signature)?;
+        Ok(())
+    }
+}
+
+/// Serializer for `Denunciation`
+pub struct DenunciationSerializer {
+    u32_serializer: U32VarIntSerializer,
+}
+
+impl DenunciationSerializer {
+    /// Creates a new `DenunciationSerializer`
+    pub const fn new() -> Self {
+        Self {
+            u32_serializer: U32VarIntSerializer::new(),
+        }
+    }
+}
+
+impl Default for DenunciationSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Serializer<Denunciation> for DenunciationSerializer {
+    fn serialize(&self, value: &Denunciation, buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
+        buffer.push(match value.kind {
+            DenunciationKind::BlockHeader => 0,
+            DenunciationKind::Endorsement => 1,
+        });
+        buffer.extend(value.public_key.to_bytes());
+        for content in [&value.contents.0, &value.contents.1] {
+            buffer.extend(content.signature.to_bytes());
+            self.u32_serializer.serialize(
+                &content.serialized_content.len().try_into().map_err(|err| {
+                    SerializeError::NumberTooBig(format!("denounced content too big: {}", err))
+                })?,
+                buffer,
+            )?;
+            buffer.extend(&content.serialized_content);
+        }
+        Ok(())
+    }
+}
+
+/// Deserializer for `Denunciation`
+pub struct DenunciationDeserializer {
+    public_key_deserializer: PublicKeyDeserializer,
+    signature_deserializer: SignatureDeserializer,
+    length_deserializer: U32VarIntDeserializer,
+}
+
+impl DenunciationDeserializer {
+    /// Creates a new `DenunciationDeserializer`
+    pub const fn new() -> Self {
+        Self {
+            public_key_deserializer: PublicKeyDeserializer::new(),
+            signature_deserializer: SignatureDeserializer::new(),
+            length_deserializer: U32VarIntDeserializer::new(Included(0), Included(MAX_BLOCK_SIZE)),
+        }
+    }
+
+    fn deserialize_content<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], DenouncedContent, E> {
+        tuple((
+            context("Failed signature deserialization", |input| {
+                self.signature_deserializer.deserialize(input)
+            }),
+            context(
+                "Failed content deserialization",
+                length_data(|input| self.length_deserializer.deserialize(input)),
+            ),
+        ))
+        .map(|(signature, serialized_content)| DenouncedContent {
+            signature,
+            serialized_content: serialized_content.to_vec(),
+        })
+        .parse(buffer)
+    }
+}
+
+impl Default for DenunciationDeserializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deserializer<Denunciation> for DenunciationDeserializer {
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], Denunciation, E> {
+        context(
+            "Failed Denunciation deserialization",
+            tuple((
+                context(
+                    "Failed kind deserialization",
+                    alt((
+                        tag(&[0]).map(|_| DenunciationKind::BlockHeader),
+                        tag(&[1]).map(|_| DenunciationKind::Endorsement),
+                    )),
+                ),
+                context("Failed public_key deserialization", |input| {
+                    self.public_key_deserializer.deserialize(input)
+                }),
+                |input| self.deserialize_content(input),
+                |input| self.deserialize_content(input),
+            )),
+        )
+        .map(|(kind, public_key, content_1, content_2)| Denunciation {
+            kind,
+            public_key,
+            contents: (content_1, content_2),
+        })
+        .parse(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wrapped::WrappedContent;
+    use crate::{BlockId, Endorsement, EndorsementSerializer};
+    use massa_hash::Hash;
+    use massa_signature::KeyPair;
+    use serial_test::serial;
+
+    fn endorsement(keypair: &KeyPair, slot: Slot, index: u32, block: &str) -> WrappedEndorsement {
+        Endorsement::new_wrapped(
+            Endorsement {
+                slot,
+                index,
+                endorsed_block: BlockId(Hash::compute_from(block.as_bytes())),
+            },
+            EndorsementSerializer::new(),
+            keypair,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    #[serial]
+    fn test_denunciation() {
+        let keypair = KeyPair::generate();
+        let slot = Slot::new(10, 1);
+        let endorsement_1 = endorsement(&keypair, slot, 0, "blk1");
+        let endorsement_2 = endorsement(&keypair, slot, 0, "blk2");
+
+        // conflicting endorsements are denounced in a canonical order
+        let denunciation = Denunciation::from_endorsements(&endorsement_1, &endorsement_2).unwrap();
+        let reversed = Denunciation::from_endorsements(&endorsement_2, &endorsement_1).unwrap();
+        assert_eq!(denunciation.contents, reversed.contents);
+        let key = denunciation.check().unwrap();
+        assert_eq!(
+            key.address,
+            Address::from_public_key(&keypair.get_public_key())
+        );
+        assert_eq!(key.slot, slot);
+        assert_eq!(key.index, Some(0));
+        assert_eq!(denunciation.get_key().unwrap(), key);
+
+        // serialization round trip
+        let mut serialized = Vec::new();
+        DenunciationSerializer::new()
+            .serialize(&denunciation, &mut serialized)
+            .unwrap();
+        let (rest, deserialized) = DenunciationDeserializer::new()
+            .deserialize::<DeserializeError>(&serialized)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(deserialized.kind, denunciation.kind);
+        assert_eq!(deserialized.public_key, denunciation.public_key);
+        assert_eq!(deserialized.contents, denunciation.contents);
+
+        // no denunciation for the same endorsement, other indexes or other stakers
+        assert!(Denunciation::from_endorsements(&endorsement_1, &endorsement_1).is_err());
+        let other_index = endorsement(&keypair, slot, 1, "blk2");
+        assert!(Denunciation::from_endorsements(&endorsement_1, &other_index).is_err());
+        let other_staker = endorsement(&KeyPair::generate(), slot, 0, "blk2");
+        assert!(Denunciation::from_endorsements(&endorsement_1, &other_staker).is_err());
+
+        // a tampered content is rejected
+        let mut tampered = denunciation;
+        tampered.contents.1.serialized_content.push(0);
+        assert!(tampered.check().is_err());
+    }
+}
@@ -1,5 +1,6 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::Address;
 use displaydoc::Display;
 use massa_serialization::SerializeError;
 use thiserror::Error;
@@ -44,6 +45,12 @@ pub enum ModelsError {
     TimeError(#[from] massa_time::TimeError),
     /// invalid roll update: {0}
     InvalidRollUpdate(String),
+    /// invalid denunciation: {0}
+    InvalidDenunciation(String),
+    /// invalid multisig: {0}
+    InvalidMultisig(String),
+    /// the public key of the signature is not the one of address {0}
+    WrongSignerError(Address),
     /// Ledger changes, Amount overflow
     AmountOverflowError,
     /// Wrong prefix for hash: expected {0}, got {1}
@@ -13,12 +13,19 @@ pub use block::{
 };
 pub use composite::{
     OperationSearchResult, OperationSearchResultBlockStatus, OperationSearchResultStatus,
-    StakersCycleProductionStats,
+    StakersCycleProductionStats, StakingAddressHealth, StakingAlert,
+};
+pub use denunciation::{
+    DenouncedContent, Denunciation, DenunciationDeserializer, DenunciationKey, DenunciationKind,
+    DenunciationSerializer,
 };
 pub use endorsement::{
     Endorsement, EndorsementDeserializer, EndorsementId, EndorsementSerializer, WrappedEndorsement,
 };
 pub use error::ModelsError;
+pub use multisig::{
+    MultisigPolicy, MultisigProof, MultisigProofDeserializer, MultisigProofSerializer,
+};
 pub use operation::{
     Operation, OperationId, OperationIdsDeserializer, OperationPrefixId,
     OperationPrefixIdDeserializer, OperationPrefixIds, OperationPrefixIdsDeserializer,
@@ -48,6 +55,7 @@ mod block;
 pub mod clique;
 /// various structures
 pub mod composite;
+mod denunciation;
 mod endorsement;
 /// models error
 pub mod error;
@@ -55,6 +63,8 @@ pub mod error;
 pub mod execution;
 /// ledger related structures
 pub mod ledger_models;
+/// multisig addresses and signatures
+pub mod multisig;
 /// node related structure
 pub mod node;
 mod node_configuration;
@@ -68,6 +78,8 @@ pub mod prehash;
 pub mod rolls;
 mod serialization;
 mod serialization_context;
+/// messages signed by addresses, outside of operations
+pub mod signed_message;
 /// slots
 pub mod slot;
 /// various statistics
@@ -0,0 +1,348 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::node_configuration::MAX_MULTISIG_KEYS;
+use crate::{Address, ModelsError};
+use massa_hash::Hash;
+use massa_serialization::{Deserializer, SerializeError, Serializer};
+use massa_signature::{
+    PublicKey, PublicKeyDeserializer, Signature, SignatureDeserializer, PUBLIC_KEY_SIZE_BYTES,
+    SIGNATURE_SIZE_BYTES,
+};
+use nom::error::{context, ContextError, ErrorKind, ParseError};
+use nom::multi::length_count;
+use nom::number::complete::u8 as parse_u8;
+use nom::sequence::tuple;
+use nom::{IResult, Parser};
+use serde::{Deserialize, Serialize};
+use std::fmt::Formatter;
+
+/// Prefix of the hashed data of multisig addresses and multisig wrapped structures,
+/// so that they never collide with their single key counterparts
+const MULTISIG_DOMAIN: &[u8] = b"massa_multisig";
+
+/// M-of-N policy over several public keys, from which a multisig address is derived.
+/// The keys are sorted so that a set of keys and a threshold give a single address.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultisigPolicy {
+    /// minimal number of signatures
+    pub threshold: u8,
+    /// sorted public keys allowed to sign
+    pub public_keys: Vec<PublicKey>,
+}
+
+impl MultisigPolicy {
+    /// Creates a policy requiring `threshold` signatures among `public_keys`, in any order
+    pub fn new(threshold: u8, mut public_keys: Vec<PublicKey>) -> Result<Self, ModelsError> {
+        public_keys.sort_unstable();
+        let policy = MultisigPolicy {
+            threshold,
+            public_keys,
+        };
+        policy.check()?;
+        Ok(policy)
+    }
+
+    /// Checks the threshold, the number of keys and their order
+    pub fn check(&self) -> Result<(), ModelsError> {
+        if self.public_keys.len() > MAX_MULTISIG_KEYS as usize {
+            return Err(ModelsError::InvalidMultisig(format!(
+                "a policy has at most {} public keys",
+                MAX_MULTISIG_KEYS
+            )));
+        }
+        if self.threshold == 0 || self.threshold as usize > self.public_keys.len() {
+            return Err(ModelsError::InvalidMultisig(format!(
+                "the threshold must be between 1 and the number of public keys ({})",
+                self.public_keys.len()
+            )));
+        }
+        if self.public_keys.windows(2).any(|keys| keys[0] >= keys[1]) {
+            return Err(ModelsError::InvalidMultisig(
+                "the public keys must be sorted and distinct".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Gets the position of a public key in the policy
+    pub fn get_index(&self, public_key: &PublicKey) -> Option<u8> {
+        self.public_keys
+            .binary_search(public_key)
+            .ok()
+            .map(|index| index as u8)
+    }
+
+    /// Serializes the policy: threshold, key count and keys
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(2 + self.public_keys.len() * PUBLIC_KEY_SIZE_BYTES);
+        bytes.push(self.threshold);
+        bytes.push(self.public_keys.len() as u8);
+        for public_key in &self.public_keys {
+            bytes.extend(public_key.to_bytes());
+        }
+        bytes
+    }
+}
+
+impl std::fmt::Display for MultisigPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{}-of-{} multisig address: {}",
+            self.threshold,
+            self.public_keys.len(),
+            Address::from_multisig_policy(self)
+        )?;
+        for public_key in &self.public_keys {
+            writeln!(f, "\t- {}", public_key)?;
+        }
+        Ok(())
+    }
+}
+
+/// Hash signed by each signer of a multisig wrapped structure, which is also its id:
+/// the hash of the policy followed by the serialized content.
+pub fn compute_multisig_wrapped_hash(policy: &MultisigPolicy, content_serialized: &[u8]) -> Hash {
+    let mut hash_data = MULTISIG_DOMAIN.to_vec();
+    hash_data.extend(policy.to_bytes());
+    hash_data.extend(content_serialized);
+    Hash::compute_from(&hash_data)
+}
+
+/// Hashed data of the address of a multisig policy
+pub(crate) fn multisig_address_data(policy: &MultisigPolicy) -> Vec<u8> {
+    let mut data = MULTISIG_DOMAIN.to_vec();
+    data.extend(policy.to_bytes());
+    data
+}
+
+/// Signatures of a content by the keys of a multisig policy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultisigProof {
+    /// policy of the signing address
+    pub policy: MultisigPolicy,
+    /// signatures, with the index of their key in the policy, sorted by index
+    pub signatures: Vec<(u8, Signature)>,
+}
+
+impl MultisigProof {
+    /// Creates a proof without any signature yet
+    pub fn new(policy: MultisigPolicy) -> Self {
+        MultisigProof {
+            policy,
+            signatures: Vec::new(),
+        }
+    }
+
+    /// Adds the signature of a key of the policy, replacing its previous one if any
+    pub fn add_signature(
+        &mut self,
+        public_key: &PublicKey,
+        signature: Signature,
+    ) -> Result<(), ModelsError> {
+        let index = self.policy.get_index(public_key).ok_or_else(|| {
+            ModelsError::InvalidMultisig(format!("{} is not a key of the policy", public_key))
+        })?;
+        match self
+            .signatures
+            .binary_search_by_key(&index, |(index, _)| *index)
+        {
+            Ok(position) => self.signatures[position].1 = signature,
+            Err(position) => self.signatures.insert(position, (index, signature)),
+        }
+        Ok(())
+    }
+
+    /// Size of the proof once serialized by `MultisigProofSerializer`
+    pub fn serialized_size(&self) -> usize {
+        2 + self.policy.public_keys.len() * PUBLIC_KEY_SIZE_BYTES
+            + 1
+            + self.signatures.len() * (1 + SIGNATURE_SIZE_BYTES)
+    }
+
+    /// Returns true if there are enough signatures to reach the threshold
+    pub fn is_complete(&self) -> bool {
+        self.signatures.len() >= self.policy.threshold as usize
+    }
+
+    /// Checks the policy and that at least `threshold` distinct keys signed the hash
+    pub fn verify(&self, hash: &Hash) -> Result<(), ModelsError> {
+        self.policy.check()?;
+        if !self.is_complete() {
+            return Err(ModelsError::InvalidMultisig(format!(
+                "{} signatures for a threshold of {}",
+                self.signatures.len(),
+                self.policy.threshold
+            )));
+        }
+        if self
+            .signatures
+            .windows(2)
+            .any(|sigs| sigs[0].0 >= sigs[1].0)
+        {
+            return Err(ModelsError::InvalidMultisig(
+                "the signatures must be sorted by distinct key index".into(),
+            ));
+        }
+        for (index, signature) in &self.signatures {
+            let public_key = self
+                .policy
+                .public_keys
+                .get(*index as usize)
+                .ok_or_else(|| {
+                    ModelsError::InvalidMultisig(format!("no public key at index {}", index))
+                })?;
+            public_key.verify_signature(hash, signature)?;
+        }
+        Ok(())
+    }
+}
+
+/// Serializer for `MultisigProof`
+#[derive(Default)]
+pub struct MultisigProofSerializer;
+
+impl MultisigProofSerializer {
+    /// Creates a new `MultisigProofSerializer`
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl Serializer<MultisigProof> for MultisigProofSerializer {
+    fn serialize(&self, value: &MultisigProof, buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
+        if value.policy.public_keys.len() > MAX_MULTISIG_KEYS as usize
+            || value.signatures.len() > value.policy.public_keys.len()
+        {
+            return Err(SerializeError::GeneralError(
+                "too many multisig keys or signatures".into(),
+            ));
+        }
+        buffer.extend(value.policy.to_bytes());
+        buffer.push(value.signatures.len() as u8);
+        for (index, signature) in &value.signatures {
+            buffer.push(*index);
+            buffer.extend(signature.to_bytes());
+        }
+        Ok(())
+    }
+}
+
+/// Deserializer for `MultisigProof`
+#[derive(Default)]
+pub struct MultisigProofDeserializer {
+    public_key_deserializer: PublicKeyDeserializer,
+    signature_deserializer: SignatureDeserializer,
+}
+
+impl MultisigProofDeserializer {
+    /// Creates a new `MultisigProofDeserializer`
+    pub const fn new() -> Self {
+        Self {
+            public_key_deserializer: PublicKeyDeserializer::new(),
+            signature_deserializer: SignatureDeserializer::new(),
+        }
+    }
+}
+
+impl Deserializer<MultisigProof> for MultisigProofDeserializer {
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], MultisigProof, E> {
+        let (rest, (threshold, public_keys, signatures)) = context(
+            "Failed MultisigProof deserialization",
+            tuple((
+                context("Failed threshold deserialization", parse_u8),
+                context(
+                    "Failed public_keys deserialization",
+                    length_count(parse_u8, |input| {
+                        self.public_key_deserializer.deserialize(input)
+                    }),
+                ),
+                context(
+                    "Failed signatures deserialization",
+                    length_count(
+                        parse_u8,
+                        tuple((parse_u8, |input| {
+                            self.signature_deserializer.deserialize(input)
+                        })),
+                    ),
+                ),
+            )),
+        )
+        .parse(buffer)?;
+        let proof = MultisigProof {
+            policy: MultisigPolicy {
+                threshold,
+                public_keys,
+            },
+            signatures,
+        };
+        // the signatures are checked later, only the structure is checked here
+        if proof.policy.check().is_err() || !proof.is_complete() {
+            return Err(nom::Err::Error(ParseError::from_error_kind(
+                buffer,
+                ErrorKind::Verify,
+            )));
+        }
+        Ok((rest, proof))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_serialization::DeserializeError;
+    use massa_signature::KeyPair;
+    use serial_test::serial;
+
+    #[test]
+    #[serial]
+    fn test_multisig_proof() {
+        let keypairs: Vec<KeyPair> = (0..3).map(|_| KeyPair::generate()).collect();
+        let public_keys: Vec<PublicKey> = keypairs.iter().map(|k| k.get_public_key()).collect();
+        let policy = MultisigPolicy::new(2, public_keys.clone()).unwrap();
+
+        // the address does not depend on the order of the keys
+        let mut reversed_keys = public_keys.clone();
+        reversed_keys.reverse();
+        let reversed = MultisigPolicy::new(2, reversed_keys).unwrap();
+        assert_eq!(
+            Address::from_multisig_policy(&policy),
+            Address::from_multisig_policy(&reversed)
+        );
+        assert!(MultisigPolicy::new(0, public_keys.clone()).is_err());
+        assert!(MultisigPolicy::new(4, public_keys.clone()).is_err());
+        assert!(MultisigPolicy::new(1, vec![public_keys[0], public_keys[0]]).is_err());
+
+        let hash = compute_multisig_wrapped_hash(&policy, b"content");
+        let mut proof = MultisigProof::new(policy);
+        proof
+            .add_signature(&public_keys[2], keypairs[2].sign(&hash).unwrap())
+            .unwrap();
+        assert!(proof.verify(&hash).is_err());
+        proof
+            .add_signature(&public_keys[0], keypairs[0].sign(&hash).unwrap())
+            .unwrap();
+        proof.verify(&hash).unwrap();
+        assert!(proof
+            .add_signature(&KeyPair::generate().get_public_key(), proof.signatures[0].1)
+            .is_err());
+
+        // serialization round trip
+        let mut serialized = Vec::new();
+        MultisigProofSerializer::new()
+            .serialize(&proof, &mut serialized)
+            .unwrap();
+        let (rest, deserialized) = MultisigProofDeserializer::new()
+            .deserialize::<DeserializeError>(&serialized)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(deserialized, proof);
+
+        // a signature of another content is rejected
+        let other_hash = compute_multisig_wrapped_hash(&proof.policy, b"other content");
+        assert!(proof.verify(&other_hash).is_err());
+    }
+}
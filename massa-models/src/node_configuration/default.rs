@@ -59,12 +59,14 @@ lazy_static::lazy_static! {
         .unwrap();
     /// number of cycle misses (strictly) above which stakers are deactivated
     pub static ref POS_MISS_RATE_DEACTIVATION_THRESHOLD: Ratio<u64> = Ratio::new(7, 10);
+    /// share of the value of the slashed rolls given to the creator of the block including a denunciation
+    pub static ref DENUNCIATION_REWARD_RATIO: Ratio<u64> = Ratio::new(1, 2);
     /// node version
     pub static ref VERSION: Version = {
         if cfg!(feature = "sandbox") {
             "SAND.0.0"
         } else {
-            "TEST.12.2"
+            "TEST.13.0"
         }
         .parse()
         .unwrap()
@@ -93,6 +95,12 @@ pub const MAX_BLOCK_SIZE: u32 = 409600;
 pub const MAX_ASYNC_POOL_LENGTH: u64 = 10_000;
 /// Maximum operation validity period count
 pub const OPERATION_VALIDITY_PERIODS: u64 = 10;
+/// Number of periods after which a double signing can no longer be denounced
+pub const DENUNCIATION_EXPIRE_PERIODS: u64 = 32;
+/// Maximum number of denunciations in a block header
+pub const MAX_DENUNCIATIONS_PER_BLOCK: u32 = 16;
+/// Maximum number of public keys in the policy of a multisig address
+pub const MAX_MULTISIG_KEYS: u8 = 16;
 /// cycle duration in periods
 pub const PERIODS_PER_CYCLE: u64 = 128;
 /// PoS look back cycles: when drawing for cycle N, we use the rolls from cycle N - `pos_look` `back_cycles` - 1
@@ -144,6 +152,8 @@ pub const MAX_ASYNC_GAS: u64 = 1_000_000_000;
 
 /// Max number of endorsements per message
 pub const MAX_ENDORSEMENTS_PER_MESSAGE: u32 = 1024;
+/// Max number of denunciations per message
+pub const MAX_DENUNCIATIONS_PER_MESSAGE: u32 = 1024;
 /// node send channel size
 pub const NODE_SEND_CHANNEL_SIZE: usize = 1024;
 /// max duplex buffer size
@@ -49,6 +49,8 @@ lazy_static::lazy_static! {
     /// process, the first use is effectively `MassaTime::now().unwrap()` but will be outdated for
     /// the latest test. That's the reason why we choose to reset it each time we get a `ConsensusConfig`.
     pub static ref POS_MISS_RATE_DEACTIVATION_THRESHOLD: Ratio<u64> = Ratio::new(1, 1);
+    /// share of the value of the slashed rolls given to the creator of the block including a denunciation
+    pub static ref DENUNCIATION_REWARD_RATIO: Ratio<u64> = Ratio::new(1, 2);
     /// node version
     pub static ref VERSION: Version = "DEVE.0.0".parse().unwrap();
 }
@@ -69,6 +71,8 @@ pub const BOOTSTRAP_RANDOMNESS_SIZE_BYTES: usize = 32;
 pub const CHANNEL_SIZE: usize = 256;
 /// fitness threshold
 pub const DELTA_F0: u64 = 32;
+/// number of periods after which a double signing can no longer be denounced
+pub const DENUNCIATION_EXPIRE_PERIODS: u64 = 10;
 /// target endorsement count
 pub const ENDORSEMENT_COUNT: u32 = 0;
 /// endorsement id size
@@ -101,10 +105,16 @@ pub const MAX_BOOTSTRAP_CHILDREN: u32 = 10;
 pub const MAX_BOOTSTRAP_CLIQUES: u32 = 100;
 /// max bootstrapped dependencies
 pub const MAX_BOOTSTRAP_DEPS: u32 = 100;
+/// max denunciations per block header
+pub const MAX_DENUNCIATIONS_PER_BLOCK: u32 = 16;
 /// max duplex buffer size
 pub const MAX_DUPLEX_BUFFER_SIZE: usize = 1024;
+/// max public keys in the policy of a multisig address
+pub const MAX_MULTISIG_KEYS: u8 = 16;
 /// max endorsements per message
 pub const MAX_ENDORSEMENTS_PER_MESSAGE: u32 = 1024;
+/// max denunciations per message
+pub const MAX_DENUNCIATIONS_PER_MESSAGE: u32 = 1024;
 /// max bootstrap message size
 pub const MAX_BOOTSTRAP_MESSAGE_SIZE: u32 = 100_000_000;
 /// max bootstrapped proof of stake entries
@@ -185,6 +195,8 @@ pub const MAX_FINAL_EVENTS: usize = 10;
 pub const MAX_FUTURE_PROCESSING_BLOCK: usize = 10;
 /// normally in `config.toml`, max item count returned
 pub const MAX_ITEM_RETURN_COUNT: usize = 1000;
+/// normally in `config.toml`, share of the deactivation threshold above which a staking address miss rate is alerted
+pub const STAKING_WARNING_PERCENT: u64 = 80;
 /// normally in `config.toml`, max operation fill attempts
 pub const MAX_OPERATION_FILL_ATTEMPTS: u32 = 6;
 /// normally in `config.toml`, operation batch size
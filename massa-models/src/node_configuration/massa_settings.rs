@@ -23,6 +23,7 @@
 //! The last step is to merge the environment variable prefixed with
 //! `MASSA_CLIENT`, override if duplicated
 //!
+use config::ConfigError;
 use directories::ProjectDirs;
 use serde::Deserialize;
 use std::path::Path;
@@ -31,49 +32,60 @@ use std::path::Path;
 /// 1. default
 /// 2. in path specified in `MASSA_CONFIG_PATH` environment variable (`base_config/config.toml` by default)
 /// 3. in path specified in `MASSA_CONFIG_OVERRIDE_PATH` environment variable (`config/config.toml` by default)
+///
+/// Panics if the settings cannot be read, see `try_build_massa_settings` otherwise.
 #[inline]
 pub fn build_massa_settings<T: Deserialize<'static>>(app_name: &str, env_prefix: &str) -> T {
+    try_build_massa_settings(app_name, env_prefix).unwrap_or_else(|error| panic!("{}", error))
+}
+
+/// Same as `build_massa_settings` but returns an error instead of panicking,
+/// to read the settings again while the program is running
+pub fn try_build_massa_settings<T: Deserialize<'static>>(
+    app_name: &str,
+    env_prefix: &str,
+) -> Result<T, ConfigError> {
     let mut settings = config::Config::default();
+    let current_dir = std::env::current_dir()
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_default();
     let config_path = std::env::var("MASSA_CONFIG_PATH")
         .unwrap_or_else(|_| "base_config/config.toml".to_string());
     settings
         .merge(config::File::with_name(&config_path))
-        .unwrap_or_else(|error| {
-            panic!(
+        .map_err(|error| {
+            ConfigError::Message(format!(
                 "failed to read {} config {}: {}",
-                config_path,
-                std::env::current_dir().unwrap().as_path().to_str().unwrap(),
-                error
-            )
-        });
+                config_path, current_dir, error
+            ))
+        })?;
     let config_override_path = std::env::var("MASSA_CONFIG_OVERRIDE_PATH")
         .unwrap_or_else(|_| "config/config.toml".to_string());
     if Path::new(&config_override_path).is_file() {
         settings
             .merge(config::File::with_name(&config_override_path))
-            .unwrap_or_else(|error| {
-                panic!(
+            .map_err(|error| {
+                ConfigError::Message(format!(
                     "failed to read {} override config {}: {}",
-                    config_override_path,
-                    std::env::current_dir().unwrap().as_path().to_str().unwrap(),
-                    error
-                )
-            });
+                    config_override_path, current_dir, error
+                ))
+            })?;
     }
     if let Some(proj_dirs) = ProjectDirs::from("com", "MassaLabs", app_name) {
         // Portable user config loading
         let user_config_path = proj_dirs.config_dir();
         if user_config_path.exists() {
-            let path_str = user_config_path.to_str().unwrap();
+            let path_str = user_config_path.to_string_lossy();
             settings
-                .merge(config::File::with_name(path_str))
-                .unwrap_or_else(|error| {
-                    panic!("failed to read {} user config: {}", path_str, error)
-                });
+                .merge(config::File::with_name(&path_str))
+                .map_err(|error| {
+                    ConfigError::Message(format!(
+                        "failed to read {} user config: {}",
+                        path_str, error
+                    ))
+                })?;
         }
     }
-    settings
-        .merge(config::Environment::with_prefix(env_prefix))
-        .unwrap();
-    settings.try_into().unwrap()
+    settings.merge(config::Environment::with_prefix(env_prefix))?;
+    settings.try_into()
 }
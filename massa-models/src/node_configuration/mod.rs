@@ -73,4 +73,4 @@ pub use compact_config::CompactConfig;
 
 // Export tool to read user setting file
 mod massa_settings;
-pub use massa_settings::build_massa_settings;
+pub use massa_settings::{build_massa_settings, try_build_massa_settings};
@@ -227,7 +227,9 @@ impl std::fmt::Display for Operation {
 /// signed operation
 pub type WrappedOperation = Wrapped<Operation, OperationId>;
 
-impl WrappedContent for Operation {}
+impl WrappedContent for Operation {
+    const SUPPORTS_MULTISIG: bool = true;
+}
 
 /// Serializer for `Operation`
 pub struct OperationSerializer {
@@ -672,6 +674,17 @@ impl WrappedOperation {
         self.verify_signature(OperationSerializer::new(), &self.creator_public_key)?;
         Ok(self.id)
     }
+
+    /// Checks that the creator address is allowed to issue this type of operation:
+    /// multisig addresses cannot buy rolls because blocks and endorsements are signed by a single key
+    pub fn check_multisig_rules(&self) -> Result<(), ModelsError> {
+        if self.multisig.is_some() && matches!(self.content.op, OperationType::RollBuy { .. }) {
+            return Err(ModelsError::InvalidMultisig(
+                "multisig addresses cannot buy rolls".into(),
+            ));
+        }
+        Ok(())
+    }
 }
 
 impl WrappedOperation {
@@ -695,6 +708,17 @@ impl WrappedOperation {
         }
     }
 
+    /// Get the fee and gas coins paid to the block producer per byte of the operation, rounded down
+    pub fn get_fee_density(&self) -> Amount {
+        let total_return = self.content.fee.saturating_add(self.get_gas_coins());
+        Amount::from_raw(
+            total_return
+                .to_raw()
+                .checked_div(self.serialized_size() as u64)
+                .unwrap_or_default(),
+        )
+    }
+
     /// Get the amount of coins used by the operation to pay for gas
     pub fn get_gas_coins(&self) -> Amount {
         match &self.content.op {
@@ -713,8 +737,7 @@ impl WrappedOperation {
     /// get the addresses that are involved in this operation from a ledger point of view
     pub fn get_ledger_involved_addresses(&self) -> Set<Address> {
         let mut res = Set::<Address>::default();
-        let emitter_address = Address::from_public_key(&self.creator_public_key);
-        res.insert(emitter_address);
+        res.insert(self.creator_address);
         match &self.content.op {
             OperationType::Transaction {
                 recipient_address, ..
@@ -737,10 +760,10 @@ impl WrappedOperation {
         match self.content.op {
             OperationType::Transaction { .. } => {}
             OperationType::RollBuy { .. } => {
-                res.insert(Address::from_public_key(&self.creator_public_key));
+                res.insert(self.creator_address);
             }
             OperationType::RollSell { .. } => {
-                res.insert(Address::from_public_key(&self.creator_public_key));
+                res.insert(self.creator_address);
             }
             OperationType::ExecuteSC { .. } => {}
             OperationType::CallSC { .. } => {}
@@ -1206,4 +1229,115 @@ mod tests {
 
         assert_eq!(op.get_validity_range(10), 40..=50);
     }
+
+    #[test]
+    #[serial]
+    fn test_multisig_transaction() {
+        use crate::multisig::compute_multisig_wrapped_hash;
+        use crate::{MultisigPolicy, MultisigProof};
+
+        let keypairs: Vec<KeyPair> = (0..3).map(|_| KeyPair::generate()).collect();
+        let policy =
+            MultisigPolicy::new(2, keypairs.iter().map(|k| k.get_public_key()).collect()).unwrap();
+        let content = Operation {
+            fee: Amount::from_str("20").unwrap(),
+            op: OperationType::RollBuy { roll_count: 1 },
+            expire_period: 50,
+        };
+        let mut ser_content = Vec::new();
+        OperationSerializer::new()
+            .serialize(&content, &mut ser_content)
+            .unwrap();
+        let hash = compute_multisig_wrapped_hash(&policy, &ser_content);
+        let mut proof = MultisigProof::new(policy.clone());
+        for keypair in &keypairs[1..] {
+            proof
+                .add_signature(&keypair.get_public_key(), keypair.sign(&hash).unwrap())
+                .unwrap();
+        }
+
+        let op: WrappedOperation =
+            Operation::new_multisig_wrapped(content, ser_content, proof).unwrap();
+        assert_eq!(op.creator_address, Address::from_multisig_policy(&policy));
+        assert_eq!(op.id.hash(), hash);
+        op.verify_integrity().unwrap();
+        assert!(op
+            .get_roll_involved_addresses()
+            .unwrap()
+            .contains(&op.creator_address));
+        // the signatures are valid but multisig addresses cannot stake
+        assert!(op.check_multisig_rules().is_err());
+
+        let mut ser_op = Vec::new();
+        WrappedSerializer::new()
+            .serialize(&op, &mut ser_op)
+            .unwrap();
+        let (rest, res_op): (&[u8], WrappedOperation) =
+            WrappedDeserializer::new(OperationDeserializer::new())
+                .deserialize::<DeserializeError>(&ser_op)
+                .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(op.serialized_size(), ser_op.len());
+        assert_eq!(res_op.id, op.id);
+        assert_eq!(res_op.multisig, op.multisig);
+        res_op.verify_integrity().unwrap();
+
+        // a single signature does not reach the threshold
+        let mut partial = op;
+        partial.multisig.as_mut().unwrap().signatures.pop();
+        assert!(partial.verify_integrity().is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_multisig_rules() {
+        use crate::multisig::compute_multisig_wrapped_hash;
+        use crate::{MultisigPolicy, MultisigProof};
+
+        let keypair = KeyPair::generate();
+        let policy = MultisigPolicy::new(1, vec![keypair.get_public_key()]).unwrap();
+        let sign = |op: OperationType| -> WrappedOperation {
+            let content = Operation {
+                fee: Amount::from_str("20").unwrap(),
+                op,
+                expire_period: 50,
+            };
+            let mut ser_content = Vec::new();
+            OperationSerializer::new()
+                .serialize(&content, &mut ser_content)
+                .unwrap();
+            let hash = compute_multisig_wrapped_hash(&policy, &ser_content);
+            let mut proof = MultisigProof::new(policy.clone());
+            proof
+                .add_signature(&keypair.get_public_key(), keypair.sign(&hash).unwrap())
+                .unwrap();
+            Operation::new_multisig_wrapped(content, ser_content, proof).unwrap()
+        };
+
+        sign(OperationType::RollBuy { roll_count: 1 })
+            .check_multisig_rules()
+            .unwrap_err();
+        sign(OperationType::RollSell { roll_count: 1 })
+            .check_multisig_rules()
+            .unwrap();
+        sign(OperationType::Transaction {
+            recipient_address: Address::from_public_key(&KeyPair::generate().get_public_key()),
+            amount: Amount::from_str("10").unwrap(),
+        })
+        .check_multisig_rules()
+        .unwrap();
+
+        // single key addresses can still buy rolls
+        let op: WrappedOperation = Operation::new_wrapped(
+            Operation {
+                fee: Amount::from_str("20").unwrap(),
+                op: OperationType::RollBuy { roll_count: 1 },
+                expire_period: 50,
+            },
+            OperationSerializer::new(),
+            &keypair,
+        )
+        .unwrap();
+        op.check_multisig_rules().unwrap();
+    }
 }
@@ -21,14 +21,15 @@ use std::collections::{btree_map, BTreeMap};
 #[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct RollCompensation(pub u64);
 
-/// roll sales and purchases
+/// roll sales, purchases and slashes
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RollUpdate {
     /// roll purchases
     pub roll_purchases: u64,
     /// roll sales
     pub roll_sales: u64,
-    // Here is space for registering any denunciations/resets
+    /// rolls removed by a denunciation, never credited back
+    pub roll_slashes: u64,
 }
 
 impl RollUpdate {
@@ -49,6 +50,12 @@ impl RollUpdate {
             .ok_or_else(|| {
                 ModelsError::InvalidRollUpdate("roll_sales overflow in RollUpdate::chain".into())
             })?;
+        self.roll_slashes = self
+            .roll_slashes
+            .checked_add(change.roll_slashes)
+            .ok_or_else(|| {
+                ModelsError::InvalidRollUpdate("roll_slashes overflow in RollUpdate::chain".into())
+            })?;
 
         let compensation_self = self.compensate().0;
 
@@ -61,6 +68,7 @@ impl RollUpdate {
     }
 
     /// compensate a roll update, return compensation count
+    /// slashes are never compensated
     pub fn compensate(&mut self) -> RollCompensation {
         let compensation = std::cmp::min(self.roll_purchases, self.roll_sales);
         self.roll_purchases -= compensation;
@@ -70,7 +78,7 @@ impl RollUpdate {
 
     /// true if the update has no effect
     pub fn is_nil(&self) -> bool {
-        self.roll_purchases == 0 && self.roll_sales == 0
+        self.roll_purchases == 0 && self.roll_sales == 0 && self.roll_slashes == 0
     }
 }
 
@@ -99,6 +107,7 @@ impl Serializer<RollUpdate> for RollUpdateSerializer {
         self.u64_serializer
             .serialize(&value.roll_purchases, buffer)?;
         self.u64_serializer.serialize(&value.roll_sales, buffer)?;
+        self.u64_serializer.serialize(&value.roll_slashes, buffer)?;
         Ok(())
     }
 }
@@ -137,11 +146,15 @@ impl Deserializer<RollUpdate> for RollUpdateDeserializer {
                 context("Failed roll_sales deserialization", |input| {
                     self.u64_deserializer.deserialize(input)
                 }),
+                context("Failed roll_slashes deserialization", |input| {
+                    self.u64_deserializer.deserialize(input)
+                }),
             )),
         )
-        .map(|(roll_purchases, roll_sales)| RollUpdate {
+        .map(|(roll_purchases, roll_sales, roll_slashes)| RollUpdate {
             roll_purchases,
             roll_sales,
+            roll_slashes,
         })
         .parse(buffer)
     }
@@ -255,21 +268,30 @@ impl RollCounts {
                                 )
                             })?;
                     }
+                    *occ.get_mut() =
+                        occ.get().checked_sub(update.roll_slashes).ok_or_else(|| {
+                            ModelsError::InvalidRollUpdate(
+                                "underflow while slashing roll count".into(),
+                            )
+                        })?;
                     if *occ.get() == 0 {
                         // remove if 0
                         occ.remove();
                     }
                 }
                 btree_map::Entry::Vacant(vac) => {
-                    if update.roll_purchases >= update.roll_sales {
-                        if update.roll_purchases > update.roll_sales {
-                            // ignore if 0
-                            vac.insert(update.roll_purchases - update.roll_sales);
-                        }
-                    } else {
-                        return Err(ModelsError::InvalidRollUpdate(
-                            "underflow while decrementing roll count".into(),
-                        ));
+                    let new_val = update
+                        .roll_purchases
+                        .checked_sub(update.roll_sales)
+                        .and_then(|v| v.checked_sub(update.roll_slashes))
+                        .ok_or_else(|| {
+                            ModelsError::InvalidRollUpdate(
+                                "underflow while decrementing roll count".into(),
+                            )
+                        })?;
+                    if new_val > 0 {
+                        // ignore if 0
+                        vac.insert(new_val);
                     }
                 }
             }
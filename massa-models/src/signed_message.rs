@@ -0,0 +1,91 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::{Address, ModelsError};
+use massa_hash::Hash;
+use massa_signature::{PublicKey, Signature};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// Prefix of the hashed data of signed messages, so that the signature of a message
+/// is never valid for an operation or another wrapped structure
+pub const SIGNED_MESSAGE_PREFIX: &[u8] = b"\x19Massa Signed Message:\n";
+
+/// Computes the hash signed for a message: the prefix, the length of the message
+/// in decimal digits, then the message itself
+pub fn compute_signed_message_hash(message: &[u8]) -> Hash {
+    let length = message.len().to_string();
+    let mut hash_data =
+        Vec::with_capacity(SIGNED_MESSAGE_PREFIX.len() + length.len() + message.len());
+    hash_data.extend(SIGNED_MESSAGE_PREFIX);
+    hash_data.extend(length.as_bytes());
+    hash_data.extend(message);
+    Hash::compute_from(&hash_data)
+}
+
+/// Signature of a message by an address, the message being kept apart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedMessage {
+    /// address of the signer
+    pub address: Address,
+    /// public key of the signer
+    pub public_key: PublicKey,
+    /// signature of the hash of the message, see `compute_signed_message_hash`
+    pub signature: Signature,
+}
+
+impl SignedMessage {
+    /// Checks that the public key is the one of the address and that it signed the message
+    pub fn verify(&self, message: &[u8]) -> Result<(), ModelsError> {
+        if Address::from_public_key(&self.public_key) != self.address {
+            return Err(ModelsError::WrongSignerError(self.address));
+        }
+        self.public_key
+            .verify_signature(&compute_signed_message_hash(message), &self.signature)?;
+        Ok(())
+    }
+}
+
+impl Display for SignedMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Address: {}", self.address)?;
+        writeln!(f, "Public key: {}", self.public_key)?;
+        writeln!(f, "Signature: {}", self.signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::KeyPair;
+    use serial_test::serial;
+
+    #[test]
+    #[serial]
+    fn test_signed_message() {
+        let keypair = KeyPair::generate();
+        let message = b"hello massa";
+        let hash = compute_signed_message_hash(message);
+        let signed = SignedMessage {
+            address: Address::from_public_key(&keypair.get_public_key()),
+            public_key: keypair.get_public_key(),
+            signature: keypair.sign(&hash).unwrap(),
+        };
+        signed.verify(message).unwrap();
+        assert!(signed.verify(b"hello massa!").is_err());
+        // the signature of the raw message is not a signature of the enveloped message
+        let raw = SignedMessage {
+            signature: keypair.sign(&Hash::compute_from(message)).unwrap(),
+            ..signed.clone()
+        };
+        assert!(raw.verify(message).is_err());
+        let other = KeyPair::generate();
+        let wrong_address = SignedMessage {
+            address: Address::from_public_key(&other.get_public_key()),
+            ..signed
+        };
+        assert!(matches!(
+            wrong_address.verify(message),
+            Err(ModelsError::WrongSignerError(_))
+        ));
+    }
+}
@@ -1,5 +1,6 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::Amount;
 use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
 use std::fmt::Formatter;
@@ -17,6 +18,16 @@ pub struct NetworkStats {
     pub banned_peer_count: u64,
     /// active node count
     pub active_node_count: u64,
+    /// total bytes sent to peers
+    pub bytes_sent: u64,
+    /// total bytes received from peers
+    pub bytes_received: u64,
+    /// total messages sent to peers
+    pub messages_sent: u64,
+    /// total messages received from peers
+    pub messages_received: u64,
+    /// number of failed handshakes
+    pub handshake_failure_count: u64,
 }
 
 impl std::fmt::Display for NetworkStats {
@@ -27,6 +38,17 @@ impl std::fmt::Display for NetworkStats {
         writeln!(f, "\tKnown peers: {}", self.known_peer_count)?;
         writeln!(f, "\tBanned peers: {}", self.banned_peer_count)?;
         writeln!(f, "\tActive nodes: {}", self.active_node_count)?;
+        writeln!(
+            f,
+            "\tSent: {} bytes in {} messages",
+            self.bytes_sent, self.messages_sent
+        )?;
+        writeln!(
+            f,
+            "\tReceived: {} bytes in {} messages",
+            self.bytes_received, self.messages_received
+        )?;
+        writeln!(f, "\tFailed handshakes: {}", self.handshake_failure_count)?;
         Ok(())
     }
 }
@@ -89,3 +111,60 @@ impl std::fmt::Display for PoolStats {
         Ok(())
     }
 }
+
+/// Fee density percentiles of a set of operations, in coins per byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeDensityPercentiles {
+    /// number of operations
+    pub operation_count: u64,
+    /// 10th percentile
+    pub p10: Amount,
+    /// 25th percentile
+    pub p25: Amount,
+    /// median
+    pub p50: Amount,
+    /// 75th percentile
+    pub p75: Amount,
+    /// 90th percentile
+    pub p90: Amount,
+}
+
+impl FeeDensityPercentiles {
+    /// Compute the percentiles of `densities` with the nearest-rank method.
+    /// Returns `None` if `densities` is empty.
+    pub fn from_densities(mut densities: Vec<Amount>) -> Option<Self> {
+        if densities.is_empty() {
+            return None;
+        }
+        densities.sort_unstable();
+        let percentile = |p: usize| densities[(p * densities.len() + 99) / 100 - 1];
+        Some(FeeDensityPercentiles {
+            operation_count: densities.len() as u64,
+            p10: percentile(10),
+            p25: percentile(25),
+            p50: percentile(50),
+            p75: percentile(75),
+            p90: percentile(90),
+        })
+    }
+}
+
+impl std::fmt::Display for FeeDensityPercentiles {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} operations, fee per byte: p10 {}, p25 {}, median {}, p75 {}, p90 {}",
+            self.operation_count, self.p10, self.p25, self.p50, self.p75, self.p90
+        )
+    }
+}
+
+/// Fee densities of the operations pending in the pool for a given thread
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PoolFeeDensities {
+    /// percentiles of the pending operations, `None` if there are none
+    pub percentiles: Option<FeeDensityPercentiles>,
+    /// fee density to beat to be among the operations that fit in the requested number of blocks,
+    /// `None` if all the pending operations fit
+    pub inclusion_threshold: Option<Amount>,
+}
@@ -1,15 +1,22 @@
 use std::fmt::Display;
 
+use crate::multisig::{
+    compute_multisig_wrapped_hash, MultisigProof, MultisigProofDeserializer,
+    MultisigProofSerializer,
+};
 use crate::{node_configuration::THREAD_COUNT, Address, ModelsError};
 use massa_hash::Hash;
 use massa_serialization::{Deserializer, SerializeError, Serializer};
 use massa_signature::{
     KeyPair, PublicKey, PublicKeyDeserializer, Signature, SignatureDeserializer,
+    PUBLIC_KEY_SIZE_BYTES, SIGNATURE_SIZE_BYTES,
 };
 use nom::{
+    branch::alt,
+    bytes::complete::tag,
     error::{context, ContextError, ParseError},
     sequence::tuple,
-    IResult,
+    IResult, Parser,
 };
 use serde::{Deserialize, Serialize};
 
@@ -32,11 +39,32 @@ where
     pub thread: u8,
     /// Id
     pub id: U,
+    /// signatures of a multisig creator address, in which case `signature` and
+    /// `creator_public_key` are the first signature of the proof and its key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multisig: Option<MultisigProof>,
     #[serde(skip)]
     /// Content serialized
     pub serialized_data: Vec<u8>,
 }
 
+/// Hash signed by the creator of a wrapped structure, which is also its id:
+/// the hash of the creator public key followed by the serialized content.
+pub fn compute_wrapped_hash(public_key: &PublicKey, content_serialized: &[u8]) -> Hash {
+    let mut hash_data = public_key.to_bytes().to_vec();
+    hash_data.extend(content_serialized);
+    Hash::compute_from(&hash_data)
+}
+
+// The envelope byte is part of the serialization of operations since the major version 13 of the
+// node: peers and bootstrap servers of older versions are refused by the version check, and the
+// pool journal carries its own format version.
+
+/// Envelope of a wrapped structure signed by a single key
+const SINGLE_SIGNATURE_ENVELOPE: u8 = 0;
+/// Envelope of a wrapped structure signed by the keys of a multisig policy
+const MULTISIG_ENVELOPE: u8 = 1;
+
 /// Used by signed structure
 pub trait Id {
     /// new id from hash
@@ -50,6 +78,10 @@ pub trait WrappedContent
 where
     Self: Sized + Display,
 {
+    /// If true, the serialized wrapped structure starts with an envelope byte telling
+    /// whether it is signed by a single key or by a multisig policy
+    const SUPPORTS_MULTISIG: bool = false;
+
     /// Creates a wrapped version of the object
     fn new_wrapped<SC: Serializer<Self>, U: Id>(
         content: Self,
@@ -58,20 +90,84 @@ where
     ) -> Result<Wrapped<Self, U>, ModelsError> {
         let mut content_serialized = Vec::new();
         content_serializer.serialize(&content, &mut content_serialized)?;
-        let mut hash_data = Vec::new();
         let public_key = keypair.get_public_key();
-        hash_data.extend(public_key.to_bytes());
-        hash_data.extend(content_serialized.clone());
-        let hash = Hash::compute_from(&hash_data);
+        let hash = compute_wrapped_hash(&public_key, &content_serialized);
+        let signature = keypair.sign(&hash)?;
+        Ok(Self::new_wrapped_with_signature(
+            content,
+            content_serialized,
+            public_key,
+            signature,
+        ))
+    }
+
+    /// Creates a wrapped version of the object from a signature computed elsewhere,
+    /// for example by a remote signer. The signature is not checked.
+    ///
+    /// # Arguments
+    /// * `content`: the object
+    /// * `content_serialized`: the object serialized with its serializer
+    /// * `public_key`: public key of the content creator
+    /// * `signature`: signature of the hash returned by `compute_wrapped_hash`
+    fn new_wrapped_with_signature<U: Id>(
+        content: Self,
+        content_serialized: Vec<u8>,
+        public_key: PublicKey,
+        signature: Signature,
+    ) -> Wrapped<Self, U> {
+        let hash = compute_wrapped_hash(&public_key, &content_serialized);
         let creator_address = Address::from_public_key(&public_key);
+        Wrapped {
+            signature,
+            creator_public_key: public_key,
+            creator_address,
+            thread: creator_address.get_thread(THREAD_COUNT),
+            content,
+            serialized_data: content_serialized,
+            id: U::new(hash),
+            multisig: None,
+        }
+    }
+
+    /// Creates a wrapped version of the object signed by the keys of a multisig policy.
+    /// The signatures are not checked.
+    ///
+    /// # Arguments
+    /// * `content`: the object
+    /// * `content_serialized`: the object serialized with its serializer
+    /// * `proof`: signatures of the hash returned by `compute_multisig_wrapped_hash`
+    fn new_multisig_wrapped<U: Id>(
+        content: Self,
+        content_serialized: Vec<u8>,
+        proof: MultisigProof,
+    ) -> Result<Wrapped<Self, U>, ModelsError> {
+        if !Self::SUPPORTS_MULTISIG {
+            return Err(ModelsError::InvalidMultisig(
+                "this content cannot be signed by a multisig address".into(),
+            ));
+        }
+        let (index, signature) = *proof
+            .signatures
+            .first()
+            .ok_or_else(|| ModelsError::InvalidMultisig("the proof has no signature".into()))?;
+        let public_key = *proof
+            .policy
+            .public_keys
+            .get(index as usize)
+            .ok_or_else(|| {
+                ModelsError::InvalidMultisig(format!("no public key at index {}", index))
+            })?;
+        let hash = compute_multisig_wrapped_hash(&proof.policy, &content_serialized);
+        let creator_address = Address::from_multisig_policy(&proof.policy);
         Ok(Wrapped {
-            signature: keypair.sign(&hash)?,
+            signature,
             creator_public_key: public_key,
             creator_address,
             thread: creator_address.get_thread(THREAD_COUNT),
             content,
             serialized_data: content_serialized,
             id: U::new(hash),
+            multisig: Some(proof),
         })
     }
 
@@ -79,9 +175,25 @@ where
     fn serialize(
         signature: &Signature,
         creator_public_key: &PublicKey,
+        multisig: Option<&MultisigProof>,
         serialized_content: &[u8],
         buffer: &mut Vec<u8>,
     ) -> Result<(), SerializeError> {
+        match (Self::SUPPORTS_MULTISIG, multisig) {
+            (true, Some(proof)) => {
+                buffer.push(MULTISIG_ENVELOPE);
+                MultisigProofSerializer::new().serialize(proof, buffer)?;
+                buffer.extend(serialized_content);
+                return Ok(());
+            }
+            (true, None) => buffer.push(SINGLE_SIGNATURE_ENVELOPE),
+            (false, Some(_)) => {
+                return Err(SerializeError::GeneralError(
+                    "this content cannot be signed by a multisig address".into(),
+                ))
+            }
+            (false, None) => {}
+        }
         buffer.extend(signature.into_bytes());
         buffer.extend(creator_public_key.into_bytes());
         buffer.extend(serialized_content);
@@ -100,6 +212,38 @@ where
         content_deserializer: &DC,
         buffer: &'a [u8],
     ) -> IResult<&'a [u8], Wrapped<Self, U>, E> {
+        let buffer = if Self::SUPPORTS_MULTISIG {
+            let (rest, is_multisig) = context(
+                "Failed envelope deserialization",
+                alt((
+                    tag(&[SINGLE_SIGNATURE_ENVELOPE]).map(|_| false),
+                    tag(&[MULTISIG_ENVELOPE]).map(|_| true),
+                )),
+            )
+            .parse(buffer)?;
+            if is_multisig {
+                let (serialized_data, proof) =
+                    context("Failed multisig proof deserialization", |input| {
+                        MultisigProofDeserializer::new().deserialize(input)
+                    })
+                    .parse(rest)?;
+                let (rest, content) = content_deserializer.deserialize(serialized_data)?;
+                let content_serialized = &serialized_data[..serialized_data.len() - rest.len()];
+                // the proof deserializer ensures that there is a first signature
+                let wrapped =
+                    Self::new_multisig_wrapped(content, content_serialized.to_vec(), proof)
+                        .map_err(|_| {
+                            nom::Err::Error(ParseError::from_error_kind(
+                                buffer,
+                                nom::error::ErrorKind::Verify,
+                            ))
+                        })?;
+                return Ok((rest, wrapped));
+            }
+            rest
+        } else {
+            buffer
+        };
         let (serialized_data, (signature, creator_public_key)) = context(
             "Failed wrapped deserialization",
             tuple((
@@ -127,6 +271,7 @@ where
                 thread: creator_address.get_thread(THREAD_COUNT),
                 serialized_data: content_serialized.to_vec(),
                 id: U::new(Hash::compute_from(&serialized_full_data)),
+                multisig: None,
             },
         ))
     }
@@ -141,6 +286,14 @@ where
         writeln!(f, "Signature: {}", self.signature)?;
         writeln!(f, "Creator pubkey: {}", self.creator_public_key)?;
         writeln!(f, "Creator address: {}", self.creator_address)?;
+        if let Some(proof) = &self.multisig {
+            writeln!(
+                f,
+                "Multisig: {} of {} keys signed",
+                proof.signatures.len(),
+                proof.policy.public_keys.len()
+            )?;
+        }
         writeln!(f, "Id: {}", self.id.hash())?;
         writeln!(f, "{}", self.content)?;
         Ok(())
@@ -152,7 +305,19 @@ where
     T: Display + WrappedContent,
     U: Id,
 {
-    /// check if self has been signed by public key
+    /// Size of the wrapped structure once serialized by `WrappedSerializer`,
+    /// including its envelope and its multisig proof if any
+    pub fn serialized_size(&self) -> usize {
+        let envelope_size = if T::SUPPORTS_MULTISIG { 1 } else { 0 };
+        let signature_size = match &self.multisig {
+            Some(proof) => proof.serialized_size(),
+            None => SIGNATURE_SIZE_BYTES + PUBLIC_KEY_SIZE_BYTES,
+        };
+        envelope_size + signature_size + self.serialized_data.len()
+    }
+
+    /// check if self has been signed by public key,
+    /// or by enough keys of its policy if its creator address is a multisig address
     pub fn verify_signature<SC: Serializer<T>>(
         &self,
         content_serializer: SC,
@@ -160,6 +325,15 @@ where
     ) -> Result<(), ModelsError> {
         let mut content_serialized = Vec::new();
         content_serializer.serialize(&self.content, &mut content_serialized)?;
+        if let Some(proof) = &self.multisig {
+            if self.creator_address != Address::from_multisig_policy(&proof.policy) {
+                return Err(ModelsError::InvalidMultisig(
+                    "the creator address does not match the policy".into(),
+                ));
+            }
+            let hash = compute_multisig_wrapped_hash(&proof.policy, &content_serialized);
+            return proof.verify(&hash);
+        }
         let mut hash_data = Vec::new();
         hash_data.extend(self.creator_public_key.to_bytes());
         hash_data.extend(content_serialized.clone());
@@ -189,6 +363,7 @@ where
         T::serialize(
             &value.signature,
             &value.creator_public_key,
+            value.multisig.as_ref(),
             &value.serialized_data,
             buffer,
         )
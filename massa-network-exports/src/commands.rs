@@ -69,13 +69,13 @@
 //! Look at `massa-protocol-worker/src/node-info.rs` to look further how we
 //! remember which node know what.
 
-use crate::{BootstrapPeers, ConnectionClosureReason, Peers};
+use crate::{metrics::NetworkMetrics, BootstrapPeers, ConnectionClosureReason, Peers};
 use massa_models::{
     composite::PubkeySig,
     node::NodeId,
     operation::{OperationIds, OperationPrefixIds, Operations},
     stats::NetworkStats,
-    BlockId, WrappedBlock, WrappedEndorsement, WrappedHeader,
+    BlockId, Denunciation, WrappedBlock, WrappedEndorsement, WrappedHeader,
 };
 use std::{collections::HashMap, net::IpAddr};
 use tokio::sync::oneshot;
@@ -103,6 +103,8 @@ pub enum NodeCommand {
     AskForOperations(OperationPrefixIds),
     /// Endorsements
     SendEndorsements(Vec<WrappedEndorsement>),
+    /// Denunciations
+    SendDenunciations(Vec<Denunciation>),
 }
 
 /// Event types that node worker can emit
@@ -131,6 +133,8 @@ pub enum NodeEventType {
     ReceivedAskForOperations(OperationPrefixIds),
     /// Receive a set of endorsement
     ReceivedEndorsements(Vec<WrappedEndorsement>),
+    /// Receive a set of denunciations
+    ReceivedDenunciations(Vec<Denunciation>),
 }
 
 /// Events node worker can emit.
@@ -186,6 +190,13 @@ pub enum NetworkCommand {
         /// endorsements
         endorsements: Vec<WrappedEndorsement>,
     },
+    /// Send denunciations to a node
+    SendDenunciations {
+        /// to node id
+        node: NodeId,
+        /// denunciations
+        denunciations: Vec<Denunciation>,
+    },
     /// sign message with our node keypair (associated to node id)
     /// != staking key
     NodeSignMessage {
@@ -199,6 +210,11 @@ pub enum NetworkCommand {
         /// response channels
         response_tx: oneshot::Sender<NetworkStats>,
     },
+    /// gets detailed network traffic metrics
+    GetMetrics {
+        /// response channels
+        response_tx: oneshot::Sender<NetworkMetrics>,
+    },
     /// Send a batch of full operations
     SendOperations {
         /// to node id
@@ -290,6 +306,13 @@ pub enum NetworkEvent {
         /// Endorsements
         endorsements: Vec<WrappedEndorsement>,
     },
+    /// received denunciations from node
+    ReceivedDenunciations {
+        /// node id
+        node: NodeId,
+        /// Denunciations
+        denunciations: Vec<Denunciation>,
+    },
 }
 
 /// Network management command
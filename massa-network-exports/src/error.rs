@@ -78,6 +78,22 @@ pub enum HandshakeErrorType {
     PeerListReceived(Vec<IpAddr>),
 }
 
+impl HandshakeErrorType {
+    /// Name of the error variant, without its content
+    pub fn name(&self) -> &'static str {
+        match self {
+            HandshakeErrorType::HandshakeIdAlreadyExist(_) => "HandshakeIdAlreadyExist",
+            HandshakeErrorType::HandshakeTimeout => "HandshakeTimeout",
+            HandshakeErrorType::HandshakeInterruption(_) => "HandshakeInterruption",
+            HandshakeErrorType::HandshakeWrongMessage => "HandshakeWrongMessage",
+            HandshakeErrorType::HandshakeKey => "HandshakeKey",
+            HandshakeErrorType::HandshakeInvalidSignature => "HandshakeInvalidSignature",
+            HandshakeErrorType::IncompatibleVersion => "IncompatibleVersion",
+            HandshakeErrorType::PeerListReceived(_) => "PeerListReceived",
+        }
+    }
+}
+
 /// return handshake error
 #[macro_export]
 macro_rules! throw_handshake_error {
@@ -11,6 +11,7 @@ pub use commands::{
 pub use common::{ConnectionClosureReason, ConnectionId};
 pub use error::{HandshakeErrorType, NetworkConnectionErrorType, NetworkError};
 pub use establisher::{Establisher, Listener, ReadHalf, WriteHalf};
+pub use metrics::{NetworkMetrics, TrafficCounters, TrafficStats};
 pub use network_controller::{NetworkCommandSender, NetworkEventReceiver, NetworkManager};
 pub use peers::{
     BootstrapPeers, BootstrapPeersDeserializer, BootstrapPeersSerializer, ConnectionCount, Peer,
@@ -22,6 +23,7 @@ mod commands;
 mod common;
 mod error;
 mod establisher;
+mod metrics;
 mod network_controller;
 mod peers;
 
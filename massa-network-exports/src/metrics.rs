@@ -0,0 +1,72 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Traffic metrics gathered by the network worker.
+//!
+//! Counters are cumulative since the start of the network worker, except the
+//! per-node ones that are only kept while the node is connected.
+
+use massa_models::node::NodeId;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Bytes and message counters for one direction of the traffic
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct TrafficCounters {
+    /// number of bytes, including the message size prefix
+    pub bytes: u64,
+    /// number of messages
+    pub messages: u64,
+}
+
+impl TrafficCounters {
+    /// Account for one more message of `bytes` bytes
+    pub fn add_message(&mut self, bytes: u64) {
+        self.bytes = self.bytes.saturating_add(bytes);
+        self.messages = self.messages.saturating_add(1);
+    }
+}
+
+/// Sent and received traffic counters
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct TrafficStats {
+    /// outgoing traffic
+    pub sent: TrafficCounters,
+    /// incoming traffic
+    pub received: TrafficCounters,
+}
+
+/// Detailed network traffic metrics
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct NetworkMetrics {
+    /// traffic per message type name (as in `MessageTypeId`)
+    pub by_message_type: BTreeMap<String, TrafficStats>,
+    /// traffic per currently connected node
+    pub by_node: HashMap<NodeId, TrafficStats>,
+    /// total time spent waiting on the read bandwidth limiter, in milliseconds
+    pub read_limiter_wait_millis: u64,
+    /// total time spent waiting on the write bandwidth limiter, in milliseconds
+    pub write_limiter_wait_millis: u64,
+    /// number of failed handshakes per `HandshakeErrorType` name
+    pub handshake_failures: BTreeMap<String, u64>,
+}
+
+impl NetworkMetrics {
+    /// Sum of the traffic over all message types
+    pub fn total(&self) -> TrafficStats {
+        self.by_message_type
+            .values()
+            .fold(TrafficStats::default(), |mut acc, stats| {
+                acc.sent.bytes = acc.sent.bytes.saturating_add(stats.sent.bytes);
+                acc.sent.messages = acc.sent.messages.saturating_add(stats.sent.messages);
+                acc.received.bytes = acc.received.bytes.saturating_add(stats.received.bytes);
+                acc.received.messages =
+                    acc.received.messages.saturating_add(stats.received.messages);
+                acc
+            })
+    }
+
+    /// Total number of failed handshakes
+    pub fn handshake_failure_count(&self) -> u64 {
+        self.handshake_failures.values().sum()
+    }
+}
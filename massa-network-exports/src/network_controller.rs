@@ -2,14 +2,14 @@
 
 use crate::{
     commands::NetworkManagementCommand, error::NetworkError, BootstrapPeers, NetworkCommand,
-    NetworkEvent, Peers,
+    NetworkEvent, NetworkMetrics, Peers,
 };
 use massa_models::{
     composite::PubkeySig,
     node::NodeId,
     operation::{OperationIds, OperationPrefixIds},
     stats::NetworkStats,
-    BlockId, WrappedEndorsement,
+    BlockId, Denunciation, WrappedEndorsement,
 };
 use std::{
     collections::{HashMap, VecDeque},
@@ -150,6 +150,18 @@ impl NetworkCommandSender {
         })
     }
 
+    /// get detailed network traffic metrics
+    pub async fn get_network_metrics(&self) -> Result<NetworkMetrics, NetworkError> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.0
+            .send(NetworkCommand::GetMetrics { response_tx })
+            .await
+            .map_err(|_| NetworkError::ChannelError("could not send GetMetrics command".into()))?;
+        response_rx.await.map_err(|_| {
+            NetworkError::ChannelError("could not send GetMetrics response upstream".into())
+        })
+    }
+
     /// Send the order to get bootstrap peers.
     pub async fn get_bootstrap_peers(&self) -> Result<BootstrapPeers, NetworkError> {
         let (response_tx, response_rx) = oneshot::channel::<BootstrapPeers>();
@@ -251,6 +263,24 @@ impl NetworkCommandSender {
         Ok(())
     }
 
+    /// send denunciations to node id
+    pub async fn send_denunciations(
+        &self,
+        node: NodeId,
+        denunciations: Vec<Denunciation>,
+    ) -> Result<(), NetworkError> {
+        self.0
+            .send(NetworkCommand::SendDenunciations {
+                node,
+                denunciations,
+            })
+            .await
+            .map_err(|_| {
+                NetworkError::ChannelError("could not send send_denunciations command".into())
+            })?;
+        Ok(())
+    }
+
     /// Sign a message using the node's keypair
     pub async fn node_sign_message(&self, msg: Vec<u8>) -> Result<PubkeySig, NetworkError> {
         let (response_tx, response_rx) = oneshot::channel();
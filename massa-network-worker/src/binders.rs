@@ -2,18 +2,24 @@
 
 //! `Flexbuffer` layer between raw data and our objects.
 use super::messages::Message;
-use async_speed_limit::{clock::StandardClock, Limiter, Resource};
+use crate::metrics::{Direction, NetworkMetricsCollector};
+use async_speed_limit::Limiter;
+use futures::future::BoxFuture;
 use massa_models::{
-    with_serialization_context, DeserializeCompact, DeserializeMinBEInt, SerializeMinBEInt,
+    node::NodeId, with_serialization_context, DeserializeCompact, DeserializeMinBEInt,
+    SerializeMinBEInt,
 };
 use massa_network_exports::{NetworkError, ReadHalf, WriteHalf};
-use std::convert::TryInto;
+use std::{convert::TryInto, time::Instant};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 /// Used to serialize and send data.
 pub struct WriteBinder {
-    write_half: Resource<WriteHalf, StandardClock>,
+    write_half: WriteHalf,
+    limiter: Limiter,
     message_index: u64,
+    metrics: NetworkMetricsCollector,
+    node_id: Option<NodeId>,
 }
 
 impl WriteBinder {
@@ -22,13 +28,23 @@ impl WriteBinder {
     /// # Argument
     /// * `write_half`: writer half.
     /// * `limit`: limit max bytes per second write
-    pub fn new(write_half: WriteHalf, limit: f64) -> Self {
+    /// * `metrics`: collector of the traffic metrics
+    pub fn new(write_half: WriteHalf, limit: f64, metrics: NetworkMetricsCollector) -> Self {
         WriteBinder {
-            write_half: <Limiter>::new(limit).limit(write_half),
+            write_half,
+            limiter: <Limiter>::new(limit),
             message_index: 0,
+            metrics,
+            node_id: None,
         }
     }
 
+    /// Set the id of the remote node once the handshake succeeded,
+    /// so that the traffic is also accounted per node.
+    pub fn set_node_id(&mut self, node_id: NodeId) {
+        self.node_id = Some(node_id);
+    }
+
     /// Sends a serialized message.
     ///
     /// # Argument
@@ -42,14 +58,22 @@ impl WriteBinder {
 
         // send length
         let max_message_size = with_serialization_context(|context| context.max_message_size);
+        let size_field = msg_size.to_be_bytes_min(max_message_size)?;
+        let wire_size = size_field.len() + buf.len();
 
-        self.write_half
-            .write_all(&msg_size.to_be_bytes_min(max_message_size)?[..])
-            .await?;
+        // wait for the bandwidth limiter before writing
+        let wait_start = Instant::now();
+        self.limiter.consume(wire_size).await;
+        self.metrics
+            .record_limiter_wait(Direction::Sent, wait_start.elapsed());
+
+        self.write_half.write_all(&size_field[..]).await?;
 
         // send message
         self.write_half.write_all(buf).await?;
 
+        self.metrics
+            .record_message(self.node_id, Direction::Sent, buf, wire_size as u64);
         let res_index = self.message_index;
         self.message_index += 1;
         //        massa_trace!("binder.send end", { "index": res_index });
@@ -59,11 +83,16 @@ impl WriteBinder {
 
 /// Used to receive and deserialize data.
 pub struct ReadBinder {
-    read_half: Resource<ReadHalf, StandardClock>,
+    read_half: ReadHalf,
+    limiter: Limiter,
+    /// Bandwidth limiter wait that was interrupted by a cancellation, with its start time
+    pending_throttle: Option<(Instant, BoxFuture<'static, ()>)>,
     message_index: u64,
     buf: Vec<u8>,
     cursor: usize,
     msg_size: Option<u32>,
+    metrics: NetworkMetricsCollector,
+    node_id: Option<NodeId>,
 }
 
 impl ReadBinder {
@@ -72,13 +101,45 @@ impl ReadBinder {
     /// # Argument
     /// * `read_half`: reader half.
     /// * `limit`: limit max bytes per second read.
-    pub fn new(read_half: ReadHalf, limit: f64) -> Self {
+    /// * `metrics`: collector of the traffic metrics
+    pub fn new(read_half: ReadHalf, limit: f64, metrics: NetworkMetricsCollector) -> Self {
         ReadBinder {
-            read_half: <Limiter>::new(limit).limit(read_half),
+            read_half,
+            limiter: <Limiter>::new(limit),
+            pending_throttle: None,
             message_index: 0,
             buf: Vec::new(),
             cursor: 0,
             msg_size: None,
+            metrics,
+            node_id: None,
+        }
+    }
+
+    /// Set the id of the remote node once the handshake succeeded,
+    /// so that the traffic is also accounted per node.
+    pub fn set_node_id(&mut self, node_id: NodeId) {
+        self.node_id = Some(node_id);
+    }
+
+    /// Registers `nr` freshly read bytes in the bandwidth limiter and waits for it.
+    ///
+    /// The wait is stored in `self.pending_throttle` before being awaited,
+    /// so that it is resumed instead of skipped if the read gets cancelled.
+    async fn throttle(&mut self, nr: usize) {
+        if self.pending_throttle.is_none() {
+            self.pending_throttle = Some((Instant::now(), Box::pin(self.limiter.consume(nr))));
+        }
+        self.wait_pending_throttle().await;
+    }
+
+    /// Finishes waiting for a bandwidth limiter wait interrupted by a cancellation, if any.
+    async fn wait_pending_throttle(&mut self) {
+        if let Some((start, fut)) = self.pending_throttle.as_mut() {
+            fut.await;
+            self.metrics
+                .record_limiter_wait(Direction::Received, start.elapsed());
+            self.pending_throttle = None;
         }
     }
 
@@ -96,6 +157,9 @@ impl ReadBinder {
     pub async fn next(&mut self) -> Result<Option<(u64, Message)>, NetworkError> {
         let max_message_size = with_serialization_context(|context| context.max_message_size);
 
+        // resume a bandwidth limiter wait that was cancelled
+        self.wait_pending_throttle().await;
+
         // check if we are in the process of reading the message length
         if self.msg_size.is_none() {
             // pre-allocate the buffer to fit the encoded message size if the buffer is not already allocated
@@ -115,6 +179,7 @@ impl ReadBinder {
                             return Ok(None);
                         }
                         self.cursor += nr;
+                        self.throttle(nr).await;
                     }
                     Err(err) => {
                         if err.kind() == std::io::ErrorKind::UnexpectedEof {
@@ -147,6 +212,7 @@ impl ReadBinder {
                         return Ok(None);
                     }
                     self.cursor += nr;
+                    self.throttle(nr).await;
                 }
                 Err(err) => {
                     if err.kind() == std::io::ErrorKind::UnexpectedEof {
@@ -158,6 +224,13 @@ impl ReadBinder {
             }
         }
         let (res_msg, _) = Message::from_bytes_compact(&self.buf)?;
+        let size_field_len = u32::be_bytes_min_length(max_message_size);
+        self.metrics.record_message(
+            self.node_id,
+            Direction::Received,
+            &self.buf,
+            (size_field_len + self.buf.len()) as u64,
+        );
 
         // now the message readout is over, we reset the state to start reading the next message's size field again at the next run
         self.cursor = 0;
@@ -5,6 +5,7 @@
 use super::{
     binders::{ReadBinder, WriteBinder},
     messages::Message,
+    metrics::NetworkMetricsCollector,
 };
 use futures::future::try_join;
 use massa_hash::Hash;
@@ -57,6 +58,9 @@ impl HandshakeWorker {
     /// * `timeout_duration`: after `timeout_duration` milliseconds, the handshake attempt is dropped.
     /// * `connection_id`: Node we are trying to connect for debugging
     /// * `version`: Node version used in handshake initialization (check peers compatibility)
+    /// * `max_bytes_read`: read bandwidth limit in bytes per second
+    /// * `max_bytes_write`: write bandwidth limit in bytes per second
+    /// * `metrics`: collector of the traffic metrics, shared with the binders
     #[allow(clippy::too_many_arguments)]
     pub fn spawn(
         socket_reader: ReadHalf,
@@ -68,6 +72,7 @@ impl HandshakeWorker {
         connection_id: ConnectionId,
         max_bytes_read: f64,
         max_bytes_write: f64,
+        metrics: NetworkMetricsCollector,
     ) -> JoinHandle<(ConnectionId, HandshakeReturnType)> {
         debug!("starting handshake with connection_id={}", connection_id);
         massa_trace!("network_worker.new_connection", {
@@ -79,8 +84,8 @@ impl HandshakeWorker {
             (
                 connection_id_copy,
                 HandshakeWorker {
-                    reader: ReadBinder::new(socket_reader, max_bytes_read),
-                    writer: WriteBinder::new(socket_writer, max_bytes_write),
+                    reader: ReadBinder::new(socket_reader, max_bytes_read, metrics.clone()),
+                    writer: WriteBinder::new(socket_writer, max_bytes_write, metrics),
                     self_node_id,
                     keypair,
                     timeout_duration,
@@ -26,6 +26,7 @@ use tracing::{debug, error, info, warn};
 mod binders;
 mod handshake_worker;
 mod messages;
+mod metrics;
 mod network_cmd_impl;
 mod network_event;
 mod network_worker;
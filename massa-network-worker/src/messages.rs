@@ -2,7 +2,9 @@
 
 use massa_models::{
     array_from_slice,
-    constants::{BLOCK_ID_SIZE_BYTES, HANDSHAKE_RANDOMNESS_SIZE_BYTES},
+    constants::{
+        BLOCK_ID_SIZE_BYTES, HANDSHAKE_RANDOMNESS_SIZE_BYTES, MAX_DENUNCIATIONS_PER_MESSAGE,
+    },
     error::ModelsResult,
     operation::OperationPrefixIds,
     operation::{
@@ -11,10 +13,11 @@ use massa_models::{
     },
     with_serialization_context,
     wrapped::{WrappedDeserializer, WrappedSerializer},
-    Block, BlockDeserializer, BlockHeader, BlockHeaderDeserializer, BlockId, DeserializeCompact,
-    DeserializeVarInt, Endorsement, EndorsementDeserializer, IpAddrDeserializer, IpAddrSerializer,
-    ModelsError, SerializeCompact, SerializeVarInt, Version, VersionDeserializer,
-    VersionSerializer, WrappedBlock, WrappedEndorsement, WrappedHeader,
+    Block, BlockDeserializer, BlockHeader, BlockHeaderDeserializer, BlockId, Denunciation,
+    DenunciationDeserializer, DenunciationSerializer, DeserializeCompact, DeserializeVarInt,
+    Endorsement, EndorsementDeserializer, IpAddrDeserializer, IpAddrSerializer, ModelsError,
+    SerializeCompact, SerializeVarInt, Version, VersionDeserializer, VersionSerializer,
+    WrappedBlock, WrappedEndorsement, WrappedHeader,
 };
 use massa_serialization::{DeserializeError, Deserializer, Serializer};
 use massa_signature::{PublicKey, Signature, PUBLIC_KEY_SIZE_BYTES, SIGNATURE_SIZE_BYTES};
@@ -50,6 +53,10 @@ static WRAPPED_BLOCK_HEADER_DESERIALIZER: WrappedDeserializer<
 
 static WRAPPED_SERIALIZER: WrappedSerializer = WrappedSerializer::new();
 
+static DENUNCIATION_DESERIALIZER: DenunciationDeserializer = DenunciationDeserializer::new();
+
+static DENUNCIATION_SERIALIZER: DenunciationSerializer = DenunciationSerializer::new();
+
 /// All messages that can be sent or received.
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, Serialize, Deserialize)]
@@ -92,6 +99,8 @@ pub enum Message {
     Operations(Operations),
     /// Endorsements
     Endorsements(Vec<WrappedEndorsement>),
+    /// Denunciations of stakers who signed twice for the same slot
+    Denunciations(Vec<Denunciation>),
 }
 
 #[derive(IntoPrimitive, Debug, Eq, PartialEq, TryFromPrimitive)]
@@ -109,6 +118,7 @@ pub(crate) enum MessageTypeId {
     Endorsements = 9,
     AskForOperations = 10,
     OperationsAnnouncement = 11,
+    Denunciations = 12,
 }
 
 /// For more details on how incoming objects are checked for validity at this stage,
@@ -184,6 +194,13 @@ impl SerializeCompact for Message {
                     WRAPPED_SERIALIZER.serialize(endorsement, &mut res)?;
                 }
             }
+            Message::Denunciations(denunciations) => {
+                res.extend(u32::from(MessageTypeId::Denunciations).to_varint_bytes());
+                res.extend((denunciations.len() as u32).to_varint_bytes());
+                for denunciation in denunciations.iter() {
+                    DENUNCIATION_SERIALIZER.serialize(denunciation, &mut res)?;
+                }
+            }
         }
         Ok(res)
     }
@@ -327,6 +344,23 @@ impl DeserializeCompact for Message {
                 }
                 Message::Endorsements(endorsements)
             }
+            MessageTypeId::Denunciations => {
+                // length
+                let (length, delta) = u32::from_varint_bytes_bounded(
+                    &buffer[cursor..],
+                    MAX_DENUNCIATIONS_PER_MESSAGE,
+                )?;
+                cursor += delta;
+                // denunciations
+                let mut denunciations = Vec::with_capacity(length as usize);
+                for _ in 0..length {
+                    let (rest, denunciation) =
+                        DENUNCIATION_DESERIALIZER.deserialize(&buffer[cursor..])?;
+                    cursor += buffer[cursor..].len() - rest.len();
+                    denunciations.push(denunciation);
+                }
+                Message::Denunciations(denunciations)
+            }
         };
         Ok((res, cursor))
     }
@@ -0,0 +1,122 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Collection of the network traffic metrics.
+//!
+//! A `NetworkMetricsCollector` is shared between the network worker, the
+//! handshake workers and the node workers. Binders report every message they
+//! send or receive, and the time they spent waiting on the bandwidth limiter.
+use crate::messages::MessageTypeId;
+use massa_models::{node::NodeId, DeserializeVarInt};
+use massa_network_exports::{HandshakeErrorType, NetworkMetrics, TrafficStats};
+use parking_lot::Mutex;
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::TryFrom,
+    sync::Arc,
+    time::Duration,
+};
+
+/// Label used for messages whose type id could not be read
+const UNKNOWN_MESSAGE_TYPE: &str = "Unknown";
+
+#[derive(Default)]
+struct MetricsState {
+    by_message_type: HashMap<u32, TrafficStats>,
+    by_node: HashMap<NodeId, TrafficStats>,
+    read_limiter_wait: Duration,
+    write_limiter_wait: Duration,
+    handshake_failures: BTreeMap<&'static str, u64>,
+}
+
+/// Shared and cheaply clonable handle on the network traffic counters
+#[derive(Clone, Default)]
+pub struct NetworkMetricsCollector(Arc<Mutex<MetricsState>>);
+
+/// Direction of a message relatively to our node
+#[derive(Clone, Copy)]
+pub(crate) enum Direction {
+    Sent,
+    Received,
+}
+
+impl NetworkMetricsCollector {
+    /// Account for a message.
+    ///
+    /// # Arguments
+    /// * `node_id`: remote node, if it is already known (not during handshakes)
+    /// * `direction`: whether the message was sent or received
+    /// * `message`: serialized message, starting with its type id
+    /// * `wire_size`: number of bytes that went through the socket
+    pub(crate) fn record_message(
+        &self,
+        node_id: Option<NodeId>,
+        direction: Direction,
+        message: &[u8],
+        wire_size: u64,
+    ) {
+        let type_id = u32::from_varint_bytes(message)
+            .map(|(id, _)| id)
+            .unwrap_or(u32::MAX);
+        let mut state = self.0.lock();
+        let add = |stats: &mut TrafficStats| match direction {
+            Direction::Sent => stats.sent.add_message(wire_size),
+            Direction::Received => stats.received.add_message(wire_size),
+        };
+        add(state.by_message_type.entry(type_id).or_default());
+        if let Some(node_id) = node_id {
+            add(state.by_node.entry(node_id).or_default());
+        }
+    }
+
+    /// Account for time spent waiting on a bandwidth limiter
+    pub(crate) fn record_limiter_wait(&self, direction: Direction, wait: Duration) {
+        let mut state = self.0.lock();
+        match direction {
+            Direction::Sent => state.write_limiter_wait += wait,
+            Direction::Received => state.read_limiter_wait += wait,
+        }
+    }
+
+    /// Account for a failed handshake
+    pub(crate) fn record_handshake_failure(&self, error: &HandshakeErrorType) {
+        *self
+            .0
+            .lock()
+            .handshake_failures
+            .entry(error.name())
+            .or_default() += 1;
+    }
+
+    /// Forget the per-node counters of a node that got disconnected
+    pub(crate) fn remove_node(&self, node_id: &NodeId) {
+        self.0.lock().by_node.remove(node_id);
+    }
+
+    /// Copy of the current state of the counters
+    pub(crate) fn snapshot(&self) -> NetworkMetrics {
+        let state = self.0.lock();
+        let mut by_message_type: BTreeMap<String, TrafficStats> = BTreeMap::new();
+        for (type_id, stats) in state.by_message_type.iter() {
+            let name = match MessageTypeId::try_from(*type_id) {
+                Ok(type_id) => format!("{:?}", type_id),
+                Err(_) => UNKNOWN_MESSAGE_TYPE.to_string(),
+            };
+            let entry = by_message_type.entry(name).or_default();
+            entry.sent.bytes += stats.sent.bytes;
+            entry.sent.messages += stats.sent.messages;
+            entry.received.bytes += stats.received.bytes;
+            entry.received.messages += stats.received.messages;
+        }
+        NetworkMetrics {
+            by_message_type,
+            by_node: state.by_node.clone(),
+            read_limiter_wait_millis: state.read_limiter_wait.as_millis() as u64,
+            write_limiter_wait_millis: state.write_limiter_wait.as_millis() as u64,
+            handshake_failures: state
+                .handshake_failures
+                .iter()
+                .map(|(name, count)| (name.to_string(), *count))
+                .collect(),
+        }
+    }
+}
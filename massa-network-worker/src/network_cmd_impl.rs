@@ -21,17 +21,18 @@
 //! ```
 use crate::network_worker::NetworkWorker;
 use futures::{stream::FuturesUnordered, StreamExt};
-use massa_hash::Hash;
 use massa_logging::massa_trace;
 use massa_models::{
     composite::PubkeySig,
     node::NodeId,
     operation::{OperationIds, OperationPrefixIds},
+    signed_message::compute_signed_message_hash,
     stats::NetworkStats,
-    BlockId, WrappedEndorsement,
+    BlockId, Denunciation, WrappedEndorsement,
 };
 use massa_network_exports::{
-    BootstrapPeers, ConnectionClosureReason, ConnectionId, NetworkError, NodeCommand, Peer, Peers,
+    BootstrapPeers, ConnectionClosureReason, ConnectionId, NetworkError, NetworkMetrics,
+    NodeCommand, Peer, Peers,
 };
 use std::{
     collections::{HashMap, HashSet},
@@ -280,6 +281,25 @@ pub async fn on_send_endorsements_cmd(
         .await;
 }
 
+pub async fn on_send_denunciations_cmd(
+    worker: &mut NetworkWorker,
+    node: NodeId,
+    denunciations: Vec<Denunciation>,
+) {
+    massa_trace!(
+        "network_worker.manage_network_command receive NetworkCommand::SendDenunciations",
+        { "node": node, "denunciations": denunciations }
+    );
+    worker
+        .event
+        .forward(
+            node,
+            worker.active_nodes.get(&node),
+            NodeCommand::SendDenunciations(denunciations),
+        )
+        .await;
+}
+
 pub async fn on_node_sign_message_cmd(
     worker: &mut NetworkWorker,
     msg: Vec<u8>,
@@ -289,7 +309,8 @@ pub async fn on_node_sign_message_cmd(
         "network_worker.manage_network_command receive NetworkCommand::NodeSignMessage",
         { "mdg": msg }
     );
-    let signature = worker.keypair.sign(&Hash::compute_from(&msg))?;
+    // in the signed message envelope, so that the node key never signs an operation this way
+    let signature = worker.keypair.sign(&compute_signed_message_hash(&msg))?;
     if response_tx
         .send(PubkeySig {
             public_key: worker.keypair.get_public_key(),
@@ -338,6 +359,8 @@ pub async fn on_get_stats_cmd(
     worker: &mut NetworkWorker,
     response_tx: oneshot::Sender<NetworkStats>,
 ) {
+    let metrics = worker.metrics.snapshot();
+    let traffic = metrics.total();
     let res = NetworkStats {
         in_connection_count: worker.peer_info_db.get_in_connection_count() as u64,
         out_connection_count: worker.peer_info_db.get_out_connection_count() as u64,
@@ -349,12 +372,26 @@ pub async fn on_get_stats_cmd(
             .filter(|(_, p)| p.banned)
             .fold(0, |acc, _| acc + 1),
         active_node_count: worker.active_nodes.len() as u64,
+        bytes_sent: traffic.sent.bytes,
+        bytes_received: traffic.received.bytes,
+        messages_sent: traffic.sent.messages,
+        messages_received: traffic.received.messages,
+        handshake_failure_count: metrics.handshake_failure_count(),
     };
     if response_tx.send(res).is_err() {
         warn!("network: could not send NodeSignMessage response upstream");
     }
 }
 
+pub async fn on_get_metrics_cmd(
+    worker: &mut NetworkWorker,
+    response_tx: oneshot::Sender<NetworkMetrics>,
+) {
+    if response_tx.send(worker.metrics.snapshot()).is_err() {
+        warn!("network: could not send GetMetrics response upstream");
+    }
+}
+
 /// Network worker received the command `NetworkCommand::SendOperations` from
 /// the controller. Happen when the program has received a new set of operation
 /// or run a kind of "send operations" loop.
@@ -81,7 +81,7 @@ pub mod event_impl {
     use massa_logging::massa_trace;
     use massa_models::{
         node::NodeId, operation::OperationPrefixIds, operation::Operations, wrapped::Id, BlockId,
-        WrappedBlock, WrappedEndorsement, WrappedHeader,
+        Denunciation, WrappedBlock, WrappedEndorsement, WrappedHeader,
     };
     use massa_network_exports::NodeCommand;
     use massa_network_exports::{NetworkError, NetworkEvent};
@@ -297,4 +297,25 @@ pub mod event_impl {
             evt_failed!(err)
         }
     }
+
+    pub async fn on_received_denunciations(
+        worker: &mut NetworkWorker,
+        from: NodeId,
+        denunciations: Vec<Denunciation>,
+    ) {
+        massa_trace!(
+            "network_worker.on_node_event receive NetworkEvent::ReceivedDenunciations",
+            { "denunciations": denunciations }
+        );
+        if let Err(err) = worker
+            .event
+            .send(NetworkEvent::ReceivedDenunciations {
+                node: from,
+                denunciations,
+            })
+            .await
+        {
+            evt_failed!(err)
+        }
+    }
 }
@@ -8,6 +8,7 @@ use crate::{
     binders::{ReadBinder, WriteBinder},
     handshake_worker::HandshakeWorker,
     messages::Message,
+    metrics::NetworkMetricsCollector,
     network_event::EventSender,
 };
 use futures::{stream::FuturesUnordered, StreamExt};
@@ -68,6 +69,8 @@ pub struct NetworkWorker {
     version: Version,
     /// Event sender
     pub(crate) event: EventSender,
+    /// Traffic metrics, shared with handshake and node workers
+    pub(crate) metrics: NetworkMetricsCollector,
 }
 
 pub struct NetworkWorkerChannels {
@@ -125,6 +128,7 @@ impl NetworkWorker {
             active_connections: HashMap::new(),
             storage,
             version,
+            metrics: NetworkMetricsCollector::default(),
         }
     }
 
@@ -237,6 +241,7 @@ impl NetworkWorker {
                     let _ = self
                         .event.send(NetworkEvent::ConnectionClosed(node_id))
                         .await;
+                    self.metrics.remove_node(&node_id);
                     if let Some((connection_id, _)) = self
                         .active_nodes
                         .remove(&node_id) {
@@ -332,7 +337,7 @@ impl NetworkWorker {
         });
         match outcome {
             // a handshake finished, and succeeded
-            Ok((new_node_id, socket_reader, socket_writer)) => {
+            Ok((new_node_id, mut socket_reader, mut socket_writer)) => {
                 debug!(
                     "handshake with connection_id={} succeeded => node_id={}",
                     new_connection_id, new_node_id
@@ -385,6 +390,10 @@ impl NetworkWorker {
                             .ok_or(NetworkError::ActiveConnectionMissing(new_connection_id))?;
                         self.peer_info_db.peer_alive(ip)?;
 
+                        // account the traffic of that node from now on
+                        socket_reader.set_node_id(new_node_id);
+                        socket_writer.set_node_id(new_node_id);
+
                         // spawn node_controller_fn
                         let (node_command_tx, node_command_rx) =
                             mpsc::channel::<NodeCommand>(CHANNEL_SIZE);
@@ -430,11 +439,14 @@ impl NetworkWorker {
                 }
             }
             // a handshake failed and sent a list of peers
-            Err(NetworkError::HandshakeError(HandshakeErrorType::PeerListReceived(peers))) => {
+            Err(NetworkError::HandshakeError(
+                ref handshake_error @ HandshakeErrorType::PeerListReceived(ref peers),
+            )) => {
+                self.metrics.record_handshake_failure(handshake_error);
                 // Manage the final of an handshake that send us a list of new peers
                 // instead of accepting a connection. Notify to the DB that `to_remove`
                 // has failed and merge new `to_add` candidates.
-                self.peer_info_db.merge_candidate_peers(&peers)?;
+                self.peer_info_db.merge_candidate_peers(peers)?;
                 self.running_handshakes.remove(&new_connection_id);
                 self.connection_closed(new_connection_id, ConnectionClosureReason::Failed)
                     .await?;
@@ -449,6 +461,9 @@ impl NetworkWorker {
                     "connection_id": new_connection_id,
                     "err": err.to_string()
                 });
+                if let NetworkError::HandshakeError(handshake_error) = &err {
+                    self.metrics.record_handshake_failure(handshake_error);
+                }
                 self.running_handshakes.remove(&new_connection_id);
                 self.connection_closed(new_connection_id, ConnectionClosureReason::Failed)
                     .await?;
@@ -542,12 +557,19 @@ impl NetworkWorker {
             NetworkCommand::SendEndorsements { node, endorsements } => {
                 on_send_endorsements_cmd(self, node, endorsements).await
             }
+            NetworkCommand::SendDenunciations {
+                node,
+                denunciations,
+            } => on_send_denunciations_cmd(self, node, denunciations).await,
             NetworkCommand::NodeSignMessage { msg, response_tx } => {
                 on_node_sign_message_cmd(self, msg, response_tx).await?
             }
             NetworkCommand::NodeUnbanByIds(ids) => on_node_unban_by_ids_cmd(self, ids).await?,
             NetworkCommand::NodeUnbanByIps(ips) => on_node_unban_by_ips_cmd(self, ips).await?,
             NetworkCommand::GetStats { response_tx } => on_get_stats_cmd(self, response_tx).await,
+            NetworkCommand::GetMetrics { response_tx } => {
+                on_get_metrics_cmd(self, response_tx).await
+            }
             NetworkCommand::Whitelist(ips) => on_whitelist_cmd(self, ips).await?,
             NetworkCommand::RemoveFromWhitelist(ips) => {
                 on_remove_from_whitelist_cmd(self, ips).await?
@@ -703,10 +725,11 @@ impl NetworkWorker {
             let timeout = self.cfg.peer_list_send_timeout.to_duration();
             let max_bytes_read = self.cfg.max_bytes_read;
             let max_bytes_write = self.cfg.max_bytes_write;
+            let metrics = self.metrics.clone();
             self.handshake_peer_list_futures
                 .push(tokio::spawn(async move {
-                    let mut writer = WriteBinder::new(writer, max_bytes_read);
-                    let mut reader = ReadBinder::new(reader, max_bytes_write);
+                    let mut writer = WriteBinder::new(writer, max_bytes_read, metrics.clone());
+                    let mut reader = ReadBinder::new(reader, max_bytes_write, metrics);
                     match tokio::time::timeout(
                         timeout,
                         futures::future::try_join(
@@ -755,6 +778,7 @@ impl NetworkWorker {
             connection_id,
             self.cfg.max_bytes_read,
             self.cfg.max_bytes_write,
+            self.metrics.clone(),
         ));
         Ok(())
     }
@@ -792,6 +816,9 @@ impl NetworkWorker {
             NodeEvent(node, NodeEventType::ReceivedEndorsements(endorsements)) => {
                 event_impl::on_received_endorsements(self, node, endorsements).await
             }
+            NodeEvent(node, NodeEventType::ReceivedDenunciations(denunciations)) => {
+                event_impl::on_received_denunciations(self, node, denunciations).await
+            }
             NodeEvent(node, NodeEventType::ReceivedOperationAnnouncements(operation_ids)) => {
                 event_impl::on_received_operations_annoncement(self, node, operation_ids).await
             }
@@ -7,7 +7,10 @@ use super::{
 use itertools::Itertools;
 use massa_logging::massa_trace;
 use massa_models::{
-    constants::{MAX_ASK_BLOCKS_PER_MESSAGE, MAX_ENDORSEMENTS_PER_MESSAGE, NODE_SEND_CHANNEL_SIZE},
+    constants::{
+        MAX_ASK_BLOCKS_PER_MESSAGE, MAX_DENUNCIATIONS_PER_MESSAGE, MAX_ENDORSEMENTS_PER_MESSAGE,
+        NODE_SEND_CHANNEL_SIZE,
+    },
     node::NodeId,
     wrapped::{Id, WrappedSerializer},
 };
@@ -338,6 +341,10 @@ impl NodeWorker {
                                 massa_trace!("node_worker.run_loop. receive Message::Endorsement", {"node": self.node_id, "endorsements": endorsements});
                                 self.send_node_event(NodeEvent(self.node_id, NodeEventType::ReceivedEndorsements(endorsements))).await;
                             }
+                            Message::Denunciations(denunciations) => {
+                                massa_trace!("node_worker.run_loop. receive Message::Denunciations", {"node": self.node_id, "denunciations": denunciations});
+                                self.send_node_event(NodeEvent(self.node_id, NodeEventType::ReceivedDenunciations(denunciations))).await;
+                            }
                             _ => {
                                 // TODO: Write a more user-friendly warning/logout after several consecutive fails? see #1082
                                 massa_trace!("node_worker.run_loop.self.socket_reader.next(). Unexpected message Warning", {});
@@ -442,6 +449,15 @@ impl NodeWorker {
                                 }
                             }
                         },
+                        Some(NodeCommand::SendDenunciations(denunciations)) => {
+                            massa_trace!("node_worker.run_loop. send Message::Denunciations", {"node": self.node_id, "denunciations": denunciations});
+                            // cut denunciation list if it exceed max_denunciations_per_message
+                            for to_send_list in denunciations.chunks(MAX_DENUNCIATIONS_PER_MESSAGE as usize) {
+                                if self.try_send_to_node(&writer_command_tx, ToSend::Msg(Message::Denunciations(to_send_list.to_vec()))).is_err() {
+                                    break 'select_loop;
+                                }
+                            }
+                        },
                         None => {
                             // Note: this should never happen,
                             // since it implies the network worker dropped its node command sender
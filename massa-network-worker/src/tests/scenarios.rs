@@ -3,6 +3,7 @@
 // To start alone RUST_BACKTRACE=1 cargo test -- --nocapture --test-threads=1
 use super::tools;
 use crate::messages::Message;
+use crate::metrics::NetworkMetricsCollector;
 use crate::node_worker::NodeWorker;
 use crate::tests::tools::{get_dummy_block_id, get_transaction};
 use crate::NetworkError;
@@ -65,8 +66,16 @@ async fn test_node_worker_shutdown() {
     let network_conf = NetworkSettings::scenarios_default(bind_port, temp_peers_file.path());
     let (duplex_controller, _duplex_mock) = tokio::io::duplex(1);
     let (duplex_mock_read, duplex_mock_write) = tokio::io::split(duplex_controller);
-    let reader = ReadBinder::new(duplex_mock_read, f64::INFINITY);
-    let writer = WriteBinder::new(duplex_mock_write, f64::INFINITY);
+    let reader = ReadBinder::new(
+        duplex_mock_read,
+        f64::INFINITY,
+        NetworkMetricsCollector::default(),
+    );
+    let writer = WriteBinder::new(
+        duplex_mock_write,
+        f64::INFINITY,
+        NetworkMetricsCollector::default(),
+    );
 
     // Note: both channels have size 1.
     let (node_command_tx, node_command_rx) = mpsc::channel::<NodeCommand>(1);
@@ -120,8 +129,16 @@ async fn test_node_worker_operations_message() {
     let network_conf = NetworkSettings::scenarios_default(bind_port, temp_peers_file.path());
     let (duplex_controller, _duplex_mock) = tokio::io::duplex(1);
     let (duplex_mock_read, duplex_mock_write) = tokio::io::split(duplex_controller);
-    let reader = ReadBinder::new(duplex_mock_read, f64::INFINITY);
-    let writer = WriteBinder::new(duplex_mock_write, f64::INFINITY);
+    let reader = ReadBinder::new(
+        duplex_mock_read,
+        f64::INFINITY,
+        NetworkMetricsCollector::default(),
+    );
+    let writer = WriteBinder::new(
+        duplex_mock_write,
+        f64::INFINITY,
+        NetworkMetricsCollector::default(),
+    );
 
     // Note: both channels have size 1.
     let (node_command_tx, node_command_rx) = mpsc::channel::<NodeCommand>(1);
@@ -177,6 +194,42 @@ async fn test_node_worker_operations_message() {
     node_fn_handle.await.unwrap().unwrap();
 }
 
+/// Test that messages going through binders are accounted in the traffic metrics.
+#[tokio::test]
+#[serial]
+async fn test_binders_traffic_metrics() {
+    let (duplex_sender, duplex_receiver) = tokio::io::duplex(1024);
+    let (_sender_read, sender_write) = tokio::io::split(duplex_sender);
+    let (receiver_read, _receiver_write) = tokio::io::split(duplex_receiver);
+    let metrics = NetworkMetricsCollector::default();
+    let mut writer = WriteBinder::new(sender_write, f64::INFINITY, metrics.clone());
+    let mut reader = ReadBinder::new(receiver_read, f64::INFINITY, metrics.clone());
+    let remote_node_id = NodeId(KeyPair::generate().get_public_key());
+    reader.set_node_id(remote_node_id);
+
+    writer
+        .send(&Message::AskPeerList.to_bytes_compact().unwrap())
+        .await
+        .unwrap();
+    let (_, msg) = reader.next().await.unwrap().unwrap();
+    assert!(matches!(msg, Message::AskPeerList));
+
+    let snapshot = metrics.snapshot();
+    let by_type = snapshot
+        .by_message_type
+        .get("AskPeerList")
+        .expect("missing AskPeerList traffic");
+    assert_eq!(by_type.sent.messages, 1);
+    assert_eq!(by_type.received.messages, 1);
+    assert_eq!(by_type.sent.bytes, by_type.received.bytes);
+    let by_node = snapshot
+        .by_node
+        .get(&remote_node_id)
+        .expect("missing node traffic");
+    assert_eq!(by_node.received.messages, 1);
+    assert_eq!(by_node.sent.messages, 0);
+}
+
 // test connecting two different peers simultaneously to the controller
 // then attempt to connect to controller from an already connected peer to test max_in_connections_per_ip
 // then try to connect a third peer to test max_in_connection
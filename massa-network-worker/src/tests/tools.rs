@@ -4,6 +4,7 @@ use super::super::binders::{ReadBinder, WriteBinder};
 use super::tools;
 use crate::handshake_worker::HandshakeWorker;
 use crate::messages::Message;
+use crate::metrics::NetworkMetricsCollector;
 use crate::start_network_controller;
 use crate::NetworkError;
 use crate::NetworkEvent;
@@ -92,6 +93,7 @@ pub async fn full_connection_to_controller(
         connection_id,
         f64::INFINITY,
         f64::INFINITY,
+        NetworkMetricsCollector::default(),
     )
     .await
     .expect("handshake creation failed")
@@ -151,6 +153,7 @@ pub async fn rejected_connection_to_controller(
         connection_id,
         f64::INFINITY,
         f64::INFINITY,
+        NetworkMetricsCollector::default(),
     )
     .await
     .expect("handshake creation failed")
@@ -236,6 +239,7 @@ pub async fn full_connection_from_controller(
         connection_id,
         f64::INFINITY,
         f64::INFINITY,
+        NetworkMetricsCollector::default(),
     )
     .await
     .expect("handshake creation failed")
@@ -5,16 +5,17 @@
 #![warn(missing_docs)]
 #![warn(unused_crate_dependencies)]
 extern crate massa_logging;
-use crate::settings::{POOL_CONFIG, SETTINGS};
+use crate::metrics::{start_metrics_server, MetricsServerHandle, MetricsSources};
+use crate::settings::{load_bootstrap_policy, POOL_CONFIG, SETTINGS};
 
 use dialoguer::Password;
-use massa_api::{Private, Public, RpcServer, StopHandle, API};
+use massa_api::{PoolEventsApi, Private, Public, RpcServer, StopHandle, API};
 use massa_async_pool::AsyncPoolConfig;
-use massa_bootstrap::{get_state, start_bootstrap_server, BootstrapManager};
-use massa_cipher::{decrypt, encrypt};
+use massa_bootstrap::{get_state, start_bootstrap_server, BootstrapManager, BootstrapPolicyHandle};
+use massa_cipher::{decrypt, encrypt, VERSION as CIPHER_VERSION};
 use massa_consensus_exports::{
-    events::ConsensusEvent, settings::ConsensusChannels, ConsensusCommandSender, ConsensusConfig,
-    ConsensusEventReceiver, ConsensusManager,
+    events::ConsensusEvent, settings::ConsensusChannels, ConsensusBalanceSource,
+    ConsensusCommandSender, ConsensusConfig, ConsensusEventReceiver, ConsensusManager,
 };
 use massa_consensus_worker::start_consensus_controller;
 use massa_execution_exports::{ExecutionConfig, ExecutionManager};
@@ -49,6 +50,7 @@ use tracing::{error, info, warn};
 #[cfg(not(feature = "instrument"))]
 use tracing_subscriber::filter::{filter_fn, LevelFilter};
 
+mod metrics;
 mod settings;
 
 async fn launch(
@@ -68,6 +70,8 @@ async fn launch(
     mpsc::Receiver<()>,
     StopHandle,
     StopHandle,
+    StopHandle,
+    Option<MetricsServerHandle>,
 ) {
     info!("Node version : {}", *VERSION);
     if let Some(end) = *END_TIMESTAMP {
@@ -166,7 +170,7 @@ async fn launch(
     .expect("could not start protocol controller");
 
     // launch pool controller
-    let (pool_command_sender, pool_manager) = start_pool_controller(
+    let (mut pool_command_sender, pool_manager) = start_pool_controller(
         &POOL_CONFIG,
         protocol_command_sender.clone(),
         protocol_pool_event_receiver,
@@ -214,12 +218,26 @@ async fn launch(
         .await
         .expect("could not start consensus controller");
 
+    // let the pool check the balances of the operation senders
+    pool_command_sender
+        .set_balance_source(Box::new(ConsensusBalanceSource::new(
+            consensus_command_sender.clone(),
+            execution_controller.clone(),
+        )))
+        .await
+        .expect("could not set the pool balance source");
+
     // launch bootstrap server
+    let bootstrap_policy = BootstrapPolicyHandle::new(
+        SETTINGS.bootstrap.server_policy.clone(),
+        load_bootstrap_policy,
+    );
     let bootstrap_manager = start_bootstrap_server(
         consensus_command_sender.clone(),
         network_command_sender.clone(),
         final_state.clone(),
         &SETTINGS.bootstrap,
+        bootstrap_policy.clone(),
         massa_bootstrap::Establisher::new(),
         private_key,
         bootstrap_state.compensation_millis,
@@ -235,6 +253,7 @@ async fn launch(
         execution_controller.clone(),
         &SETTINGS.api,
         consensus_config.clone(),
+        bootstrap_policy,
     );
     let api_private_handle = api_private.serve(&SETTINGS.api.bind_private);
 
@@ -253,6 +272,29 @@ async fn launch(
     );
     let api_public_handle = api_public.serve(&SETTINGS.api.bind_public);
 
+    // spawn public WebSocket API
+    let api_pool_events_handle = PoolEventsApi::new(
+        pool_command_sender.clone(),
+        SETTINGS.api.max_ws_subscriptions,
+    )
+    .serve(&SETTINGS.api.bind_public_ws);
+
+    // spawn metrics server
+    let metrics_server_handle = start_metrics_server(
+        &SETTINGS.metrics,
+        MetricsSources {
+            network_command_sender: network_command_sender.clone(),
+            consensus_command_sender: consensus_command_sender.clone(),
+            execution_controller: execution_controller.clone(),
+            pool_command_sender: pool_command_sender.clone(),
+            final_state: final_state.clone(),
+            bootstrap_sessions: bootstrap_manager
+                .as_ref()
+                .map(BootstrapManager::get_session_counter),
+        },
+    )
+    .expect("could not start metrics server");
+
     (
         pool_command_sender,
         consensus_event_receiver,
@@ -267,6 +309,8 @@ async fn launch(
         api_private_stop_rx,
         api_private_handle,
         api_public_handle,
+        api_pool_events_handle,
+        metrics_server_handle,
     )
 }
 
@@ -291,7 +335,14 @@ async fn stop(
     }: Managers,
     api_private_handle: StopHandle,
     api_public_handle: StopHandle,
+    api_pool_events_handle: StopHandle,
+    metrics_server_handle: Option<MetricsServerHandle>,
 ) {
+    // stop metrics server
+    if let Some(metrics_server_handle) = metrics_server_handle {
+        metrics_server_handle.stop().await;
+    }
+
     // stop bootstrap
     if let Some(bootstrap_manager) = bootstrap_manager {
         bootstrap_manager
@@ -300,6 +351,9 @@ async fn stop(
             .expect("bootstrap server shutdown failed")
     }
 
+    // stop public WebSocket API
+    api_pool_events_handle.stop();
+
     // stop public API
     api_public_handle.stop();
 
@@ -352,10 +406,21 @@ async fn load_or_create_staking_keys_file(
                 .interact()
                 .expect("IO error: Password reading failed, staking keys file couldn't be unlocked")
         });
-        let (_version, decrypted_data) = decrypt(&password, &tokio::fs::read(path).await?)?;
+        let (version, decrypted_data) = decrypt(&password, &tokio::fs::read(path).await?)?;
         let staking_keys: anyhow::Result<Map<Address, KeyPair>> = Ok(serde_json::from_slice::<
             Map<Address, KeyPair>,
         >(&decrypted_data)?);
+        if version < CIPHER_VERSION {
+            // upgrade the key derivation of the file, its content is unchanged
+            // the new file is written aside first so that a crash does not lose the keys
+            let tmp_path = path.with_extension("tmp");
+            tokio::fs::write(&tmp_path, encrypt(&password, &decrypted_data)?).await?;
+            tokio::fs::rename(&tmp_path, path).await?;
+            info!(
+                "staking keys file upgraded to cipher version {}",
+                CIPHER_VERSION
+            );
+        }
         Ok((password, staking_keys?))
     } else {
         let password = password.unwrap_or_else(|| {
@@ -430,6 +495,8 @@ async fn main(args: Args) -> anyhow::Result<()> {
             mut api_private_stop_rx,
             api_private_handle,
             api_public_handle,
+            api_pool_events_handle,
+            metrics_server_handle,
         ) = launch(&password, &staking_keys).await;
 
         // interrupt signal listener
@@ -477,6 +544,8 @@ async fn main(args: Args) -> anyhow::Result<()> {
             },
             api_private_handle,
             api_public_handle,
+            api_pool_events_handle,
+            metrics_server_handle,
         )
         .await;
 
@@ -0,0 +1,463 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Optional HTTP server exposing the node metrics in the Prometheus text format on `/metrics`.
+use crate::settings::MetricsSettings;
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use massa_bootstrap::BootstrapSessionCounter;
+use massa_consensus_exports::ConsensusCommandSender;
+use massa_execution_exports::ExecutionController;
+use massa_final_state::FinalState;
+use massa_network_exports::{NetworkCommandSender, TrafficStats};
+use massa_pool::PoolCommandSender;
+use parking_lot::RwLock;
+use std::{convert::Infallible, fmt::Display, fmt::Write, sync::Arc};
+use tokio::{
+    sync::oneshot,
+    task::{spawn_blocking, JoinHandle},
+};
+use tracing::{info, warn};
+
+/// Content type of the Prometheus text exposition format
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Components queried when rendering the metrics
+#[derive(Clone)]
+pub struct MetricsSources {
+    /// network command sender
+    pub network_command_sender: NetworkCommandSender,
+    /// consensus command sender
+    pub consensus_command_sender: ConsensusCommandSender,
+    /// execution controller
+    pub execution_controller: Box<dyn ExecutionController>,
+    /// pool command sender
+    pub pool_command_sender: PoolCommandSender,
+    /// final state, for the async pool and ledger sizes
+    pub final_state: Arc<RwLock<FinalState>>,
+    /// bootstrap server sessions, if the bootstrap server is running
+    pub bootstrap_sessions: Option<BootstrapSessionCounter>,
+}
+
+/// Handle on a running metrics server
+pub struct MetricsServerHandle {
+    stop_tx: oneshot::Sender<()>,
+    join_handle: JoinHandle<()>,
+}
+
+impl MetricsServerHandle {
+    /// Stop the server and wait for it to finish serving the ongoing requests
+    pub async fn stop(self) {
+        let _ = self.stop_tx.send(());
+        if let Err(err) = self.join_handle.await {
+            warn!("metrics server panicked: {}", err);
+        }
+    }
+}
+
+/// Start the metrics server if it is enabled in the settings
+pub fn start_metrics_server(
+    settings: &MetricsSettings,
+    sources: MetricsSources,
+) -> Result<Option<MetricsServerHandle>, hyper::Error> {
+    if !settings.enabled {
+        return Ok(None);
+    }
+    let make_service = make_service_fn(move |_conn| {
+        let sources = sources.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req| {
+                let sources = sources.clone();
+                async move { Ok::<_, Infallible>(serve(req, &sources).await) }
+            }))
+        }
+    });
+    let server = Server::try_bind(&settings.bind)?.serve(make_service);
+    info!("metrics server listening on {}", settings.bind);
+    let (stop_tx, stop_rx) = oneshot::channel::<()>();
+    let join_handle = tokio::spawn(async move {
+        let graceful = server.with_graceful_shutdown(async {
+            let _ = stop_rx.await;
+        });
+        if let Err(err) = graceful.await {
+            warn!("metrics server error: {}", err);
+        }
+    });
+    Ok(Some(MetricsServerHandle {
+        stop_tx,
+        join_handle,
+    }))
+}
+
+async fn serve(req: Request<Body>, sources: &MetricsSources) -> Response<Body> {
+    if req.method() != Method::GET || req.uri().path() != "/metrics" {
+        let mut response = Response::new(Body::from("not found"));
+        *response.status_mut() = StatusCode::NOT_FOUND;
+        return response;
+    }
+    let mut encoder = PrometheusEncoder::default();
+    render_network_metrics(&mut encoder, sources).await;
+    render_consensus_metrics(&mut encoder, sources).await;
+    render_execution_metrics(&mut encoder, sources).await;
+    render_pool_metrics(&mut encoder, sources).await;
+    render_final_state_metrics(&mut encoder, sources).await;
+    if let Some(sessions) = &sources.bootstrap_sessions {
+        encoder.gauge(
+            "massa_bootstrap_server_sessions",
+            "Number of bootstrap sessions currently being served",
+            sessions.get(),
+        );
+    }
+    let mut response = Response::new(Body::from(encoder.finish()));
+    if let Ok(content_type) = PROMETHEUS_CONTENT_TYPE.parse() {
+        response.headers_mut().insert(CONTENT_TYPE, content_type);
+    }
+    response
+}
+
+async fn render_network_metrics(encoder: &mut PrometheusEncoder, sources: &MetricsSources) {
+    match sources.network_command_sender.get_network_stats().await {
+        Ok(stats) => {
+            encoder.family(
+                "massa_network_connections",
+                "gauge",
+                "Number of established connections",
+            );
+            encoder.sample(
+                "massa_network_connections",
+                &[("direction", "in")],
+                stats.in_connection_count,
+            );
+            encoder.sample(
+                "massa_network_connections",
+                &[("direction", "out")],
+                stats.out_connection_count,
+            );
+            encoder.gauge(
+                "massa_network_known_peers",
+                "Number of known peers",
+                stats.known_peer_count,
+            );
+            encoder.gauge(
+                "massa_network_banned_peers",
+                "Number of banned peers",
+                stats.banned_peer_count,
+            );
+            encoder.gauge(
+                "massa_network_active_nodes",
+                "Number of nodes with a running node worker",
+                stats.active_node_count,
+            );
+        }
+        Err(err) => warn!("metrics: could not get network stats: {}", err),
+    }
+
+    let metrics = match sources.network_command_sender.get_network_metrics().await {
+        Ok(metrics) => metrics,
+        Err(err) => {
+            warn!("metrics: could not get network metrics: {}", err);
+            return;
+        }
+    };
+    render_traffic(
+        encoder,
+        "massa_network",
+        "message_type",
+        metrics.by_message_type.iter(),
+    );
+    render_traffic(
+        encoder,
+        "massa_network_peer",
+        "node_id",
+        metrics.by_node.iter(),
+    );
+    encoder.family(
+        "massa_network_limiter_wait_seconds_total",
+        "counter",
+        "Time spent waiting on the bandwidth limiters",
+    );
+    encoder.sample(
+        "massa_network_limiter_wait_seconds_total",
+        &[("direction", "received")],
+        metrics.read_limiter_wait_millis as f64 / 1000.0,
+    );
+    encoder.sample(
+        "massa_network_limiter_wait_seconds_total",
+        &[("direction", "sent")],
+        metrics.write_limiter_wait_millis as f64 / 1000.0,
+    );
+    encoder.family(
+        "massa_network_handshake_failures_total",
+        "counter",
+        "Number of failed handshakes per error type",
+    );
+    for (error, count) in metrics.handshake_failures.iter() {
+        encoder.sample(
+            "massa_network_handshake_failures_total",
+            &[("error", error.as_str())],
+            count,
+        );
+    }
+}
+
+async fn render_consensus_metrics(encoder: &mut PrometheusEncoder, sources: &MetricsSources) {
+    let stats = match sources.consensus_command_sender.get_stats().await {
+        Ok(stats) => stats,
+        Err(err) => {
+            warn!("metrics: could not get consensus stats: {}", err);
+            return;
+        }
+    };
+    encoder.gauge(
+        "massa_consensus_final_blocks",
+        "Number of blocks that became final during the stats time span",
+        stats.final_block_count,
+    );
+    encoder.gauge(
+        "massa_consensus_final_operations",
+        "Number of operations in blocks that became final during the stats time span",
+        stats.final_operation_count,
+    );
+    encoder.gauge(
+        "massa_consensus_stale_blocks",
+        "Number of stale blocks kept in memory",
+        stats.stale_block_count,
+    );
+    encoder.gauge(
+        "massa_consensus_cliques",
+        "Number of active cliques",
+        stats.clique_count,
+    );
+    encoder.gauge(
+        "massa_consensus_stakers",
+        "Number of stakers",
+        stats.staker_count,
+    );
+
+    let staking_health = match sources.consensus_command_sender.get_staking_health().await {
+        Ok(staking_health) => staking_health,
+        Err(err) => {
+            warn!("metrics: could not get staking health: {}", err);
+            return;
+        }
+    };
+    encoder.family(
+        "massa_staking_blocks",
+        "gauge",
+        "Number of final blocks produced or missed by each staking address in the latest cycle",
+    );
+    for health in staking_health.iter() {
+        let address = health.address.to_string();
+        encoder.sample(
+            "massa_staking_blocks",
+            &[("address", address.as_str()), ("result", "produced")],
+            health.ok_count,
+        );
+        encoder.sample(
+            "massa_staking_blocks",
+            &[("address", address.as_str()), ("result", "missed")],
+            health.nok_count,
+        );
+    }
+    encoder.family(
+        "massa_staking_deactivation_imminent",
+        "gauge",
+        "1 if the miss rate of the staking address in the latest cycle is above the roll deactivation threshold",
+    );
+    for health in staking_health.iter() {
+        encoder.sample(
+            "massa_staking_deactivation_imminent",
+            &[("address", health.address.to_string().as_str())],
+            u8::from(health.deactivation_imminent),
+        );
+    }
+}
+
+async fn render_execution_metrics(encoder: &mut PrometheusEncoder, sources: &MetricsSources) {
+    // the execution state is locked for the whole execution of a slot
+    let execution_controller = sources.execution_controller.clone();
+    let stats = match spawn_blocking(move || execution_controller.get_stats()).await {
+        Ok(stats) => stats,
+        Err(err) => {
+            warn!("metrics: could not get execution stats: {}", err);
+            return;
+        }
+    };
+    encoder.family(
+        "massa_execution_cursor_period",
+        "gauge",
+        "Period of the last executed slot",
+    );
+    encoder.sample(
+        "massa_execution_cursor_period",
+        &[("cursor", "active")],
+        stats.active_cursor.period,
+    );
+    encoder.sample(
+        "massa_execution_cursor_period",
+        &[("cursor", "final")],
+        stats.final_cursor.period,
+    );
+    encoder.family(
+        "massa_execution_executed_slots_total",
+        "counter",
+        "Number of executed slots, final or speculative",
+    );
+    encoder.sample(
+        "massa_execution_executed_slots_total",
+        &[],
+        stats.executed_slot_count,
+    );
+    encoder.family(
+        "massa_execution_slot_execution_seconds_total",
+        "counter",
+        "Time spent executing slots",
+    );
+    encoder.sample(
+        "massa_execution_slot_execution_seconds_total",
+        &[],
+        stats.slot_execution_time.as_secs_f64(),
+    );
+    encoder.gauge(
+        "massa_execution_readonly_queue_length",
+        "Number of read-only execution requests waiting to be executed",
+        stats.readonly_queue_length,
+    );
+}
+
+async fn render_pool_metrics(encoder: &mut PrometheusEncoder, sources: &MetricsSources) {
+    let stats = match sources.pool_command_sender.clone().get_pool_stats().await {
+        Ok(stats) => stats,
+        Err(err) => {
+            warn!("metrics: could not get pool stats: {}", err);
+            return;
+        }
+    };
+    encoder.gauge(
+        "massa_pool_operations",
+        "Number of operations in the operation pool",
+        stats.operation_count,
+    );
+    encoder.gauge(
+        "massa_pool_endorsements",
+        "Number of endorsements in the endorsement pool",
+        stats.endorsement_count,
+    );
+}
+
+async fn render_final_state_metrics(encoder: &mut PrometheusEncoder, sources: &MetricsSources) {
+    // the final state is locked while final slots are applied
+    let final_state = sources.final_state.clone();
+    let (async_pool_length, ledger_disk_size) = match spawn_blocking(move || {
+        let final_state = final_state.read();
+        (
+            final_state.async_pool.len(),
+            final_state.ledger.get_disk_size(),
+        )
+    })
+    .await
+    {
+        Ok(sizes) => sizes,
+        Err(err) => {
+            warn!("metrics: could not get final state stats: {}", err);
+            return;
+        }
+    };
+    encoder.gauge(
+        "massa_async_pool_messages",
+        "Number of messages in the final asynchronous pool",
+        async_pool_length,
+    );
+    encoder.gauge(
+        "massa_ledger_disk_size_bytes",
+        "Estimated size of the final ledger on disk",
+        ledger_disk_size,
+    );
+}
+
+/// Render bytes and messages counters of a traffic breakdown, labelled by `label`
+fn render_traffic<'a, K: Display + 'a>(
+    encoder: &mut PrometheusEncoder,
+    prefix: &str,
+    label: &str,
+    traffic: impl Iterator<Item = (&'a K, &'a TrafficStats)> + Clone,
+) {
+    let bytes_name = format!("{}_bytes_total", prefix);
+    let messages_name = format!("{}_messages_total", prefix);
+    encoder.family(&bytes_name, "counter", "Number of bytes exchanged");
+    for (key, stats) in traffic.clone() {
+        let key = key.to_string();
+        encoder.sample(
+            &bytes_name,
+            &[(label, key.as_str()), ("direction", "sent")],
+            stats.sent.bytes,
+        );
+        encoder.sample(
+            &bytes_name,
+            &[(label, key.as_str()), ("direction", "received")],
+            stats.received.bytes,
+        );
+    }
+    encoder.family(&messages_name, "counter", "Number of messages exchanged");
+    for (key, stats) in traffic {
+        let key = key.to_string();
+        encoder.sample(
+            &messages_name,
+            &[(label, key.as_str()), ("direction", "sent")],
+            stats.sent.messages,
+        );
+        encoder.sample(
+            &messages_name,
+            &[(label, key.as_str()), ("direction", "received")],
+            stats.received.messages,
+        );
+    }
+}
+
+/// Minimal writer of the Prometheus text exposition format
+#[derive(Default)]
+pub struct PrometheusEncoder {
+    buffer: String,
+}
+
+impl PrometheusEncoder {
+    /// Write the `HELP` and `TYPE` lines of a metric family
+    pub fn family(&mut self, name: &str, metric_type: &str, help: &str) {
+        let _ = writeln!(self.buffer, "# HELP {} {}", name, help);
+        let _ = writeln!(self.buffer, "# TYPE {} {}", name, metric_type);
+    }
+
+    /// Write one sample of a metric family
+    pub fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: impl Display) {
+        let _ = write!(self.buffer, "{}", name);
+        if !labels.is_empty() {
+            let labels = labels
+                .iter()
+                .map(|(key, value)| format!("{}=\"{}\"", key, escape_label_value(value)))
+                .collect::<Vec<_>>()
+                .join(",");
+            let _ = write!(self.buffer, "{{{}}}", labels);
+        }
+        let _ = writeln!(self.buffer, " {}", value);
+    }
+
+    /// Write a single unlabelled gauge
+    pub fn gauge(&mut self, name: &str, help: &str, value: impl Display) {
+        self.family(name, "gauge", help);
+        self.sample(name, &[], value);
+    }
+
+    /// Get the rendered text
+    pub fn finish(self) -> String {
+        self.buffer
+    }
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
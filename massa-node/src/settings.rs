@@ -1,12 +1,15 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 //! Build here the default node settings from the configuration file toml
-use std::path::PathBuf;
+use std::{net::SocketAddr, path::PathBuf};
 
 use massa_api::APISettings;
-use massa_bootstrap::BootstrapSettings;
+use massa_bootstrap::{BootstrapServerPolicy, BootstrapSettings};
 use massa_consensus_exports::ConsensusSettings;
-use massa_models::constants::{build_massa_settings, OPERATION_VALIDITY_PERIODS, THREAD_COUNT};
+use massa_models::constants::{
+    build_massa_settings, try_build_massa_settings, OPERATION_VALIDITY_PERIODS, ROLL_PRICE,
+    THREAD_COUNT,
+};
 use massa_network_exports::NetworkSettings;
 use massa_pool::{PoolConfig, PoolSettings};
 use massa_protocol_exports::ProtocolSettings;
@@ -16,9 +19,10 @@ use serde::Deserialize;
 lazy_static::lazy_static! {
     pub static ref SETTINGS: Settings = build_massa_settings("massa-node", "MASSA_NODE");
     pub static ref POOL_CONFIG: PoolConfig = PoolConfig {
-        settings: SETTINGS.pool,
+        settings: SETTINGS.pool.clone(),
         thread_count: THREAD_COUNT,
-        operation_validity_periods: OPERATION_VALIDITY_PERIODS
+        operation_validity_periods: OPERATION_VALIDITY_PERIODS,
+        roll_price: ROLL_PRICE
     };
 }
 
@@ -41,6 +45,12 @@ pub struct LedgerSettings {
     pub final_history_length: usize,
 }
 
+#[derive(Clone, Debug, Deserialize)]
+pub struct MetricsSettings {
+    pub enabled: bool,
+    pub bind: SocketAddr,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct Settings {
     pub logging: LoggingSettings,
@@ -52,6 +62,25 @@ pub struct Settings {
     pub pool: PoolSettings,
     pub execution: ExecutionSettings,
     pub ledger: LedgerSettings,
+    pub metrics: MetricsSettings,
+}
+
+#[derive(Debug, Deserialize)]
+struct BootstrapPolicySettings {
+    #[serde(default)]
+    server_policy: BootstrapServerPolicy,
+}
+
+#[derive(Debug, Deserialize)]
+struct BootstrapPolicyConfig {
+    bootstrap: BootstrapPolicySettings,
+}
+
+/// Read the bootstrap server policy again from the configuration files
+pub fn load_bootstrap_policy() -> Result<BootstrapServerPolicy, String> {
+    try_build_massa_settings::<BootstrapPolicyConfig>("massa-node", "MASSA_NODE")
+        .map(|config| config.bootstrap.server_policy)
+        .map_err(|error| error.to_string())
 }
 
 #[cfg(test)]
@@ -0,0 +1,122 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Checks that the senders of the operations entering the pool can pay for them.
+//!
+//! The candidate balances of the senders are provided by a `BalanceSource`.
+//! An operation is admitted if the balances of its sender cover its worst case
+//! spending plus the spending of the other operations of that sender already in the pool.
+//! Pending operations already included in active blocks are not counted twice:
+//! the candidate balances are computed after their execution.
+
+use massa_models::{
+    prehash::{Map, Set},
+    Address, Amount, OperationId, OperationType, WrappedOperation,
+};
+use std::{future::Future, pin::Pin};
+
+/// Sequential and parallel amounts of an address: balances or spending
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SenderBalances {
+    /// sequential amount, handled by consensus
+    pub sequential: Amount,
+    /// parallel amount, handled by execution
+    pub parallel: Amount,
+}
+
+impl SenderBalances {
+    /// add both amounts, saturating at the maximal value
+    pub fn saturating_add(self, other: SenderBalances) -> SenderBalances {
+        SenderBalances {
+            sequential: self.sequential.saturating_add(other.sequential),
+            parallel: self.parallel.saturating_add(other.parallel),
+        }
+    }
+
+    /// subtract both amounts, saturating at zero
+    pub fn saturating_sub(self, other: SenderBalances) -> SenderBalances {
+        SenderBalances {
+            sequential: self.sequential.saturating_sub(other.sequential),
+            parallel: self.parallel.saturating_sub(other.parallel),
+        }
+    }
+}
+
+/// Candidate balances of the operation senders
+#[derive(Debug, Default, Clone)]
+pub struct CandidateBalances {
+    /// candidate balances of the senders
+    pub balances: Map<Address, SenderBalances>,
+    /// pending operations of the senders that are already included in active blocks,
+    /// and so already paid from the candidate balances
+    pub included_operations: Set<OperationId>,
+}
+
+/// Future resolving to the candidate balances of a set of addresses
+pub type BalancesFuture = Pin<Box<dyn Future<Output = Result<CandidateBalances, String>> + Send>>;
+
+/// Source of the candidate balances of the operation senders.
+///
+/// The returned future is polled by the pool worker alongside its other tasks,
+/// so it can safely query components that are themselves waiting on the pool.
+pub trait BalanceSource: Send + Sync {
+    /// Get the candidate balances of `addresses`. Missing addresses are considered empty.
+    /// `pending_operations` are the pending operations of those addresses:
+    /// the ones already included in active blocks are reported in the result.
+    fn get_balances(
+        &self,
+        addresses: Set<Address>,
+        pending_operations: Set<OperationId>,
+    ) -> BalancesFuture;
+}
+
+/// Result of the admission of a batch of operations in the pool
+#[derive(Debug, Default)]
+pub struct AdmissionOutcome {
+    /// operations that were not in the pool yet and were added to it
+    pub added: Set<OperationId>,
+    /// pending operations that were evicted by a new operation of the same sender paying a higher fee,
+    /// with the id of the operation that replaced them
+    pub replaced: Map<OperationId, OperationId>,
+    /// refused operations, with the reason why
+    pub rejected: Map<OperationId, String>,
+}
+
+/// Maximal amounts the sender of `operation` spends from its balances
+pub(crate) fn get_operation_spending(
+    operation: &WrappedOperation,
+    roll_price: Amount,
+) -> SenderBalances {
+    let mut spending = SenderBalances {
+        sequential: operation.content.fee,
+        parallel: Amount::default(),
+    };
+    match &operation.content.op {
+        OperationType::Transaction { amount, .. } => {
+            spending.sequential = spending.sequential.saturating_add(*amount);
+        }
+        OperationType::RollBuy { roll_count } => {
+            spending.sequential = spending
+                .sequential
+                .saturating_add(roll_price.saturating_mul_u64(*roll_count));
+        }
+        OperationType::RollSell { .. } => {}
+        OperationType::ExecuteSC { coins, .. } => {
+            spending.sequential = spending
+                .sequential
+                .saturating_add(operation.get_gas_coins())
+                .saturating_add(*coins);
+        }
+        OperationType::CallSC {
+            sequential_coins,
+            parallel_coins,
+            ..
+        } => {
+            spending.sequential = spending
+                .sequential
+                .saturating_add(operation.get_gas_coins())
+                .saturating_add(*sequential_coins);
+            spending.parallel = *parallel_coins;
+        }
+    }
+    spending
+}
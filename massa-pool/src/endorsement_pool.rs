@@ -105,6 +105,11 @@ impl EndorsementPool {
         Ok(newly_added)
     }
 
+    /// Get all the endorsements of the pool
+    pub fn get_all_endorsements(&self) -> Vec<WrappedEndorsement> {
+        self.endorsements.values().cloned().collect()
+    }
+
     pub fn update_current_slot(&mut self, slot: Slot) {
         self.current_slot = Some(slot);
     }
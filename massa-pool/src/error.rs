@@ -21,6 +21,10 @@ pub enum PoolError {
     ModelsError(#[from] ModelsError),
     /// missing operation error: {0}
     MissingOperation(String),
+    /// IO error: {0}
+    IOError(#[from] std::io::Error),
+    /// pool journal error: {0}
+    JournalError(String),
 }
 
 impl From<ProtocolError> for PoolError {
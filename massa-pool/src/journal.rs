@@ -0,0 +1,233 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! On-disk journal of the pool contents, so that pending operations and endorsements survive restarts.
+//!
+//! The journal starts with a header made of `JOURNAL_MAGIC` and `JOURNAL_VERSION`, so that a
+//! journal written in another format is refused instead of being misread. It then holds the
+//! number of operations followed by the serialized operations, then the same for endorsements.
+//! It is written periodically and when the pool stops.
+//! At startup, it is reloaded once the final periods are known and its content goes
+//! through the usual checks before entering the pool again.
+
+use crate::{settings::PoolConfig, PoolError};
+use massa_models::constants::ENDORSEMENT_COUNT;
+use massa_models::prehash::Map;
+use massa_models::wrapped::{WrappedDeserializer, WrappedSerializer};
+use massa_models::{
+    Endorsement, EndorsementDeserializer, EndorsementId, EndorsementSerializer, ModelsError,
+    Operation, OperationDeserializer, OperationId, WrappedEndorsement, WrappedOperation,
+};
+use massa_serialization::{
+    DeserializeError, Deserializer, SerializeError, Serializer, U64VarIntDeserializer,
+    U64VarIntSerializer,
+};
+use nom::bytes::complete::tag;
+use nom::error::{context, ContextError, ParseError};
+use nom::multi::length_count;
+use nom::sequence::tuple;
+use nom::{IResult, Parser};
+use std::ops::Bound::Included;
+use std::path::Path;
+use tracing::warn;
+
+/// Marker at the start of the journal file
+const JOURNAL_MAGIC: &[u8] = b"MASSA_POOL_JOURNAL";
+/// Version of the journal format, to increase whenever the serialization of its content changes
+const JOURNAL_VERSION: u8 = 1;
+
+/// Contents of the pool journal
+#[derive(Debug, Default)]
+pub(crate) struct PoolJournal {
+    /// pending operations
+    pub operations: Vec<WrappedOperation>,
+    /// pending endorsements
+    pub endorsements: Vec<WrappedEndorsement>,
+}
+
+/// Pool contents read from the journal, by id
+pub(crate) type JournalContents = (
+    Map<OperationId, WrappedOperation>,
+    Map<EndorsementId, WrappedEndorsement>,
+);
+
+/// Serializer for `PoolJournal`
+struct PoolJournalSerializer {
+    u64_serializer: U64VarIntSerializer,
+    wrapped_serializer: WrappedSerializer,
+}
+
+impl PoolJournalSerializer {
+    /// Creates a new `PoolJournalSerializer`
+    const fn new() -> Self {
+        Self {
+            u64_serializer: U64VarIntSerializer::new(),
+            wrapped_serializer: WrappedSerializer::new(),
+        }
+    }
+}
+
+impl Serializer<PoolJournal> for PoolJournalSerializer {
+    fn serialize(&self, value: &PoolJournal, buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
+        buffer.extend(JOURNAL_MAGIC);
+        buffer.push(JOURNAL_VERSION);
+        self.u64_serializer
+            .serialize(&(value.operations.len() as u64), buffer)?;
+        for operation in value.operations.iter() {
+            self.wrapped_serializer.serialize(operation, buffer)?;
+        }
+        self.u64_serializer
+            .serialize(&(value.endorsements.len() as u64), buffer)?;
+        for endorsement in value.endorsements.iter() {
+            self.wrapped_serializer.serialize(endorsement, buffer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Deserializer for `PoolJournal`
+struct PoolJournalDeserializer {
+    operation_count_deserializer: U64VarIntDeserializer,
+    endorsement_count_deserializer: U64VarIntDeserializer,
+    operation_deserializer: WrappedDeserializer<Operation, OperationDeserializer>,
+    endorsement_deserializer: WrappedDeserializer<Endorsement, EndorsementDeserializer>,
+}
+
+impl PoolJournalDeserializer {
+    /// Creates a new `PoolJournalDeserializer`
+    ///
+    /// # Arguments
+    /// * `max_operations`: maximal number of operations in the journal
+    /// * `max_endorsements`: maximal number of endorsements in the journal
+    const fn new(max_operations: u64, max_endorsements: u64) -> Self {
+        Self {
+            operation_count_deserializer: U64VarIntDeserializer::new(
+                Included(0),
+                Included(max_operations),
+            ),
+            endorsement_count_deserializer: U64VarIntDeserializer::new(
+                Included(0),
+                Included(max_endorsements),
+            ),
+            operation_deserializer: WrappedDeserializer::new(OperationDeserializer::new()),
+            endorsement_deserializer: WrappedDeserializer::new(EndorsementDeserializer::new(
+                ENDORSEMENT_COUNT,
+            )),
+        }
+    }
+}
+
+impl Deserializer<PoolJournal> for PoolJournalDeserializer {
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], PoolJournal, E> {
+        context(
+            "Failed PoolJournal deserialization",
+            tuple((
+                context(
+                    "Failed journal header deserialization: unknown journal format",
+                    tuple((tag(JOURNAL_MAGIC), tag(&[JOURNAL_VERSION]))),
+                ),
+                context(
+                    "Failed operations deserialization",
+                    length_count(
+                        |input| self.operation_count_deserializer.deserialize(input),
+                        |input| self.operation_deserializer.deserialize(input),
+                    ),
+                ),
+                context(
+                    "Failed endorsements deserialization",
+                    length_count(
+                        |input| self.endorsement_count_deserializer.deserialize(input),
+                        |input| self.endorsement_deserializer.deserialize(input),
+                    ),
+                ),
+            )),
+        )
+        .map(|(_, operations, endorsements)| PoolJournal {
+            operations,
+            endorsements,
+        })
+        .parse(buffer)
+    }
+}
+
+/// Saves the pool contents to the journal file.
+/// The journal is written to a temporary file first so that an interrupted dump does not corrupt it.
+///
+/// # Arguments
+/// * `journal`: pool contents
+/// * `file_path`: path to the journal file
+pub(crate) async fn dump_journal(journal: &PoolJournal, file_path: &Path) -> Result<(), PoolError> {
+    let mut buffer = Vec::new();
+    PoolJournalSerializer::new()
+        .serialize(journal, &mut buffer)
+        .map_err(ModelsError::from)?;
+    let tmp_path = file_path.with_extension("tmp");
+    tokio::fs::write(&tmp_path, buffer).await?;
+    tokio::fs::rename(&tmp_path, file_path).await?;
+    Ok(())
+}
+
+/// Reads the journal file and checks the integrity of its content.
+/// Returns empty maps if there is no journal yet. Items with an invalid signature are dropped.
+///
+/// # Arguments
+/// * `file_path`: path to the journal file
+/// * `cfg`: pool configuration, bounding the number of items in the journal
+pub(crate) async fn load_journal(
+    file_path: &Path,
+    cfg: &PoolConfig,
+) -> Result<JournalContents, PoolError> {
+    if !file_path.exists() {
+        return Ok(Default::default());
+    }
+    let buffer = tokio::fs::read(file_path).await?;
+    let (rest, journal) = PoolJournalDeserializer::new(
+        cfg.settings
+            .max_pool_size_per_thread
+            .saturating_mul(cfg.thread_count as u64),
+        cfg.settings.max_endorsement_count,
+    )
+    .deserialize::<DeserializeError>(&buffer)
+    .map_err(|err| PoolError::JournalError(err.to_string()))?;
+    if !rest.is_empty() {
+        return Err(PoolError::JournalError(
+            "there is data left after the journal deserialization".into(),
+        ));
+    }
+
+    let operation_count = journal.operations.len();
+    let operations: Map<OperationId, WrappedOperation> = journal
+        .operations
+        .into_iter()
+        .filter_map(|operation| {
+            operation
+                .verify_integrity()
+                .ok()
+                .map(|operation_id| (operation_id, operation))
+        })
+        .collect();
+    let endorsement_count = journal.endorsements.len();
+    let endorsements: Map<EndorsementId, WrappedEndorsement> = journal
+        .endorsements
+        .into_iter()
+        .filter(|endorsement| {
+            endorsement
+                .verify_signature(
+                    EndorsementSerializer::new(),
+                    &endorsement.creator_public_key,
+                )
+                .is_ok()
+        })
+        .map(|endorsement| (endorsement.id, endorsement))
+        .collect();
+    if operations.len() != operation_count || endorsements.len() != endorsement_count {
+        warn!(
+            "pool journal: dropped {} operations and {} endorsements with an invalid signature",
+            operation_count - operations.len(),
+            endorsement_count - endorsements.len()
+        );
+    }
+    Ok((operations, endorsements))
+}
@@ -8,13 +8,16 @@
 #[macro_use]
 extern crate massa_logging;
 
+pub use admission::{BalanceSource, BalancesFuture, CandidateBalances, SenderBalances};
 pub use error::PoolError;
 pub use pool_controller::{start_pool_controller, PoolCommandSender, PoolManager};
 pub use pool_worker::PoolCommand;
 pub use settings::{PoolConfig, PoolSettings};
 
+mod admission;
 mod endorsement_pool;
 mod error;
+mod journal;
 mod operation_pool;
 mod pool_controller;
 mod pool_worker;
@@ -1,15 +1,24 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::admission::{
+    get_operation_spending, AdmissionOutcome, CandidateBalances, SenderBalances,
+};
 use crate::{settings::PoolConfig, PoolError};
+use massa_hash::Hash;
+use massa_models::api::{PoolEvictionReason, PoolOperationEvent, PoolOperationFilter};
+use massa_models::operation::OperationTypeSerializer;
 use massa_models::prehash::{Map, Set};
+use massa_models::stats::{FeeDensityPercentiles, PoolFeeDensities};
 use massa_models::{
-    Address, OperationId, OperationSearchResult, OperationSearchResultStatus, Slot,
-    WrappedOperation,
+    Address, Amount, ModelsError, OperationId, OperationSearchResult, OperationSearchResultStatus,
+    Slot, WrappedOperation,
 };
+use massa_serialization::Serializer;
 use massa_storage::Storage;
 use num::rational::Ratio;
 use std::ops::RangeInclusive;
 use std::{collections::BTreeSet, usize};
+use tokio::sync::broadcast;
 
 struct OperationIndex(Map<Address, Set<OperationId>>);
 
@@ -28,6 +37,10 @@ impl OperationIndex {
         self.0.get(address)
     }
 
+    fn count_ops_for_address(&self, address: &Address) -> usize {
+        self.0.get(address).map_or(0, |ops| ops.len())
+    }
+
     fn remove_op_for_address(&mut self, address: &Address, op_id: &OperationId) {
         if let Some(old) = self.0.get_mut(address) {
             old.remove(op_id);
@@ -41,6 +54,14 @@ impl OperationIndex {
 struct OperationMetadata {
     byte_count: u64,
     thread: u8,
+    /// Address of the operation creator, paying for it
+    sender: Address,
+    /// Maximal amounts spent by the sender
+    spending: SenderBalances,
+    /// Fee paid to the block producer
+    fee: Amount,
+    /// Hash of the type specific content: operations of the same sender with the same key replace each other
+    replacement_key: Hash,
     /// After `expire_period` slot the operation won't be included in a block.
     expire_period: u64,
     /// The addresses that are involved in this operation from a ledger point of view.
@@ -53,7 +74,12 @@ struct OperationMetadata {
 }
 
 impl OperationMetadata {
-    fn new(operation: &WrappedOperation, byte_count: u64, operation_validity_periods: u64) -> Self {
+    fn new(
+        operation: &WrappedOperation,
+        byte_count: u64,
+        operation_validity_periods: u64,
+        roll_price: Amount,
+    ) -> Result<Self, PoolError> {
         // Fee density
         // add inclusion fee and gas fees
         let total_return = operation
@@ -65,14 +91,23 @@ impl OperationMetadata {
         let thread = operation.thread;
         let ledger_involved_addresses = operation.get_ledger_involved_addresses();
         let validity_range = operation.get_validity_range(operation_validity_periods);
-        OperationMetadata {
+        // computed from the signed content, so that every node receiving the operation replaces the same ones
+        let mut replacement_bytes = Vec::new();
+        OperationTypeSerializer::new()
+            .serialize(&operation.content.op, &mut replacement_bytes)
+            .map_err(ModelsError::from)?;
+        Ok(OperationMetadata {
             byte_count,
             thread,
+            sender: operation.creator_address,
+            spending: get_operation_spending(operation, roll_price),
+            fee: operation.content.fee,
+            replacement_key: Hash::compute_from(&replacement_bytes),
             expire_period: operation.content.expire_period,
             ledger_involved_addresses,
             fee_density,
             validity_range,
-        }
+        })
     }
 }
 
@@ -83,6 +118,10 @@ pub struct OperationPool {
         Vec<BTreeSet<(std::cmp::Reverse<num::rational::Ratio<u64>>, OperationId)>>, // [thread][order by: (rev rentability, OperationId)]
     /// Maps Address -> Op id
     ops_by_address: OperationIndex,
+    /// Maps sender Address -> Op id
+    ops_by_sender: OperationIndex,
+    /// Total spending of the operations in the pool, by sender
+    spending_by_sender: Map<Address, SenderBalances>,
     /// latest final blocks periods
     last_final_periods: Vec<u64>,
     /// current slot
@@ -93,6 +132,8 @@ pub struct OperationPool {
     final_operations: Map<OperationId, (u64, u8)>,
     /// Shared storage.
     storage: Storage,
+    /// Operations entering and leaving the pool
+    events: broadcast::Sender<PoolOperationEvent>,
 }
 
 impl OperationPool {
@@ -105,39 +146,76 @@ impl OperationPool {
             cfg,
             final_operations: Default::default(),
             ops_by_address: OperationIndex::new(),
+            ops_by_sender: OperationIndex::new(),
+            spending_by_sender: Default::default(),
             storage,
+            events: broadcast::channel(cfg.settings.operation_event_channel_size).0,
         }
     }
 
+    /// Subscribe to the operations entering and leaving the pool
+    pub fn subscribe_events(&self) -> broadcast::Receiver<PoolOperationEvent> {
+        self.events.subscribe()
+    }
+
+    /// Notify the subscribers, if any, of a pool change
+    fn notify(&self, event: PoolOperationEvent) {
+        // an error only means that nobody listens
+        let _ = self.events.send(event);
+    }
+
     /// Process incoming operations.
-    /// Returns newly added.
+    ///
+    /// # Arguments
+    /// * `operations`: operations to add to the pool
+    /// * `balances`: candidate balances of the senders of the operations, `None` to skip the balance checks.
+    ///   The spending of the pending operations already included in active blocks is not subtracted from them.
+    ///
+    /// An operation replaces a pending operation of the same sender having the same
+    /// type specific content if it pays a higher fee, and is refused otherwise.
+    /// An operation that does not replace another one is refused if its sender
+    /// already has `max_operations_per_sender` pending operations.
+    ///
+    /// Returns the newly added operations, the replaced ones and the refused ones.
+    /// Operations already in the pool or already final are neither added nor refused.
     pub fn process_operations(
         &mut self,
-        mut operations: Map<OperationId, WrappedOperation>,
-    ) -> Result<Set<OperationId>, PoolError> {
-        let mut removed = Set::<OperationId>::default();
+        operations: Map<OperationId, WrappedOperation>,
+        balances: Option<&CandidateBalances>,
+    ) -> Result<AdmissionOutcome, PoolError> {
+        let mut outcome = AdmissionOutcome::default();
         for (op_id, op) in operations.iter() {
             massa_trace!("pool add_operations op", { "op_id": op_id });
 
             // Already present
             if self.ops.contains_key(op_id) {
                 massa_trace!("pool add_operations op already present", {});
-                removed.insert(*op_id);
                 continue;
             }
 
             // already final
             if self.final_operations.contains_key(op_id) {
                 massa_trace!("pool add_operations op already final", {});
-                removed.insert(*op_id);
+                continue;
+            }
+
+            // operation types forbidden to the creator address
+            if let Err(err) = op.check_multisig_rules() {
+                massa_trace!("pool add_operations op forbidden to its creator", {});
+                outcome.rejected.insert(*op_id, err.to_string());
                 continue;
             }
 
             // wrap
             let operation_validity_periods = self.cfg.operation_validity_periods;
             let (wrapped_op, validity_start_period) = {
-                let byte_count = op.serialized_data.len() as u64;
-                let wrapped = OperationMetadata::new(op, byte_count, operation_validity_periods);
+                let byte_count = op.serialized_size() as u64;
+                let wrapped = OperationMetadata::new(
+                    op,
+                    byte_count,
+                    operation_validity_periods,
+                    self.cfg.roll_price,
+                )?;
                 let validity_range = op.get_validity_range(operation_validity_periods);
                 let validity_start_period = validity_range.start();
                 (wrapped, *validity_start_period)
@@ -161,7 +239,13 @@ impl OperationPool {
                         "range": validity_start_period.saturating_sub(cur_period_in_thread),
                         "max_operation_future_validity_start_periods": self.cfg.settings.max_operation_future_validity_start_periods
                     });
-                    removed.insert(*op_id);
+                    outcome.rejected.insert(
+                        *op_id,
+                        format!(
+                            "validity starts at period {} which is too far in the future",
+                            validity_start_period
+                        ),
+                    );
                     continue;
                 }
             }
@@ -172,10 +256,120 @@ impl OperationPool {
                     "expire_period": wrapped_op.expire_period,
                     "self.last_final_periods[wrapped_op.thread as usize]": self.last_final_periods[wrapped_op.thread as usize]
                 });
-                removed.insert(*op_id);
+                outcome.rejected.insert(
+                    *op_id,
+                    format!("expired at period {}", wrapped_op.expire_period),
+                );
                 continue;
             }
 
+            // look for a pending operation of the same sender that this one replaces
+            let replaced = self
+                .ops_by_sender
+                .get_ops_for_address(&wrapped_op.sender)
+                .and_then(|sender_ops| {
+                    sender_ops.iter().find_map(|pending_id| {
+                        self.ops
+                            .get(pending_id)
+                            .filter(|pending| pending.replacement_key == wrapped_op.replacement_key)
+                            .map(|pending| (*pending_id, pending.fee, pending.spending))
+                    })
+                });
+            if let Some((replaced_id, replaced_fee, _)) = replaced {
+                if wrapped_op.fee <= replaced_fee {
+                    massa_trace!("pool add_operations replacement fee too low", {
+                        "replaced_id": replaced_id
+                    });
+                    outcome.rejected.insert(
+                        *op_id,
+                        format!(
+                            "it replaces pending operation {} but does not pay a higher fee than {}",
+                            replaced_id, replaced_fee
+                        ),
+                    );
+                    continue;
+                }
+            } else if self.ops_by_sender.count_ops_for_address(&wrapped_op.sender)
+                >= self.cfg.settings.max_operations_per_sender as usize
+            {
+                massa_trace!("pool add_operations too many operations for sender", {
+                    "sender": wrapped_op.sender
+                });
+                outcome.rejected.insert(
+                    *op_id,
+                    format!(
+                        "sender {} already has {} pending operations",
+                        wrapped_op.sender, self.cfg.settings.max_operations_per_sender
+                    ),
+                );
+                continue;
+            }
+
+            // check if the sender can pay for it on top of its other pending operations
+            if let Some(balances) = balances {
+                let mut pending_spending = self
+                    .spending_by_sender
+                    .get(&wrapped_op.sender)
+                    .copied()
+                    .unwrap_or_default();
+                if let Some(sender_ops) = self.ops_by_sender.get_ops_for_address(&wrapped_op.sender)
+                {
+                    for included_id in sender_ops
+                        .iter()
+                        .filter(|id| balances.included_operations.contains(*id))
+                    {
+                        if let Some(included) = self.ops.get(included_id) {
+                            pending_spending = pending_spending.saturating_sub(included.spending);
+                        }
+                    }
+                }
+                if let Some((replaced_id, _, replaced_spending)) = replaced {
+                    if !balances.included_operations.contains(&replaced_id) {
+                        pending_spending = pending_spending.saturating_sub(replaced_spending);
+                    }
+                }
+                let available = balances
+                    .balances
+                    .get(&wrapped_op.sender)
+                    .copied()
+                    .unwrap_or_default()
+                    .saturating_sub(pending_spending);
+                if wrapped_op.spending.sequential > available.sequential
+                    || wrapped_op.spending.parallel > available.parallel
+                {
+                    massa_trace!("pool add_operations insufficient balance", {
+                        "sender": wrapped_op.sender
+                    });
+                    outcome.rejected.insert(
+                        *op_id,
+                        format!(
+                            "sender {} cannot pay for it: it may spend {} sequential and {} parallel coins but only {} sequential and {} parallel coins are available once its other pending operations are paid",
+                            wrapped_op.sender,
+                            wrapped_op.spending.sequential,
+                            wrapped_op.spending.parallel,
+                            available.sequential,
+                            available.parallel
+                        ),
+                    );
+                    continue;
+                }
+            }
+
+            // evict the replaced operation
+            if let Some((replaced_id, _, _)) = replaced {
+                massa_trace!("pool add_operations replace operation", {
+                    "replaced_id": replaced_id
+                });
+                self.remove_ops(vec![replaced_id])?;
+                if !outcome.added.remove(&replaced_id) {
+                    self.notify(PoolOperationEvent::Removed {
+                        id: replaced_id,
+                        reason: PoolEvictionReason::Replaced(*op_id),
+                    });
+                }
+                outcome.replaced.insert(replaced_id, *op_id);
+            }
+
             // insert
             let interest = (std::cmp::Reverse(wrapped_op.fee_density), *op_id);
 
@@ -186,8 +380,15 @@ impl OperationPool {
                 .for_each(|addr| {
                     self.ops_by_address.insert_op(*addr, *op_id);
                 });
+            self.ops_by_sender.insert_op(wrapped_op.sender, *op_id);
+            let sender_spending = self
+                .spending_by_sender
+                .entry(wrapped_op.sender)
+                .or_default();
+            *sender_spending = sender_spending.saturating_add(wrapped_op.spending);
             self.ops.insert(*op_id, wrapped_op);
             self.storage.store_operation(op.clone());
+            outcome.added.insert(*op_id);
         }
 
         // remove excess operations if pool is full
@@ -200,24 +401,51 @@ impl OperationPool {
                     .pop_last()
                     .unwrap(); // will not panic because of the while condition. complexity = log or better
                 if let Some(removed_op) = self.ops.remove(&removed_id) {
-                    // complexity: const
-                    for addr in removed_op.ledger_involved_addresses {
-                        self.ops_by_address
-                            .remove_op_for_address(&addr, &removed_id);
-                    }
+                    self.unindex_operation(&removed_id, removed_op);
                     self.storage.remove_operations(vec![removed_id].as_slice());
                 }
-                operations.remove(&removed_id);
+                if outcome.added.remove(&removed_id) {
+                    outcome.rejected.insert(
+                        removed_id,
+                        "the pool is full of operations with a higher fee density".to_string(),
+                    );
+                } else {
+                    self.notify(PoolOperationEvent::Removed {
+                        id: removed_id,
+                        reason: PoolEvictionReason::PoolFull,
+                    });
+                }
             }
         }
 
-        let newly_added_ids = operations
-            .keys()
-            .filter(|id| !removed.contains(id))
-            .copied()
-            .collect();
+        for op_id in outcome.added.iter() {
+            if let Some(wrapped_op) = self.ops.get(op_id) {
+                self.notify(PoolOperationEvent::Added {
+                    id: *op_id,
+                    thread: wrapped_op.thread,
+                    sender: wrapped_op.sender,
+                    fee: wrapped_op.fee,
+                });
+            }
+        }
 
-        Ok(newly_added_ids)
+        Ok(outcome)
+    }
+
+    /// Remove an operation that was just removed from `ops` from the address and sender indexes
+    fn unindex_operation(&mut self, op_id: &OperationId, wrapped_op: OperationMetadata) {
+        // complexity: const
+        for addr in wrapped_op.ledger_involved_addresses {
+            self.ops_by_address.remove_op_for_address(&addr, op_id);
+        }
+        self.ops_by_sender
+            .remove_op_for_address(&wrapped_op.sender, op_id);
+        if let Some(spending) = self.spending_by_sender.get_mut(&wrapped_op.sender) {
+            *spending = spending.saturating_sub(wrapped_op.spending);
+            if *spending == SenderBalances::default() {
+                self.spending_by_sender.remove(&wrapped_op.sender);
+            }
+        }
     }
 
     pub fn new_final_operations(
@@ -228,9 +456,11 @@ impl OperationPool {
             if let Some(wrapped) = self.ops.remove(id) {
                 self.ops_by_thread_and_interest[wrapped.thread as usize]
                     .remove(&(std::cmp::Reverse(wrapped.fee_density), *id));
-                for addr in wrapped.ledger_involved_addresses {
-                    self.ops_by_address.remove_op_for_address(&addr, id);
-                }
+                self.unindex_operation(id, wrapped);
+                self.notify(PoolOperationEvent::Removed {
+                    id: *id,
+                    reason: PoolEvictionReason::Final,
+                });
             } // else final op wasn't in pool.
         }
         self.storage
@@ -239,6 +469,21 @@ impl OperationPool {
         Ok(())
     }
 
+    /// Get the pending operations sent by `senders`
+    pub fn get_sender_operations(&self, senders: &Set<Address>) -> Set<OperationId> {
+        senders
+            .iter()
+            .filter_map(|sender| self.ops_by_sender.get_ops_for_address(sender))
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    /// Whether the operation is already in the pool or known to be final
+    pub fn is_known(&self, op_id: &OperationId) -> bool {
+        self.ops.contains_key(op_id) || self.final_operations.contains_key(op_id)
+    }
+
     pub fn update_current_slot(&mut self, slot: Slot) {
         self.current_slot = Some(slot);
     }
@@ -248,7 +493,7 @@ impl OperationPool {
     }
 
     fn prune(&mut self) -> Result<(), PoolError> {
-        let ids = self
+        let ids: Vec<OperationId> = self
             .ops
             .iter()
             .filter(|(_id, w_op)| {
@@ -257,7 +502,13 @@ impl OperationPool {
             .map(|(id, _)| *id)
             .collect();
 
-        self.remove_ops(ids)?;
+        self.remove_ops(ids.clone())?;
+        for id in ids {
+            self.notify(PoolOperationEvent::Removed {
+                id,
+                reason: PoolEvictionReason::Expired,
+            });
+        }
 
         let ids = self
             .final_operations
@@ -290,9 +541,7 @@ impl OperationPool {
                 self.ops_by_thread_and_interest[wrapped_op.thread as usize].remove(&interest);
                 // complexity: log
 
-                for addr in wrapped_op.ledger_involved_addresses {
-                    self.ops_by_address.remove_op_for_address(&addr, &op_id);
-                }
+                self.unindex_operation(&op_id, wrapped_op);
             }
         }
         self.storage.remove_operations(&op_ids);
@@ -341,6 +590,106 @@ impl OperationPool {
             .collect()
     }
 
+    /// Get the fee densities of the pending operations of each thread.
+    ///
+    /// # Arguments
+    /// * `inclusion_capacity`: number of operations that the blocks of a thread can include
+    ///   during the period range considered for inclusion
+    pub fn get_fee_densities(&self, inclusion_capacity: usize) -> Vec<PoolFeeDensities> {
+        self.ops_by_thread_and_interest
+            .iter()
+            .map(|thread_ops| {
+                // ordered by decreasing fee density
+                let densities: Vec<Amount> = thread_ops
+                    .iter()
+                    .map(|(std::cmp::Reverse(fee_density), _id)| {
+                        Amount::from_raw(fee_density.to_integer())
+                    })
+                    .collect();
+                let inclusion_threshold =
+                    densities.get(inclusion_capacity.saturating_sub(1)).copied();
+                PoolFeeDensities {
+                    percentiles: FeeDensityPercentiles::from_densities(densities),
+                    inclusion_threshold,
+                }
+            })
+            .collect()
+    }
+
+    /// Get the pending operations matching a filter, by thread then by decreasing fee density.
+    /// At most `max_item_return_count` operations are returned.
+    /// The search stops as soon as the requested page is full, and only goes through
+    /// the operations of the sender when the filter has one.
+    ///
+    /// Returns whether more operations match after the requested page, and the requested page.
+    pub fn get_filtered_operations(
+        &self,
+        filter: &PoolOperationFilter,
+    ) -> (bool, Vec<WrappedOperation>) {
+        let limit = filter
+            .limit
+            .unwrap_or(self.cfg.settings.max_item_return_count)
+            .min(self.cfg.settings.max_item_return_count);
+        let candidates: Box<dyn Iterator<Item = OperationId> + '_> = match filter.sender {
+            Some(sender) => {
+                // the operations of a sender are all in its thread: sort them by decreasing fee density
+                let mut sender_ops: Vec<_> = self
+                    .ops_by_sender
+                    .get_ops_for_address(&sender)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|op_id| {
+                        self.ops
+                            .get(op_id)
+                            .map(|op| (std::cmp::Reverse(op.fee_density), *op_id))
+                    })
+                    .collect();
+                sender_ops.sort_unstable();
+                Box::new(sender_ops.into_iter().map(|(_rentability, op_id)| op_id))
+            }
+            None => Box::new(
+                self.ops_by_thread_and_interest
+                    .iter()
+                    .enumerate()
+                    .filter(|(thread, _)| filter.thread.map_or(true, |t| t as usize == *thread))
+                    .flat_map(|(_, thread_ops)| {
+                        thread_ops.iter().map(|(_rentability, op_id)| *op_id)
+                    }),
+            ),
+        };
+        let mut skipped: usize = 0;
+        let mut operations = Vec::new();
+        for op_id in candidates {
+            let matching = self.storage.with_operation(&op_id, |op| {
+                op.map_or(false, |op| {
+                    filter.matches(op, self.cfg.operation_validity_periods)
+                })
+            });
+            if !matching {
+                continue;
+            }
+            if skipped < filter.offset {
+                skipped += 1;
+                continue;
+            }
+            if operations.len() >= limit {
+                return (true, operations);
+            }
+            if let Some(op) = self.storage.retrieve_operation(&op_id) {
+                operations.push(op);
+            }
+        }
+        (false, operations)
+    }
+
+    /// Get all the operations of the pool
+    pub fn get_all_operations(&self) -> Vec<WrappedOperation> {
+        self.ops
+            .keys()
+            .filter_map(|op_id| self.storage.retrieve_operation(op_id))
+            .collect()
+    }
+
     pub fn get_operations(
         &self,
         operation_ids: &Set<OperationId>,
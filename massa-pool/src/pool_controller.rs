@@ -1,6 +1,6 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use crate::settings::PoolConfig;
+use crate::{admission::BalanceSource, journal::load_journal, settings::PoolConfig};
 
 use super::{
     error::PoolError,
@@ -8,19 +8,20 @@ use super::{
 };
 use massa_logging::massa_trace;
 use massa_models::{
+    api::{PoolOperationEvent, PoolOperationFilter},
     constants::CHANNEL_SIZE,
     prehash::{Map, Set},
-    stats::PoolStats,
+    stats::{PoolFeeDensities, PoolStats},
     Address, BlockId, EndorsementId, OperationId, OperationSearchResult, Slot, WrappedEndorsement,
     WrappedOperation,
 };
 use massa_protocol_exports::{ProtocolCommandSender, ProtocolPoolEventReceiver};
 use massa_storage::Storage;
 use tokio::{
-    sync::{mpsc, oneshot},
+    sync::{broadcast, mpsc, oneshot},
     task::JoinHandle,
 };
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 /// Creates a new pool controller.
 ///
@@ -28,6 +29,10 @@ use tracing::{debug, error, info};
 /// * `pool_settings`: pool configuration
 /// * `protocol_command_sender`: a `ProtocolCommandSender` instance to send commands to Protocol.
 /// * `protocol_pool_event_receiver`: a `ProtocolPoolEventReceiver` instance to receive pool events from `Protocol`.
+/// * `storage`: shared storage
+///
+/// If a journal file is configured, the pool contents saved before the last stop are reloaded
+/// once the pool receives the final periods.
 pub async fn start_pool_controller(
     cfg: &'static PoolConfig,
     protocol_command_sender: ProtocolCommandSender,
@@ -37,6 +42,21 @@ pub async fn start_pool_controller(
     debug!("starting pool controller");
     massa_trace!("pool.pool_controller.start_pool_controller", {});
 
+    // read the pool contents saved before the last stop
+    let journal_to_reload = match &cfg.settings.journal_file {
+        Some(journal_file) => match load_journal(journal_file, cfg).await {
+            Ok(journal_contents) => Some(journal_contents),
+            Err(err) => {
+                warn!(
+                    "could not load the pool journal, starting with an empty pool: {}",
+                    err
+                );
+                None
+            }
+        },
+        None => None,
+    };
+
     // start worker
     let (command_tx, command_rx) = mpsc::channel::<PoolCommand>(CHANNEL_SIZE);
     let (manager_tx, manager_rx) = mpsc::channel::<PoolManagementCommand>(1);
@@ -48,6 +68,7 @@ pub async fn start_pool_controller(
             command_rx,
             manager_rx,
             storage,
+            journal_to_reload,
         )?
         .run_loop()
         .await;
@@ -77,15 +98,39 @@ pub struct PoolCommandSender(pub mpsc::Sender<PoolCommand>);
 
 impl PoolCommandSender {
     /// add operations to pool
+    /// Returns the refused operations, with the reason why
     pub async fn add_operations(
         &mut self,
         operations: Map<OperationId, WrappedOperation>,
-    ) -> Result<(), PoolError> {
+    ) -> Result<Map<OperationId, String>, PoolError> {
         massa_trace!("pool.command_sender.add_operations", { "ops": operations });
+        let (response_tx, response_rx) = oneshot::channel();
+        self.0
+            .send(PoolCommand::AddOperations {
+                operations,
+                response_tx,
+            })
+            .await
+            .map_err(|_| PoolError::ChannelError("add_operations command send error".into()))?;
+        response_rx.await.map_err(|e| {
+            PoolError::ChannelError(format!(
+                "pool command response read error in add_operations {}",
+                e
+            ))
+        })
+    }
+
+    /// set the source of the balances used to check that operation senders can pay for their operations.
+    /// Until then, operations are admitted without balance checks.
+    pub async fn set_balance_source(
+        &mut self,
+        balance_source: Box<dyn BalanceSource>,
+    ) -> Result<(), PoolError> {
+        massa_trace!("pool.command_sender.set_balance_source", {});
         self.0
-            .send(PoolCommand::AddOperations(operations))
+            .send(PoolCommand::SetBalanceSource(balance_source))
             .await
-            .map_err(|_| PoolError::ChannelError("add_operations command send error".into()))
+            .map_err(|_| PoolError::ChannelError("set_balance_source command send error".into()))
     }
 
     /// update current slots
@@ -114,6 +159,80 @@ impl PoolCommandSender {
         })
     }
 
+    /// get the fee densities of the pending operations of each thread
+    ///
+    /// # Arguments
+    /// * `inclusion_capacity`: number of operations that the blocks of a thread can include during the considered periods
+    pub async fn get_fee_densities(
+        &mut self,
+        inclusion_capacity: usize,
+    ) -> Result<Vec<PoolFeeDensities>, PoolError> {
+        massa_trace!("pool.command_sender.get_fee_densities", {});
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.0
+            .send(PoolCommand::GetFeeDensities {
+                inclusion_capacity,
+                response_tx,
+            })
+            .await
+            .map_err(|_| PoolError::ChannelError("get_fee_densities command send error".into()))?;
+        response_rx.await.map_err(|e| {
+            PoolError::ChannelError(format!(
+                "pool command response read error in get_fee_densities {}",
+                e
+            ))
+        })
+    }
+
+    /// Get the pending operations matching a filter, by thread then by decreasing fee density.
+    /// Returns whether more operations match after the requested page, and the requested page.
+    pub async fn get_filtered_operations(
+        &mut self,
+        filter: PoolOperationFilter,
+    ) -> Result<(bool, Vec<WrappedOperation>), PoolError> {
+        massa_trace!("pool.command_sender.get_filtered_operations", {});
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.0
+            .send(PoolCommand::GetFilteredOperations {
+                filter,
+                response_tx,
+            })
+            .await
+            .map_err(|_| {
+                PoolError::ChannelError("get_filtered_operations command send error".into())
+            })?;
+        response_rx.await.map_err(|e| {
+            PoolError::ChannelError(format!(
+                "pool command response read error in get_filtered_operations {}",
+                e
+            ))
+        })
+    }
+
+    /// Subscribe to the operations entering and leaving the pool.
+    /// A subscriber that lags behind by more than `operation_event_channel_size` events misses the oldest ones.
+    pub async fn subscribe_operation_events(
+        &mut self,
+    ) -> Result<broadcast::Receiver<PoolOperationEvent>, PoolError> {
+        massa_trace!("pool.command_sender.subscribe_operation_events", {});
+        let (response_tx, response_rx) = oneshot::channel();
+
+        self.0
+            .send(PoolCommand::SubscribeOperationEvents(response_tx))
+            .await
+            .map_err(|_| {
+                PoolError::ChannelError("subscribe_operation_events command send error".into())
+            })?;
+        response_rx.await.map_err(|e| {
+            PoolError::ChannelError(format!(
+                "pool command response read error in subscribe_operation_events {}",
+                e
+            ))
+        })
+    }
+
     /// mark operations as final
     pub async fn final_operations(
         &mut self,
@@ -1,24 +1,36 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use super::error::PoolError;
+use crate::admission::{BalanceSource, CandidateBalances};
+use crate::journal::{dump_journal, JournalContents, PoolJournal};
 use crate::operation_pool::OperationPool;
 use crate::{endorsement_pool::EndorsementPool, settings::PoolConfig};
+use futures::{future::BoxFuture, stream::FuturesUnordered, StreamExt};
+use massa_models::api::{PoolOperationEvent, PoolOperationFilter};
 use massa_models::prehash::{Map, Set};
-use massa_models::stats::PoolStats;
+use massa_models::stats::{PoolFeeDensities, PoolStats};
 use massa_models::{
     Address, BlockId, EndorsementId, OperationId, OperationSearchResult, Slot, WrappedEndorsement,
     WrappedOperation,
 };
 use massa_protocol_exports::{ProtocolCommandSender, ProtocolPoolEvent, ProtocolPoolEventReceiver};
 use massa_storage::Storage;
-use tokio::sync::{mpsc, oneshot};
-use tracing::warn;
+use tokio::sync::{broadcast, mpsc, oneshot};
+use tokio::time::sleep;
+use tracing::{debug, info, warn};
 
 /// Commands that can be processed by pool.
 #[derive(Debug)]
 pub enum PoolCommand {
     /// Add operations to the pool
-    AddOperations(Map<OperationId, WrappedOperation>),
+    AddOperations {
+        /// operations to add
+        operations: Map<OperationId, WrappedOperation>,
+        /// response channel: refused operations, with the reason why
+        response_tx: oneshot::Sender<Map<OperationId, String>>,
+    },
+    /// Set the source of the balances used to check that operation senders can pay for their operations
+    SetBalanceSource(Box<dyn BalanceSource>),
     /// current slot update
     UpdateCurrentSlot(Slot),
     /// Latest final periods update
@@ -68,6 +80,22 @@ pub enum PoolCommand {
     AddEndorsements(Map<EndorsementId, WrappedEndorsement>),
     /// get pool stats
     GetStats(oneshot::Sender<PoolStats>),
+    /// get the fee densities of the pending operations of each thread
+    GetFeeDensities {
+        /// number of operations that the blocks of a thread can include during the considered periods
+        inclusion_capacity: usize,
+        /// response channel
+        response_tx: oneshot::Sender<Vec<PoolFeeDensities>>,
+    },
+    /// get the pending operations matching a filter
+    GetFilteredOperations {
+        /// filter and pagination
+        filter: PoolOperationFilter,
+        /// response channel: whether more operations match after the requested page, and the requested page
+        response_tx: oneshot::Sender<(bool, Vec<WrappedOperation>)>,
+    },
+    /// subscribe to the operations entering and leaving the pool
+    SubscribeOperationEvents(oneshot::Sender<broadcast::Receiver<PoolOperationEvent>>),
     /// get endorsements by address
     GetEndorsementsByAddress {
         /// address
@@ -84,12 +112,32 @@ pub enum PoolCommand {
     },
 }
 
+impl std::fmt::Debug for dyn BalanceSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BalanceSource")
+    }
+}
+
+/// Operations waiting for the balances of their senders before being processed
+struct PendingAdmission {
+    /// operations to add
+    operations: Map<OperationId, WrappedOperation>,
+    /// whether to propagate the newly added operations
+    propagate: bool,
+    /// channel on which to send the refused operations
+    response_tx: Option<oneshot::Sender<Map<OperationId, String>>>,
+    /// balances of the senders, `None` to skip the balance checks
+    balances: Option<CandidateBalances>,
+}
+
 /// Events that are emitted by pool.
 #[derive(Debug, Clone)]
 pub enum PoolManagementCommand {}
 
 /// Manages pool.
 pub struct PoolWorker {
+    /// Pool configuration.
+    cfg: &'static PoolConfig,
     /// Associated protocol command sender.
     protocol_command_sender: ProtocolCommandSender,
     /// Associated protocol pool event listener.
@@ -102,6 +150,15 @@ pub struct PoolWorker {
     operation_pool: OperationPool,
     /// Endorsement pool.
     endorsement_pool: EndorsementPool,
+    /// Source of the balances of the operation senders, if already available
+    balance_source: Option<Box<dyn BalanceSource>>,
+    /// Operations waiting for the balances of their senders,
+    /// at most `max_pending_admissions` batches at a time
+    pending_admissions: FuturesUnordered<BoxFuture<'static, PendingAdmission>>,
+    /// Journaled pool contents, reloaded once the final periods and the balance source are known
+    journal_to_reload: Option<JournalContents>,
+    /// Whether the latest final periods were received
+    final_periods_known: bool,
 }
 
 impl PoolWorker {
@@ -116,6 +173,8 @@ impl PoolWorker {
     /// * `protocol_command_sender`: protocol pool event receiver
     /// * `controller_command_rx`: Channel receiving pool commands.
     /// * `controller_manager_rx`: Channel receiving pool management commands.
+    /// * `storage`: shared storage
+    /// * `journal_to_reload`: pool contents read from the journal, if any
     pub fn new(
         cfg: &'static PoolConfig,
         protocol_command_sender: ProtocolCommandSender,
@@ -123,21 +182,29 @@ impl PoolWorker {
         controller_command_rx: mpsc::Receiver<PoolCommand>,
         controller_manager_rx: mpsc::Receiver<PoolManagementCommand>,
         storage: Storage,
+        journal_to_reload: Option<JournalContents>,
     ) -> Result<PoolWorker, PoolError> {
         massa_trace!("pool.pool_worker.new", {});
         Ok(PoolWorker {
+            cfg,
             protocol_command_sender,
             protocol_pool_event_receiver,
             controller_command_rx,
             controller_manager_rx,
             operation_pool: OperationPool::new(cfg, storage),
             endorsement_pool: EndorsementPool::new(cfg),
+            balance_source: None,
+            pending_admissions: FuturesUnordered::new(),
+            journal_to_reload,
+            final_periods_known: false,
         })
     }
 
     /// Pool work is managed here.
     /// It's mostly a tokio::select within a loop.
     pub async fn run_loop(mut self) -> Result<ProtocolPoolEventReceiver, PoolError> {
+        let journal_timer = sleep(self.cfg.settings.journal_dump_interval.to_duration());
+        tokio::pin!(journal_timer);
         loop {
             massa_trace!("pool.pool_worker.run_loop.select", {});
             /*
@@ -146,7 +213,9 @@ impl PoolWorker {
                 We choose this order:
                     * manager commands: low freq, avoid having to wait to stop
                     * pool commands (low to medium freq): respond quickly to consensus to avoid blocking it
+                    * operations whose sender balances were received
                     * protocol commands (high frequency): process incoming protocol objects
+                    * journal timer (low freq): save the pool contents
             */
             tokio::select! {
                 // listen to manager commands
@@ -163,6 +232,12 @@ impl PoolWorker {
                     self.process_pool_command(cmd).await?
                 },
 
+                // balances of operation senders received
+                Some(admission) = self.pending_admissions.next() => {
+                    massa_trace!("pool.pool_worker.run_loop.select.admission", {});
+                    self.finish_admission(admission).await?
+                },
+
                 // receive protocol controller pool events
                 evt = self.protocol_pool_event_receiver.wait_event() => {
                     massa_trace!("pool.pool_worker.run_loop.select.protocol_event", {});
@@ -172,9 +247,17 @@ impl PoolWorker {
                         Err(err) => return Err(PoolError::ProtocolError(Box::new(err)))
                     }
                 },
+
+                // save the pool contents
+                _ = &mut journal_timer, if self.cfg.settings.journal_file.is_some() => {
+                    massa_trace!("pool.pool_worker.run_loop.select.journal_timer", {});
+                    self.save_journal().await;
+                    journal_timer.set(sleep(self.cfg.settings.journal_dump_interval.to_duration()));
+                },
             }
         }
         // end loop
+        self.save_journal().await;
         Ok(self.protocol_pool_event_receiver)
     }
 
@@ -184,13 +267,16 @@ impl PoolWorker {
     /// * `cmd`: consensus command to process
     async fn process_pool_command(&mut self, cmd: PoolCommand) -> Result<(), PoolError> {
         match cmd {
-            PoolCommand::AddOperations(operations) => {
-                let newly_added = self.operation_pool.process_operations(operations)?;
-                if !newly_added.is_empty() {
-                    self.protocol_command_sender
-                        .propagate_operations(newly_added)
-                        .await?;
-                }
+            PoolCommand::AddOperations {
+                operations,
+                response_tx,
+            } => {
+                self.admit_operations(operations, true, Some(response_tx))
+                    .await?
+            }
+            PoolCommand::SetBalanceSource(balance_source) => {
+                self.balance_source = Some(balance_source);
+                self.reload_journal().await?;
             }
             PoolCommand::UpdateCurrentSlot(slot) => {
                 self.operation_pool.update_current_slot(slot);
@@ -199,7 +285,9 @@ impl PoolWorker {
             PoolCommand::UpdateLatestFinalPeriods(periods) => {
                 self.operation_pool
                     .update_latest_final_periods(periods.clone())?;
-                self.endorsement_pool.update_latest_final_periods(periods)
+                self.endorsement_pool.update_latest_final_periods(periods);
+                self.final_periods_known = true;
+                self.reload_journal().await?;
             }
             PoolCommand::GetOperationBatch {
                 target_slot,
@@ -284,6 +372,36 @@ impl PoolWorker {
                     warn!("pool: could not send PoolStats response");
                 }
             }
+            PoolCommand::GetFeeDensities {
+                inclusion_capacity,
+                response_tx,
+            } => {
+                if response_tx
+                    .send(self.operation_pool.get_fee_densities(inclusion_capacity))
+                    .is_err()
+                {
+                    warn!("pool: could not send get_fee_densities response");
+                }
+            }
+            PoolCommand::GetFilteredOperations {
+                filter,
+                response_tx,
+            } => {
+                if response_tx
+                    .send(self.operation_pool.get_filtered_operations(&filter))
+                    .is_err()
+                {
+                    warn!("pool: could not send get_filtered_operations response");
+                }
+            }
+            PoolCommand::SubscribeOperationEvents(response_tx) => {
+                if response_tx
+                    .send(self.operation_pool.subscribe_events())
+                    .is_err()
+                {
+                    warn!("pool: could not send subscribe_operation_events response");
+                }
+            }
             PoolCommand::GetEndorsementsByAddress {
                 response_tx,
                 address,
@@ -322,18 +440,7 @@ impl PoolWorker {
             ProtocolPoolEvent::ReceivedOperations {
                 operations,
                 propagate,
-            } => {
-                if propagate {
-                    let newly_added = self.operation_pool.process_operations(operations)?;
-                    if !newly_added.is_empty() {
-                        self.protocol_command_sender
-                            .propagate_operations(newly_added)
-                            .await?;
-                    }
-                } else {
-                    self.operation_pool.process_operations(operations)?;
-                }
-            }
+            } => self.admit_operations(operations, propagate, None).await?,
             ProtocolPoolEvent::ReceivedEndorsements {
                 mut endorsements,
                 propagate,
@@ -355,4 +462,139 @@ impl PoolWorker {
         }
         Ok(())
     }
+
+    /// Start the admission of operations in the pool.
+    /// If a balance source is available, the operations wait for the balances of their senders.
+    /// When `max_pending_admissions` batches are already waiting, the operations are dropped,
+    /// and refused if a response is expected.
+    ///
+    /// # Arguments
+    /// * `operations`: operations to add
+    /// * `propagate`: whether to propagate the newly added operations
+    /// * `response_tx`: channel on which to send the refused operations
+    async fn admit_operations(
+        &mut self,
+        mut operations: Map<OperationId, WrappedOperation>,
+        propagate: bool,
+        response_tx: Option<oneshot::Sender<Map<OperationId, String>>>,
+    ) -> Result<(), PoolError> {
+        // avoid asking for balances for operations that would be ignored anyway
+        operations.retain(|op_id, _| !self.operation_pool.is_known(op_id));
+        let balance_source = match &self.balance_source {
+            Some(balance_source) if !operations.is_empty() => balance_source,
+            _ => {
+                return self
+                    .finish_admission(PendingAdmission {
+                        operations,
+                        propagate,
+                        response_tx,
+                        balances: None,
+                    })
+                    .await
+            }
+        };
+        if self.pending_admissions.len() >= self.cfg.settings.max_pending_admissions {
+            debug!(
+                "pool: too many operation batches waiting for balance checks, dropping {} operations",
+                operations.len()
+            );
+            if let Some(response_tx) = response_tx {
+                let rejected = operations
+                    .into_keys()
+                    .map(|op_id| {
+                        (
+                            op_id,
+                            "the pool is busy checking other operations, retry later".to_string(),
+                        )
+                    })
+                    .collect();
+                if response_tx.send(rejected).is_err() {
+                    warn!("pool: could not send add_operations response");
+                }
+            }
+            return Ok(());
+        }
+        let senders: Set<Address> = operations.values().map(|op| op.creator_address).collect();
+        let pending_operations = self.operation_pool.get_sender_operations(&senders);
+        let balances = balance_source.get_balances(senders, pending_operations);
+        self.pending_admissions.push(Box::pin(async move {
+            let balances = match balances.await {
+                Ok(balances) => Some(balances),
+                Err(err) => {
+                    warn!(
+                        "pool: could not get the balances of operation senders, admitting operations without balance checks: {}",
+                        err
+                    );
+                    None
+                }
+            };
+            PendingAdmission {
+                operations,
+                propagate,
+                response_tx,
+                balances,
+            }
+        }));
+        Ok(())
+    }
+
+    /// Put the journaled pool contents back in the pool once the final periods and the balance source are known.
+    /// They go through the same checks as incoming operations and endorsements,
+    /// but are not propagated again since they were when they first entered the pool.
+    async fn reload_journal(&mut self) -> Result<(), PoolError> {
+        if !self.final_periods_known || self.balance_source.is_none() {
+            return Ok(());
+        }
+        let (operations, endorsements) = match self.journal_to_reload.take() {
+            Some(journal_contents) => journal_contents,
+            None => return Ok(()),
+        };
+        info!(
+            "pool: reloading {} operations and {} endorsements from the journal",
+            operations.len(),
+            endorsements.len()
+        );
+        self.endorsement_pool.add_endorsements(endorsements)?;
+        self.admit_operations(operations, false, None).await
+    }
+
+    /// Save the pool contents to the journal file, if any.
+    /// Nothing is saved until the previous journal was reloaded, so that it is not overwritten.
+    async fn save_journal(&self) {
+        let journal_file = match &self.cfg.settings.journal_file {
+            Some(journal_file) if self.journal_to_reload.is_none() => journal_file,
+            _ => return,
+        };
+        let journal = PoolJournal {
+            operations: self.operation_pool.get_all_operations(),
+            endorsements: self.endorsement_pool.get_all_endorsements(),
+        };
+        if let Err(err) = dump_journal(&journal, journal_file).await {
+            warn!("pool: could not save the pool journal: {}", err);
+        }
+    }
+
+    /// Add operations to the pool once the balances of their senders are known
+    async fn finish_admission(&mut self, admission: PendingAdmission) -> Result<(), PoolError> {
+        let outcome = self
+            .operation_pool
+            .process_operations(admission.operations, admission.balances.as_ref())?;
+        if !outcome.rejected.is_empty() {
+            debug!("pool: refused {} operations", outcome.rejected.len());
+        }
+        for (replaced_id, op_id) in outcome.replaced.iter() {
+            debug!("pool: operation {} replaced by {}", replaced_id, op_id);
+        }
+        if let Some(response_tx) = admission.response_tx {
+            if response_tx.send(outcome.rejected).is_err() {
+                warn!("pool: could not send add_operations response");
+            }
+        }
+        if admission.propagate && !outcome.added.is_empty() {
+            self.protocol_command_sender
+                .propagate_operations(outcome.added)
+                .await?;
+        }
+        Ok(())
+    }
 }
@@ -1,9 +1,12 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use massa_models::Amount;
+use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 /// Pool configuration
-#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct PoolConfig {
     /// Configuration set in file configuration (ex: `config.toml`)
     pub settings: PoolSettings,
@@ -11,17 +14,31 @@ pub struct PoolConfig {
     pub thread_count: u8,
     /// operation validity periods
     pub operation_validity_periods: u64,
+    /// price of a roll, spent by roll buy operations
+    pub roll_price: Amount,
 }
 
 /// Pool configuration, read from a file configuration
-#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct PoolSettings {
     /// max pool size per thread (in number of operations)
     pub max_pool_size_per_thread: u64,
     /// how many periods in the future can an op validity start ? Otherwise op is ignored
     pub max_operation_future_validity_start_periods: u64,
+    /// max number of pending operations per sender, not counting replacements
+    pub max_operations_per_sender: u64,
+    /// max number of operation batches waiting for the balances of their senders.
+    /// Further batches are dropped until some balances are received
+    pub max_pending_admissions: usize,
     /// max endorsement we keep in pool
     pub max_endorsement_count: u64,
     /// Maximum number of item the pool can pop at a time
     pub max_item_return_count: usize,
+    /// max number of pool changes buffered for each subscriber to the pool events
+    pub operation_event_channel_size: usize,
+    /// file in which the pool contents are saved to survive restarts, no journal if absent
+    #[serde(default)]
+    pub journal_file: Option<PathBuf>,
+    /// the pool contents are saved to the journal file every `journal_dump_interval` milliseconds
+    pub journal_dump_interval: MassaTime,
 }
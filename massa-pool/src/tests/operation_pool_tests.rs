@@ -1,4 +1,5 @@
 use massa_models::{
+    api::{OperationKind, PoolEvictionReason, PoolOperationEvent, PoolOperationFilter},
     prehash::{Map, Set},
     wrapped::WrappedContent,
     Address, Amount, Operation, OperationId, OperationSerializer, OperationType, Slot,
@@ -8,9 +9,14 @@ use massa_signature::KeyPair;
 use serial_test::serial;
 use std::str::FromStr;
 
-use crate::operation_pool::OperationPool;
+use crate::journal::{dump_journal, load_journal, PoolJournal};
+use crate::{
+    operation_pool::OperationPool, CandidateBalances, PoolConfig, PoolError, PoolSettings,
+    SenderBalances,
+};
 
 use super::settings::POOL_CONFIG;
+use super::tools::get_transaction_with_addresses;
 
 fn get_transaction(expire_period: u64, fee: u64) -> WrappedOperation {
     let sender_keypair = KeyPair::generate();
@@ -46,12 +52,13 @@ fn test_pool() {
         let mut ops = Map::default();
         ops.insert(id, op.clone());
 
-        let newly_added = pool.process_operations(ops.clone()).unwrap();
+        let newly_added = pool.process_operations(ops.clone(), None).unwrap().added;
         assert_eq!(newly_added, ops.keys().copied().collect());
 
         // duplicate
-        let newly_added = pool.process_operations(ops).unwrap();
-        assert_eq!(newly_added, Set::<OperationId>::default());
+        let outcome = pool.process_operations(ops, None).unwrap();
+        assert_eq!(outcome.added, Set::<OperationId>::default());
+        assert!(outcome.rejected.is_empty());
 
         thread_tx_lists[op.thread as usize].push((op, start_period..=expire_period));
     }
@@ -118,8 +125,9 @@ fn test_pool() {
         let id = op.verify_integrity().unwrap();
         let mut ops = Map::default();
         ops.insert(id, op.clone());
-        let newly_added = pool.process_operations(ops).unwrap();
-        assert_eq!(newly_added, Set::<OperationId>::default());
+        let outcome = pool.process_operations(ops, None).unwrap();
+        assert_eq!(outcome.added, Set::<OperationId>::default());
+        assert!(outcome.rejected.contains_key(&id));
         let res = pool
             .get_operation_batch(
                 Slot::new(expire_period - 1, op.thread),
@@ -131,3 +139,325 @@ fn test_pool() {
         assert!(res.is_empty());
     }
 }
+
+#[test]
+#[serial]
+fn test_pool_balance_checks() {
+    let mut pool = OperationPool::new(&POOL_CONFIG, Default::default());
+    let sender_keypair = KeyPair::generate();
+    let sender_address = Address::from_public_key(&sender_keypair.get_public_key());
+    let get_sender_transaction = |fee: u64, amount: u64| {
+        let content = Operation {
+            fee: Amount::from_str(&fee.to_string()).unwrap(),
+            op: OperationType::Transaction {
+                recipient_address: Address::from_public_key(&KeyPair::generate().get_public_key()),
+                amount: Amount::from_str(&amount.to_string()).unwrap(),
+            },
+            expire_period: 10,
+        };
+        Operation::new_wrapped(content, OperationSerializer::new(), &sender_keypair).unwrap()
+    };
+    let mut balances = CandidateBalances::default();
+    balances.balances.insert(
+        sender_address,
+        SenderBalances {
+            sequential: Amount::from_str("100").unwrap(),
+            parallel: Amount::default(),
+        },
+    );
+
+    // fee and amount are covered by the balance
+    let op = get_sender_transaction(10, 60);
+    let mut ops = Map::default();
+    ops.insert(op.id, op.clone());
+    let outcome = pool.process_operations(ops, Some(&balances)).unwrap();
+    assert!(outcome.added.contains(&op.id));
+
+    // the balance left once the first operation is paid is too low
+    let op = get_sender_transaction(10, 40);
+    let mut ops = Map::default();
+    ops.insert(op.id, op.clone());
+    let outcome = pool.process_operations(ops, Some(&balances)).unwrap();
+    assert!(outcome.added.is_empty());
+    assert!(outcome.rejected.contains_key(&op.id));
+
+    // but enough for a smaller one
+    let op = get_sender_transaction(10, 20);
+    let mut ops = Map::default();
+    ops.insert(op.id, op.clone());
+    let outcome = pool.process_operations(ops, Some(&balances)).unwrap();
+    assert!(outcome.added.contains(&op.id));
+    let first_ops = pool.get_sender_operations(&std::iter::once(sender_address).collect());
+    assert_eq!(first_ops.len(), 2);
+
+    // the whole balance is spent by the pending operations
+    let op = get_sender_transaction(10, 20);
+    let mut ops = Map::default();
+    ops.insert(op.id, op.clone());
+    let outcome = pool.process_operations(ops, Some(&balances)).unwrap();
+    assert!(outcome.rejected.contains_key(&op.id));
+
+    // once the pending operations are included in active blocks, the candidate balance
+    // already accounts for them and they are not charged a second time
+    balances.included_operations = first_ops;
+    balances.balances.insert(
+        sender_address,
+        SenderBalances {
+            sequential: Amount::from_str("20").unwrap(),
+            parallel: Amount::default(),
+        },
+    );
+    let op = get_sender_transaction(10, 10);
+    let mut ops = Map::default();
+    ops.insert(op.id, op.clone());
+    let outcome = pool.process_operations(ops, Some(&balances)).unwrap();
+    assert!(outcome.added.contains(&op.id));
+
+    // unknown senders have an empty balance
+    let op = get_transaction(10, 1);
+    let mut ops = Map::default();
+    ops.insert(op.id, op.clone());
+    let outcome = pool.process_operations(ops, Some(&balances)).unwrap();
+    assert!(outcome.rejected.contains_key(&op.id));
+}
+
+#[test]
+#[serial]
+fn test_pool_sender_quota_and_replace_by_fee() {
+    let cfg: &'static PoolConfig = Box::leak(Box::new(PoolConfig {
+        settings: PoolSettings {
+            max_operations_per_sender: 2,
+            ..PoolSettings::default()
+        },
+        ..PoolConfig::default()
+    }));
+    let mut pool = OperationPool::new(cfg, Default::default());
+    let sender_keypair = KeyPair::generate();
+    let recipient_address = Address::from_public_key(&KeyPair::generate().get_public_key());
+    let get_sender_transaction = |fee: u64, amount: u64| {
+        let content = Operation {
+            fee: Amount::from_str(&fee.to_string()).unwrap(),
+            op: OperationType::Transaction {
+                recipient_address,
+                amount: Amount::from_str(&amount.to_string()).unwrap(),
+            },
+            expire_period: 10,
+        };
+        Operation::new_wrapped(content, OperationSerializer::new(), &sender_keypair).unwrap()
+    };
+    let add = |pool: &mut OperationPool, op: &WrappedOperation| {
+        let mut ops = Map::default();
+        ops.insert(op.id, op.clone());
+        pool.process_operations(ops, None).unwrap()
+    };
+
+    // fill the quota of the sender
+    let first_op = get_sender_transaction(10, 1);
+    assert!(add(&mut pool, &first_op).added.contains(&first_op.id));
+    let second_op = get_sender_transaction(10, 2);
+    assert!(add(&mut pool, &second_op).added.contains(&second_op.id));
+    let op = get_sender_transaction(10, 3);
+    let outcome = add(&mut pool, &op);
+    assert!(outcome.added.is_empty());
+    assert!(outcome.rejected.contains_key(&op.id));
+
+    // a replacement must pay a higher fee
+    let op = get_sender_transaction(5, 1);
+    let outcome = add(&mut pool, &op);
+    assert!(outcome.rejected.contains_key(&op.id));
+    assert!(pool.is_known(&first_op.id));
+
+    // only pending operations of the same sender can be replaced
+    let other_sender_op = Operation::new_wrapped(
+        first_op.content.clone(),
+        OperationSerializer::new(),
+        &KeyPair::generate(),
+    )
+    .unwrap();
+    assert!(add(&mut pool, &other_sender_op)
+        .added
+        .contains(&other_sender_op.id));
+    assert!(pool.is_known(&first_op.id));
+
+    // a replacement paying more evicts the pending operation, even when the quota is reached
+    let replacing_op = get_sender_transaction(20, 1);
+    let outcome = add(&mut pool, &replacing_op);
+    assert!(outcome.added.contains(&replacing_op.id));
+    assert_eq!(outcome.replaced.get(&first_op.id), Some(&replacing_op.id));
+    assert_eq!(pool.len(), 3);
+    assert!(!pool.is_known(&first_op.id));
+    assert!(pool.is_known(&other_sender_op.id));
+    let involving_recipient = pool
+        .get_operations_involving_address(&recipient_address)
+        .unwrap();
+    assert!(!involving_recipient.contains_key(&first_op.id));
+    assert!(involving_recipient.contains_key(&replacing_op.id));
+    assert!(involving_recipient.contains_key(&second_op.id));
+}
+
+#[test]
+#[serial]
+fn test_pool_fee_densities() {
+    let mut pool = OperationPool::new(&POOL_CONFIG, Default::default());
+    let sender_keypair = KeyPair::generate();
+    let mut ops = Map::default();
+    let mut densities = Vec::new();
+    for fee in [10, 20, 30] {
+        let content = Operation {
+            fee: Amount::from_str(&fee.to_string()).unwrap(),
+            op: OperationType::Transaction {
+                recipient_address: Address::from_public_key(&KeyPair::generate().get_public_key()),
+                amount: Amount::default(),
+            },
+            expire_period: 10,
+        };
+        let op =
+            Operation::new_wrapped(content, OperationSerializer::new(), &sender_keypair).unwrap();
+        densities.push(op.get_fee_density());
+        ops.insert(op.id, op);
+    }
+    let thread = Address::from_public_key(&sender_keypair.get_public_key())
+        .get_thread(POOL_CONFIG.thread_count) as usize;
+    pool.process_operations(ops, None).unwrap();
+
+    // the two best operations fit: the second best one is the threshold
+    let pool_densities = pool.get_fee_densities(2);
+    assert_eq!(pool_densities.len(), POOL_CONFIG.thread_count as usize);
+    let percentiles = pool_densities[thread].percentiles.unwrap();
+    assert_eq!(percentiles.operation_count, 3);
+    assert_eq!(percentiles.p10, densities[0]);
+    assert_eq!(percentiles.p50, densities[1]);
+    assert_eq!(percentiles.p90, densities[2]);
+    assert_eq!(
+        pool_densities[thread].inclusion_threshold,
+        Some(densities[1])
+    );
+
+    // all the operations fit
+    let pool_densities = pool.get_fee_densities(5);
+    assert_eq!(pool_densities[thread].inclusion_threshold, None);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_pool_journal_round_trip() {
+    let ops: Vec<WrappedOperation> = (0..3).map(|fee| get_transaction(10, fee)).collect();
+    let file_path = std::env::temp_dir().join(format!(
+        "massa_pool_journal_{}.bin",
+        KeyPair::generate().get_public_key()
+    ));
+    let journal = PoolJournal {
+        operations: ops.clone(),
+        endorsements: Vec::new(),
+    };
+    dump_journal(&journal, &file_path).await.unwrap();
+    let (loaded_ops, loaded_endorsements) = load_journal(&file_path, &POOL_CONFIG).await.unwrap();
+    std::fs::remove_file(&file_path).unwrap();
+
+    assert_eq!(loaded_ops.len(), ops.len());
+    assert!(ops.iter().all(|op| loaded_ops.contains_key(&op.id)));
+    assert!(loaded_endorsements.is_empty());
+
+    // a missing journal is an empty pool
+    let (loaded_ops, _) = load_journal(&file_path, &POOL_CONFIG).await.unwrap();
+    assert!(loaded_ops.is_empty());
+
+    // a journal without the format header is refused
+    std::fs::write(&file_path, [0u8, 0u8]).unwrap();
+    let res = load_journal(&file_path, &POOL_CONFIG).await;
+    std::fs::remove_file(&file_path).unwrap();
+    assert!(matches!(res, Err(PoolError::JournalError(_))));
+}
+
+#[test]
+#[serial]
+fn test_pool_filtered_operations_and_events() {
+    let mut pool = OperationPool::new(&POOL_CONFIG, Default::default());
+    let mut events = pool.subscribe_events();
+    let sender_keypair = KeyPair::generate();
+    let thread = Address::from_public_key(&sender_keypair.get_public_key())
+        .get_thread(POOL_CONFIG.thread_count);
+    let mut ops = Map::default();
+    for fee in [10, 20, 30] {
+        let op = get_transaction_with_addresses(
+            10,
+            fee,
+            &sender_keypair,
+            KeyPair::generate().get_public_key(),
+        );
+        ops.insert(op.id, op);
+    }
+    let other_op = get_transaction(10, 40);
+    let mut all_ops = ops.clone();
+    all_ops.insert(other_op.id, other_op.clone());
+    pool.process_operations(all_ops, None).unwrap();
+    for _ in 0..4 {
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            PoolOperationEvent::Added { .. }
+        ));
+    }
+
+    // filter by sender and minimal fee, sorted by decreasing fee density
+    let filter = PoolOperationFilter {
+        sender: Some(Address::from_public_key(&sender_keypair.get_public_key())),
+        min_fee: Some(Amount::from_str("20").unwrap()),
+        ..Default::default()
+    };
+    let (has_more, page) = pool.get_filtered_operations(&filter);
+    assert!(!has_more);
+    assert_eq!(page.len(), 2);
+    assert_eq!(page[0].content.fee, Amount::from_str("30").unwrap());
+    assert!(page.iter().all(|op| op.thread == thread));
+
+    // pagination
+    let filter = PoolOperationFilter {
+        thread: Some(thread),
+        kind: Some(OperationKind::Transaction),
+        offset: 1,
+        limit: Some(1),
+        ..filter
+    };
+    let (has_more, page) = pool.get_filtered_operations(&filter);
+    assert!(!has_more);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].content.fee, Amount::from_str("20").unwrap());
+    let (has_more, page) = pool.get_filtered_operations(&PoolOperationFilter {
+        offset: 0,
+        ..filter
+    });
+    assert!(has_more);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].content.fee, Amount::from_str("30").unwrap());
+
+    // no operation of another kind
+    let filter = PoolOperationFilter {
+        kind: Some(OperationKind::RollBuy),
+        ..Default::default()
+    };
+    assert!(pool.get_filtered_operations(&filter).1.is_empty());
+
+    // final and expired operations leave the pool
+    let mut final_ops = Map::default();
+    final_ops.insert(other_op.id, (10, other_op.thread));
+    pool.new_final_operations(final_ops).unwrap();
+    match events.try_recv().unwrap() {
+        PoolOperationEvent::Removed { id, reason } => {
+            assert_eq!(id, other_op.id);
+            assert_eq!(reason, PoolEvictionReason::Final);
+        }
+        event => panic!("unexpected pool event: {}", event),
+    }
+    pool.update_latest_final_periods(vec![10; POOL_CONFIG.thread_count as usize])
+        .unwrap();
+    for _ in 0..3 {
+        match events.try_recv().unwrap() {
+            PoolOperationEvent::Removed { id, reason } => {
+                assert!(ops.contains_key(&id));
+                assert_eq!(reason, PoolEvictionReason::Expired);
+            }
+            event => panic!("unexpected pool event: {}", event),
+        }
+    }
+    assert_eq!(pool.len(), 0);
+}
@@ -9,6 +9,7 @@ impl Default for PoolConfig {
         Self {
             thread_count: 2,
             operation_validity_periods: 50,
+            roll_price: Default::default(),
             settings: PoolSettings::default(),
         }
     }
@@ -19,8 +20,13 @@ impl Default for PoolSettings {
         Self {
             max_pool_size_per_thread: 10,
             max_operation_future_validity_start_periods: 200,
+            max_operations_per_sender: 1000,
+            max_pending_admissions: 100,
             max_endorsement_count: 1000,
             max_item_return_count: 1000,
+            operation_event_channel_size: 1024,
+            journal_file: None,
+            journal_dump_interval: 10000.into(),
         }
     }
 }
@@ -11,7 +11,7 @@ mod settings;
 
 use massa_models::{
     rolls::{RollUpdate, RollUpdates},
-    Address, OperationType, WrappedOperation,
+    OperationType, WrappedOperation,
 };
 
 mod proof_of_stake;
@@ -39,19 +39,21 @@ impl OperationRollInterface for WrappedOperation {
             OperationType::Transaction { .. } => {}
             OperationType::RollBuy { roll_count } => {
                 res.apply(
-                    &Address::from_public_key(&self.creator_public_key),
+                    &self.creator_address,
                     &RollUpdate {
                         roll_purchases: roll_count,
                         roll_sales: 0,
+                        roll_slashes: 0,
                     },
                 )?;
             }
             OperationType::RollSell { roll_count } => {
                 res.apply(
-                    &Address::from_public_key(&self.creator_public_key),
+                    &self.creator_address,
                     &RollUpdate {
                         roll_purchases: 0,
                         roll_sales: roll_count,
+                        roll_slashes: 0,
                     },
                 )?;
             }
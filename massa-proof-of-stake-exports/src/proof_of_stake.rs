@@ -6,12 +6,13 @@ use massa_models::{
     active_block::ActiveBlock,
     prehash::{Map, Set},
     rolls::{RollCounts, RollUpdates},
-    Address, Amount, BlockId, Slot, StakersCycleProductionStats,
+    Address, Amount, BlockId, Slot, StakersCycleProductionStats, StakingAddressHealth,
+    StakingAlert,
 };
 use num::rational::Ratio;
 use rand::{distributions::Uniform, Rng, SeedableRng};
 use rand_xoshiro::Xoshiro256PlusPlus;
-use tracing::warn;
+use tracing::{info, warn};
 
 use crate::{
     error::POSResult, error::ProofOfStakeError, export_pos::ExportProofOfStake,
@@ -37,6 +38,14 @@ pub struct ProofOfStake {
     initial_seeds: Vec<Vec<u8>>,
     /// watched addresses
     watched_addresses: Set<Address>,
+    /// alerts about the watched addresses, waiting to be taken
+    staking_alerts: Vec<StakingAlert>,
+    /// latest cycle in which a missed block of each watched address was alerted
+    alerted_misses: Map<Address, u64>,
+    /// latest cycle in which the imminent deactivation of each watched address was alerted
+    alerted_deactivations: Map<Address, u64>,
+    /// latest cycle final in all threads whose production was reported
+    reported_cycle: Option<u64>,
 }
 
 impl ProofOfStake {
@@ -100,6 +109,10 @@ impl ProofOfStake {
             cfg,
             draw_cache_counter,
             watched_addresses: Set::<Address>::default(),
+            staking_alerts: Vec::new(),
+            alerted_misses: Map::default(),
+            alerted_deactivations: Map::default(),
+            reported_cycle: None,
         })
     }
 
@@ -403,6 +416,7 @@ impl ProofOfStake {
                                 "address {} missed a production opportunity at slot {} (cycle {})",
                                 evt_addr, evt_slot, evt_cycle
                             );
+                            self.alert_missed_block(*evt_addr, evt_slot);
                         }
                         if let Some(neg_relative_cycle) =
                             last_final_block_cycle.checked_sub(evt_cycle)
@@ -440,6 +454,14 @@ impl ProofOfStake {
                 // check if we are applying the block itself or a miss
                 if period == block_slot.period {
                     // we are applying the block itself
+                    for (addr, update) in a_block.roll_updates.0.iter() {
+                        if update.roll_slashes > 0 && self.watched_addresses.contains(addr) {
+                            warn!(
+                                "address {} was denounced for signing twice for the same slot: {} rolls were slashed at slot {}",
+                                addr, update.roll_slashes, slot
+                            );
+                        }
+                    }
                     // compensations/deactivations have already been taken into account within the block and converted to ledger changes so we ignore them here
                     entry.cycle_updates.chain(&a_block.roll_updates)?;
                     entry.roll_count.apply_updates(&a_block.roll_updates)?;
@@ -463,9 +485,163 @@ impl ProofOfStake {
             self.initial_rolls = None;
         }
 
+        self.check_staking_health();
+
         Ok(())
     }
 
+    /// Alert about a missed block of a watched address, at most once per address and cycle
+    /// so that a staker missing many blocks in a row does not trigger as many alerts.
+    fn alert_missed_block(&mut self, address: Address, slot: Slot) {
+        let cycle = slot.get_cycle(self.cfg.periods_per_cycle);
+        if self
+            .alerted_misses
+            .get(&address)
+            .map_or(false, |alerted| *alerted >= cycle)
+        {
+            return;
+        }
+        self.alerted_misses.insert(address, cycle);
+        self.staking_alerts
+            .push(StakingAlert::MissedBlock { address, slot });
+    }
+
+    /// Alert about the watched addresses whose miss rate got close to the deactivation threshold in the latest cycle,
+    /// and report the production of the watched addresses once a cycle is final in all threads.
+    fn check_staking_health(&mut self) {
+        if self.watched_addresses.is_empty() {
+            return;
+        }
+        let lookback_cycles = self.cfg.pos_lookback_cycles;
+
+        for health in self.get_staking_health() {
+            if !health.deactivation_imminent
+                || self.alerted_deactivations.get(&health.address) == Some(&health.cycle)
+            {
+                continue;
+            }
+            self.alerted_deactivations
+                .insert(health.address, health.cycle);
+            let deactivation_cycle = health.cycle + lookback_cycles + 1;
+            warn!(
+                address = %health.address,
+                cycle = health.cycle,
+                ok_count = health.ok_count,
+                nok_count = health.nok_count,
+                deactivation_cycle,
+                "address {} is close to missing too many blocks in cycle {}",
+                health.address,
+                health.cycle
+            );
+            self.staking_alerts
+                .push(StakingAlert::DeactivationImminent {
+                    address: health.address,
+                    cycle: health.cycle,
+                    deactivation_cycle,
+                    ok_count: health.ok_count,
+                    nok_count: health.nok_count,
+                });
+        }
+
+        // a cycle is final in all threads once all threads have final blocks in a later cycle
+        let final_cycle = match (0..self.cfg.thread_count)
+            .map(|thread| self.get_last_final_block_cycle(thread))
+            .min()
+            .and_then(|cycle| cycle.checked_sub(1))
+        {
+            Some(cycle) => cycle,
+            None => return,
+        };
+        if self
+            .reported_cycle
+            .map_or(false, |reported| reported >= final_cycle)
+        {
+            return;
+        }
+        self.reported_cycle = Some(final_cycle);
+        for address in self.watched_addresses.iter() {
+            let (ok_count, nok_count) = self.get_cycle_production_counts(final_cycle, address);
+            if self.is_above_miss_rate_threshold(ok_count, nok_count) {
+                warn!(
+                    address = %address,
+                    cycle = final_cycle,
+                    ok_count,
+                    nok_count,
+                    deactivation_cycle = final_cycle + lookback_cycles + 1,
+                    "address {} missed too many blocks in cycle {}",
+                    address,
+                    final_cycle
+                );
+            } else {
+                info!(
+                    address = %address,
+                    cycle = final_cycle,
+                    ok_count,
+                    nok_count,
+                    "address {} produced {} blocks and missed {} in cycle {}",
+                    address,
+                    ok_count,
+                    nok_count,
+                    final_cycle
+                );
+            }
+        }
+    }
+
+    /// produced and missed block counts of an address during a cycle, in all threads
+    fn get_cycle_production_counts(&self, cycle: u64, address: &Address) -> (u64, u64) {
+        (0..self.cfg.thread_count)
+            .filter_map(|thread| self.get_final_roll_data(cycle, thread))
+            .filter_map(|roll_data| roll_data.production_stats.get(address))
+            .fold((0, 0), |(ok_count, nok_count), (n_ok, n_nok)| {
+                (ok_count + n_ok, nok_count + n_nok)
+            })
+    }
+
+    /// true if the rolls of an address with these production counts are deactivated
+    fn is_above_miss_rate_threshold(&self, ok_count: u64, nok_count: u64) -> bool {
+        ok_count + nok_count > 0
+            && Ratio::new(nok_count, ok_count + nok_count)
+                > self.cfg.pos_miss_rate_deactivation_threshold
+    }
+
+    /// true if the miss rate of an address with these production counts is above the warning share of the deactivation threshold
+    fn is_above_miss_rate_warning(&self, ok_count: u64, nok_count: u64) -> bool {
+        ok_count + nok_count > 0
+            && Ratio::new(nok_count, ok_count + nok_count)
+                > self.cfg.pos_miss_rate_deactivation_threshold
+                    * self.cfg.pos_miss_rate_warning_ratio
+    }
+
+    /// production health of the watched addresses during the latest cycle with final blocks
+    pub fn get_staking_health(&self) -> Vec<StakingAddressHealth> {
+        let cycle = (0..self.cfg.thread_count)
+            .map(|thread| self.get_last_final_block_cycle(thread))
+            .max()
+            .unwrap_or_default();
+        let mut res: Vec<StakingAddressHealth> = self
+            .watched_addresses
+            .iter()
+            .map(|address| {
+                let (ok_count, nok_count) = self.get_cycle_production_counts(cycle, address);
+                StakingAddressHealth {
+                    address: *address,
+                    cycle,
+                    ok_count,
+                    nok_count,
+                    deactivation_imminent: self.is_above_miss_rate_warning(ok_count, nok_count),
+                }
+            })
+            .collect();
+        res.sort_unstable_by_key(|health| health.address);
+        res
+    }
+
+    /// take the alerts about the watched addresses noted since the last call
+    pub fn take_staking_alerts(&mut self) -> Vec<StakingAlert> {
+        std::mem::take(&mut self.staking_alerts)
+    }
+
     /// get production stats for addresses
     pub fn get_stakers_production_stats(
         &self,
@@ -607,11 +783,7 @@ impl ProofOfStake {
         let res: Set<Address> = addr_stats
             .into_iter()
             .filter_map(|(addr, (ok_count, nok_count))| {
-                if ok_count + nok_count == 0 {
-                    return None;
-                }
-                let miss_ratio = Ratio::new(nok_count, ok_count + nok_count);
-                if miss_ratio > self.cfg.pos_miss_rate_deactivation_threshold {
+                if self.is_above_miss_rate_threshold(ok_count, nok_count) {
                     return Some(addr);
                 }
                 None
@@ -685,3 +857,161 @@ impl ProofOfStake {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::KeyPair;
+
+    /// Proof of stake at cycle 3 in both threads, with the given production stats in each thread
+    fn create_pos(production_stats: Vec<Map<Address, (u64, u64)>>) -> ProofOfStake {
+        let cfg = ProofOfStakeConfig {
+            thread_count: 2,
+            genesis_key: KeyPair::generate(),
+            periods_per_cycle: 10,
+            pos_lookback_cycles: 2,
+            pos_lock_cycles: 1,
+            pos_draw_cached_cycles: 2,
+            pos_miss_rate_deactivation_threshold: Ratio::new(7, 10),
+            pos_miss_rate_warning_ratio: Ratio::new(8, 10),
+            initial_rolls_path: Default::default(),
+            initial_draw_seed: "test".into(),
+            roll_price: Amount::from_mantissa_scale(100, 0),
+            endorsement_count: 9,
+        };
+        let cycle_states = production_stats
+            .into_iter()
+            .enumerate()
+            .map(|(thread, production_stats)| {
+                VecDeque::from(vec![ThreadCycleState {
+                    cycle: 3,
+                    last_final_slot: Slot::new(35, thread as u8),
+                    roll_count: Default::default(),
+                    cycle_updates: Default::default(),
+                    rng_seed: BitVec::new(),
+                    production_stats,
+                }])
+            })
+            .collect();
+        ProofOfStake {
+            initial_seeds: ProofOfStake::generate_initial_seeds(&cfg),
+            cfg,
+            cycle_states,
+            draw_cache: HashMap::new(),
+            draw_cache_counter: 0,
+            initial_rolls: None,
+            watched_addresses: Set::default(),
+            staking_alerts: Vec::new(),
+            alerted_misses: Map::default(),
+            alerted_deactivations: Map::default(),
+            reported_cycle: None,
+        }
+    }
+
+    fn create_address() -> Address {
+        Address::from_public_key(&KeyPair::generate().get_public_key())
+    }
+
+    #[test]
+    fn test_staking_health_sums_threads() {
+        let (healthy, failing) = (create_address(), create_address());
+        let mut pos = create_pos(vec![
+            vec![(healthy, (4, 1)), (failing, (1, 4))]
+                .into_iter()
+                .collect(),
+            vec![(healthy, (5, 0)), (failing, (0, 5))]
+                .into_iter()
+                .collect(),
+        ]);
+        pos.set_watched_addresses(vec![healthy, failing].into_iter().collect());
+
+        let health = pos.get_staking_health();
+        assert_eq!(health.len(), 2);
+        for health in health {
+            assert_eq!(health.cycle, 3);
+            if health.address == healthy {
+                assert_eq!((health.ok_count, health.nok_count), (9, 1));
+                assert!(!health.deactivation_imminent);
+            } else {
+                assert_eq!((health.ok_count, health.nok_count), (1, 9));
+                assert!(health.deactivation_imminent);
+            }
+        }
+    }
+
+    #[test]
+    fn test_staking_health_warns_below_threshold() {
+        // the deactivation threshold is 70% and the warning is raised above 80% of it (56%)
+        let (close, safe) = (create_address(), create_address());
+        let mut pos = create_pos(vec![
+            vec![(close, (2, 3)), (safe, (3, 2))].into_iter().collect(),
+            vec![(close, (2, 3)), (safe, (2, 3))].into_iter().collect(),
+        ]);
+        pos.set_watched_addresses(vec![close, safe].into_iter().collect());
+
+        for health in pos.get_staking_health() {
+            if health.address == close {
+                assert_eq!((health.ok_count, health.nok_count), (4, 6));
+                assert!(!pos.is_above_miss_rate_threshold(health.ok_count, health.nok_count));
+                assert!(health.deactivation_imminent);
+            } else {
+                assert_eq!((health.ok_count, health.nok_count), (5, 5));
+                assert!(!health.deactivation_imminent);
+            }
+        }
+    }
+
+    #[test]
+    fn test_deactivation_alerted_once_per_cycle() {
+        let (healthy, failing) = (create_address(), create_address());
+        let mut pos = create_pos(vec![
+            vec![(healthy, (2, 0)), (failing, (1, 3))]
+                .into_iter()
+                .collect(),
+            Map::default(),
+        ]);
+        pos.set_watched_addresses(vec![healthy, failing].into_iter().collect());
+
+        pos.check_staking_health();
+        let alerts = pos.take_staking_alerts();
+        assert_eq!(alerts.len(), 1);
+        match &alerts[0] {
+            StakingAlert::DeactivationImminent {
+                address,
+                cycle,
+                deactivation_cycle,
+                ok_count,
+                nok_count,
+            } => {
+                assert_eq!(*address, failing);
+                assert_eq!(*cycle, 3);
+                assert_eq!(*deactivation_cycle, 6);
+                assert_eq!((*ok_count, *nok_count), (1, 3));
+            }
+            alert => panic!("unexpected alert {:?}", alert),
+        }
+
+        // the same cycle is not alerted again
+        pos.check_staking_health();
+        assert!(pos.take_staking_alerts().is_empty());
+    }
+
+    #[test]
+    fn test_missed_blocks_alerted_once_per_cycle() {
+        let address = create_address();
+        let mut pos = create_pos(vec![Map::default(), Map::default()]);
+
+        pos.alert_missed_block(address, Slot::new(31, 0));
+        pos.alert_missed_block(address, Slot::new(32, 1));
+        pos.alert_missed_block(address, Slot::new(41, 0));
+        let slots: Vec<Slot> = pos
+            .take_staking_alerts()
+            .into_iter()
+            .map(|alert| match alert {
+                StakingAlert::MissedBlock { slot, .. } => slot,
+                alert => panic!("unexpected alert {:?}", alert),
+            })
+            .collect();
+        assert_eq!(slots, vec![Slot::new(31, 0), Slot::new(41, 0)]);
+    }
+}
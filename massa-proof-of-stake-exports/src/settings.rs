@@ -22,6 +22,8 @@ pub struct ProofOfStakeConfig {
     pub pos_draw_cached_cycles: usize,
     /// number of cycle misses (strictly) above which stakers are deactivated
     pub pos_miss_rate_deactivation_threshold: Ratio<u64>,
+    /// share of `pos_miss_rate_deactivation_threshold` above which the miss rate of a staking address is alerted
+    pub pos_miss_rate_warning_ratio: Ratio<u64>,
     /// path to initial rolls
     pub initial_rolls_path: PathBuf,
     /// initial seed
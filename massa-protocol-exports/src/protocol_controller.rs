@@ -9,7 +9,8 @@ use massa_models::{
     Slot, WrappedBlock,
 };
 use massa_models::{
-    BlockId, EndorsementId, OperationId, WrappedEndorsement, WrappedHeader, WrappedOperation,
+    BlockId, Denunciation, EndorsementId, OperationId, WrappedEndorsement, WrappedHeader,
+    WrappedOperation,
 };
 use massa_network_exports::NetworkEventReceiver;
 use serde::Serialize;
@@ -41,6 +42,8 @@ pub enum ProtocolEvent {
     },
     /// Ask for a list of blocks from consensus.
     GetBlocks(Vec<BlockId>),
+    /// Denunciations with valid signatures have been received.
+    ReceivedDenunciations(Vec<Denunciation>),
 }
 /// Possible types of pool events that can happen.
 #[derive(Debug, Serialize)]
@@ -100,6 +103,8 @@ pub enum ProtocolCommand {
     PropagateOperations(OperationIds),
     /// Propagate endorsements
     PropagateEndorsements(Map<EndorsementId, WrappedEndorsement>),
+    /// Propagate denunciations
+    PropagateDenunciations(Vec<Denunciation>),
 }
 
 /// protocol management commands
@@ -211,6 +216,22 @@ impl ProtocolCommandSender {
                 ProtocolError::ChannelError("propagate_endorsements command send error".into())
             })
     }
+
+    /// propagate denunciations to connected nodes
+    pub async fn propagate_denunciations(
+        &mut self,
+        denunciations: Vec<Denunciation>,
+    ) -> Result<(), ProtocolError> {
+        massa_trace!("protocol.command_sender.propagate_denunciations", {
+            "denunciations": denunciations
+        });
+        self.0
+            .send(ProtocolCommand::PropagateDenunciations(denunciations))
+            .await
+            .map_err(|_| {
+                ProtocolError::ChannelError("propagate_denunciations command send error".into())
+            })
+    }
 }
 
 /// Protocol event receiver
@@ -10,7 +10,8 @@ use massa_models::{
     node::NodeId,
     operation::{OperationIds, OperationPrefixId, Operations},
     prehash::{BuildMap, Map, Set},
-    BlockHeaderSerializer, BlockId, EndorsementId, OperationId, WrappedEndorsement, WrappedHeader,
+    BlockHeaderSerializer, BlockId, Denunciation, EndorsementId, OperationId, WrappedEndorsement,
+    WrappedHeader,
 };
 use massa_models::{EndorsementSerializer, OperationSerializer, WrappedBlock};
 use massa_network_exports::{NetworkCommandSender, NetworkEvent, NetworkEventReceiver};
@@ -566,6 +567,18 @@ impl ProtocolWorker {
                     }
                 }
             }
+            ProtocolCommand::PropagateDenunciations(denunciations) => {
+                massa_trace!(
+                    "protocol.protocol_worker.process_command.propagate_denunciations.begin",
+                    { "denunciations": denunciations }
+                );
+                // consensus only asks to propagate the denunciations it did not know yet
+                for node in self.active_nodes.keys() {
+                    self.network_command_sender
+                        .send_denunciations(*node, denunciations.clone())
+                        .await?;
+                }
+            }
         }
         massa_trace!("protocol.protocol_worker.process_command.end", {});
         Ok(())
@@ -1201,6 +1214,27 @@ impl ProtocolWorker {
         Ok((endorsement_ids, contains_duplicates))
     }
 
+    /// Note denunciations coming from a given node and forward them to consensus.
+    ///
+    /// Checks performed:
+    /// - Valid signatures of both denounced contents.
+    /// - The denounced contents conflict.
+    async fn note_denunciations_from_node(
+        &mut self,
+        denunciations: Vec<Denunciation>,
+        source_node_id: &NodeId,
+    ) -> Result<(), ProtocolError> {
+        massa_trace!("protocol.protocol_worker.note_denunciations_from_node", { "node": source_node_id, "denunciations": denunciations});
+        for denunciation in denunciations.iter() {
+            denunciation.check()?;
+        }
+        if !denunciations.is_empty() {
+            self.send_protocol_event(ProtocolEvent::ReceivedDenunciations(denunciations))
+                .await;
+        }
+        Ok(())
+    }
+
     /// Manages network event
     /// Only used by the worker.
     ///
@@ -1330,6 +1364,20 @@ impl ProtocolWorker {
                     let _ = self.ban_node(&node).await;
                 }
             }
+            NetworkEvent::ReceivedDenunciations {
+                node,
+                denunciations,
+            } => {
+                massa_trace!("protocol.protocol_worker.on_network_event.received_denunciations", { "node": node, "denunciations": denunciations});
+                if self
+                    .note_denunciations_from_node(denunciations, &node)
+                    .await
+                    .is_err()
+                {
+                    warn!("node {} sent us invalid denunciations, which may be an attack attempt by the remote node", node);
+                    let _ = self.ban_node(&node).await;
+                }
+            }
             NetworkEvent::ReceivedOperationAnnouncements {
                 node,
                 operation_prefix_ids,
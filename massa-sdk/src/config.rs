@@ -0,0 +1,26 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+
+/// Settings of the requests sent to a node
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct ClientConfig {
+    /// maximum duration of a request, in milliseconds
+    pub timeout: MassaTime,
+    /// number of times a request is sent again after a timeout or a transport error.
+    /// Errors returned by the node are never retried
+    pub max_retries: u64,
+    /// delay before a request is sent again, in milliseconds
+    pub retry_delay: MassaTime,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        ClientConfig {
+            timeout: MassaTime::from(10000),
+            max_retries: 2,
+            retry_delay: MassaTime::from(1000),
+        }
+    }
+}
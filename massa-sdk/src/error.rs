@@ -0,0 +1,30 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use displaydoc::Display;
+use jsonrpc_core_client::RpcError;
+use massa_models::api::PoolEvictionReason;
+use massa_models::OperationId;
+use massa_time::MassaTime;
+use thiserror::Error;
+
+/// SDK error
+#[non_exhaustive]
+#[derive(Display, Error, Debug)]
+pub enum SdkError {
+    /// RPC error: {0}
+    RpcError(#[from] RpcError),
+    /// Models error: {0}
+    ModelsError(#[from] massa_models::ModelsError),
+    /// Wallet error: {0}
+    WalletError(#[from] massa_wallet::WalletError),
+    /// Time error: {0}
+    TimeError(#[from] massa_time::TimeError),
+    /// Signing task error: {0}
+    SigningTaskError(#[from] tokio::task::JoinError),
+    /// The node did not accept the operation {0}: {1}
+    OperationRejected(OperationId, String),
+    /// The operation {0} left the pool without being final: {1}
+    OperationNotFinal(OperationId, PoolEvictionReason),
+    /// The operation {0} is still not final after {1} ms
+    FinalityTimeout(OperationId, MassaTime),
+}
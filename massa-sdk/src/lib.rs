@@ -1,5 +1,11 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 //! Utilities for a massa client
+//!
+//! `Client` sends typed requests to the public and private JSON-RPC APIs of a node,
+//! with a timeout and retries set by `ClientConfig`.
+//! `OperationBuilder` builds, signs with a `massa_wallet::Wallet` and sends operations,
+//! then waits until they are final by polling the node.
+//! `WsClient` subscribes to the public `WebSocket` API, which can also be used to wait for finality.
 
 #![warn(missing_docs)]
 #![warn(unused_crate_dependencies)]
@@ -7,22 +13,36 @@
 use jsonrpc_core_client::transports::http;
 use jsonrpc_core_client::{RpcChannel, RpcError, RpcResult, TypedClient};
 use massa_models::api::{
-    AddressInfo, BlockInfo, BlockSummary, DatastoreEntryInput, DatastoreEntryOutput,
-    EndorsementInfo, EventFilter, NodeStatus, OperationInfo, OperationInput,
-    ReadOnlyBytecodeExecution, ReadOnlyCall, TimeInterval,
+    AddressInfo, BlockInfo, BlockInput, BlockSummary, BlockTemplate, BlockTemplateInput,
+    DatastoreEntryInput, DatastoreEntryOutput, EndorsementInfo, EventFilter, FeeEstimate,
+    FeeEstimateInput, NodeStatus, OperationInfo, OperationInput, PoolOperationFilter,
+    PoolOperationsPage, ReadOnlyBytecodeExecution, ReadOnlyCall, RejectedOperation,
+    SelectionDrawsInput, SelectionDrawsOutput, SignatureVerificationInput, TimeInterval,
 };
 use massa_models::clique::Clique;
 use massa_models::composite::PubkeySig;
 use massa_models::execution::ExecuteReadOnlyResponse;
 use massa_models::node::NodeId;
 use massa_models::output_event::SCOutputEvent;
-use massa_models::prehash::{Map, Set};
+use massa_models::prehash::Set;
 use massa_models::{Address, BlockId, EndorsementId, OperationId};
 use massa_signature::KeyPair;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::net::{IpAddr, SocketAddr};
 
+pub use config::ClientConfig;
+pub use error::SdkError;
+pub use operation::{OperationBuilder, FEE_ESTIMATE_TARGET_PERIODS};
+pub use ws::{wait_pool_operation_final, PoolOperationEvents, WsClient};
+
+mod config;
+mod error;
+mod operation;
+#[cfg(test)]
+mod tests;
+mod ws;
+
 /// Client
 pub struct Client {
     /// public component
@@ -32,23 +52,46 @@ pub struct Client {
 }
 
 impl Client {
-    /// creates a new client
-    pub async fn new(ip: IpAddr, public_port: u16, private_port: u16) -> Client {
+    /// creates a new client with the default configuration
+    pub async fn new(ip: IpAddr, public_port: u16, private_port: u16) -> RpcResult<Client> {
+        Client::new_with_config(ip, public_port, private_port, ClientConfig::default()).await
+    }
+
+    /// creates a new client sending its requests with the given configuration
+    pub async fn new_with_config(
+        ip: IpAddr,
+        public_port: u16,
+        private_port: u16,
+        config: ClientConfig,
+    ) -> RpcResult<Client> {
         let public_socket_addr = SocketAddr::new(ip, public_port);
         let private_socket_addr = SocketAddr::new(ip, private_port);
         let public_url = format!("http://{}", public_socket_addr);
         let private_url = format!("http://{}", private_socket_addr);
-        Client {
-            public: RpcClient::from_url(&public_url).await,
-            private: RpcClient::from_url(&private_url).await,
-        }
+        Ok(Client {
+            public: RpcClient::from_url_with_config(&public_url, config).await?,
+            private: RpcClient::from_url_with_config(&private_url, config).await?,
+        })
     }
 }
 
-/// TODO ask @yvan-sraka
+/// Get the operations refused by the pool, with the reason why,
+/// from the error returned by `send_operations`
+pub fn rejected_operations(err: &RpcError) -> Vec<RejectedOperation> {
+    match err {
+        RpcError::JsonRpcError(err) => err
+            .data
+            .clone()
+            .and_then(|data| serde_json::from_value(data).ok())
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+/// Typed client of the JSON-RPC API of a node
 pub struct RpcClient {
     client: TypedClient,
-    timeout: u64,
+    config: ClientConfig,
 }
 
 /// This is required by `jsonrpc_core_client::transports::http::connect`
@@ -56,33 +99,54 @@ impl From<RpcChannel> for RpcClient {
     fn from(channel: RpcChannel) -> Self {
         RpcClient {
             client: channel.into(),
-            timeout: 10000,
+            config: ClientConfig::default(),
         }
     }
 }
 
 impl RpcClient {
     /// Default constructor
-    pub async fn from_url(url: &str) -> RpcClient {
-        match http::connect::<RpcClient>(url).await {
-            Ok(client) => client,
-            Err(_) => panic!("unable to connect to Node."),
-        }
+    pub async fn from_url(url: &str) -> RpcResult<RpcClient> {
+        RpcClient::from_url_with_config(url, ClientConfig::default()).await
+    }
+
+    /// Creates a client sending its requests with the given configuration.
+    /// Fails if the url is invalid, the node being only reached by the first request
+    pub async fn from_url_with_config(url: &str, config: ClientConfig) -> RpcResult<RpcClient> {
+        let mut client = http::connect::<RpcClient>(url).await?;
+        client.config = config;
+        Ok(client)
     }
 
-    /// Typed wrapper to API calls based on the method given by `jsonrpc_core_client`
+    /// Typed wrapper to API calls based on the method given by `jsonrpc_core_client`.
+    /// Timeouts and transport errors are retried, errors returned by the node are not
     async fn call_method<T: Serialize, R: DeserializeOwned>(
         &self,
         method: &str,
         returns: &str,
         args: T,
     ) -> RpcResult<R> {
-        tokio::time::timeout(
-            tokio::time::Duration::from_millis(self.timeout),
-            self.client.call_method(method, returns, args),
-        )
-        .await
-        .map_err(|e| RpcError::Client(format!("timeout during {}: {}", method, e)))?
+        let args = serde_json::to_value(args).map_err(|e| RpcError::Client(e.to_string()))?;
+        let mut attempt = 0;
+        loop {
+            let result = tokio::time::timeout(
+                self.config.timeout.to_duration(),
+                self.client.call_method(method, returns, args.clone()),
+            )
+            .await
+            .map_err(|e| RpcError::Client(format!("timeout during {}: {}", method, e)))
+            .and_then(|result| result);
+            match result {
+                Err(RpcError::JsonRpcError(_)) | Err(RpcError::ParseError(..)) | Ok(_) => {
+                    return result
+                }
+                Err(_) if attempt < self.config.max_retries => {
+                    attempt += 1;
+                    tokio::time::sleep(self.config.retry_delay.to_duration()).await;
+                }
+                Err(_) => return result,
+            }
+        }
     }
 
     /// Gracefully stop the node.
@@ -90,7 +154,7 @@ impl RpcClient {
         self.call_method("stop_node", "()", ()).await
     }
 
-    /// Sign message with node's key.
+    /// Sign message with node's key, in the signed message envelope.
     /// Returns the public key that signed the message and the signature.
     pub async fn node_sign_message(&self, message: Vec<u8>) -> RpcResult<PubkeySig> {
         self.call_method("node_sign_message", "PubkeySig", vec![message])
@@ -117,6 +181,18 @@ impl RpcClient {
             .await
     }
 
+    /// Return the template of a block for the given slot and block producer.
+    pub async fn get_block_template(&self, input: BlockTemplateInput) -> RpcResult<BlockTemplate> {
+        self.call_method("get_block_template", "BlockTemplate", vec![input])
+            .await
+    }
+
+    /// Submit a block built and signed outside of the node, for propagation.
+    pub async fn submit_block(&self, block: BlockInput) -> RpcResult<BlockId> {
+        self.call_method("submit_block", "BlockId", vec![block])
+            .await
+    }
+
     /// Bans given ip address(es)
     /// No confirmation to expect.
     pub async fn node_ban_by_ip(&self, ips: Vec<IpAddr>) -> RpcResult<()> {
@@ -153,6 +229,12 @@ impl RpcClient {
             .await
     }
 
+    /// read the bootstrap server policy again from the node configuration
+    pub async fn node_reload_bootstrap_policy(&self) -> RpcResult<()> {
+        self.call_method("node_reload_bootstrap_policy", "()", ())
+            .await
+    }
+
     ////////////////
     // public-api //
     ////////////////
@@ -164,15 +246,16 @@ impl RpcClient {
         self.call_method("get_status", "NodeStatus", ()).await
     }
 
-    pub(crate) async fn _get_cliques(&self) -> RpcResult<Vec<Clique>> {
+    /// Returns the cliques of the block graph.
+    pub async fn get_cliques(&self) -> RpcResult<Vec<Clique>> {
         self.call_method("get_cliques", "Vec<Clique>", ()).await
     }
 
     // Debug (specific information)
 
     /// Returns the active stakers and their roll counts for the current cycle.
-    pub(crate) async fn _get_stakers(&self) -> RpcResult<Map<Address, u64>> {
-        self.call_method("get_stakers", "Map<Address, u64>", ())
+    pub async fn get_stakers(&self) -> RpcResult<Vec<(Address, u64)>> {
+        self.call_method("get_stakers", "Vec<(Address, u64)>", ())
             .await
     }
 
@@ -219,12 +302,16 @@ impl RpcClient {
 
     /// Get the block graph within the specified time interval.
     /// Optional parameters: from `<time_start>` (included) and to `<time_end>` (excluded) millisecond timestamp
-    pub(crate) async fn _get_graph_interval(
+    pub async fn get_graph_interval(
         &self,
         time_interval: TimeInterval,
     ) -> RpcResult<Vec<BlockSummary>> {
-        self.call_method("get_graph_interval", "Vec<BlockSummary>", time_interval)
-            .await
+        self.call_method(
+            "get_graph_interval",
+            "Vec<BlockSummary>",
+            vec![time_interval],
+        )
+        .await
     }
 
     /// Get info by addresses
@@ -233,6 +320,15 @@ impl RpcClient {
             .await
     }
 
+    /// Get the block and endorsement draws of a list of addresses within a slot range
+    pub async fn get_selection_draws(
+        &self,
+        input: SelectionDrawsInput,
+    ) -> RpcResult<SelectionDrawsOutput> {
+        self.call_method("get_selection_draws", "SelectionDrawsOutput", vec![input])
+            .await
+    }
+
     /// Get datastore entries
     pub async fn get_datastore_entries(
         &self,
@@ -249,6 +345,8 @@ impl RpcClient {
     // User (interaction with the node)
 
     /// Adds operations to pool. Returns operations that were ok and sent to pool.
+    /// If some are refused by the pool, the others are still sent to it,
+    /// and `rejected_operations` tells which ones were refused from the returned error.
     pub async fn send_operations(
         &self,
         operations: Vec<OperationInput>,
@@ -257,6 +355,28 @@ impl RpcClient {
             .await
     }
 
+    /// Get the fee densities of the pending operations and of the operations included in recent blocks,
+    /// and the fee recommended to include an operation of the given size within the given number of periods.
+    pub async fn get_fee_estimate(&self, input: FeeEstimateInput) -> RpcResult<FeeEstimate> {
+        self.call_method("get_fee_estimate", "FeeEstimate", vec![input])
+            .await
+    }
+
+    /// Check the signature of a message signed by an address, with the signed message envelope.
+    pub async fn verify_signature(&self, input: SignatureVerificationInput) -> RpcResult<bool> {
+        self.call_method("verify_signature", "bool", vec![input])
+            .await
+    }
+
+    /// Get the operations pending in the pool matching a filter, with pagination.
+    pub async fn get_pool_operations(
+        &self,
+        filter: PoolOperationFilter,
+    ) -> RpcResult<PoolOperationsPage> {
+        self.call_method("get_pool_operations", "PoolOperationsPage", vec![filter])
+            .await
+    }
+
     /// execute read only bytecode
     pub async fn execute_read_only_bytecode(
         &self,
@@ -0,0 +1,279 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::{rejected_operations, RpcClient, SdkError};
+use massa_models::api::{FeeEstimateInput, OperationInfo, OperationInput};
+use massa_models::timeslots::get_current_latest_block_slot;
+use massa_models::wrapped::WrappedContent;
+use massa_models::{
+    Address, Amount, Operation, OperationId, OperationSerializer, OperationType, Slot,
+    WrappedOperation,
+};
+use massa_signature::KeyPair;
+use massa_time::MassaTime;
+use massa_wallet::Wallet;
+use tokio::task::spawn_blocking;
+
+/// number of periods within which operations built without an explicit fee should be included
+pub const FEE_ESTIMATE_TARGET_PERIODS: u64 = 5;
+
+/// Builds, signs and sends an operation.
+///
+/// The expire period is computed from the current slot of the node and the fee is estimated
+/// by the node, unless they are set explicitly.
+#[derive(Debug, Clone)]
+pub struct OperationBuilder {
+    op: OperationType,
+    fee: Option<Amount>,
+    expire_period: Option<u64>,
+}
+
+impl OperationBuilder {
+    /// Builds an operation of any type
+    pub fn new(op: OperationType) -> Self {
+        OperationBuilder {
+            op,
+            fee: None,
+            expire_period: None,
+        }
+    }
+
+    /// Builds a transfer of `amount` coins to `recipient_address`
+    pub fn transaction(recipient_address: Address, amount: Amount) -> Self {
+        OperationBuilder::new(OperationType::Transaction {
+            recipient_address,
+            amount,
+        })
+    }
+
+    /// Builds the purchase of `roll_count` rolls
+    pub fn roll_buy(roll_count: u64) -> Self {
+        OperationBuilder::new(OperationType::RollBuy { roll_count })
+    }
+
+    /// Builds the sale of `roll_count` rolls
+    pub fn roll_sell(roll_count: u64) -> Self {
+        OperationBuilder::new(OperationType::RollSell { roll_count })
+    }
+
+    /// Builds the execution of a smart contract bytecode
+    pub fn execute_sc(data: Vec<u8>, max_gas: u64, gas_price: Amount, coins: Amount) -> Self {
+        OperationBuilder::new(OperationType::ExecuteSC {
+            data,
+            max_gas,
+            coins,
+            gas_price,
+        })
+    }
+
+    /// Builds a call to the function `target_func` of the smart contract at `target_addr`.
+    /// Use `with_call_coins` to transfer coins to the smart contract.
+    pub fn call_sc(
+        target_addr: Address,
+        target_func: String,
+        param: String,
+        max_gas: u64,
+        gas_price: Amount,
+    ) -> Self {
+        OperationBuilder::new(OperationType::CallSC {
+            target_addr,
+            target_func,
+            param,
+            max_gas,
+            sequential_coins: Amount::default(),
+            parallel_coins: Amount::default(),
+            gas_price,
+        })
+    }
+
+    /// Sets the coins transferred to the smart contract by a call,
+    /// ignored for the other operation types
+    pub fn with_call_coins(mut self, sequential: Amount, parallel: Amount) -> Self {
+        if let OperationType::CallSC {
+            sequential_coins,
+            parallel_coins,
+            ..
+        } = &mut self.op
+        {
+            *sequential_coins = sequential;
+            *parallel_coins = parallel;
+        }
+        self
+    }
+
+    /// Sets the fee instead of using the estimate of the node
+    pub fn with_fee(mut self, fee: Amount) -> Self {
+        self.fee = Some(fee);
+        self
+    }
+
+    /// Sets the expire period instead of computing it from the current slot
+    pub fn with_expire_period(mut self, expire_period: u64) -> Self {
+        self.expire_period = Some(expire_period);
+        self
+    }
+
+    /// Builds the content of the operation sent by `creator_address`
+    pub async fn build(
+        self,
+        client: &RpcClient,
+        creator_address: Address,
+    ) -> Result<Operation, SdkError> {
+        let expire_period = match self.expire_period {
+            Some(expire_period) => expire_period,
+            None => client.get_expire_period(creator_address).await?,
+        };
+        let fee = match self.fee {
+            Some(fee) => fee,
+            None => {
+                // measure the operation with a zero fee, which takes 9 bytes less than the largest fee
+                // the draft is never sent, and its size does not depend on the key wrapping it
+                let draft: WrappedOperation = Operation::new_wrapped(
+                    Operation {
+                        fee: Amount::default(),
+                        expire_period,
+                        op: self.op.clone(),
+                    },
+                    OperationSerializer::new(),
+                    &KeyPair::generate(),
+                )?;
+                let estimate = client
+                    .get_fee_estimate(FeeEstimateInput {
+                        operation_size: draft.serialized_size() as u64 + 9,
+                        target_periods: FEE_ESTIMATE_TARGET_PERIODS,
+                    })
+                    .await?;
+                // the draft is signed by another key, the operation goes to the thread of its creator
+                let thread =
+                    creator_address.get_thread(client.get_status().await?.config.thread_count);
+                let thread_estimate = estimate
+                    .threads
+                    .iter()
+                    .find(|thread_estimate| thread_estimate.thread == thread)
+                    .ok_or_else(|| {
+                        jsonrpc_core_client::RpcError::Client(format!(
+                            "missing fee estimate for the thread of {}",
+                            creator_address
+                        ))
+                    })?;
+                // the gas coins are paid to the block producer as well
+                thread_estimate
+                    .recommended_fee
+                    .saturating_sub(draft.get_gas_coins())
+            }
+        };
+        Ok(Operation {
+            fee,
+            expire_period,
+            op: self.op,
+        })
+    }
+
+    /// Builds the operation and signs it with the wallet signer of `creator_address`,
+    /// on a blocking thread as an external signer can take a while to answer
+    pub async fn sign(
+        self,
+        client: &RpcClient,
+        wallet: &Wallet,
+        creator_address: Address,
+    ) -> Result<WrappedOperation, SdkError> {
+        let content = self.build(client, creator_address).await?;
+        let wallet = wallet.clone();
+        Ok(spawn_blocking(move || wallet.create_operation(content, creator_address)).await??)
+    }
+
+    /// Builds, signs and sends the operation, returning its id
+    pub async fn send(
+        self,
+        client: &RpcClient,
+        wallet: &Wallet,
+        creator_address: Address,
+    ) -> Result<OperationId, SdkError> {
+        let op = self.sign(client, wallet, creator_address).await?;
+        client.send_wrapped_operation(op).await
+    }
+
+    /// Builds, signs and sends the operation, then waits until it is final
+    /// or until `timeout` is reached, polling the node every `poll_interval`
+    pub async fn send_and_wait_final(
+        self,
+        client: &RpcClient,
+        wallet: &Wallet,
+        creator_address: Address,
+        timeout: MassaTime,
+        poll_interval: MassaTime,
+    ) -> Result<OperationInfo, SdkError> {
+        let id = self.send(client, wallet, creator_address).await?;
+        client
+            .wait_operation_final(id, timeout, poll_interval)
+            .await
+    }
+}
+
+impl RpcClient {
+    /// Gets the last period at which an operation sent now by `creator_address` can be included,
+    /// `operation_validity_periods` periods after the current slot
+    pub async fn get_expire_period(&self, creator_address: Address) -> Result<u64, SdkError> {
+        let cfg = self.get_status().await?.config;
+        let slot =
+            get_current_latest_block_slot(cfg.thread_count, cfg.t0, cfg.genesis_timestamp, 0)? // clock compensation is zero
+                .unwrap_or_else(|| Slot::new(0, 0));
+        let mut expire_period = slot.period + cfg.operation_validity_periods;
+        if slot.thread >= creator_address.get_thread(cfg.thread_count) {
+            expire_period += 1;
+        };
+        Ok(expire_period)
+    }
+
+    /// Sends a signed operation to the node, returning its id
+    pub async fn send_wrapped_operation(
+        &self,
+        op: WrappedOperation,
+    ) -> Result<OperationId, SdkError> {
+        let id = op.id;
+        match self
+            .send_operations(vec![OperationInput {
+                creator_public_key: op.creator_public_key,
+                serialized_content: op.serialized_data,
+                signature: op.signature,
+                multisig: op.multisig,
+            }])
+            .await
+        {
+            Ok(_) => Ok(id),
+            Err(err) => Err(
+                match rejected_operations(&err)
+                    .into_iter()
+                    .find(|rejected| rejected.id == id)
+                {
+                    Some(rejected) => SdkError::OperationRejected(id, rejected.reason),
+                    None => err.into(),
+                },
+            ),
+        }
+    }
+
+    /// Polls the node every `poll_interval` until the operation is final,
+    /// or until `timeout` is reached
+    pub async fn wait_operation_final(
+        &self,
+        id: OperationId,
+        timeout: MassaTime,
+        poll_interval: MassaTime,
+    ) -> Result<OperationInfo, SdkError> {
+        let deadline = MassaTime::now()?.saturating_add(timeout);
+        loop {
+            if let Some(info) = self
+                .get_operations(vec![id])
+                .await?
+                .into_iter()
+                .find(|info| info.id == id && info.is_final)
+            {
+                return Ok(info);
+            }
+            if MassaTime::now()? >= deadline {
+                return Err(SdkError::FinalityTimeout(id, timeout));
+            }
+            tokio::time::sleep(poll_interval.to_duration()).await;
+        }
+    }
+}
@@ -0,0 +1,206 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use super::tools::{node_status, start_mock_node, test_client_config};
+use crate::{OperationBuilder, SdkError, FEE_ESTIMATE_TARGET_PERIODS};
+use jsonrpc_core::{IoHandler, Params, Value};
+use jsonrpc_core_client::RpcError;
+use massa_models::api::{FeeEstimate, FeeEstimateInput, OperationInfo, ThreadFeeEstimate};
+use massa_models::wrapped::WrappedContent;
+use massa_models::{
+    Address, Amount, CompactConfig, Operation, OperationSerializer, OperationType, WrappedOperation,
+};
+use massa_signature::KeyPair;
+use massa_time::MassaTime;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_node_errors_are_not_retried() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let mut io = IoHandler::new();
+    let method_calls = calls.clone();
+    io.add_sync_method("get_status", move |_| {
+        method_calls.fetch_add(1, Ordering::SeqCst);
+        Err(jsonrpc_core::Error::invalid_request())
+    });
+    let (_server, client) = start_mock_node(io, test_client_config()).await;
+
+    assert!(matches!(
+        client.get_status().await,
+        Err(RpcError::JsonRpcError(_))
+    ));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_timeouts_are_retried() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let mut io = IoHandler::new();
+    let method_calls = calls.clone();
+    io.add_method("get_status", move |_| {
+        let calls = method_calls.clone();
+        async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_secs(2)).await;
+            Ok(Value::Null)
+        }
+    });
+    let config = test_client_config();
+    let (_server, client) = start_mock_node(io, config).await;
+
+    assert!(matches!(
+        client.get_status().await,
+        Err(RpcError::Client(_))
+    ));
+    assert_eq!(
+        calls.load(Ordering::SeqCst),
+        1 + config.max_retries as usize
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_build_operation() {
+    // before genesis, the expire period is counted from the slot (0, 0)
+    let config = CompactConfig {
+        genesis_timestamp: MassaTime::now()
+            .unwrap()
+            .saturating_add(MassaTime::from(3_600_000)),
+        operation_validity_periods: 10,
+        ..Default::default()
+    };
+    let operation_size = Arc::new(AtomicU64::new(0));
+    let mut io = IoHandler::new();
+    io.add_sync_method("get_status", move |_| {
+        Ok(serde_json::to_value(node_status(config)).unwrap())
+    });
+    let estimated_size = operation_size.clone();
+    io.add_sync_method("get_fee_estimate", move |params: Params| {
+        let input: Vec<FeeEstimateInput> = params.parse()?;
+        assert_eq!(input[0].target_periods, FEE_ESTIMATE_TARGET_PERIODS);
+        estimated_size.store(input[0].operation_size, Ordering::SeqCst);
+        Ok(serde_json::to_value(FeeEstimate {
+            operation_size: input[0].operation_size,
+            target_periods: input[0].target_periods,
+            threads: (0..config.thread_count)
+                .map(|thread| ThreadFeeEstimate {
+                    thread,
+                    pool: None,
+                    recent_blocks: None,
+                    recommended_fee_density: Amount::default(),
+                    // a different fee in each thread
+                    recommended_fee: Amount::from_mantissa_scale(thread as u64 + 1, 0),
+                })
+                .collect(),
+        })
+        .unwrap())
+    });
+    let (_server, client) = start_mock_node(io, test_client_config()).await;
+
+    let creator_address = Address::from_public_key(&KeyPair::generate().get_public_key());
+    let builder = OperationBuilder::execute_sc(
+        vec![1, 2, 3],
+        1000,
+        Amount::from_raw(100),
+        Amount::default(),
+    );
+    let operation = builder
+        .clone()
+        .build(&client, creator_address)
+        .await
+        .unwrap();
+
+    let expected_expire_period = if creator_address.get_thread(config.thread_count) == 0 {
+        11
+    } else {
+        10
+    };
+    assert_eq!(operation.expire_period, expected_expire_period);
+    // the gas coins are deduced from the recommended fee of the thread of the creator
+    let creator_thread = creator_address.get_thread(config.thread_count);
+    assert_eq!(
+        operation.fee,
+        Amount::from_mantissa_scale(creator_thread as u64 + 1, 0)
+            .saturating_sub(Amount::from_raw(100 * 1000))
+    );
+    // the size is estimated for the largest fee
+    let largest: WrappedOperation = Operation::new_wrapped(
+        Operation {
+            fee: Amount::from_raw(u64::MAX),
+            expire_period: expected_expire_period,
+            op: operation.op.clone(),
+        },
+        OperationSerializer::new(),
+        &KeyPair::generate(),
+    )
+    .unwrap();
+    assert_eq!(
+        operation_size.load(Ordering::SeqCst),
+        largest.serialized_size() as u64
+    );
+
+    // explicit values do not query the node
+    let (_server, client) = start_mock_node(IoHandler::new(), test_client_config()).await;
+    let operation = builder
+        .with_fee(Amount::from_raw(5))
+        .with_expire_period(42)
+        .build(&client, creator_address)
+        .await
+        .unwrap();
+    assert_eq!(operation.fee, Amount::from_raw(5));
+    assert_eq!(operation.expire_period, 42);
+}
+
+/// Node answering `get_operations` with an operation that becomes final at the `final_after`-th call
+fn operation_node(op: WrappedOperation, final_after: usize) -> (IoHandler, Arc<AtomicUsize>) {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let mut io = IoHandler::new();
+    let method_calls = calls.clone();
+    io.add_sync_method("get_operations", move |_| {
+        let call = method_calls.fetch_add(1, Ordering::SeqCst) + 1;
+        Ok(serde_json::to_value(vec![OperationInfo {
+            id: op.id,
+            in_pool: call < final_after,
+            in_blocks: Vec::new(),
+            is_final: call >= final_after,
+            operation: op.clone(),
+        }])
+        .unwrap())
+    });
+    (io, calls)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_wait_operation_final() {
+    let op: WrappedOperation = Operation::new_wrapped(
+        Operation {
+            fee: Amount::default(),
+            expire_period: 10,
+            op: OperationType::RollBuy { roll_count: 1 },
+        },
+        OperationSerializer::new(),
+        &KeyPair::generate(),
+    )
+    .unwrap();
+
+    // the node is polled until the operation is final
+    let (io, calls) = operation_node(op.clone(), 3);
+    let (_server, client) = start_mock_node(io, test_client_config()).await;
+    let info = client
+        .wait_operation_final(op.id, MassaTime::from(10_000), MassaTime::from(10))
+        .await
+        .unwrap();
+    assert_eq!(info.id, op.id);
+    assert!(info.is_final);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    // or until the timeout
+    let (io, _) = operation_node(op.clone(), usize::MAX);
+    let (_server, client) = start_mock_node(io, test_client_config()).await;
+    assert!(matches!(
+        client
+            .wait_operation_final(op.id, MassaTime::from(100), MassaTime::from(10))
+            .await,
+        Err(SdkError::FinalityTimeout(id, _)) if id == op.id
+    ));
+}
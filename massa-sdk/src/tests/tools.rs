@@ -0,0 +1,74 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::{ClientConfig, RpcClient};
+use jsonrpc_core::IoHandler;
+use jsonrpc_http_server::{Server, ServerBuilder};
+use massa_models::api::NodeStatus;
+use massa_models::node::NodeId;
+use massa_models::stats::{ConsensusStats, NetworkStats, PoolStats};
+use massa_models::{CompactConfig, Slot};
+use massa_signature::KeyPair;
+use massa_time::MassaTime;
+
+/// Client configuration retrying quickly, so that the tests do not wait
+pub fn test_client_config() -> ClientConfig {
+    ClientConfig {
+        timeout: MassaTime::from(200),
+        max_retries: 2,
+        retry_delay: MassaTime::from(10),
+    }
+}
+
+/// Starts a node answering the JSON-RPC methods of `io` and a client connected to it.
+/// The node stops when the returned server is dropped.
+pub async fn start_mock_node(io: IoHandler, config: ClientConfig) -> (Server, RpcClient) {
+    let server = ServerBuilder::new(io)
+        .event_loop_executor(tokio::runtime::Handle::current())
+        .start_http(&"127.0.0.1:0".parse().unwrap())
+        .expect("could not start the mock node");
+    let client = RpcClient::from_url_with_config(&format!("http://{}", server.address()), config)
+        .await
+        .unwrap();
+    (server, client)
+}
+
+/// Status of a node with the given configuration
+pub fn node_status(config: CompactConfig) -> NodeStatus {
+    let now = MassaTime::now().unwrap();
+    NodeStatus {
+        node_id: NodeId(KeyPair::generate().get_public_key()),
+        node_ip: None,
+        version: "TEST.13.0".parse().unwrap(),
+        current_time: now,
+        current_cycle: 0,
+        connected_nodes: Default::default(),
+        last_slot: None,
+        next_slot: Slot::new(0, 0),
+        consensus_stats: ConsensusStats {
+            start_timespan: now,
+            end_timespan: now,
+            final_block_count: 0,
+            final_operation_count: 0,
+            stale_block_count: 0,
+            clique_count: 0,
+            staker_count: 0,
+        },
+        pool_stats: PoolStats {
+            operation_count: 0,
+            endorsement_count: 0,
+        },
+        network_stats: NetworkStats {
+            in_connection_count: 0,
+            out_connection_count: 0,
+            known_peer_count: 0,
+            banned_peer_count: 0,
+            active_node_count: 0,
+            bytes_sent: 0,
+            bytes_received: 0,
+            messages_sent: 0,
+            messages_received: 0,
+            handshake_failure_count: 0,
+        },
+        config,
+    }
+}
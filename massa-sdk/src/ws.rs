@@ -0,0 +1,73 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::SdkError;
+use futures::StreamExt;
+use jsonrpc_core_client::transports::ws;
+use jsonrpc_core_client::{RpcChannel, RpcResult, TypedClient, TypedSubscriptionStream};
+use massa_models::api::{PoolEvictionReason, PoolOperationEvent};
+use massa_models::OperationId;
+use massa_time::MassaTime;
+
+/// Client of the public `WebSocket` API, serving subscriptions
+pub struct WsClient {
+    client: TypedClient,
+}
+
+/// This is required by `jsonrpc_core_client::transports::ws::try_connect`
+impl From<RpcChannel> for WsClient {
+    fn from(channel: RpcChannel) -> Self {
+        WsClient {
+            client: channel.into(),
+        }
+    }
+}
+
+/// Stream of the operations entering and leaving the pool
+pub type PoolOperationEvents = TypedSubscriptionStream<PoolOperationEvent>;
+
+impl WsClient {
+    /// Connects to the `WebSocket` API at the given url, such as `ws://127.0.0.1:33037`
+    pub async fn from_url(url: &str) -> RpcResult<WsClient> {
+        ws::try_connect::<WsClient>(url)?.await
+    }
+
+    /// Subscribe to the operations entering and leaving the pool
+    pub fn subscribe_pool_operation_events(&self) -> RpcResult<PoolOperationEvents> {
+        self.client.subscribe(
+            "subscribe_pool_operation_events",
+            (),
+            "pool_operation_events",
+            "unsubscribe_pool_operation_events",
+            "PoolOperationEvent",
+        )
+    }
+}
+
+/// Waits on a pool events subscription until the operation leaves the pool because it is final,
+/// or until `timeout` is reached.
+/// The subscription must be started before the operation is sent.
+pub async fn wait_pool_operation_final(
+    events: &mut PoolOperationEvents,
+    id: OperationId,
+    timeout: MassaTime,
+) -> Result<(), SdkError> {
+    let wait = async {
+        while let Some(event) = events.next().await {
+            match event? {
+                PoolOperationEvent::Removed {
+                    id: removed_id,
+                    reason: PoolEvictionReason::Final,
+                } if removed_id == id => return Ok(()),
+                PoolOperationEvent::Removed {
+                    id: removed_id,
+                    reason,
+                } if removed_id == id => return Err(SdkError::OperationNotFinal(id, reason)),
+                _ => {}
+            }
+        }
+        Err(jsonrpc_core_client::RpcError::Client("the subscription was closed".into()).into())
+    };
+    tokio::time::timeout(timeout.to_duration(), wait)
+        .await
+        .map_err(|_| SdkError::FinalityTimeout(id, timeout))?
+}
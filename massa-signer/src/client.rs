@@ -0,0 +1,269 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Client used by the node to reach a remote signer
+
+use crate::protocol::{
+    read_message, write_message, SignedContent, SignerEndpoint, SignerRequest, SignerResponse,
+};
+use crate::SignerError;
+use massa_models::wrapped::compute_wrapped_hash;
+use massa_signature::{PublicKey, Signature};
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, BufReader};
+use tokio::net::TcpStream;
+
+/// Remote signer settings, read from the node configuration
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RemoteSignerSettings {
+    /// endpoint on which the remote signer listens
+    pub endpoint: SignerEndpoint,
+    /// max time to wait for an answer of the remote signer, in milliseconds
+    pub timeout: MassaTime,
+    /// secret shared with the remote signer, required by it over TCP
+    #[serde(default)]
+    pub secret: Option<String>,
+}
+
+/// Client of a remote signer. It opens a new connection for each request,
+/// so that a restart of the signer does not require a restart of the node.
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    settings: RemoteSignerSettings,
+}
+
+impl RemoteSigner {
+    /// Creates a new client
+    pub fn new(settings: RemoteSignerSettings) -> Self {
+        RemoteSigner { settings }
+    }
+
+    /// Get the public keys the remote signer can sign for
+    pub async fn get_public_keys(&self) -> Result<Vec<PublicKey>, SignerError> {
+        match self.request(&SignerRequest::GetPublicKeys).await? {
+            SignerResponse::PublicKeys(public_keys) => Ok(public_keys),
+            response => Err(unexpected_response(response)),
+        }
+    }
+
+    /// Get the signature of a content by the remote signer.
+    /// The signature is checked before being returned.
+    ///
+    /// # Arguments
+    /// * `public_key`: public key of the content creator
+    /// * `content`: content to sign
+    pub async fn sign(
+        &self,
+        public_key: PublicKey,
+        content: SignedContent,
+    ) -> Result<Signature, SignerError> {
+        let hash = compute_wrapped_hash(&public_key, content.get_serialized());
+        let request = SignerRequest::Sign {
+            public_key,
+            content,
+        };
+        match self.request(&request).await? {
+            SignerResponse::Signature(signature) => {
+                if public_key.verify_signature(&hash, &signature).is_err() {
+                    return Err(SignerError::InvalidSignature(public_key.to_string()));
+                }
+                Ok(signature)
+            }
+            SignerResponse::Refused(reason) => Err(SignerError::Refused(reason)),
+            response => Err(unexpected_response(response)),
+        }
+    }
+
+    /// Send a request and wait for the response
+    async fn request(&self, request: &SignerRequest) -> Result<SignerResponse, SignerError> {
+        let exchange = async {
+            match &self.settings.endpoint {
+                SignerEndpoint::Tcp(addr) => {
+                    exchange(
+                        TcpStream::connect(addr).await?,
+                        self.settings.secret.as_deref(),
+                        request,
+                    )
+                    .await
+                }
+                #[cfg(unix)]
+                SignerEndpoint::Unix(path) => {
+                    exchange(
+                        tokio::net::UnixStream::connect(path).await?,
+                        self.settings.secret.as_deref(),
+                        request,
+                    )
+                    .await
+                }
+                #[cfg(not(unix))]
+                SignerEndpoint::Unix(_) => Err(SignerError::UnsupportedEndpoint(
+                    "Unix sockets are not available on this platform".into(),
+                )),
+            }
+        };
+        tokio::time::timeout(self.settings.timeout.to_duration(), exchange)
+            .await
+            .map_err(|_| SignerError::Timeout)?
+    }
+}
+
+/// Send a request on a new connection, after authenticating if there is a secret, and read the response
+async fn exchange<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: S,
+    secret: Option<&str>,
+    request: &SignerRequest,
+) -> Result<SignerResponse, SignerError> {
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+    if let Some(secret) = secret {
+        let authenticate = SignerRequest::Authenticate {
+            secret: secret.to_string(),
+        };
+        write_message(&mut writer, &authenticate).await?;
+        match read_message(&mut lines).await? {
+            Some(SignerResponse::Authenticated) => {}
+            Some(response) => return Err(unexpected_response(response)),
+            None => return Err(SignerError::ConnectionClosed),
+        }
+    }
+    write_message(&mut writer, request).await?;
+    read_message(&mut lines)
+        .await?
+        .ok_or(SignerError::ConnectionClosed)
+}
+
+fn unexpected_response(response: SignerResponse) -> SignerError {
+    match response {
+        SignerResponse::Error(err) => SignerError::RemoteError(err),
+        response => SignerError::UnexpectedResponse(format!("{:?}", response)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::KeyPair;
+    use std::net::SocketAddr;
+    use tokio::net::TcpListener;
+
+    /// Starts a signer answering each request with the response given by `respond`
+    async fn start_mock_signer<F>(respond: F) -> SocketAddr
+    where
+        F: Fn(SignerRequest) -> SignerResponse + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let (reader, mut writer) = tokio::io::split(stream);
+                let mut lines = BufReader::new(reader).lines();
+                while let Some(request) = read_message(&mut lines).await.unwrap() {
+                    write_message(&mut writer, &respond(request)).await.unwrap();
+                }
+            }
+        });
+        addr
+    }
+
+    fn create_client(addr: SocketAddr) -> RemoteSigner {
+        RemoteSigner::new(RemoteSignerSettings {
+            endpoint: SignerEndpoint::Tcp(addr),
+            timeout: 1000.into(),
+            secret: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_get_public_keys() {
+        let public_key = KeyPair::generate().get_public_key();
+        let addr = start_mock_signer(move |request| match request {
+            SignerRequest::GetPublicKeys => SignerResponse::PublicKeys(vec![public_key]),
+            _ => SignerResponse::Error("unexpected request".into()),
+        })
+        .await;
+        let public_keys = create_client(addr).get_public_keys().await.unwrap();
+        assert_eq!(public_keys, vec![public_key]);
+    }
+
+    #[tokio::test]
+    async fn test_authentication() {
+        let public_key = KeyPair::generate().get_public_key();
+        let addr = start_mock_signer(move |request| match request {
+            SignerRequest::Authenticate { secret } if secret == "secret" => {
+                SignerResponse::Authenticated
+            }
+            SignerRequest::Authenticate { .. } => SignerResponse::Error("wrong secret".into()),
+            SignerRequest::GetPublicKeys => SignerResponse::PublicKeys(vec![public_key]),
+            _ => SignerResponse::Error("unexpected request".into()),
+        })
+        .await;
+        let client = |secret: &str| {
+            RemoteSigner::new(RemoteSignerSettings {
+                secret: Some(secret.to_string()),
+                ..create_client(addr).settings
+            })
+        };
+        let public_keys = client("secret").get_public_keys().await.unwrap();
+        assert_eq!(public_keys, vec![public_key]);
+        assert!(matches!(
+            client("guess").get_public_keys().await,
+            Err(SignerError::RemoteError(err)) if err == "wrong secret"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_sign() {
+        let keypair = KeyPair::generate();
+        let public_key = keypair.get_public_key();
+        let addr = start_mock_signer(move |request| match request {
+            SignerRequest::Sign {
+                public_key,
+                content,
+            } => SignerResponse::Signature(
+                keypair
+                    .sign(&compute_wrapped_hash(&public_key, content.get_serialized()))
+                    .unwrap(),
+            ),
+            _ => SignerResponse::Error("unexpected request".into()),
+        })
+        .await;
+        let content = SignedContent::Endorsement(vec![1, 2, 3]);
+        let hash = compute_wrapped_hash(&public_key, content.get_serialized());
+        let signature = create_client(addr).sign(public_key, content).await.unwrap();
+        public_key.verify_signature(&hash, &signature).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_sign_with_wrong_key_is_rejected() {
+        let other_keypair = KeyPair::generate();
+        let addr = start_mock_signer(move |request| match request {
+            SignerRequest::Sign { content, .. } => SignerResponse::Signature(
+                other_keypair
+                    .sign(&compute_wrapped_hash(
+                        &other_keypair.get_public_key(),
+                        content.get_serialized(),
+                    ))
+                    .unwrap(),
+            ),
+            _ => SignerResponse::Error("unexpected request".into()),
+        })
+        .await;
+        let public_key = KeyPair::generate().get_public_key();
+        let result = create_client(addr)
+            .sign(public_key, SignedContent::Endorsement(vec![1, 2, 3]))
+            .await;
+        assert!(matches!(result, Err(SignerError::InvalidSignature(_))));
+    }
+
+    #[tokio::test]
+    async fn test_refusal() {
+        let addr = start_mock_signer(|_| SignerResponse::Refused("double signing".into())).await;
+        let result = create_client(addr)
+            .sign(
+                KeyPair::generate().get_public_key(),
+                SignedContent::Endorsement(vec![1, 2, 3]),
+            )
+            .await;
+        assert!(matches!(result, Err(SignerError::Refused(reason)) if reason == "double signing"));
+    }
+}
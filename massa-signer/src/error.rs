@@ -0,0 +1,33 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! massa-signer error module
+
+use displaydoc::Display;
+use massa_models::ModelsError;
+use thiserror::Error;
+
+/// Remote signer error
+#[non_exhaustive]
+#[derive(Display, Error, Debug)]
+pub enum SignerError {
+    /// IO error: {0}
+    IOError(#[from] std::io::Error),
+    /// serde error: {0}
+    SerdeError(#[from] serde_json::Error),
+    /// models error: {0}
+    ModelsError(#[from] ModelsError),
+    /// the remote signer did not answer in time
+    Timeout,
+    /// the remote signer closed the connection without answering
+    ConnectionClosed,
+    /// the remote signer refused to sign: {0}
+    Refused(String),
+    /// remote signer error: {0}
+    RemoteError(String),
+    /// unexpected response from the remote signer: {0}
+    UnexpectedResponse(String),
+    /// invalid signature from the remote signer for public key {0}
+    InvalidSignature(String),
+    /// unsupported endpoint: {0}
+    UnsupportedEndpoint(String),
+}
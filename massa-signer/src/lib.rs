@@ -0,0 +1,22 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Signing of the block headers, blocks and endorsements of the staking addresses
+//! by an external process, so that the staking secret keys do not live in the node.
+//!
+//! The node reaches the signer over TCP or a Unix socket with a simple request/response protocol
+//! (see `protocol`). The `massa-signer` binary is a reference signer that holds the keys of a staking keys file
+//! and refuses to sign two different block headers for the same slot, or two different endorsements
+//! for the same slot and index.
+#![warn(missing_docs)]
+
+mod client;
+mod error;
+pub mod protocol;
+mod slashing_protection;
+
+pub use client::{RemoteSigner, RemoteSignerSettings};
+pub use error::SignerError;
+pub use protocol::{SignedContent, SignerEndpoint};
+pub use slashing_protection::{
+    PendingRecord, SlashingProtection, HISTORY_PERIODS, MAX_FUTURE_PERIODS,
+};
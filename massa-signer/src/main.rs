@@ -0,0 +1,380 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Reference remote signer for the staking keys of a massa node.
+//!
+//! It holds the keys of a staking keys file and signs the block headers, blocks and endorsements
+//! requested by the node, unless signing them could lead to a penalty.
+//! Over TCP, it only answers the connections presenting the secret shared with the node,
+//! and it listens on the loopback address unless told otherwise.
+
+use anyhow::{bail, Result};
+use dialoguer::Password;
+use massa_cipher::decrypt;
+use massa_models::constants::{GENESIS_TIMESTAMP, T0, THREAD_COUNT};
+use massa_models::prehash::Map;
+use massa_models::timeslots::get_current_latest_block_slot;
+use massa_models::wrapped::compute_wrapped_hash;
+use massa_models::Address;
+use massa_signature::{KeyPair, PublicKey};
+use massa_signer::protocol::{read_message, write_message, SignerRequest, SignerResponse};
+use massa_signer::SlashingProtection;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use structopt::StructOpt;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, BufReader};
+use tokio::net::TcpListener;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+#[derive(StructOpt)]
+struct Args {
+    /// Staking keys file, encrypted as the one of the node
+    #[structopt(long = "keys", parse(from_os_str))]
+    keys: PathBuf,
+    /// Password of the staking keys file, prompted if absent
+    #[structopt(short = "p", long = "pwd")]
+    password: Option<String>,
+    /// File in which the signed block headers and endorsements are recorded
+    #[structopt(
+        long = "history",
+        parse(from_os_str),
+        default_value = "signer_history.json"
+    )]
+    history: PathBuf,
+    /// TCP port to listen on. Requires --secret-file
+    #[structopt(long = "tcp")]
+    tcp: Option<u16>,
+    /// IP address to listen on with --tcp. Only local processes can reach the loopback address
+    #[structopt(long = "tcp-ip", default_value = "127.0.0.1")]
+    tcp_ip: IpAddr,
+    /// File holding the secret shared with the node, that the connections must present
+    #[structopt(long = "secret-file", parse(from_os_str))]
+    secret_file: Option<PathBuf>,
+    /// Unix socket to listen on
+    #[structopt(long = "unix", parse(from_os_str))]
+    unix: Option<PathBuf>,
+}
+
+/// Keys and signing history, shared by the connections
+struct Signer {
+    keys: HashMap<PublicKey, KeyPair>,
+    protection: SlashingProtection,
+    history_path: PathBuf,
+}
+
+impl Signer {
+    async fn process_request(&mut self, request: SignerRequest) -> SignerResponse {
+        match request {
+            SignerRequest::GetPublicKeys => {
+                SignerResponse::PublicKeys(self.keys.keys().copied().collect())
+            }
+            // the secret is checked by the connection before its requests get here
+            SignerRequest::Authenticate { .. } => SignerResponse::Authenticated,
+            SignerRequest::Sign {
+                public_key,
+                content,
+            } => {
+                let keypair = match self.keys.get(&public_key) {
+                    Some(keypair) => keypair,
+                    None => {
+                        return SignerResponse::Error(format!("unknown public key {}", public_key))
+                    }
+                };
+                let current_slot =
+                    match get_current_latest_block_slot(THREAD_COUNT, T0, *GENESIS_TIMESTAMP, 0) {
+                        Ok(current_slot) => current_slot,
+                        Err(err) => {
+                            return SignerResponse::Error(format!(
+                                "could not get the current slot: {}",
+                                err
+                            ))
+                        }
+                    };
+                match self.protection.check(&public_key, &content, current_slot) {
+                    Ok(pending) => {
+                        // the history must be saved before the signature leaves the signer
+                        if let Some(pending) = pending {
+                            if let Err(err) =
+                                self.protection.record(pending, &self.history_path).await
+                            {
+                                return SignerResponse::Error(format!(
+                                    "could not save the signing history: {}",
+                                    err
+                                ));
+                            }
+                        }
+                        match keypair
+                            .sign(&compute_wrapped_hash(&public_key, content.get_serialized()))
+                        {
+                            Ok(signature) => SignerResponse::Signature(signature),
+                            Err(err) => SignerResponse::Error(err.to_string()),
+                        }
+                    }
+                    Err(reason) => {
+                        warn!("refused to sign for {}: {}", public_key, reason);
+                        SignerResponse::Refused(reason)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Compare secrets in constant time, so that they cannot be guessed byte by byte
+fn secrets_match(expected: &str, given: &str) -> bool {
+    expected.len() == given.len()
+        && expected
+            .bytes()
+            .zip(given.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Answer the requests of a connection until it is closed.
+/// If there is a secret, the connection is closed unless its first request presents it.
+async fn serve_connection<S: AsyncRead + AsyncWrite>(
+    stream: S,
+    signer: Arc<Mutex<Signer>>,
+    secret: Option<Arc<String>>,
+) {
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+    let mut authenticated = secret.is_none();
+    loop {
+        let response = match read_message::<_, SignerRequest>(&mut lines).await {
+            Ok(Some(SignerRequest::Authenticate { secret: given })) => {
+                if secret
+                    .as_ref()
+                    .map_or(true, |secret| secrets_match(secret, &given))
+                {
+                    authenticated = true;
+                    SignerResponse::Authenticated
+                } else {
+                    warn!("refused a connection presenting a wrong secret");
+                    SignerResponse::Error("wrong secret".into())
+                }
+            }
+            Ok(Some(_)) if !authenticated => {
+                warn!("refused a connection that did not present the secret");
+                SignerResponse::Error("authentication required".into())
+            }
+            Ok(Some(request)) => signer.lock().await.process_request(request).await,
+            Ok(None) => break,
+            Err(err) => SignerResponse::Error(format!("invalid request: {}", err)),
+        };
+        if let Err(err) = write_message(&mut writer, &response).await {
+            warn!("could not answer a request: {}", err);
+            break;
+        }
+        if !authenticated {
+            break;
+        }
+    }
+}
+
+async fn load_keys(
+    path: &PathBuf,
+    password: Option<String>,
+) -> Result<HashMap<PublicKey, KeyPair>> {
+    let password = password.unwrap_or_else(|| {
+        Password::new()
+            .with_prompt("Enter staking keys file password")
+            .interact()
+            .expect("IO error: Password reading failed, staking keys file couldn't be unlocked")
+    });
+    let (_version, decrypted_data) = decrypt(&password, &tokio::fs::read(path).await?)?;
+    let keys: Map<Address, KeyPair> = serde_json::from_slice(&decrypted_data)?;
+    Ok(keys
+        .into_values()
+        .map(|keypair| (keypair.get_public_key(), keypair))
+        .collect())
+}
+
+#[paw::main]
+#[tokio::main]
+async fn main(args: Args) -> Result<()> {
+    tracing_subscriber::fmt::init();
+
+    if args.tcp.is_none() && args.unix.is_none() {
+        bail!("no endpoint to listen on: use --tcp and/or --unix");
+    }
+    let secret = match &args.secret_file {
+        Some(path) => {
+            let secret = tokio::fs::read_to_string(path).await?.trim().to_string();
+            if secret.is_empty() {
+                bail!("the secret file {} is empty", path.display());
+            }
+            Some(Arc::new(secret))
+        }
+        None if args.tcp.is_some() => {
+            bail!("listening on TCP requires the secret shared with the node: use --secret-file")
+        }
+        None => None,
+    };
+    let keys = load_keys(&args.keys, args.password).await?;
+    for public_key in keys.keys() {
+        info!(
+            "Signing for address {}",
+            Address::from_public_key(public_key)
+        );
+    }
+    let signer = Arc::new(Mutex::new(Signer {
+        keys,
+        protection: SlashingProtection::load(&args.history).await?,
+        history_path: args.history,
+    }));
+
+    let mut listeners = Vec::new();
+    if let Some(port) = args.tcp {
+        let addr = SocketAddr::new(args.tcp_ip, port);
+        if !addr.ip().is_loopback() {
+            warn!(
+                "listening on {}: other hosts can reach the signer, and the secret is not encrypted on the way",
+                addr.ip()
+            );
+        }
+        let listener = TcpListener::bind(addr).await?;
+        info!("Listening on tcp://{}", addr);
+        let signer = signer.clone();
+        let secret = secret.clone();
+        listeners.push(tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        tokio::spawn(serve_connection(stream, signer.clone(), secret.clone()));
+                    }
+                    Err(err) => warn!("could not accept a TCP connection: {}", err),
+                }
+            }
+        }));
+    }
+    if let Some(path) = args.unix {
+        #[cfg(unix)]
+        {
+            // a socket file left by a previous run prevents binding
+            if path.exists() {
+                std::fs::remove_file(&path)?;
+            }
+            let listener = tokio::net::UnixListener::bind(&path)?;
+            // only the processes of the user running the signer can connect
+            {
+                use std::os::unix::fs::PermissionsExt;
+                std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+            }
+            info!("Listening on unix://{}", path.display());
+            let signer = signer.clone();
+            listeners.push(tokio::spawn(async move {
+                loop {
+                    match listener.accept().await {
+                        Ok((stream, _)) => {
+                            tokio::spawn(serve_connection(stream, signer.clone(), secret.clone()));
+                        }
+                        Err(err) => warn!("could not accept a Unix socket connection: {}", err),
+                    }
+                }
+            }));
+        }
+        #[cfg(not(unix))]
+        bail!(
+            "Unix sockets are not available on this platform: {}",
+            path.display()
+        );
+    }
+
+    tokio::signal::ctrl_c().await?;
+    info!("interrupt signal received");
+    for listener in listeners {
+        listener.abort();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Send requests on a new connection until it is closed, and collect the responses
+    async fn exchange(
+        signer: Arc<Mutex<Signer>>,
+        secret: Option<Arc<String>>,
+        requests: Vec<SignerRequest>,
+    ) -> Vec<SignerResponse> {
+        let (client, server) = tokio::io::duplex(4096);
+        tokio::spawn(serve_connection(server, signer, secret));
+        let (reader, mut writer) = tokio::io::split(client);
+        let mut lines = BufReader::new(reader).lines();
+        let mut responses = Vec::new();
+        for request in requests {
+            if write_message(&mut writer, &request).await.is_err() {
+                break;
+            }
+            match read_message(&mut lines).await.unwrap() {
+                Some(response) => responses.push(response),
+                None => break,
+            }
+        }
+        responses
+    }
+
+    #[tokio::test]
+    async fn test_connections_must_present_the_secret() {
+        let dir = tempfile::tempdir().unwrap();
+        let history_path = dir.path().join("history.json");
+        let keypair = KeyPair::generate();
+        let public_key = keypair.get_public_key();
+        let signer = Arc::new(Mutex::new(Signer {
+            keys: std::iter::once((public_key, keypair)).collect(),
+            protection: SlashingProtection::load(&history_path).await.unwrap(),
+            history_path,
+        }));
+        let secret = Some(Arc::new("secret".to_string()));
+
+        // the connection is closed at the first request if it does not present the secret
+        let responses = exchange(
+            signer.clone(),
+            secret.clone(),
+            vec![SignerRequest::GetPublicKeys, SignerRequest::GetPublicKeys],
+        )
+        .await;
+        assert_eq!(responses.len(), 1);
+        assert!(matches!(&responses[0], SignerResponse::Error(_)));
+
+        // or a wrong one
+        let responses = exchange(
+            signer.clone(),
+            secret.clone(),
+            vec![
+                SignerRequest::Authenticate {
+                    secret: "guess".into(),
+                },
+                SignerRequest::GetPublicKeys,
+            ],
+        )
+        .await;
+        assert_eq!(responses.len(), 1);
+        assert!(matches!(&responses[0], SignerResponse::Error(_)));
+
+        // the requests following the right secret are answered
+        let responses = exchange(
+            signer.clone(),
+            secret,
+            vec![
+                SignerRequest::Authenticate {
+                    secret: "secret".into(),
+                },
+                SignerRequest::GetPublicKeys,
+            ],
+        )
+        .await;
+        assert!(matches!(&responses[0], SignerResponse::Authenticated));
+        assert!(
+            matches!(&responses[1], SignerResponse::PublicKeys(keys) if keys == &vec![public_key])
+        );
+
+        // no secret is needed when the signer does not have one
+        let responses = exchange(signer, None, vec![SignerRequest::GetPublicKeys]).await;
+        assert!(matches!(&responses[0], SignerResponse::PublicKeys(_)));
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Messages exchanged with a remote signer.
+//!
+//! Each request and each response is a JSON object written on its own line.
+//! The node sends one request per connection and waits for the response.
+//! When the signer requires it, as it does over TCP, the request is preceded by an
+//! `Authenticate` request carrying the secret shared by the node and the signer.
+
+use crate::SignerError;
+use massa_signature::{PublicKey, Signature};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, Lines};
+
+/// Endpoint on which a remote signer listens
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignerEndpoint {
+    /// TCP address
+    Tcp(SocketAddr),
+    /// path to a Unix socket
+    Unix(PathBuf),
+}
+
+impl std::fmt::Display for SignerEndpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignerEndpoint::Tcp(addr) => write!(f, "tcp://{}", addr),
+            SignerEndpoint::Unix(path) => write!(f, "unix://{}", path.display()),
+        }
+    }
+}
+
+/// Content to sign, serialized in the binary format of the network
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum SignedContent {
+    /// a block header
+    BlockHeader(Vec<u8>),
+    /// a block, whose header must have been signed by the same signer
+    Block(Vec<u8>),
+    /// an endorsement
+    Endorsement(Vec<u8>),
+}
+
+impl SignedContent {
+    /// Get the serialized content
+    pub fn get_serialized(&self) -> &[u8] {
+        match self {
+            SignedContent::BlockHeader(data)
+            | SignedContent::Block(data)
+            | SignedContent::Endorsement(data) => data,
+        }
+    }
+}
+
+/// Request sent to a remote signer
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum SignerRequest {
+    /// prove that the connection comes from the node
+    Authenticate {
+        /// secret shared by the node and the signer
+        secret: String,
+    },
+    /// get the public keys the signer can sign for
+    GetPublicKeys,
+    /// sign a content
+    Sign {
+        /// public key of the content creator
+        public_key: PublicKey,
+        /// content to sign
+        content: SignedContent,
+    },
+}
+
+/// Response of a remote signer
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum SignerResponse {
+    /// the secret is the expected one
+    Authenticated,
+    /// public keys the signer can sign for
+    PublicKeys(Vec<PublicKey>),
+    /// signature of the hash of the public key followed by the serialized content
+    Signature(Signature),
+    /// signing the content could lead to a penalty, with the reason why
+    Refused(String),
+    /// the request could not be processed
+    Error(String),
+}
+
+/// Write a message on its own line
+pub async fn write_message<W: AsyncWrite + Unpin, T: Serialize>(
+    writer: &mut W,
+    message: &T,
+) -> Result<(), SignerError> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    writer.write_all(&line).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Read the message of the next line. Returns `None` at the end of the stream.
+pub async fn read_message<R: AsyncBufRead + Unpin, T: DeserializeOwned>(
+    lines: &mut Lines<R>,
+) -> Result<Option<T>, SignerError> {
+    match lines.next_line().await? {
+        Some(line) => Ok(Some(serde_json::from_str(&line)?)),
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,373 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! History of the block headers and endorsements signed by a signer, so that it never signs
+//! two different block headers for the same slot, nor two different endorsements for the same slot and index.
+//!
+//! Records older than `HISTORY_PERIODS` periods before the latest signed content are pruned,
+//! and nothing is signed for their periods anymore. Contents more than `MAX_FUTURE_PERIODS` periods
+//! after the current slot are refused, so that a single request cannot prune the whole history.
+
+use crate::protocol::SignedContent;
+use crate::SignerError;
+use massa_hash::Hash;
+use massa_models::constants::ENDORSEMENT_COUNT;
+use massa_models::wrapped::{compute_wrapped_hash, Id};
+use massa_models::{BlockDeserializer, BlockHeaderDeserializer, EndorsementDeserializer, Slot};
+use massa_serialization::{DeserializeError, Deserializer};
+use massa_signature::PublicKey;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Number of periods during which the signed contents are remembered
+pub const HISTORY_PERIODS: u64 = 1000;
+
+/// Number of periods after the current slot for which contents can be signed
+pub const MAX_FUTURE_PERIODS: u64 = 2;
+
+/// Signed content, as saved on disk
+#[derive(Debug, Deserialize, Serialize)]
+struct SignedRecord {
+    /// public key of the creator
+    public_key: PublicKey,
+    /// slot of the block header or of the endorsed block
+    slot: Slot,
+    /// endorsement index, `None` for a block header
+    endorsement_index: Option<u32>,
+    /// signed hash
+    hash: Hash,
+}
+
+/// History, as saved on disk
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct SignedHistory {
+    /// nothing is signed before that period
+    min_period: u64,
+    /// signed contents
+    records: Vec<SignedRecord>,
+}
+
+/// Signed contents, by creator public key, slot and endorsement index (`None` for a block header)
+type RecordKey = (PublicKey, Slot, Option<u32>);
+
+/// Content allowed to be signed, to be recorded before its signature leaves the signer
+#[derive(Debug, PartialEq, Eq)]
+pub struct PendingRecord {
+    /// creator public key, slot and endorsement index
+    key: RecordKey,
+    /// signed hash
+    hash: Hash,
+}
+
+/// Refuses to sign contents that would lead to a penalty
+#[derive(Debug, Default)]
+pub struct SlashingProtection {
+    /// nothing is signed before that period
+    min_period: u64,
+    /// signed hashes
+    records: HashMap<RecordKey, Hash>,
+}
+
+impl SlashingProtection {
+    /// Load the history from a file. The history is empty if the file does not exist.
+    pub async fn load(path: &Path) -> Result<Self, SignerError> {
+        if !path.exists() {
+            return Ok(Default::default());
+        }
+        let history: SignedHistory = serde_json::from_slice(&tokio::fs::read(path).await?)?;
+        Ok(SlashingProtection {
+            min_period: history.min_period,
+            records: history
+                .records
+                .into_iter()
+                .map(|record| {
+                    (
+                        (record.public_key, record.slot, record.endorsement_index),
+                        record.hash,
+                    )
+                })
+                .collect(),
+        })
+    }
+
+    /// Save the history to a file, with an additional record and the pruning it leads to.
+    /// It is written to a temporary file first so that an interrupted save does not corrupt it.
+    async fn save(
+        &self,
+        path: &Path,
+        pending: Option<&PendingRecord>,
+        min_period: u64,
+    ) -> Result<(), SignerError> {
+        let history = SignedHistory {
+            min_period,
+            records: self
+                .records
+                .iter()
+                .chain(pending.map(|pending| (&pending.key, &pending.hash)))
+                .filter(|((_, slot, _), _)| slot.period >= min_period)
+                .map(
+                    |((public_key, slot, endorsement_index), hash)| SignedRecord {
+                        public_key: *public_key,
+                        slot: *slot,
+                        endorsement_index: *endorsement_index,
+                        hash: *hash,
+                    },
+                )
+                .collect(),
+        };
+        let tmp_path = path.with_extension("tmp");
+        tokio::fs::write(&tmp_path, serde_json::to_vec(&history)?).await?;
+        tokio::fs::rename(&tmp_path, path).await?;
+        Ok(())
+    }
+
+    /// Record a content allowed by `check`. The history is saved to a file before it changes in memory,
+    /// so that nothing is recorded if the save fails. The signature must only be sent once this succeeded.
+    ///
+    /// # Arguments
+    /// * `pending`: content to record
+    /// * `path`: history file
+    pub async fn record(&mut self, pending: PendingRecord, path: &Path) -> Result<(), SignerError> {
+        let min_period = std::cmp::max(
+            self.min_period,
+            pending.key.1.period.saturating_sub(HISTORY_PERIODS),
+        );
+        self.save(path, Some(&pending), min_period).await?;
+        self.records.insert(pending.key, pending.hash);
+        if min_period > self.min_period {
+            self.min_period = min_period;
+            self.records
+                .retain(|(_, slot, _), _| slot.period >= min_period);
+        }
+        Ok(())
+    }
+
+    /// Check that signing a content cannot lead to a penalty.
+    ///
+    /// Returns the record to save with `record` before signing if the content was not signed yet,
+    /// or the reason why the content must not be signed.
+    ///
+    /// # Arguments
+    /// * `public_key`: public key of the content creator
+    /// * `content`: content to sign
+    /// * `current_slot`: current slot according to the wall clock, `None` before genesis
+    pub fn check(
+        &self,
+        public_key: &PublicKey,
+        content: &SignedContent,
+        current_slot: Option<Slot>,
+    ) -> Result<Option<PendingRecord>, String> {
+        let hash = compute_wrapped_hash(public_key, content.get_serialized());
+        let key = match content {
+            SignedContent::BlockHeader(data) => {
+                let header = deserialize_all(&BlockHeaderDeserializer::new(), data)?;
+                (*public_key, header.slot, None)
+            }
+            SignedContent::Endorsement(data) => {
+                let endorsement =
+                    deserialize_all(&EndorsementDeserializer::new(ENDORSEMENT_COUNT), data)?;
+                (*public_key, endorsement.slot, Some(endorsement.index))
+            }
+            SignedContent::Block(data) => {
+                // a block can only be signed once its header is
+                let block = deserialize_all(&BlockDeserializer::new(), data)?;
+                let header_key = (
+                    block.header.creator_public_key,
+                    block.header.content.slot,
+                    None,
+                );
+                if block.header.creator_public_key != *public_key
+                    || self.records.get(&header_key) != Some(&block.header.id.hash())
+                {
+                    return Err(format!(
+                        "the header of the block at slot {} was not signed by this signer for public key {}",
+                        block.header.content.slot, public_key
+                    ));
+                }
+                return Ok(None);
+            }
+        };
+
+        let (_, slot, endorsement_index) = key;
+        if slot.period < self.min_period {
+            return Err(format!(
+                "slot {} is older than the signing history, which starts at period {}",
+                slot, self.min_period
+            ));
+        }
+        let max_period = current_slot
+            .map_or(0, |current_slot| current_slot.period)
+            .saturating_add(MAX_FUTURE_PERIODS);
+        if slot.period > max_period {
+            return Err(format!(
+                "slot {} is too far in the future, contents can be signed up to period {}",
+                slot, max_period
+            ));
+        }
+        match self.records.get(&key) {
+            Some(signed_hash) if *signed_hash == hash => Ok(None),
+            Some(_) => Err(match endorsement_index {
+                None => format!(
+                    "a different block header was already signed at slot {} for public key {}",
+                    slot, public_key
+                ),
+                Some(index) => format!(
+                    "a different endorsement with index {} was already signed at slot {} for public key {}",
+                    index, slot, public_key
+                ),
+            }),
+            None => Ok(Some(PendingRecord { key, hash })),
+        }
+    }
+}
+
+/// Deserialize a content, checking that there is no data left
+fn deserialize_all<T, D: Deserializer<T>>(deserializer: &D, data: &[u8]) -> Result<T, String> {
+    let (rest, value) = deserializer
+        .deserialize::<DeserializeError>(data)
+        .map_err(|err| format!("invalid content: {}", err))?;
+    if !rest.is_empty() {
+        return Err("invalid content: there is data left after deserialization".into());
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_models::wrapped::WrappedContent;
+    use massa_models::{Block, BlockHeader, BlockHeaderSerializer, BlockSerializer};
+    use massa_serialization::Serializer;
+    use massa_signature::KeyPair;
+
+    fn create_header(slot: Slot, merkle_data: &[u8]) -> BlockHeader {
+        BlockHeader {
+            slot,
+            parents: Vec::new(),
+            operation_merkle_root: Hash::compute_from(merkle_data),
+            endorsements: Vec::new(),
+            denunciations: Vec::new(),
+        }
+    }
+
+    fn header_content(header: &BlockHeader) -> SignedContent {
+        let mut data = Vec::new();
+        BlockHeaderSerializer::new()
+            .serialize(header, &mut data)
+            .unwrap();
+        SignedContent::BlockHeader(data)
+    }
+
+    fn block_content(header: BlockHeader, keypair: &KeyPair) -> SignedContent {
+        let header =
+            BlockHeader::new_wrapped(header, BlockHeaderSerializer::new(), keypair).unwrap();
+        let mut data = Vec::new();
+        BlockSerializer::new()
+            .serialize(
+                &Block {
+                    header,
+                    operations: Vec::new(),
+                },
+                &mut data,
+            )
+            .unwrap();
+        SignedContent::Block(data)
+    }
+
+    #[tokio::test]
+    async fn test_slashing_protection() {
+        let keypair = KeyPair::generate();
+        let public_key = keypair.get_public_key();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.json");
+        let current_slot = Some(Slot::new(5, 0));
+        let mut protection = SlashingProtection::default();
+        let header = create_header(Slot::new(5, 0), b"first");
+        let conflicting_header = create_header(Slot::new(5, 0), b"second");
+
+        // a header is signed once per slot, and can be signed again
+        let pending = protection
+            .check(&public_key, &header_content(&header), current_slot)
+            .unwrap()
+            .unwrap();
+        protection.record(pending, &path).await.unwrap();
+        assert_eq!(
+            protection.check(&public_key, &header_content(&header), current_slot),
+            Ok(None)
+        );
+        assert!(protection
+            .check(
+                &public_key,
+                &header_content(&conflicting_header),
+                current_slot
+            )
+            .is_err());
+        assert!(protection
+            .check(
+                &KeyPair::generate().get_public_key(),
+                &header_content(&conflicting_header),
+                current_slot
+            )
+            .unwrap()
+            .is_some());
+
+        // a block is signed only if its header was
+        assert_eq!(
+            protection.check(&public_key, &block_content(header, &keypair), current_slot),
+            Ok(None)
+        );
+        assert!(protection
+            .check(
+                &public_key,
+                &block_content(conflicting_header.clone(), &keypair),
+                current_slot
+            )
+            .is_err());
+
+        // the history survives restarts
+        let mut protection = SlashingProtection::load(&path).await.unwrap();
+        assert!(protection
+            .check(
+                &public_key,
+                &header_content(&conflicting_header),
+                current_slot
+            )
+            .is_err());
+
+        // nothing is recorded if the history cannot be saved
+        let other_header = create_header(Slot::new(5, 1), b"other");
+        let pending = protection
+            .check(&public_key, &header_content(&other_header), current_slot)
+            .unwrap()
+            .unwrap();
+        assert!(protection
+            .record(pending, &dir.path().join("missing").join("history.json"))
+            .await
+            .is_err());
+        assert!(protection
+            .check(&public_key, &header_content(&other_header), current_slot)
+            .unwrap()
+            .is_some());
+
+        // contents too far after the current slot are refused
+        let recent_header = create_header(Slot::new(6 + HISTORY_PERIODS, 0), b"recent");
+        assert!(protection
+            .check(&public_key, &header_content(&recent_header), current_slot)
+            .is_err());
+
+        // old slots are not signed anymore once pruned
+        let current_slot = Some(Slot::new(6 + HISTORY_PERIODS, 0));
+        let pending = protection
+            .check(&public_key, &header_content(&recent_header), current_slot)
+            .unwrap()
+            .unwrap();
+        protection.record(pending, &path).await.unwrap();
+        assert!(protection
+            .check(&public_key, &header_content(&other_header), current_slot)
+            .is_err());
+        let protection = SlashingProtection::load(&path).await.unwrap();
+        assert!(protection
+            .check(&public_key, &header_content(&other_header), current_slot)
+            .is_err());
+    }
+}
@@ -22,4 +22,22 @@ pub enum WalletError {
     MissingKeyError(Address),
     /// `MassaCipher` error: {0}
     MassaCipherError(#[from] massa_cipher::CipherError),
+    /// `MassaSignature` error: {0}
+    MassaSignatureError(#[from] massa_signature::MassaSignatureError),
+    /// Mnemonic error: {0}
+    MnemonicError(#[from] bip39::Error),
+    /// Key derivation error: {0}
+    DerivationError(String),
+    /// The wallet has no seed phrase, generate or restore one first
+    MissingSeedError,
+    /// The wallet already has a seed phrase
+    SeedAlreadyDefinedError,
+    /// External signer error: {0}
+    ExternalSignerError(String),
+    /// Invalid label {0}: a label is a single word that is not an address
+    InvalidLabelError(String),
+    /// {0} is neither an address nor a label of the address book
+    UnknownLabelError(String),
+    /// Labels already used for other addresses: {0}
+    LabelConflictError(String),
 }
@@ -0,0 +1,107 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Hierarchical deterministic key derivation
+//!
+//! Keys are derived from a BIP-39 mnemonic seed following SLIP-0010 for ed25519,
+//! which only allows hardened derivation.
+
+use crate::WalletError;
+use hmac::{Hmac, Mac};
+use massa_signature::KeyPair;
+use sha2::Sha512;
+
+/// SLIP-0044 coin type registered for Massa
+pub const MASSA_COIN_TYPE: u32 = 632;
+
+/// number of words of the mnemonics generated by the wallet
+pub const MNEMONIC_WORD_COUNT: usize = 24;
+
+/// offset of hardened indices
+const HARDENED_OFFSET: u32 = 0x8000_0000;
+
+/// HMAC key used to compute the master key of an ed25519 tree
+const ED25519_CURVE_KEY: &[u8] = b"ed25519 seed";
+
+type HmacSha512 = Hmac<Sha512>;
+
+/// derivation path of the account with the given index: `m/44'/632'/index'/0'`
+pub fn account_path(index: u32) -> [u32; 4] {
+    [44, MASSA_COIN_TYPE, index, 0]
+}
+
+/// formats a derivation path, all the indices being hardened
+pub fn format_path(path: &[u32]) -> String {
+    path.iter().fold("m".to_string(), |mut res, index| {
+        res.push_str(&format!("/{}'", index));
+        res
+    })
+}
+
+/// derives the keypair at the given path from a BIP-39 seed,
+/// every index of the path being used as a hardened index
+pub fn derive_keypair(seed: &[u8], path: &[u32]) -> Result<KeyPair, WalletError> {
+    let (mut key, mut chain_code) = split(hmac_sha512(ED25519_CURVE_KEY, &[seed]));
+    for index in path {
+        if *index >= HARDENED_OFFSET {
+            return Err(WalletError::DerivationError(format!(
+                "derivation index {} is out of range",
+                index
+            )));
+        }
+        let hardened_index = (index | HARDENED_OFFSET).to_be_bytes();
+        (key, chain_code) = split(hmac_sha512(&chain_code, &[&[0u8], &key, &hardened_index]));
+    }
+    Ok(KeyPair::from_bytes(&key)?)
+}
+
+/// splits an HMAC output into a key and a chain code
+fn split(data: [u8; 64]) -> ([u8; 32], [u8; 32]) {
+    let mut key = [0u8; 32];
+    let mut chain_code = [0u8; 32];
+    key.copy_from_slice(&data[..32]);
+    chain_code.copy_from_slice(&data[32..]);
+    (key, chain_code)
+}
+
+fn hmac_sha512(key: &[u8], data: &[&[u8]]) -> [u8; 64] {
+    let mut mac = HmacSha512::new_from_slice(key).expect("HMAC accepts keys of any size");
+    for chunk in data {
+        mac.update(chunk);
+    }
+    let mut res = [0u8; 64];
+    res.copy_from_slice(&mac.finalize().into_bytes());
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_hex(data: &str) -> Vec<u8> {
+        (0..data.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&data[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_slip10_ed25519_vector() {
+        // test vector 1 of SLIP-0010 for ed25519
+        let seed = from_hex("000102030405060708090a0b0c0d0e0f");
+        let master = derive_keypair(&seed, &[]).unwrap();
+        assert_eq!(
+            master.to_bytes().to_vec(),
+            from_hex("2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7")
+        );
+        let child = derive_keypair(&seed, &[0, 1, 2, 2, 1000000000]).unwrap();
+        assert_eq!(
+            child.to_bytes().to_vec(),
+            from_hex("8f94d394a8e8fd6b1bc2f3f49f5c47e385281d5c17e65324b0f62483e37e8793")
+        );
+    }
+
+    #[test]
+    fn test_account_path() {
+        assert_eq!(format_path(&account_path(3)), "m/44'/632'/3'/0'");
+    }
+}
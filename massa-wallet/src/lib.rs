@@ -5,71 +5,241 @@
 #![warn(unused_crate_dependencies)]
 
 pub use error::WalletError;
+pub use hd::{account_path, format_path, MASSA_COIN_TYPE, MNEMONIC_WORD_COUNT};
+pub use signer::{ExternalSigner, ExternalSignerEndpoint, WalletSigner};
 
-use massa_cipher::{decrypt, encrypt};
-use massa_hash::Hash;
+use bip39::Mnemonic;
+pub use massa_cipher::CipherParams;
+
+use massa_cipher::{decrypt, encrypt_with_params, VERSION as CIPHER_VERSION};
 use massa_models::address::Address;
 use massa_models::composite::PubkeySig;
+use massa_models::multisig::compute_multisig_wrapped_hash;
 use massa_models::operation::OperationSerializer;
 use massa_models::prehash::{Map, Set};
-use massa_models::wrapped::WrappedContent;
-use massa_models::{Operation, WrappedOperation};
+use massa_models::signed_message::{compute_signed_message_hash, SignedMessage};
+use massa_models::wrapped::{compute_wrapped_hash, WrappedContent};
+use massa_models::{ModelsError, MultisigPolicy, MultisigProof, Operation, WrappedOperation};
+use massa_serialization::Serializer;
 use massa_signature::{KeyPair, PublicKey};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 mod error;
+mod hd;
+mod signer;
 
 /// Contains the keypairs created in the wallet.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Wallet {
-    /// Keypairs and addresses
+    /// Keypairs and addresses, both derived from the seed and imported
     pub keys: Map<Address, KeyPair>,
+    /// BIP-39 seed from which accounts are derived, if any
+    #[serde(skip)]
+    pub seed: Option<Vec<u8>>,
+    /// Account index of each address derived from the seed
+    pub derived_accounts: Map<Address, u32>,
+    /// Index of the next account to derive from the seed
+    pub next_account_index: u32,
+    /// Policies of the multisig addresses the wallet co-signs for
+    pub multisig_policies: Map<Address, MultisigPolicy>,
+    /// Signers outside of the wallet holding the keys of some addresses
+    pub external_signers: Map<Address, ExternalSigner>,
+    /// Address book: addresses of the wallet or not, by label
+    pub labels: BTreeMap<String, Address>,
     /// Path to the file containing the keypairs (encrypted)
     pub wallet_path: PathBuf,
     /// Password
     pub password: String,
+    /// Key derivation parameters used when the wallet file is written
+    #[serde(skip)]
+    pub cipher_params: CipherParams,
+    /// Cipher version of the wallet file when it was read
+    #[serde(skip)]
+    pub file_version: u32,
+}
+
+/// Content of a wallet file, before encryption
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum WalletFile {
+    /// seed, derivation indices and imported keys
+    Hd {
+        seed: Option<Vec<u8>>,
+        derived_accounts: Map<Address, u32>,
+        next_account_index: u32,
+        imported_keys: Map<Address, KeyPair>,
+        #[serde(default)]
+        multisig_policies: Map<Address, MultisigPolicy>,
+        #[serde(default)]
+        external_signers: Map<Address, ExternalSigner>,
+        #[serde(default)]
+        labels: BTreeMap<String, Address>,
+    },
+    /// imported keys only, written by the wallets predating seed phrases
+    Legacy(Map<Address, KeyPair>),
 }
 
 impl Wallet {
     /// Generates a new wallet initialized with the provided file content
     pub fn new(path: PathBuf, password: String) -> Result<Wallet, WalletError> {
+        Wallet::new_with_cipher_params(path, password, CipherParams::default())
+    }
+
+    /// Generates a new wallet initialized with the provided file content,
+    /// written with the given key derivation parameters
+    pub fn new_with_cipher_params(
+        path: PathBuf,
+        password: String,
+        cipher_params: CipherParams,
+    ) -> Result<Wallet, WalletError> {
         if path.is_file() {
             let content = &std::fs::read(&path)?[..];
-            let (_version, decrypted_content) = decrypt(&password, content)?;
-            let keys = serde_json::from_slice::<Map<Address, KeyPair>>(&decrypted_content[..])?;
-            Ok(Wallet {
-                keys,
+            let (file_version, decrypted_content) = decrypt(&password, content)?;
+            let mut wallet = Wallet {
+                keys: Map::default(),
+                seed: None,
+                derived_accounts: Map::default(),
+                next_account_index: 0,
+                multisig_policies: Map::default(),
+                external_signers: Map::default(),
+                labels: BTreeMap::new(),
                 wallet_path: path,
                 password,
-            })
+                cipher_params,
+                file_version,
+            };
+            match serde_json::from_slice::<WalletFile>(&decrypted_content[..])? {
+                WalletFile::Hd {
+                    seed,
+                    derived_accounts,
+                    next_account_index,
+                    imported_keys,
+                    multisig_policies,
+                    external_signers,
+                    labels,
+                } => {
+                    wallet.keys = imported_keys;
+                    wallet.next_account_index = next_account_index;
+                    wallet.multisig_policies = multisig_policies;
+                    wallet.external_signers = external_signers;
+                    wallet.labels = labels;
+                    if let Some(seed) = seed {
+                        for index in derived_accounts.into_values() {
+                            wallet.insert_account(&seed, index)?;
+                        }
+                        wallet.seed = Some(seed);
+                    }
+                }
+                WalletFile::Legacy(keys) => wallet.keys = keys,
+            }
+            Ok(wallet)
         } else {
             let wallet = Wallet {
                 keys: Map::default(),
+                seed: None,
+                derived_accounts: Map::default(),
+                next_account_index: 0,
+                multisig_policies: Map::default(),
+                external_signers: Map::default(),
+                labels: BTreeMap::new(),
                 wallet_path: path,
                 password,
+                cipher_params,
+                file_version: CIPHER_VERSION,
             };
             wallet.save()?;
             Ok(wallet)
         }
     }
 
-    /// Sign arbitrary message with the associated keypair
+    /// Generates a new mnemonic, uses its seed to derive the accounts of the wallet
+    /// and derives the first account.
+    /// Returns the mnemonic, which is not stored, and the address of the first account
+    pub fn generate_mnemonic(&mut self) -> Result<(String, Address), WalletError> {
+        let mnemonic = Mnemonic::generate(MNEMONIC_WORD_COUNT)?;
+        let address = self.set_seed(mnemonic.to_seed(""))?;
+        Ok((mnemonic.to_string(), address))
+    }
+
+    /// Uses the seed of an existing mnemonic to derive the accounts of the wallet
+    /// and derives the first account.
+    /// Returns the address of the first account
+    pub fn restore_from_mnemonic(&mut self, phrase: &str) -> Result<Address, WalletError> {
+        let mnemonic = Mnemonic::parse(phrase)?;
+        self.set_seed(mnemonic.to_seed(""))
+    }
+
+    /// Derives the next account from the seed of the wallet
+    /// returns corresponding address
+    pub fn derive_next_account(&mut self) -> Result<Address, WalletError> {
+        let seed = self.seed.clone().ok_or(WalletError::MissingSeedError)?;
+        let address = self.insert_account(&seed, self.next_account_index)?;
+        self.next_account_index += 1;
+        self.save()?;
+        Ok(address)
+    }
+
+    /// Returns the derivation path of an address derived from the seed of the wallet
+    pub fn get_derivation_path(&self, address: &Address) -> Option<String> {
+        self.derived_accounts
+            .get(address)
+            .map(|index| format_path(&account_path(*index)))
+    }
+
+    /// Sets the seed of a wallet that has none, then derives its first account
+    fn set_seed(&mut self, seed: [u8; 64]) -> Result<Address, WalletError> {
+        if self.seed.is_some() {
+            return Err(WalletError::SeedAlreadyDefinedError);
+        }
+        self.seed = Some(seed.to_vec());
+        self.next_account_index = 0;
+        self.derive_next_account()
+    }
+
+    /// Derives the account with the given index and adds it to the keys
+    fn insert_account(&mut self, seed: &[u8], index: u32) -> Result<Address, WalletError> {
+        let key = hd::derive_keypair(seed, &account_path(index))?;
+        let address = Address::from_public_key(&key.get_public_key());
+        self.keys.insert(address, key);
+        self.derived_accounts.insert(address, index);
+        Ok(address)
+    }
+
+    /// Sign arbitrary message with the signer of the address, in the signed message envelope
     /// returns none if the address isn't in the wallet or if an error occurred during the signature
     /// else returns the public key that signed the message and the signature
     pub fn sign_message(&self, address: Address, msg: Vec<u8>) -> Option<PubkeySig> {
-        if let Some(key) = self.keys.get(&address) {
-            if let Ok(signature) = key.sign(&Hash::compute_from(&msg)) {
-                Some(PubkeySig {
-                    public_key: key.get_public_key(),
-                    signature,
-                })
-            } else {
-                None
-            }
-        } else {
-            None
-        }
+        let signer = self.find_signer(&address)?;
+        let description = format!("Signed message: {}", String::from_utf8_lossy(&msg));
+        signer
+            .sign(&compute_signed_message_hash(&msg), &description)
+            .ok()
+            .map(|signature| PubkeySig {
+                public_key: signer.get_public_key(),
+                signature,
+            })
+    }
+
+    /// Sign a message with the signer of the address, in the signed message envelope
+    /// so that the signature can not be used as the signature of an operation.
+    /// See `massa_models::signed_message`
+    pub fn sign_message_with_envelope(
+        &self,
+        address: Address,
+        msg: &[u8],
+    ) -> Result<SignedMessage, WalletError> {
+        let signer = self
+            .find_signer(&address)
+            .ok_or(WalletError::MissingKeyError(address))?;
+        let description = format!("Signed message: {}", String::from_utf8_lossy(msg));
+        let signature = signer.sign(&compute_signed_message_hash(msg), &description)?;
+        Ok(SignedMessage {
+            address,
+            public_key: signer.get_public_key(),
+            signature,
+        })
     }
 
     /// Adds a new keypair to wallet, if it was missing
@@ -98,12 +268,89 @@ impl Wallet {
     /// Remove a wallet entry (keys and address) given the address
     /// The file is overwritten
     pub fn remove_address(&mut self, address: Address) -> Result<(), WalletError> {
-        self.keys
-            .remove(&address)
-            .ok_or(WalletError::MissingKeyError(address))?;
+        if self.keys.remove(&address).is_none() && self.external_signers.remove(&address).is_none()
+        {
+            return Err(WalletError::MissingKeyError(address));
+        }
+        self.derived_accounts.remove(&address);
         self.save()
     }
 
+    /// Binds the address of a public key to a signer outside of the wallet,
+    /// replacing the previous one if any
+    /// returns corresponding address
+    pub fn add_external_signer(&mut self, signer: ExternalSigner) -> Result<Address, WalletError> {
+        let address = Address::from_public_key(&signer.public_key);
+        if self.keys.contains_key(&address) {
+            return Err(WalletError::ExternalSignerError(format!(
+                "the keypair of {} is already in the wallet",
+                address
+            )));
+        }
+        self.external_signers.insert(address, signer);
+        self.save()?;
+        Ok(address)
+    }
+
+    /// Finds the external signer associated with given address, if any
+    pub fn find_external_signer(&self, address: &Address) -> Option<&ExternalSigner> {
+        self.external_signers.get(address)
+    }
+
+    /// Finds the backend signing for given address: its keypair or its external signer
+    pub fn find_signer(&self, address: &Address) -> Option<&dyn WalletSigner> {
+        match self.keys.get(address) {
+            Some(keypair) => Some(keypair as &dyn WalletSigner),
+            None => self
+                .external_signers
+                .get(address)
+                .map(|signer| signer as &dyn WalletSigner),
+        }
+    }
+
+    /// Adds the policy of a multisig address the wallet co-signs for
+    /// returns the multisig address
+    pub fn add_multisig_policy(&mut self, policy: MultisigPolicy) -> Result<Address, WalletError> {
+        policy.check()?;
+        let address = Address::from_multisig_policy(&policy);
+        self.multisig_policies.insert(address, policy);
+        self.save()?;
+        Ok(address)
+    }
+
+    /// Adds to a multisig proof the signatures of an operation
+    /// by the keys of the policy that are in the wallet.
+    /// returns the number of keys of the wallet that signed
+    pub fn sign_multisig_operation(
+        &self,
+        content: &Operation,
+        proof: &mut MultisigProof,
+    ) -> Result<usize, WalletError> {
+        let mut content_serialized = Vec::new();
+        OperationSerializer::new()
+            .serialize(content, &mut content_serialized)
+            .map_err(ModelsError::from)?;
+        let hash = compute_multisig_wrapped_hash(&proof.policy, &content_serialized);
+        let description = format!(
+            "Multisig operation from {}\n{}",
+            Address::from_multisig_policy(&proof.policy),
+            content
+        );
+        let signers: Vec<&dyn WalletSigner> = proof
+            .policy
+            .public_keys
+            .iter()
+            .filter_map(|public_key| {
+                self.find_signer(&Address::from_public_key(public_key))
+                    .filter(|signer| signer.get_public_key() == *public_key)
+            })
+            .collect();
+        for signer in &signers {
+            proof.add_signature(&signer.get_public_key(), signer.sign(&hash, &description)?)?;
+        }
+        Ok(signers.len())
+    }
+
     /// Finds the keypair associated with given address
     pub fn find_associated_keypair(&self, address: Address) -> Option<&KeyPair> {
         self.keys.get(&address)
@@ -111,22 +358,145 @@ impl Wallet {
 
     /// Finds the public key associated with given address
     pub fn find_associated_public_key(&self, address: Address) -> Option<PublicKey> {
-        self.keys
-            .get(&address)
-            .map(|keypair| keypair.get_public_key())
+        self.find_signer(&address)
+            .map(|signer| signer.get_public_key())
+    }
+
+    /// Labels an address, which does not need to be in the wallet.
+    /// A label is a single word that is not an address, and replaces its previous address if any
+    pub fn set_label(&mut self, label: String, address: Address) -> Result<(), WalletError> {
+        if !is_valid_label(&label) {
+            return Err(WalletError::InvalidLabelError(label));
+        }
+        self.labels.insert(label, address);
+        self.save()
+    }
+
+    /// Removes a label from the address book
+    pub fn remove_label(&mut self, label: &str) -> Result<Address, WalletError> {
+        let address = self
+            .labels
+            .remove(label)
+            .ok_or_else(|| WalletError::UnknownLabelError(label.to_string()))?;
+        self.save()?;
+        Ok(address)
+    }
+
+    /// Adds labels to the address book, such as the ones of another wallet.
+    /// Nothing is imported if a label is already used for another address, unless `force` is set,
+    /// in which case the existing labels are replaced.
+    pub fn import_labels(
+        &mut self,
+        labels: BTreeMap<String, Address>,
+        force: bool,
+    ) -> Result<(), WalletError> {
+        if let Some(label) = labels.keys().find(|label| !is_valid_label(label)) {
+            return Err(WalletError::InvalidLabelError(label.clone()));
+        }
+        if !force {
+            let conflicts: Vec<&str> = labels
+                .iter()
+                .filter(|(label, address)| {
+                    self.labels
+                        .get(*label)
+                        .map_or(false, |existing| existing != *address)
+                })
+                .map(|(label, _)| label.as_str())
+                .collect();
+            if !conflicts.is_empty() {
+                return Err(WalletError::LabelConflictError(conflicts.join(", ")));
+            }
+        }
+        self.labels.extend(labels);
+        self.save()
     }
 
-    /// Get all addresses in the wallet
+    /// Gets the labels of an address
+    pub fn get_labels(&self, address: &Address) -> Vec<&str> {
+        self.labels
+            .iter()
+            .filter(|(_, labeled)| *labeled == address)
+            .map(|(label, _)| label.as_str())
+            .collect()
+    }
+
+    /// Parses an address, or finds it in the address book if it is a label
+    pub fn resolve_address(&self, address_or_label: &str) -> Result<Address, WalletError> {
+        match address_or_label.parse::<Address>() {
+            Ok(address) => Ok(address),
+            Err(_) => self
+                .labels
+                .get(address_or_label)
+                .copied()
+                .ok_or_else(|| WalletError::UnknownLabelError(address_or_label.to_string())),
+        }
+    }
+
+    /// Parses a list of addresses or labels
+    pub fn resolve_addresses(
+        &self,
+        addresses_or_labels: &[String],
+    ) -> Result<Vec<Address>, WalletError> {
+        addresses_or_labels
+            .iter()
+            .map(|address_or_label| self.resolve_address(address_or_label))
+            .collect()
+    }
+
+    /// Get all addresses in the wallet, including the ones signed by external signers
     pub fn get_wallet_address_list(&self) -> Set<Address> {
-        self.keys.keys().copied().collect()
+        self.keys
+            .keys()
+            .chain(self.external_signers.keys())
+            .copied()
+            .collect()
+    }
+
+    /// Returns true if the wallet file was encrypted with an older cipher version
+    pub fn is_outdated(&self) -> bool {
+        self.file_version < CIPHER_VERSION
+    }
+
+    /// Writes the wallet file again with the current cipher version and parameters
+    pub fn reencrypt(&mut self) -> Result<(), WalletError> {
+        self.save()?;
+        self.file_version = CIPHER_VERSION;
+        Ok(())
+    }
+
+    /// Changes the password of the wallet file, which is also written with the current cipher version
+    pub fn change_password(&mut self, password: String) -> Result<(), WalletError> {
+        let old_password = std::mem::replace(&mut self.password, password);
+        if let Err(err) = self.reencrypt() {
+            self.password = old_password;
+            return Err(err);
+        }
+        Ok(())
     }
 
     /// Save the wallet in json format in a file
-    /// Only the keypair is dumped
+    /// Only the seed, the derivation indices and the imported keypairs are dumped
     fn save(&self) -> Result<(), WalletError> {
-        let ser_keys = serde_json::to_string(&self.keys)?;
-        let encrypted_content = encrypt(&self.password, ser_keys.as_bytes())?;
-        std::fs::write(&self.wallet_path, encrypted_content)?;
+        let ser_keys = serde_json::to_string(&WalletFile::Hd {
+            seed: self.seed.clone(),
+            derived_accounts: self.derived_accounts.clone(),
+            next_account_index: self.next_account_index,
+            imported_keys: self
+                .keys
+                .iter()
+                .filter(|(address, _)| !self.derived_accounts.contains_key(address))
+                .map(|(address, keypair)| (*address, keypair.clone()))
+                .collect(),
+            multisig_policies: self.multisig_policies.clone(),
+            external_signers: self.external_signers.clone(),
+            labels: self.labels.clone(),
+        })?;
+        let encrypted_content =
+            encrypt_with_params(&self.password, ser_keys.as_bytes(), &self.cipher_params)?;
+        // written aside first so that a crash while writing does not lose the keys
+        let tmp_path = self.wallet_path.with_extension("tmp");
+        std::fs::write(&tmp_path, encrypted_content)?;
+        std::fs::rename(&tmp_path, &self.wallet_path)?;
         Ok(())
     }
 
@@ -135,19 +505,38 @@ impl Wallet {
         &self.keys
     }
 
-    /// Signs an operation with the keypair corresponding to the given address
+    /// Signs an operation with the signer of the given address
     pub fn create_operation(
         &self,
         content: Operation,
         address: Address,
     ) -> Result<WrappedOperation, WalletError> {
-        let sender_keypair = self
-            .find_associated_keypair(address)
+        let signer = self
+            .find_signer(&address)
             .ok_or(WalletError::MissingKeyError(address))?;
-        Ok(Operation::new_wrapped(content, OperationSerializer::new(), sender_keypair).unwrap())
+        let public_key = signer.get_public_key();
+        let mut content_serialized = Vec::new();
+        OperationSerializer::new()
+            .serialize(&content, &mut content_serialized)
+            .map_err(ModelsError::from)?;
+        let hash = compute_wrapped_hash(&public_key, &content_serialized);
+        let description = format!("Operation from {}\n{}", address, content);
+        let signature = signer.sign(&hash, &description)?;
+        Ok(Operation::new_wrapped_with_signature(
+            content,
+            content_serialized,
+            public_key,
+            signature,
+        ))
     }
 }
 
+/// Returns true if a label is a single word that is not an address,
+/// so that it can be used in place of an address in the client commands
+fn is_valid_label(label: &str) -> bool {
+    !label.is_empty() && !label.contains(char::is_whitespace) && label.parse::<Address>().is_err()
+}
+
 impl std::fmt::Display for Wallet {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         writeln!(f)?;
@@ -155,7 +544,245 @@ impl std::fmt::Display for Wallet {
             writeln!(f, "Secret key: {}", keypair)?;
             writeln!(f, "Public key: {}", keypair.get_public_key())?;
             writeln!(f, "Address: {}", addr)?;
+            let labels = self.get_labels(addr);
+            if !labels.is_empty() {
+                writeln!(f, "Labels: {}", labels.join(", "))?;
+            }
+            if let Some(path) = self.get_derivation_path(addr) {
+                writeln!(f, "Derivation path: {}", path)?;
+            }
+        }
+        for (addr, signer) in &self.external_signers {
+            writeln!(f, "External signer: {}", signer.endpoint)?;
+            writeln!(f, "Public key: {}", signer.public_key)?;
+            writeln!(f, "Address: {}", addr)?;
+        }
+        for policy in self.multisig_policies.values() {
+            writeln!(f)?;
+            write!(f, "{}", policy)?;
+        }
+        if !self.labels.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "Address book:")?;
+            for (label, addr) in &self.labels {
+                writeln!(f, "\t- {}: {}", label, addr)?;
+            }
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const PASSWORD: &str = "password";
+
+    /// Cheap key derivation so that the tests do not spend their time in Argon2id
+    const TEST_CIPHER_PARAMS: CipherParams = CipherParams {
+        memory_cost_kib: 8,
+        time_cost: 1,
+        parallelism: 1,
+    };
+
+    fn open_wallet(path: &std::path::Path) -> Wallet {
+        Wallet::new_with_cipher_params(path.to_path_buf(), PASSWORD.into(), TEST_CIPHER_PARAMS)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_hd_wallet_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("wallet.dat");
+        let mut wallet = open_wallet(&path);
+        let (mnemonic, first) = wallet.generate_mnemonic().unwrap();
+        let second = wallet.derive_next_account().unwrap();
+        let imported = wallet.add_keypair(KeyPair::generate()).unwrap();
+
+        // the seed, the derived accounts and the imported keys are read back from the file
+        let reloaded = open_wallet(&path);
+        assert_eq!(reloaded.seed, wallet.seed);
+        assert_eq!(reloaded.next_account_index, 2);
+        assert_eq!(
+            reloaded.get_wallet_address_list(),
+            vec![first, second, imported].into_iter().collect()
+        );
+        assert_eq!(
+            reloaded.get_derivation_path(&second),
+            wallet.get_derivation_path(&second)
+        );
+        assert_eq!(reloaded.get_derivation_path(&imported), None);
+
+        // the seed phrase restores the same accounts in another wallet
+        let mut restored = open_wallet(&dir.path().join("restored.dat"));
+        assert_eq!(restored.restore_from_mnemonic(&mnemonic).unwrap(), first);
+        assert_eq!(restored.derive_next_account().unwrap(), second);
+    }
+
+    #[test]
+    fn test_legacy_wallet_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("wallet.dat");
+        let keypair = KeyPair::generate();
+        let address = Address::from_public_key(&keypair.get_public_key());
+        let legacy_keys: Map<Address, KeyPair> = std::iter::once((address, keypair)).collect();
+        let content = serde_json::to_vec(&legacy_keys).unwrap();
+        std::fs::write(
+            &path,
+            encrypt_with_params(PASSWORD, &content, &TEST_CIPHER_PARAMS).unwrap(),
+        )
+        .unwrap();
+
+        // a wallet predating seed phrases is read as imported keys
+        let mut wallet = open_wallet(&path);
+        assert!(wallet.seed.is_none());
+        assert_eq!(
+            wallet.get_wallet_address_list(),
+            std::iter::once(address).collect()
+        );
+        assert!(matches!(
+            wallet.derive_next_account(),
+            Err(WalletError::MissingSeedError)
+        ));
+
+        // a seed can be added, and the file is then written in the current format
+        let derived = wallet.generate_mnemonic().unwrap().1;
+        let reloaded = open_wallet(&path);
+        assert_eq!(
+            reloaded.get_wallet_address_list(),
+            vec![address, derived].into_iter().collect()
+        );
+        assert_eq!(reloaded.get_derivation_path(&address), None);
+        assert!(reloaded.get_derivation_path(&derived).is_some());
+    }
+
+    #[test]
+    fn test_reencrypt() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("wallet.dat");
+        let mut wallet = open_wallet(&path);
+        let address = wallet.add_keypair(KeyPair::generate()).unwrap();
+
+        // a file of an older cipher version is written again with the current one
+        wallet.file_version = CIPHER_VERSION - 1;
+        assert!(wallet.is_outdated());
+        wallet.reencrypt().unwrap();
+        assert!(!wallet.is_outdated());
+        let (version, _) = decrypt(PASSWORD, &std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(version, CIPHER_VERSION);
+        assert_eq!(
+            open_wallet(&path).get_wallet_address_list(),
+            std::iter::once(address).collect()
+        );
+        // the file is replaced, not left aside
+        assert!(!path.with_extension("tmp").exists());
+    }
+
+    #[test]
+    fn test_change_password() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("wallet.dat");
+        let mut wallet = open_wallet(&path);
+        let address = wallet.add_keypair(KeyPair::generate()).unwrap();
+
+        wallet.change_password("new password".into()).unwrap();
+        assert!(decrypt(PASSWORD, &std::fs::read(&path).unwrap()).is_err());
+        let reloaded =
+            Wallet::new_with_cipher_params(path.clone(), "new password".into(), TEST_CIPHER_PARAMS)
+                .unwrap();
+        assert_eq!(
+            reloaded.get_wallet_address_list(),
+            std::iter::once(address).collect()
+        );
+
+        // the password is kept if the file cannot be written
+        wallet.wallet_path = dir.path().join("missing").join("wallet.dat");
+        assert!(wallet.change_password("other password".into()).is_err());
+        assert_eq!(wallet.password, "new password");
+    }
+
+    #[test]
+    fn test_labels() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("wallet.dat");
+        let mut wallet = open_wallet(&path);
+        let alice = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let bob = Address::from_public_key(&KeyPair::generate().get_public_key());
+
+        assert!(is_valid_label("alice"));
+        assert!(!is_valid_label(""));
+        assert!(!is_valid_label("alice smith"));
+        assert!(!is_valid_label(&bob.to_string()));
+        assert!(matches!(
+            wallet.set_label(bob.to_string(), alice),
+            Err(WalletError::InvalidLabelError(_))
+        ));
+
+        // a label resolves to its address, an address to itself
+        wallet.set_label("alice".into(), alice).unwrap();
+        assert_eq!(wallet.resolve_address("alice").unwrap(), alice);
+        assert_eq!(wallet.resolve_address(&bob.to_string()).unwrap(), bob);
+        assert!(matches!(
+            wallet.resolve_address("bob"),
+            Err(WalletError::UnknownLabelError(_))
+        ));
+
+        // imported labels cannot repoint existing ones unless forced
+        let imported: BTreeMap<String, Address> =
+            vec![("alice".to_string(), bob), ("bob".to_string(), bob)]
+                .into_iter()
+                .collect();
+        assert!(matches!(
+            wallet.import_labels(imported.clone(), false),
+            Err(WalletError::LabelConflictError(conflicts)) if conflicts == "alice"
+        ));
+        assert_eq!(wallet.resolve_address("alice").unwrap(), alice);
+        assert!(wallet.resolve_address("bob").is_err());
+        let same: BTreeMap<String, Address> = std::iter::once(("alice".to_string(), alice))
+            .chain(std::iter::once(("bob".to_string(), bob)))
+            .collect();
+        wallet.import_labels(same, false).unwrap();
+        assert_eq!(wallet.resolve_address("bob").unwrap(), bob);
+        wallet.import_labels(imported, true).unwrap();
+        assert_eq!(wallet.resolve_address("alice").unwrap(), bob);
+
+        // the labels are stored in the wallet file
+        assert_eq!(wallet.remove_label("bob").unwrap(), bob);
+        let reloaded = open_wallet(&path);
+        assert_eq!(reloaded.labels, wallet.labels);
+        assert_eq!(reloaded.get_labels(&bob), vec!["alice"]);
+    }
+
+    #[test]
+    fn test_sign_message_is_not_an_operation_signature() {
+        let dir = TempDir::new().unwrap();
+        let mut wallet = open_wallet(&dir.path().join("wallet.dat"));
+        let address = wallet.add_keypair(KeyPair::generate()).unwrap();
+        let public_key = wallet.find_associated_public_key(address).unwrap();
+
+        // a message made of a public key and an operation content
+        let operation = Operation {
+            fee: massa_models::Amount::default(),
+            expire_period: 10,
+            op: massa_models::OperationType::RollBuy { roll_count: 1 },
+        };
+        let mut content = Vec::new();
+        OperationSerializer::new()
+            .serialize(&operation, &mut content)
+            .unwrap();
+        let mut message = public_key.to_bytes().to_vec();
+        message.extend(&content);
+
+        let signed = wallet.sign_message(address, message.clone()).unwrap();
+        public_key
+            .verify_signature(&compute_signed_message_hash(&message), &signed.signature)
+            .unwrap();
+        assert!(public_key
+            .verify_signature(
+                &compute_wrapped_hash(&public_key, &content),
+                &signed.signature
+            )
+            .is_err());
+    }
+}
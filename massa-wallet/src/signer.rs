@@ -0,0 +1,336 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Backends signing for the addresses of the wallet
+//!
+//! The keypairs of the wallet sign in memory. An address can instead be bound to an external signer,
+//! such as a hardware wallet or an HSM bridge, reached through a process or a socket.
+//! The external signer receives one JSON request per line on its input:
+//! `{"public_key": ..., "hash": ..., "description": ...}` where `description` is a readable
+//! description of what is signed, to be shown to the user for confirmation,
+//! and answers with one JSON line: `{"signature": ...}`, or `{"error": ...}` if it refuses to sign.
+//! A signer that does not answer within `EXTERNAL_SIGNER_TIMEOUT` is given up.
+
+use crate::WalletError;
+use massa_hash::Hash;
+use massa_signature::{KeyPair, PublicKey, Signature};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Maximal time given to an external signer to answer,
+/// long enough for the user to check and confirm the signature on a device
+pub const EXTERNAL_SIGNER_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Backend signing the hashes of an address
+pub trait WalletSigner {
+    /// Gets the public key of the address
+    fn get_public_key(&self) -> PublicKey;
+
+    /// Signs a hash
+    ///
+    /// # Arguments
+    /// * `hash`: hash to sign
+    /// * `description`: readable description of what is signed
+    fn sign(&self, hash: &Hash, description: &str) -> Result<Signature, WalletError>;
+}
+
+impl WalletSigner for KeyPair {
+    fn get_public_key(&self) -> PublicKey {
+        KeyPair::get_public_key(self)
+    }
+
+    fn sign(&self, hash: &Hash, _description: &str) -> Result<Signature, WalletError> {
+        Ok(KeyPair::sign(self, hash)?)
+    }
+}
+
+/// Way to reach an external signer
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExternalSignerEndpoint {
+    /// program and arguments of a process started for each signature,
+    /// receiving the request on its standard input and answering on its standard output
+    Command(Vec<String>),
+    /// TCP address of a signer answering one request per connection
+    Tcp(SocketAddr),
+    /// path to the Unix socket of a signer answering one request per connection
+    Unix(PathBuf),
+}
+
+impl std::fmt::Display for ExternalSignerEndpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExternalSignerEndpoint::Command(command) => {
+                write!(f, "command `{}`", command.join(" "))
+            }
+            ExternalSignerEndpoint::Tcp(addr) => write!(f, "tcp://{}", addr),
+            ExternalSignerEndpoint::Unix(path) => write!(f, "unix://{}", path.display()),
+        }
+    }
+}
+
+/// Signer outside of the wallet, holding the secret key of a single public key
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ExternalSigner {
+    /// public key of the address
+    pub public_key: PublicKey,
+    /// way to reach the signer
+    pub endpoint: ExternalSignerEndpoint,
+}
+
+/// Request sent to an external signer
+#[derive(Serialize)]
+struct ExternalSignRequest<'a> {
+    public_key: &'a PublicKey,
+    hash: &'a Hash,
+    description: &'a str,
+}
+
+/// Response of an external signer
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ExternalSignResponse {
+    Signature(Signature),
+    Error(String),
+}
+
+impl WalletSigner for ExternalSigner {
+    fn get_public_key(&self) -> PublicKey {
+        self.public_key
+    }
+
+    fn sign(&self, hash: &Hash, description: &str) -> Result<Signature, WalletError> {
+        self.sign_with_timeout(hash, description, EXTERNAL_SIGNER_TIMEOUT)
+    }
+}
+
+impl ExternalSigner {
+    /// Signs a hash, giving up if the signer does not answer in time
+    fn sign_with_timeout(
+        &self,
+        hash: &Hash,
+        description: &str,
+        timeout: Duration,
+    ) -> Result<Signature, WalletError> {
+        let mut request = serde_json::to_vec(&ExternalSignRequest {
+            public_key: &self.public_key,
+            hash,
+            description,
+        })?;
+        request.push(b'\n');
+        let response = match &self.endpoint {
+            ExternalSignerEndpoint::Command(command) => run_command(command, &request, timeout)?,
+            ExternalSignerEndpoint::Tcp(addr) => {
+                let stream = TcpStream::connect_timeout(addr, timeout)
+                    .map_err(|err| timeout_error(err, timeout))?;
+                stream.set_read_timeout(Some(timeout))?;
+                stream.set_write_timeout(Some(timeout))?;
+                exchange(stream, &request, timeout)?
+            }
+            #[cfg(unix)]
+            ExternalSignerEndpoint::Unix(path) => {
+                let stream = std::os::unix::net::UnixStream::connect(path)?;
+                stream.set_read_timeout(Some(timeout))?;
+                stream.set_write_timeout(Some(timeout))?;
+                exchange(stream, &request, timeout)?
+            }
+            #[cfg(not(unix))]
+            ExternalSignerEndpoint::Unix(_) => {
+                return Err(WalletError::ExternalSignerError(
+                    "Unix sockets are not available on this platform".into(),
+                ))
+            }
+        };
+        match serde_json::from_str(&response)? {
+            ExternalSignResponse::Signature(signature) => {
+                // a signer answering for another key must not produce an invalid operation
+                self.public_key
+                    .verify_signature(hash, &signature)
+                    .map_err(|_| {
+                        WalletError::ExternalSignerError(format!(
+                            "invalid signature for public key {}",
+                            self.public_key
+                        ))
+                    })?;
+                Ok(signature)
+            }
+            ExternalSignResponse::Error(err) => Err(WalletError::ExternalSignerError(err)),
+        }
+    }
+}
+
+/// Starts the signer command, writes the request on its input and reads its output.
+/// The command is killed if it does not exit in time.
+fn run_command(
+    command: &[String],
+    request: &[u8],
+    timeout: Duration,
+) -> Result<String, WalletError> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| WalletError::ExternalSignerError("empty signer command".into()))?;
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    child
+        .stdin
+        .take()
+        .expect("signer standard input is piped")
+        .write_all(request)?;
+    let mut stdout = child
+        .stdout
+        .take()
+        .expect("signer standard output is piped");
+    // the output is read aside so that waiting for it can be bounded
+    let (output_tx, output_rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut output = Vec::new();
+        let _ = output_tx.send(stdout.read_to_end(&mut output).map(|_| output));
+    });
+    let output = match output_rx.recv_timeout(timeout) {
+        Ok(output) => output?,
+        Err(_) => {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(timeout_error(ErrorKind::TimedOut.into(), timeout));
+        }
+    };
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(WalletError::ExternalSignerError(format!(
+            "signer command exited with {}",
+            status
+        )));
+    }
+    first_line(&output[..])
+}
+
+/// Reports the IO errors of a signer that did not answer in time as such
+fn timeout_error(err: std::io::Error, timeout: Duration) -> WalletError {
+    match err.kind() {
+        ErrorKind::TimedOut | ErrorKind::WouldBlock => WalletError::ExternalSignerError(format!(
+            "the signer did not answer within {} seconds",
+            timeout.as_secs_f32()
+        )),
+        _ => WalletError::IOError(err),
+    }
+}
+
+/// Writes the request on a new connection and reads the response line
+fn exchange<S: Read + Write>(
+    mut stream: S,
+    request: &[u8],
+    timeout: Duration,
+) -> Result<String, WalletError> {
+    let mut line = String::new();
+    stream
+        .write_all(request)
+        .and_then(|_| stream.flush())
+        .and_then(|_| BufReader::new(&mut stream).read_line(&mut line))
+        .map_err(|err| timeout_error(err, timeout))?;
+    answered(line)
+}
+
+/// Reads the first line of a response
+fn first_line<R: Read>(reader: R) -> Result<String, WalletError> {
+    let mut line = String::new();
+    BufReader::new(reader).read_line(&mut line)?;
+    answered(line)
+}
+
+/// Checks that the signer answered something before closing
+fn answered(line: String) -> Result<String, WalletError> {
+    if line.trim().is_empty() {
+        return Err(WalletError::ExternalSignerError(
+            "the signer closed without answering".into(),
+        ));
+    }
+    Ok(line)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    /// External signer backed by a shell command writing the given answer
+    fn mock_command_signer(public_key: PublicKey, answer: &serde_json::Value) -> ExternalSigner {
+        ExternalSigner {
+            public_key,
+            endpoint: ExternalSignerEndpoint::Command(vec![
+                "sh".into(),
+                "-c".into(),
+                format!("cat > /dev/null; echo '{}'", answer),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_command_signer() {
+        let keypair = KeyPair::generate();
+        let hash = Hash::compute_from(b"operation");
+        let signature = keypair.sign(&hash).unwrap();
+
+        let signer = mock_command_signer(
+            keypair.get_public_key(),
+            &serde_json::json!({ "signature": signature }),
+        );
+        assert_eq!(
+            signer.sign(&hash, "operation").unwrap().to_bytes(),
+            signature.to_bytes()
+        );
+
+        // a signature of another key is rejected
+        let signer = mock_command_signer(
+            KeyPair::generate().get_public_key(),
+            &serde_json::json!({ "signature": signature }),
+        );
+        assert!(matches!(
+            signer.sign(&hash, "operation"),
+            Err(WalletError::ExternalSignerError(_))
+        ));
+
+        // so is a refusal
+        let signer = mock_command_signer(
+            keypair.get_public_key(),
+            &serde_json::json!({ "error": "refused" }),
+        );
+        assert!(matches!(
+            signer.sign(&hash, "operation"),
+            Err(WalletError::ExternalSignerError(err)) if err == "refused"
+        ));
+    }
+
+    #[test]
+    fn test_signer_timeout() {
+        let public_key = KeyPair::generate().get_public_key();
+        let hash = Hash::compute_from(b"operation");
+        let timeout = Duration::from_millis(200);
+
+        // a command that never answers is killed
+        let signer = ExternalSigner {
+            public_key,
+            endpoint: ExternalSignerEndpoint::Command(vec!["sleep".into(), "10".into()]),
+        };
+        assert!(matches!(
+            signer.sign_with_timeout(&hash, "operation", timeout),
+            Err(WalletError::ExternalSignerError(_))
+        ));
+
+        // so is a socket that never answers: the connection is queued but never accepted
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let signer = ExternalSigner {
+            public_key,
+            endpoint: ExternalSignerEndpoint::Tcp(listener.local_addr().unwrap()),
+        };
+        assert!(matches!(
+            signer.sign_with_timeout(&hash, "operation", timeout),
+            Err(WalletError::ExternalSignerError(_))
+        ));
+    }
+}